@@ -16,24 +16,42 @@
 //!
 //! See `types::ExitCode` for the enum definition.
 
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use iam_policy_autopilot_policy_generation::api::model::{
-    AwsContext, ExtractSdkCallsConfig, GeneratePolicyConfig,
+    AwsContext, DeriveSdkWrappersConfig, DownloadServiceReferenceSnapshotConfig,
+    ExtractSdkCallsConfig, GeneratePolicyConfig, PolicyGenerationOptions,
+    PruneServiceReferenceCacheConfig, ResourceCutoffOverride, ServiceReferenceCacheConfig,
+    ServiceReferenceSource, ValidatePoliciesConfig, WarmServiceReferenceCacheConfig, WildcardMode,
 };
-use iam_policy_autopilot_policy_generation::api::{extract_sdk_calls, generate_policies};
-use iam_policy_autopilot_policy_generation::extraction::SdkMethodCall;
-use iam_policy_autopilot_tools::PolicyUploader;
+use iam_policy_autopilot_policy_generation::api::{
+    derive_sdk_wrappers, download_service_reference_snapshot, extract_sdk_calls,
+    generate_function_policies, generate_policies, generate_workload_policies,
+    prune_service_reference_cache, service_reference_cache_status, validate_policies,
+    warm_service_reference_cache,
+};
+use iam_policy_autopilot_policy_generation::extraction::{EntryPoint, EntryPoints, SdkMethodCall};
+use iam_policy_autopilot_policy_generation::{
+    discover_lambda_functions, JsonProvider, PolicyKind, PolicyWithMetadata, ProjectConfig,
+    Workload, WorkloadManifest, PROJECT_CONFIG_FILE_NAME,
+};
+use iam_policy_autopilot_tools::{BatchUploadResponse, PolicyUploader};
 use log::{debug, info, trace};
 
 mod commands;
 mod output;
+mod sarif;
 mod types;
 
-use iam_policy_autopilot_mcp_server::{start_mcp_server, McpTransport};
+use iam_policy_autopilot_mcp_server::http::{
+    HttpTransportConfig, TlsConfig, DEFAULT_BIND_ADDRESS, DEFAULT_MAX_REQUEST_SIZE,
+};
+use iam_policy_autopilot_mcp_server::{start_mcp_server, McpServerConfig, McpTransport};
 use types::ExitCode;
 
 use crate::commands::print_version_info;
@@ -46,19 +64,68 @@ static MCP_HTTP_DEFAULT_PORT: u16 = 8001;
 struct SharedConfig {
     /// Source files to analyze
     source_files: Vec<PathBuf>,
-    /// Enable pretty JSON output formatting
-    pretty: bool,
+    /// Enable pretty JSON output formatting, `None` if not given on the command line
+    pretty: Option<bool>,
     /// Override programming language detection
     language: Option<String>,
     /// Output full ExtractedMethods instead of simplified operations (extract-sdk-calls only)
     full_output: bool,
     /// Optional service hints for filtering
     service_hints: Option<Vec<String>>,
+    /// Explicit project configuration file, discovered from the working directory if not set
+    config_file: Option<PathBuf>,
+    /// SDK wrapper mapping files, added to those of the project configuration
+    sdk_wrappers: Vec<PathBuf>,
+    /// Entry points SDK calls must be reachable from, added to those of the project configuration
+    entry_points: EntryPoints,
+    /// Detect entry points, `None` if not given on the command line
+    detect_entry_points: Option<bool>,
+    /// Extract SDK calls of test files and files that stub the SDK, `None` if not given on the
+    /// command line
+    include_tests: Option<bool>,
+    /// Write the extraction diagnostics as a SARIF log to this file
+    sarif: Option<PathBuf>,
 }
 
 impl SharedConfig {
+    /// Fill settings that were not given on the command line from the project configuration
+    fn apply_project_config(&mut self, project: &ProjectConfig) -> Result<()> {
+        if self.source_files.is_empty() {
+            self.source_files = project
+                .source_files()
+                .context("Failed to collect source files from the project configuration")?;
+        }
+        self.pretty.get_or_insert(project.output.pretty);
+        if self.language.is_none() {
+            self.language.clone_from(&project.sources.language);
+        }
+        if self.service_hints.is_none() {
+            self.service_hints
+                .clone_from(&project.sources.service_hints);
+        }
+        let mut sdk_wrappers = project.sdk_wrappers();
+        sdk_wrappers.append(&mut self.sdk_wrappers);
+        self.sdk_wrappers = sdk_wrappers;
+        let mut entry_points = project.entry_points();
+        entry_points.detect = *self.detect_entry_points.get_or_insert(entry_points.detect);
+        entry_points
+            .declared
+            .append(&mut self.entry_points.declared);
+        self.entry_points = entry_points;
+        self.include_tests
+            .get_or_insert(project.sources.include_tests);
+        Ok(())
+    }
+
     /// Validate the configuration
     fn validate(&self) -> Result<()> {
+        if self.source_files.is_empty() {
+            anyhow::bail!(
+                "No source files provided. Pass source files or declare [sources] in {}",
+                PROJECT_CONFIG_FILE_NAME
+            );
+        }
+
         // Check that all source files exist
         for file in &self.source_files {
             if !file.exists() {
@@ -79,28 +146,316 @@ struct GeneratePolicyCliConfig {
     /// Shared configuration
     shared: SharedConfig,
     /// AWS region
-    region: String,
+    region: Option<String>,
     /// AWS account ID
-    account: String,
+    account: Option<String>,
     /// Output individual policies instead of merged policy
     individual_policies: bool,
     /// Upload policies to AWS with optional custom name prefix
     upload_policies: Option<String>,
-    /// Enable minimal policy size by allowing cross-service merging
-    minimal_policy_size: bool,
-    /// Disable file system caching for service references
-    disable_cache: bool,
-    /// Generate explanations for why actions were added
-    explain: bool,
+    /// Enable minimal policy size by allowing cross-service merging, `None` if not given on the
+    /// command line
+    minimal_policy_size: Option<bool>,
+    /// Disable file system caching for service references, `None` if not given on the command line
+    disable_cache: Option<bool>,
+    /// Generate explanations for why actions were added, `None` if not given on the command line
+    explain: Option<bool>,
+    /// Resource cutoffs, either `N` or `KEY=N`
+    resource_cutoff: Vec<String>,
+    /// Wildcard modes, either `MODE` or `KEY=MODE`
+    wildcard_mode: Vec<String>,
+    /// Policy generation options from the project configuration, overridden by the flags above
+    project_policy_options: PolicyGenerationOptions,
+    /// Workloads, as `NAME=GLOB`
+    workloads: Vec<String>,
+    /// Workloads from the project configuration, replaced by the flags above
+    project_workloads: Option<WorkloadManifest>,
+    /// Directory of the project configuration, against which workload globs are matched
+    project_root: Option<PathBuf>,
+    /// File or directory declaring Lambda functions to generate separate policies for
+    lambda_functions: Option<PathBuf>,
+    /// Lambda functions path from the project configuration, overridden by the flag above
+    project_lambda_functions: Option<PathBuf>,
+    /// Where service reference data is loaded from
+    service_reference: ServiceReferenceSource,
+    /// Never fetch service reference data from the network, `None` if not given on the command
+    /// line
+    offline: Option<bool>,
+    /// Operation FAS map files, added to those of the project configuration
+    fas_maps: Vec<PathBuf>,
 }
 
 impl GeneratePolicyCliConfig {
+    /// Fill settings that were not given on the command line from the project configuration
+    fn apply_project_config(&mut self, project: &ProjectConfig) -> Result<()> {
+        self.shared.apply_project_config(project)?;
+        if self.region.is_none() {
+            self.region.clone_from(&project.aws.region);
+        }
+        if self.account.is_none() {
+            self.account.clone_from(&project.aws.account);
+        }
+        self.minimal_policy_size
+            .get_or_insert(project.generation.minimize_policy_size);
+        self.disable_cache
+            .get_or_insert(project.generation.disable_cache);
+        self.explain.get_or_insert(project.generation.explain);
+        self.project_policy_options = project.policy_options();
+        self.project_workloads = project.workload_manifest();
+        self.project_lambda_functions = project.lambda_functions();
+        self.project_root = Some(project.root.clone());
+        let project_service_reference = project.service_reference_source();
+        self.service_reference.offline = *self
+            .offline
+            .get_or_insert(project_service_reference.offline);
+        if self.service_reference.snapshot.is_none() {
+            self.service_reference.snapshot = project_service_reference.snapshot;
+        }
+        // Mirror settings are only available in the project configuration
+        self.service_reference.endpoint = project_service_reference.endpoint;
+        self.service_reference.ca_certificate = project_service_reference.ca_certificate;
+        self.service_reference.proxy = project_service_reference.proxy;
+        let mut fas_maps = project.fas_map_extensions();
+        fas_maps.append(&mut self.fas_maps);
+        self.fas_maps = fas_maps;
+        Ok(())
+    }
+
     /// Validate the configuration
     fn validate(&self) -> Result<()> {
-        self.shared.validate()
+        self.shared.validate()?;
+        self.policy_options()?;
+        if self.workload_manifest()?.is_some() && self.individual_policies {
+            anyhow::bail!("--individual-policies cannot be combined with workloads");
+        }
+        if self.lambda_functions().is_some() {
+            if self.individual_policies {
+                anyhow::bail!("--individual-policies cannot be combined with --lambda-functions");
+            }
+            if self.workload_manifest()?.is_some() {
+                anyhow::bail!("--lambda-functions cannot be combined with workloads");
+            }
+        }
+        Ok(())
+    }
+
+    /// Lambda functions path from the --lambda-functions flag, or from the project
+    fn lambda_functions(&self) -> Option<&Path> {
+        self.lambda_functions
+            .as_deref()
+            .or(self.project_lambda_functions.as_deref())
+    }
+
+    /// Build the workload manifest from the --workload flags, or take it from the project
+    ///
+    /// Workload globs are matched relative to the project root, or the working directory if
+    /// there is no project configuration.
+    fn workload_manifest(&self) -> Result<Option<WorkloadManifest>> {
+        if self.workloads.is_empty() {
+            return Ok(self.project_workloads.clone());
+        }
+
+        let mut workloads: Vec<Workload> = Vec::new();
+        for value in &self.workloads {
+            let (Some(name), glob) = split_override(value) else {
+                anyhow::bail!("Invalid --workload '{}': expected NAME=GLOB", value);
+            };
+            if name.is_empty() || glob.is_empty() {
+                anyhow::bail!("Invalid --workload '{}': expected NAME=GLOB", value);
+            }
+            match workloads.iter_mut().find(|workload| workload.name == name) {
+                Some(workload) => workload.paths.push(glob.to_string()),
+                None => workloads.push(Workload {
+                    name: name.to_string(),
+                    paths: vec![glob.to_string()],
+                }),
+            }
+        }
+
+        let root = match &self.project_root {
+            Some(root) => root.clone(),
+            None => std::env::current_dir().context("Failed to get the working directory")?,
+        };
+        Ok(Some(WorkloadManifest::new(root, workloads)))
+    }
+
+    /// Build the policy generation options from the --resource-cutoff and --wildcard-mode flags
+    ///
+    /// Values without a key set the global default. Keys containing a colon (e.g. `s3:GetObject`)
+    /// override a single action, other keys (e.g. `s3`) override a service.
+    fn policy_options(&self) -> Result<PolicyGenerationOptions> {
+        let mut options = self.project_policy_options.clone();
+
+        for value in &self.resource_cutoff {
+            let (key, cutoff) = split_override(value);
+            let cutoff = cutoff
+                .parse::<usize>()
+                .ok()
+                .filter(|cutoff| *cutoff > 0)
+                .with_context(|| {
+                    format!(
+                        "Invalid --resource-cutoff '{}': expected a positive number, optionally prefixed with a service or action and '='",
+                        value
+                    )
+                })?;
+            match key {
+                None => options.resource_cutoff = cutoff,
+                Some(key) => override_for(&mut options, key).cutoff = Some(cutoff),
+            }
+        }
+
+        for value in &self.wildcard_mode {
+            let (key, mode) = split_override(value);
+            let mode = match mode {
+                "wildcard" => WildcardMode::Wildcard,
+                "service-wildcard" => WildcardMode::ServiceWildcard,
+                "never" => WildcardMode::Never,
+                _ => anyhow::bail!(
+                    "Invalid --wildcard-mode '{}': expected wildcard, service-wildcard or never, optionally prefixed with a service or action and '='",
+                    value
+                ),
+            };
+            match key {
+                None => options.wildcard_mode = mode,
+                Some(key) => override_for(&mut options, key).wildcard_mode = Some(mode),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Load the project configuration from `path`, or discover it from the working directory
+fn load_project_config(path: Option<&Path>) -> Result<Option<ProjectConfig>> {
+    let project = match path {
+        Some(path) => Some(ProjectConfig::load(path)?),
+        None => ProjectConfig::discover(
+            &std::env::current_dir().context("Failed to get the working directory")?,
+        )?,
+    };
+    if let Some(project) = &project {
+        debug!(
+            "Loaded project configuration from {}",
+            project.root.display()
+        );
     }
+    Ok(project)
 }
 
+/// Split a `KEY=VALUE` flag value into its optional key and value
+fn split_override(value: &str) -> (Option<&str>, &str) {
+    match value.split_once('=') {
+        Some((key, value)) => (Some(key), value),
+        None => (None, value),
+    }
+}
+
+/// Get the override for an action (if the key contains a colon) or a service
+fn override_for<'a>(
+    options: &'a mut PolicyGenerationOptions,
+    key: &str,
+) -> &'a mut ResourceCutoffOverride {
+    let overrides = if key.contains(':') {
+        &mut options.action_overrides
+    } else {
+        &mut options.service_overrides
+    };
+    overrides.entry(key.to_string()).or_default()
+}
+
+/// Configuration specific to validate-policies subcommand
+#[derive(Debug, Clone)]
+struct ValidatePoliciesCliConfig {
+    /// Policy files to validate
+    policy_files: Vec<PathBuf>,
+    /// Enable pretty JSON output formatting
+    pretty: bool,
+    /// Whether the policies are managed or inline policies
+    policy_kind: PolicyKind,
+    /// Disable file system caching for service references
+    disable_cache: bool,
+    /// Where service reference data is loaded from
+    service_reference: ServiceReferenceSource,
+}
+
+/// Configuration specific to download-service-reference subcommand
+#[derive(Debug, Clone)]
+struct DownloadServiceReferenceCliConfig {
+    /// Snapshot directory or bundle file to write
+    output: PathBuf,
+    /// Services to download, or all services
+    services: Option<Vec<String>>,
+    /// Enable pretty JSON output formatting
+    pretty: bool,
+}
+
+const OFFLINE_LONG_HELP: &str = "Never fetch service reference data from the network. Service \
+references are loaded from the snapshot given with --service-reference, or from the file system \
+cache (regardless of its age) without a snapshot. Fails if a required service is not available.";
+
+const SERVICE_REFERENCE_LONG_HELP: &str = "Load service reference data from a snapshot created \
+with the download-service-reference command: a directory, or a bundle file ending in .json. \
+The checksums of the snapshot are verified. Services missing from the snapshot are fetched from \
+the network unless --offline is set.";
+
+const CACHE_LONG_HELP: &str = "Fetched service reference data is cached in \
+$XDG_CACHE_HOME/iam-policy-autopilot/service-reference/v1 (~/.cache on Linux, ~/Library/Caches on \
+macOS, %LOCALAPPDATA% on Windows), or in the directory given by the IAM_POLICY_AUTOPILOT_CACHE_DIR \
+environment variable. Every entry records the mapping index version it was fetched with and a \
+SHA-256 checksum. Entries older than 6 hours are revalidated with the endpoint using their ETag or \
+Last-Modified header.";
+
+/// Value of a flag and its negation given on the command line, or `None` if neither was given
+///
+/// The negations only matter when the project configuration enables the flag.
+fn cli_flag(enabled: bool, disabled: bool) -> Option<bool> {
+    match (enabled, disabled) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+const CONFIG_LONG_HELP: &str = "Path to a project configuration file. By default, \
+.iam-autopilot.toml is searched in the working directory and its parents. Settings given on the \
+command line override the project configuration.";
+
+const SDK_WRAPPER_LONG_HELP: &str = "SDK wrapper mapping file declaring in-house functions \
+and methods that wrap AWS SDK calls, e.g. storage.put_blob(bucket, key) wrapping s3.put_object. \
+Calls of declared wrappers are extracted as calls of the AWS operations they invoke. The file \
+has the form {\"Wrappers\": [{\"Language\": \"python\", \"Module\": \"mycorp.storage\", \"Name\": \
+\"put_blob\", \"Receiver\": \"<type of methods>\", \"Operations\": [\"s3:PutObject\"], \
+\"Parameters\": [{\"Name\": \"bucket\", \"Position\": 0, \"Member\": \"Bucket\"}]}]}, where \
+Language, Receiver, Name and Position of parameters are optional. Added to the mapping files of \
+the project configuration. Can be repeated.";
+
+const ENTRY_POINT_LONG_HELP: &str = "Entry point of the application, as FILE:FUNCTION, \
+FILE:TYPE.METHOD, or FILE for the top-level code of a script. Only SDK calls reachable from \
+an entry point, through the calls between the analyzed files, are extracted; unreachable calls \
+are excluded and reported as diagnostics. Added to the entry points of the project configuration. \
+Can be repeated.";
+
+const DETECT_ENTRY_POINTS_LONG_HELP: &str = "Only extract SDK calls reachable from detected \
+entry points: Go main functions, Python and JavaScript scripts, Lambda handlers (functions \
+taking event and context, Python lambda_handler, exported JavaScript handler, functions passed \
+to Go's lambda.Start), and HTTP route handlers (e.g. @app.get(\"/items\"), router.post(\"/items\", \
+create), http.HandleFunc(\"/items\", list)). If no entry point is detected, all SDK calls are \
+extracted. Combines with --entry-point.";
+
+const INCLUDE_TESTS_LONG_HELP: &str = "Extract SDK calls of test files and of files that \
+stub the AWS SDK, which are excluded by default and reported as diagnostics. Test files are \
+recognized by name (test_*.py, *_test.py, conftest.py, *_test.go, *.test.ts, *.spec.ts and \
+their JavaScript variants, files below __tests__ and __mocks__), and stubbed SDK usage by the \
+import of moto, botocore.stub, aws-sdk-client-mock, aws-sdk-mock, gomock, testify/mock or a \
+Go mocks package.";
+
+const SARIF_LONG_HELP: &str = "Write the diagnostics of the extraction as a SARIF 2.1.0 log \
+to this file, for code scanning tools. Diagnostics report code whose SDK calls could not be \
+extracted exactly: syntax errors, SDK calls matching several services, parameters passed with \
+dictionary unpacking, methods looked up by a name computed at runtime, imports of unknown \
+services, and excluded test files and unreachable SDK calls. Warnings are also printed to \
+stderr.";
+
 const SERVICE_HINTS_LONG_HELP: &str =
     "Space-separated list of AWS service names to filter which SDK calls are analyzed. \
 This helps reduce unnecessary permissions by limiting analysis to only the services your application actually uses. \
@@ -120,10 +475,13 @@ required for the operations you perform (e.g., KMS actions for S3 encryption).";
 with automatic AccessDenied error fixing. Supports three main operations:\n\n\
 • fix-access-denied: Fix AccessDenied errors by analyzing and applying IAM policy changes\n\
 • generate-policies: Complete pipeline with enrichment for policy generation\n\
+• validate-policies: Check IAM policy documents for grammar, quota and service reference problems\n\
+• cache: Inspect, prune and pre-populate the service reference cache\n\
 • mcp-server: Start MCP server for IDE integration. Uses STDIO transport by default.\n\n\
 iam-policy-autopilot fix-access-denied 'User: arn:aws:iam::123456789012:user/testuser is not authorized to perform: s3:GetObject on resource: arn:aws:s3:::my-bucket/my-key because no identity-based policy allows the s3:GetObject action'\n  \
-iam-policy-autopilot generate-policies tests/resources/test_example.py --region us-east-1 --account 123456789012 --pretty\n  \
-iam-policy-autopilot generate-policies tests/resources/test_example.py --service-hints s3 iam --region us-east-1 --account 123456789012 --pretty\n  \
+iam-policy-autopilot generate-policies tests/resources/example.py --region us-east-1 --account 123456789012 --pretty\n  \
+iam-policy-autopilot generate-policies tests/resources/example.py --service-hints s3 iam --region us-east-1 --account 123456789012 --pretty\n  \
+iam-policy-autopilot validate-policies policy.json --pretty\n  \
 iam-policy-autopilot mcp-server\n  \
 iam-policy-autopilot mcp-server --transport http --port 8001"
 )]
@@ -132,6 +490,8 @@ struct Cli {
     command: Commands,
 }
 
+// Parsed once per invocation, so the size of the largest subcommand does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Fix AccessDenied errors by analyzing and optionally applying IAM policy changes
@@ -170,9 +530,10 @@ and basic metadata without enrichment."
     )]
     ExtractSdkCalls {
         /// Source files to analyze for SDK method extraction
-        #[arg(required = true, num_args = 1.., long_help = "One or more source code files to analyze. \
+        #[arg(num_args = 0.., long_help = "One or more source code files to analyze. \
 Supports multiple programming languages including Python (.py), TypeScript (.ts), JavaScript (.js), \
-Go (.go), and others. Files are processed concurrently for better performance.")]
+Go (.go), and others. Files are processed concurrently for better performance. \
+If omitted, the source files declared in the project configuration are analyzed.")]
         source_files: Vec<PathBuf>,
 
         /// Enable debug logging output to stderr (most verbose)
@@ -192,12 +553,17 @@ If both --debug and --verbose are specified, --debug takes precedence."
         #[arg(
             short = 'p',
             long = "pretty",
+            overrides_with = "no_pretty",
             long_help = "Formats the JSON output with proper indentation \
 and line breaks for human readability. When disabled, outputs compact JSON suitable for \
 machine processing and pipelines."
         )]
         pretty: bool,
 
+        /// Output compact JSON, overriding the project configuration
+        #[arg(long = "no-pretty", overrides_with = "pretty")]
+        no_pretty: bool,
+
         /// Override programming language detection
         #[arg(
             short = 'l',
@@ -212,7 +578,7 @@ go, rust, java, cpp, c, csharp. When not specified, all source files must have t
         #[arg(
             long = "full-output",
             long_help = "When enabled, outputs the complete ExtractedMethods \
-structure including metadata about extraction time, source files, and warnings. By default, \
+structure including metadata about extraction time, source files, and diagnostics. By default, \
 extract-sdk-calls outputs a simplified list of operations with their possible services. \
 This flag has no effect on the generate-policies subcommand."
         )]
@@ -225,6 +591,41 @@ This flag has no effect on the generate-policies subcommand."
             long_help = SERVICE_HINTS_LONG_HELP,
         )]
         service_hints: Option<Vec<String>>,
+
+        /// Additional SDK wrapper mapping file
+        #[arg(long = "sdk-wrapper", value_name = "PATH", long_help = SDK_WRAPPER_LONG_HELP)]
+        sdk_wrapper: Vec<PathBuf>,
+
+        /// Only extract SDK calls reachable from this entry point
+        #[arg(long = "entry-point", value_name = "FILE[:FUNCTION]", long_help = ENTRY_POINT_LONG_HELP)]
+        entry_point: Vec<EntryPoint>,
+
+        /// Only extract SDK calls reachable from detected entry points
+        #[arg(long = "detect-entry-points", overrides_with = "no_detect_entry_points", long_help = DETECT_ENTRY_POINTS_LONG_HELP)]
+        detect_entry_points: bool,
+
+        /// Do not detect entry points, overriding the project configuration
+        #[arg(
+            long = "no-detect-entry-points",
+            overrides_with = "detect_entry_points"
+        )]
+        no_detect_entry_points: bool,
+
+        /// Extract SDK calls of test files and files that stub the SDK
+        #[arg(long = "include-tests", overrides_with = "no_include_tests", long_help = INCLUDE_TESTS_LONG_HELP)]
+        include_tests: bool,
+
+        /// Skip test files and files that stub the SDK, overriding the project configuration
+        #[arg(long = "no-include-tests", overrides_with = "include_tests")]
+        no_include_tests: bool,
+
+        /// Write the extraction diagnostics as a SARIF log
+        #[arg(long = "sarif", value_name = "PATH", long_help = SARIF_LONG_HELP)]
+        sarif: Option<PathBuf>,
+
+        /// Project configuration file
+        #[arg(long = "config", value_name = "PATH", long_help = CONFIG_LONG_HELP)]
+        config: Option<PathBuf>,
     },
 
     /// Generates complete IAM policy documents from source files
@@ -235,8 +636,8 @@ Optionally takes AWS context (region and account) for accurate ARN generation.\n
 TIP: Use --service-hints to specify the particular AWS services that your application uses if you know them. \
 The final policy may still include actions from other services if required for your operations.")]
     GeneratePolicies {
-        /// Source files to analyze for SDK method extraction
-        #[arg(required = true, num_args = 1..)]
+        /// Source files to analyze for SDK method extraction (default: from the project configuration)
+        #[arg(num_args = 0..)]
         source_files: Vec<PathBuf>,
 
         /// Enable debug logging output to stderr (most verbose)
@@ -244,9 +645,13 @@ The final policy may still include actions from other services if required for y
         debug: bool,
 
         /// Format JSON output with indentation for readability
-        #[arg(short = 'p', long = "pretty")]
+        #[arg(short = 'p', long = "pretty", overrides_with = "no_pretty")]
         pretty: bool,
 
+        /// Output compact JSON, overriding the project configuration
+        #[arg(long = "no-pretty", overrides_with = "pretty")]
+        no_pretty: bool,
+
         /// Override programming language detection
         #[arg(short = 'l', long = "language")]
         language: Option<String>,
@@ -259,20 +664,19 @@ The final policy may still include actions from other services if required for y
         #[arg(
             short = 'r',
             long = "region",
-            default_value = "*",
             long_help = "AWS region to use for ARN generation. \
-Examples: us-east-1, us-west-2, eu-west-1."
+Examples: us-east-1, us-west-2, eu-west-1. Defaults to the project configuration, or * if not set."
         )]
-        region: String,
+        region: Option<String>,
 
         /// AWS account ID
         #[arg(
             short = 'a',
             long = "account",
-            default_value = "*",
-            long_help = "AWS account ID to use for ARN generation."
+            long_help = "AWS account ID to use for ARN generation. \
+Defaults to the project configuration, or * if not set."
         )]
-        account: String,
+        account: Option<String>,
 
         /// Output separate policies for each method call instead of a single merged policy
         #[arg(
@@ -297,6 +701,7 @@ The tool automatically finds the lowest available number for each policy name.")
         /// Enable minimal policy size by allowing cross-service action merging
         #[arg(
             long = "minimize-policy-size",
+            overrides_with = "no_minimal_policy_size",
             long_help = "When enabled, allows merging of actions from \
 different AWS services into the same policy statement. This can result in smaller, more compact policies \
 but may be less readable. By default, actions from different services are kept in separate statements \
@@ -304,15 +709,28 @@ for better organization."
         )]
         minimal_policy_size: bool,
 
+        /// Keep actions of different services in separate statements, overriding the project
+        /// configuration
+        #[arg(
+            long = "no-minimize-policy-size",
+            overrides_with = "minimal_policy_size"
+        )]
+        no_minimal_policy_size: bool,
+
         /// Disable file system caching for service references
         #[arg(
             long = "disable-cache",
+            overrides_with = "enable_cache",
             long_help = "When enabled, disables file system caching for service reference data. \
-By default, service reference data is cached in the system temp directory for 6 hours to improve performance. \
+By default, service reference data is cached in the user cache directory and revalidated after 6 hours to improve performance. \
 Use this flag to force fresh data retrieval on every run."
         )]
         disable_cache: bool,
 
+        /// Cache service references on the file system, overriding the project configuration
+        #[arg(long = "enable-cache", overrides_with = "disable_cache")]
+        enable_cache: bool,
+
         /// Filter extracted SDK calls to specific AWS services
         #[arg(
             long = "service-hints",
@@ -321,14 +739,253 @@ Use this flag to force fresh data retrieval on every run."
         )]
         service_hints: Option<Vec<String>>,
 
+        /// Additional SDK wrapper mapping file
+        #[arg(long = "sdk-wrapper", value_name = "PATH", long_help = SDK_WRAPPER_LONG_HELP)]
+        sdk_wrapper: Vec<PathBuf>,
+
+        /// Only extract SDK calls reachable from this entry point
+        #[arg(long = "entry-point", value_name = "FILE[:FUNCTION]", long_help = ENTRY_POINT_LONG_HELP)]
+        entry_point: Vec<EntryPoint>,
+
+        /// Only extract SDK calls reachable from detected entry points
+        #[arg(long = "detect-entry-points", overrides_with = "no_detect_entry_points", long_help = DETECT_ENTRY_POINTS_LONG_HELP)]
+        detect_entry_points: bool,
+
+        /// Do not detect entry points, overriding the project configuration
+        #[arg(
+            long = "no-detect-entry-points",
+            overrides_with = "detect_entry_points"
+        )]
+        no_detect_entry_points: bool,
+
+        /// Extract SDK calls of test files and files that stub the SDK
+        #[arg(long = "include-tests", overrides_with = "no_include_tests", long_help = INCLUDE_TESTS_LONG_HELP)]
+        include_tests: bool,
+
+        /// Skip test files and files that stub the SDK, overriding the project configuration
+        #[arg(long = "no-include-tests", overrides_with = "include_tests")]
+        no_include_tests: bool,
+
+        /// Write the extraction diagnostics as a SARIF log
+        #[arg(long = "sarif", value_name = "PATH", long_help = SARIF_LONG_HELP)]
+        sarif: Option<PathBuf>,
+
         /// Generate explanations for why actions were added
         #[arg(
             long = "explain",
+            overrides_with = "no_explain",
             long_help = "When enabled, generates detailed explanations for why each IAM action \
 was added to the policy. Explanations include the initial operation with location information, FAS (https://docs.aws.amazon.com/IAM/latest/UserGuide/access_forward_access_sessions.html) expansion chains. The output format \
 may change in future versions."
         )]
         explain: bool,
+
+        /// Do not generate explanations, overriding the project configuration
+        #[arg(long = "no-explain", overrides_with = "explain")]
+        no_explain: bool,
+
+        /// Number of resource types from which an action's resources are collapsed
+        #[arg(
+            long = "resource-cutoff",
+            value_name = "[KEY=]N",
+            long_help = "Number of resource types from which the resources of an action are \
+collapsed according to --wildcard-mode (default: 5). Prefix the number with a service (e.g. s3=10) \
+or an action (e.g. ec2:RunInstances=20) to override the cutoff for that service or action only. \
+Can be repeated."
+        )]
+        resource_cutoff: Vec<String>,
+
+        /// How an action's resources are collapsed once the cutoff is reached
+        #[arg(
+            long = "wildcard-mode",
+            value_name = "[KEY=]MODE",
+            long_help = "How the resources of an action are collapsed once the resource cutoff is \
+reached: 'wildcard' replaces them with '*' (default), 'service-wildcard' replaces them with one ARN \
+wildcard per service, region and account (e.g. arn:aws:ec2:us-east-1:123456789012:*), and 'never' \
+always lists the ARN of every resource type. Prefix the mode with a service (e.g. iam=never) or an \
+action (e.g. ec2:RunInstances=service-wildcard) to override the mode for that service or action \
+only. Can be repeated."
+        )]
+        wildcard_mode: Vec<String>,
+
+        /// Generate separate policies for a workload
+        #[arg(
+            long = "workload",
+            value_name = "NAME=GLOB",
+            long_help = "Generate a separate set of policies for the workload NAME, from the \
+source files matching GLOB (relative to the project root, or the working directory without a \
+project configuration) and the source files they import. Repeat the flag to add globs or \
+workloads. Replaces the workloads of the project configuration."
+        )]
+        workload: Vec<String>,
+
+        /// Generate separate policies for each declared Lambda function
+        #[arg(
+            long = "lambda-functions",
+            value_name = "PATH",
+            long_help = "Generate a separate set of policies for each Lambda function declared \
+in PATH, from the SDK calls reachable from the function's handler. PATH is an AWS SAM or \
+CloudFormation template, a Serverless Framework configuration, an AWS CDK source file, or a \
+directory searched for template.yaml, serverless.yml and CDK files. Handlers are resolved in \
+each function's code directory and must be among the source files. Cannot be combined with \
+workloads."
+        )]
+        lambda_functions: Option<PathBuf>,
+
+        /// Never fetch service reference data from the network
+        #[arg(long = "offline", overrides_with = "no_offline", long_help = OFFLINE_LONG_HELP)]
+        offline: bool,
+
+        /// Fetch service reference data from the network, overriding the project configuration
+        #[arg(long = "no-offline", overrides_with = "offline")]
+        no_offline: bool,
+
+        /// Service reference snapshot to load service reference data from
+        #[arg(long = "service-reference", value_name = "PATH", long_help = SERVICE_REFERENCE_LONG_HELP)]
+        service_reference: Option<PathBuf>,
+
+        /// Additional operation FAS map file
+        #[arg(
+            long = "fas-map",
+            value_name = "PATH",
+            long_help = "Operation FAS map file declaring additional permissions that AWS \
+services need on your behalf (Forward Access Sessions), e.g. ec2:CreateNetworkInterface for \
+lambda:CreateFunction with a VPC configuration. The file uses the schema of the built-in maps: \
+{\"Name\": \"<service>\", \"Operations\": [{\"Name\": \"<Operation>\", \"FasOperations\": \
+[{\"Service\": \"<service>\", \"Operation\": \"<Operation>\", \"Context\": {}}]}]}. It is merged \
+with the built-in maps and those of the project configuration. Can be repeated."
+        )]
+        fas_map: Vec<PathBuf>,
+
+        /// Project configuration file
+        #[arg(long = "config", value_name = "PATH", long_help = CONFIG_LONG_HELP)]
+        config: Option<PathBuf>,
+    },
+
+    /// Validates IAM policy documents offline
+    #[command(long_about = "\
+Validates IAM policy documents against the IAM policy grammar, IAM quotas and the AWS service \
+reference, similar to IAM Access Analyzer policy validation but without calling AWS. Reports \
+invalid actions, resources that do not match the action's resource types, unsupported condition \
+keys, duplicate Sids, size quotas and risky use of wildcards and NotAction. \
+Files may contain a single policy document or the output of generate-policies.\n\n\
+Exits with code 2 if any finding of type ERROR is reported.")]
+    ValidatePolicies {
+        /// Policy files to validate
+        #[arg(required = true, num_args = 1..)]
+        policy_files: Vec<PathBuf>,
+
+        /// Enable debug logging output to stderr (most verbose)
+        #[arg(hide = true, short = 'd', long = "debug")]
+        debug: bool,
+
+        /// Format JSON output with indentation for readability
+        #[arg(short = 'p', long = "pretty")]
+        pretty: bool,
+
+        /// Whether the policies are attached as managed or inline policies
+        #[arg(
+            long = "policy-type",
+            default_value = "managed",
+            value_parser = ["managed", "inline"],
+            long_help = "Determines which IAM quotas apply. 'managed' checks each policy against the \
+managed policy size quota and the number of managed policies that can be attached to a role. \
+'inline' checks the combined size of all policies against the inline policy quota of a role."
+        )]
+        policy_type: String,
+
+        /// Disable file system caching for service references
+        #[arg(
+            long = "disable-cache",
+            long_help = "When enabled, disables file system caching for service reference data. \
+By default, service reference data is cached in the user cache directory and revalidated after 6 hours."
+        )]
+        disable_cache: bool,
+
+        /// Never fetch service reference data from the network
+        #[arg(long = "offline", long_help = OFFLINE_LONG_HELP)]
+        offline: bool,
+
+        /// Service reference snapshot to load service reference data from
+        #[arg(long = "service-reference", value_name = "PATH", long_help = SERVICE_REFERENCE_LONG_HELP)]
+        service_reference: Option<PathBuf>,
+    },
+
+    /// Derives an SDK wrapper mapping from the source code of a library
+    #[command(long_about = "\
+Analyzes the source code of an internal library and records, for each exported function and \
+method, the AWS operations it calls directly or through other functions of the library. The \
+resulting SDK wrapper mapping can be published with the library and passed to extract-sdk-calls \
+and generate-policies of applications with --sdk-wrapper, so that they get the permissions of the \
+library calls without the library's source code. Parameters that wrappers pass unchanged to SDK \
+calls are mapped to the operations' input members. Prints the mapping to stdout unless --output \
+is given.")]
+    DeriveSdkWrappers {
+        /// Root directory of the library
+        library_root: PathBuf,
+
+        /// Override programming language detection
+        #[arg(
+            short = 'l',
+            long = "language",
+            long_help = "Only analyze source files of this language: python, typescript, \
+javascript or go. Required if the library contains source files of several languages."
+        )]
+        language: Option<String>,
+
+        /// SDK wrapper mapping of a library used by the analyzed library
+        #[arg(long = "sdk-wrapper", value_name = "PATH", long_help = SDK_WRAPPER_LONG_HELP)]
+        sdk_wrapper: Vec<PathBuf>,
+
+        /// File to write the mapping to
+        #[arg(short = 'o', long = "output", value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Enable debug logging output to stderr (most verbose)
+        #[arg(hide = true, short = 'd', long = "debug")]
+        debug: bool,
+
+        /// Format JSON output with indentation for readability
+        #[arg(short = 'p', long = "pretty")]
+        pretty: bool,
+    },
+
+    /// Downloads a service reference snapshot for offline use
+    #[command(long_about = "\
+Downloads the service reference mapping index and the service reference data of all (or the \
+given) AWS services into a snapshot, with a manifest of SHA-256 checksums. Pass the snapshot to \
+generate-policies or validate-policies with --service-reference, together with --offline, to run \
+without network access. Prints the manifest to stdout.")]
+    DownloadServiceReference {
+        /// Snapshot directory or bundle file to write
+        #[arg(
+            short = 'o',
+            long = "output",
+            value_name = "PATH",
+            long_help = "Where to write the snapshot. Paths ending in .json are written as a single \
+bundle file, other paths as a directory containing manifest.json, mapping.json and \
+services/<service>.json."
+        )]
+        output: PathBuf,
+
+        /// Services to download (default: all services)
+        #[arg(long = "services", num_args = 1.., value_name = "SERVICES")]
+        services: Option<Vec<String>>,
+
+        /// Enable debug logging output to stderr (most verbose)
+        #[arg(hide = true, short = 'd', long = "debug")]
+        debug: bool,
+
+        /// Format JSON output with indentation for readability
+        #[arg(short = 'p', long = "pretty")]
+        pretty: bool,
+    },
+
+    /// Inspect, prune and pre-populate the service reference cache
+    #[command(long_about = CACHE_LONG_HELP)]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
     },
 
     /// Start MCP server
@@ -348,8 +1005,87 @@ for direct integration with IDEs and tools. 'http' starts an HTTP server for net
         /// Port number for HTTP transport (ignored for stdio transport)
         #[arg(short = 'p', long = "port", default_value_t = MCP_HTTP_DEFAULT_PORT,
               long_help = "Port number to bind the HTTP server to when using HTTP transport. \
-Only used when --transport=http. The server will bind to 127.0.0.1 (localhost) on the specified port.")]
+Only used when --transport=http. The server binds to --bind-address on the specified port.")]
         port: u16,
+
+        /// Address to bind the HTTP server to
+        #[arg(long = "bind-address", value_name = "ADDRESS", default_value_t = DEFAULT_BIND_ADDRESS,
+              long_help = "Address to bind the HTTP server to, e.g. 0.0.0.0 to accept connections from \
+a dev container host or a remote workspace. Binding to an address other than a loopback address \
+requires --auth-token or --tls-client-ca.")]
+        bind_address: IpAddr,
+
+        /// Bearer token clients must send
+        #[arg(
+            long = "auth-token",
+            value_name = "TOKEN",
+            env = "IAM_POLICY_AUTOPILOT_MCP_AUTH_TOKEN",
+            hide_env_values = true,
+            long_help = "Token HTTP clients must send in an 'Authorization: Bearer <TOKEN>' header. \
+Prefer the IAM_POLICY_AUTOPILOT_MCP_AUTH_TOKEN environment variable, which other users cannot see \
+in the process list."
+        )]
+        auth_token: Option<String>,
+
+        /// PEM file with the TLS certificate chain of the server
+        #[arg(
+            long = "tls-certificate",
+            value_name = "PATH",
+            requires = "tls_private_key",
+            long_help = "PEM file with the certificate chain of the server. When set, the HTTP \
+transport serves HTTPS. Requires --tls-private-key."
+        )]
+        tls_certificate: Option<PathBuf>,
+
+        /// PEM file with the private key of the TLS certificate
+        #[arg(
+            long = "tls-private-key",
+            value_name = "PATH",
+            requires = "tls_certificate"
+        )]
+        tls_private_key: Option<PathBuf>,
+
+        /// PEM file with the CA certificates of TLS client certificates
+        #[arg(
+            long = "tls-client-ca",
+            value_name = "PATH",
+            requires = "tls_certificate",
+            long_help = "PEM file with the CA certificates issuing client certificates. When set, \
+HTTPS clients must present a certificate issued by one of them (mutual TLS). Requires --tls-certificate."
+        )]
+        tls_client_ca: Option<PathBuf>,
+
+        /// Origin allowed to send requests, in addition to loopback origins
+        #[arg(
+            long = "allowed-origin",
+            value_name = "ORIGIN",
+            long_help = "Origin accepted in the Origin header of HTTP requests, e.g. \
+https://workspace.example.com. Requests with an Origin header are rejected unless it is a loopback \
+origin or an allowed origin, which protects against DNS rebinding. Repeat the flag to allow several origins."
+        )]
+        allowed_origin: Vec<String>,
+
+        /// Maximum size of an HTTP request body, in bytes
+        #[arg(long = "max-request-size", value_name = "BYTES", default_value_t = DEFAULT_MAX_REQUEST_SIZE)]
+        max_request_size: usize,
+
+        /// Disable the tools that change the AWS account
+        #[arg(
+            long = "read-only",
+            long_help = "Disable the tools that change resources of the AWS account, such as \
+fix_access_denied. Recommended when the HTTP transport is reachable beyond localhost."
+        )]
+        read_only: bool,
+
+        /// Directory the source files read by the tools must be within
+        #[arg(
+            long = "workspace-root",
+            value_name = "DIR",
+            long_help = "Directory the source files read by the tools must be within, after resolving \
+symlinks. Repeat the flag to allow several directories. Source files must also be within the roots \
+declared by the MCP client, if any. Without workspace roots, the tools read any file the server can read."
+        )]
+        workspace_root: Vec<PathBuf>,
     },
 
     #[command(
@@ -363,6 +1099,74 @@ Only used when --transport=http. The server will bind to 127.0.0.1 (localhost) o
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum CacheCommands {
+    /// Print the location, size and state of every cache entry
+    #[command(long_about = "\
+Prints the cache directory and every cached service reference with its size, fetch time, mapping \
+index version and state as JSON. Fresh entries are used as is, stale entries are revalidated on \
+their next use, outdated entries were fetched with an older mapping index and corrupt entries fail \
+their checksum.")]
+    Status {
+        /// Enable debug logging output to stderr (most verbose)
+        #[arg(hide = true, short = 'd', long = "debug")]
+        debug: bool,
+
+        /// Format JSON output with indentation for readability
+        #[arg(short = 'p', long = "pretty")]
+        pretty: bool,
+    },
+
+    /// Remove corrupt and outdated cache entries
+    Prune {
+        /// Remove all cache entries
+        #[arg(long = "all")]
+        all: bool,
+
+        /// Enable debug logging output to stderr (most verbose)
+        #[arg(hide = true, short = 'd', long = "debug")]
+        debug: bool,
+
+        /// Format JSON output with indentation for readability
+        #[arg(short = 'p', long = "pretty")]
+        pretty: bool,
+    },
+
+    /// Fetch service references into the cache
+    #[command(long_about = "\
+Fetches the service reference data of all (or the given) AWS services into the cache, so that \
+later runs, including --offline runs, do not need to download it. Fresh entries are kept and stale \
+entries are revalidated. The service reference endpoint, CA certificate and proxy of the project \
+configuration apply.")]
+    Warm {
+        /// Services to cache (default: all services)
+        #[arg(long = "services", num_args = 1.., value_name = "SERVICES")]
+        services: Option<Vec<String>>,
+
+        /// Project configuration file (default: discover .iam-autopilot.toml)
+        #[arg(long = "config", value_name = "PATH", long_help = CONFIG_LONG_HELP)]
+        config_file: Option<PathBuf>,
+
+        /// Enable debug logging output to stderr (most verbose)
+        #[arg(hide = true, short = 'd', long = "debug")]
+        debug: bool,
+
+        /// Format JSON output with indentation for readability
+        #[arg(short = 'p', long = "pretty")]
+        pretty: bool,
+    },
+}
+
+impl CacheCommands {
+    fn debug(&self) -> bool {
+        match self {
+            Self::Status { debug, .. } | Self::Prune { debug, .. } | Self::Warm { debug, .. } => {
+                *debug
+            }
+        }
+    }
+}
+
 /// Initialize logging based on configuration
 fn init_logging(debug: bool) -> Result<()> {
     let log_level = if debug {
@@ -383,9 +1187,14 @@ fn init_logging(debug: bool) -> Result<()> {
 }
 
 /// Handle the extract-sdk-calls subcommand
-async fn handle_extract_sdk_calls(config: &SharedConfig) -> Result<()> {
+async fn handle_extract_sdk_calls(config: SharedConfig) -> Result<()> {
     info!("Running extract-sdk-calls command");
 
+    let mut config = config;
+    if let Some(project) = load_project_config(config.config_file.as_deref())? {
+        config.apply_project_config(&project)?;
+    }
+
     // Validate configuration
     config
         .validate()
@@ -401,16 +1210,27 @@ async fn handle_extract_sdk_calls(config: &SharedConfig) -> Result<()> {
         source_files: config.source_files.to_owned(),
         language: config.language.to_owned(),
         service_hints,
+        sdk_wrappers: config.sdk_wrappers.to_owned(),
+        entry_points: config.entry_points.to_owned(),
+        include_tests: config.include_tests.unwrap_or_default(),
     })
     .await?;
 
-    let json_output =
-        SdkMethodCall::serialize_list(&results.methods, config.full_output, config.pretty)
-            .context("Failed to output extracted operations")?;
+    output::warn_diagnostics(&results.metadata.diagnostics, None);
+    if let Some(path) = &config.sarif {
+        sarif::write_sarif(path, &results.metadata.diagnostics)?;
+    }
+
+    let json_output = SdkMethodCall::serialize_list(
+        &results.methods,
+        config.full_output,
+        config.pretty.unwrap_or_default(),
+    )
+    .context("Failed to output extracted operations")?;
 
     // Output to stdout (not using println! to avoid extra newline in compact mode)
     print!("{}", json_output);
-    if config.pretty {
+    if config.pretty.unwrap_or_default() {
         println!(); // Add newline for pretty output
     }
 
@@ -419,9 +1239,14 @@ async fn handle_extract_sdk_calls(config: &SharedConfig) -> Result<()> {
 }
 
 /// Handle the generate-policies subcommand
-async fn handle_generate_policy(config: &GeneratePolicyCliConfig) -> Result<()> {
+async fn handle_generate_policy(config: GeneratePolicyCliConfig) -> Result<()> {
     info!("Running generate-policies command");
 
+    let mut config = config;
+    if let Some(project) = load_project_config(config.shared.config_file.as_deref())? {
+        config.apply_project_config(&project)?;
+    }
+
     // Validate configuration
     config
         .validate()
@@ -437,69 +1262,370 @@ async fn handle_generate_policy(config: &GeneratePolicyCliConfig) -> Result<()>
             service_names: names.clone(),
         });
 
-    let result = generate_policies(&GeneratePolicyConfig {
+    let generate_config = GeneratePolicyConfig {
         extract_sdk_calls_config: ExtractSdkCallsConfig {
             source_files: config.shared.source_files.to_owned(),
             language: config.shared.language.to_owned(),
             service_hints,
+            sdk_wrappers: config.shared.sdk_wrappers.to_owned(),
+            entry_points: config.shared.entry_points.to_owned(),
+            include_tests: config.shared.include_tests.unwrap_or_default(),
         },
-        aws_context: AwsContext::new(config.region.clone(), config.account.clone()),
+        aws_context: AwsContext::new(
+            config.region.clone().unwrap_or_else(|| "*".to_string()),
+            config.account.clone().unwrap_or_else(|| "*".to_string()),
+        ),
         individual_policies: config.individual_policies,
-        minimize_policy_size: config.minimal_policy_size,
-        disable_file_system_cache: config.disable_cache,
-        generate_explanations: config.explain,
-    })
-    .await?;
+        minimize_policy_size: config.minimal_policy_size.unwrap_or_default(),
+        disable_file_system_cache: config.disable_cache.unwrap_or_default(),
+        generate_explanations: config.explain.unwrap_or_default(),
+        policy_options: config.policy_options()?,
+        service_reference: config.service_reference.clone(),
+        session: None,
+        fas_map_extensions: config.fas_maps.clone(),
+        progress: None,
+        cancellation: None,
+    };
+
+    if let Some(path) = config.lambda_functions() {
+        return handle_generate_function_policies(&config, &generate_config, path).await;
+    }
+
+    if let Some(manifest) = config.workload_manifest()? {
+        return handle_generate_workload_policies(&config, &generate_config, &manifest).await;
+    }
+
+    let result = generate_policies(&generate_config).await?;
+
+    output::warn_diagnostics(&result.diagnostics, None);
+    output::warn_policy_findings(&result.policies, None);
+    if let Some(path) = &config.shared.sarif {
+        sarif::write_sarif(path, &result.diagnostics)?;
+    }
 
     if config.individual_policies {
         // Output individual policies
         trace!("Outputting {} individual policies", result.policies.len());
-        output::output_iam_policies(result, None, config.shared.pretty)
+        output::output_iam_policies(result, None, config.shared.pretty.unwrap_or_default())
             .context("Failed to output individual IAM policies")?;
     } else {
         // Default behavior: output merged policy with optional upload
-        let upload_result = if config.upload_policies.is_some() {
-            trace!("Uploading policies to AWS IAM");
-
-            let uploader = PolicyUploader::new()
-                .await
-                .context("Failed to create policy uploader")?;
-
-            let custom_name = config.upload_policies.as_deref().filter(|s| !s.is_empty());
-            let batch_response = uploader
-                .upload_policies(&result.policies, custom_name)
-                .await
-                .context("Failed to upload policies to AWS IAM")?;
-
-            debug!(
-                "Upload completed: {} successful, {} failed",
-                batch_response.successful.len(),
-                batch_response.failed.len()
-            );
-
-            // Log upload results
-            for upload in &batch_response.successful {
-                debug!(
-                    "Successfully uploaded policy: {} (ARN: {})",
-                    upload.policy_name, upload.policy_arn
-                );
+        let upload_result = match config.upload_policies.as_deref() {
+            Some(name) => {
+                let custom_name = Some(name).filter(|s| !s.is_empty());
+                Some(upload_policies(&result.policies, custom_name).await?)
             }
-            for (index, error) in &batch_response.failed {
-                debug!("Failed to upload policy {}: {}", index, error);
-            }
-
-            Some(batch_response)
-        } else {
-            None
+            None => None,
         };
 
-        output::output_iam_policies(result, upload_result, config.shared.pretty)
-            .context("Failed to output merged IAM policy")?
+        output::output_iam_policies(
+            result,
+            upload_result,
+            config.shared.pretty.unwrap_or_default(),
+        )
+        .context("Failed to output merged IAM policy")?
     }
 
     Ok(())
 }
 
+/// Generate, upload and output a separate set of policies per workload
+async fn handle_generate_workload_policies(
+    config: &GeneratePolicyCliConfig,
+    generate_config: &GeneratePolicyConfig,
+    manifest: &WorkloadManifest,
+) -> Result<()> {
+    let result = generate_workload_policies(generate_config, manifest).await?;
+
+    if !result.unassigned_files.is_empty() {
+        output::warn(&format!(
+            "{} source file(s) do not belong to any workload; see \"UnassignedFiles\" in the output",
+            result.unassigned_files.len()
+        ));
+    }
+
+    let mut upload_results = BTreeMap::new();
+    for (name, workload) in &result.workloads {
+        let owner = format!("workload '{}'", name);
+        output::warn_diagnostics(&workload.result.diagnostics, Some(&owner));
+        output::warn_policy_findings(&workload.result.policies, Some(&owner));
+
+        if let Some(prefix) = config.upload_policies.as_deref() {
+            // Each workload's policies are named after the workload
+            let custom_name = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}-{}", prefix, name)
+            };
+            let batch_response =
+                upload_policies(&workload.result.policies, Some(&custom_name)).await?;
+            upload_results.insert(name.clone(), batch_response);
+        }
+    }
+
+    if let Some(path) = &config.shared.sarif {
+        let diagnostics: Vec<_> = result
+            .workloads
+            .values()
+            .flat_map(|workload| workload.result.diagnostics.iter().cloned())
+            .collect();
+        sarif::write_sarif(path, &diagnostics)?;
+    }
+
+    output::output_workload_policies(
+        result,
+        upload_results,
+        config.shared.pretty.unwrap_or_default(),
+    )
+    .context("Failed to output workload IAM policies")
+}
+
+/// Generate, upload and output a separate set of policies per Lambda function
+async fn handle_generate_function_policies(
+    config: &GeneratePolicyCliConfig,
+    generate_config: &GeneratePolicyConfig,
+    path: &Path,
+) -> Result<()> {
+    let functions = discover_lambda_functions(path)
+        .with_context(|| format!("Failed to read Lambda functions from {}", path.display()))?;
+    if functions.is_empty() {
+        anyhow::bail!("No Lambda functions declared in {}", path.display());
+    }
+
+    let result = generate_function_policies(generate_config, &functions).await?;
+
+    if !result.unresolved_functions.is_empty() {
+        output::warn(&format!(
+            "the handler of {} Lambda function(s) was not found among the source files; see \"UnresolvedFunctions\" in the output",
+            result.unresolved_functions.len()
+        ));
+    }
+
+    let mut upload_results = BTreeMap::new();
+    for (name, function) in &result.functions {
+        let owner = format!("Lambda function '{}'", name);
+        output::warn_diagnostics(&function.result.diagnostics, Some(&owner));
+        output::warn_policy_findings(&function.result.policies, Some(&owner));
+
+        if let Some(prefix) = config.upload_policies.as_deref() {
+            // Each function's policies are named after the function
+            let custom_name = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}-{}", prefix, name)
+            };
+            let batch_response =
+                upload_policies(&function.result.policies, Some(&custom_name)).await?;
+            upload_results.insert(name.clone(), batch_response);
+        }
+    }
+
+    if let Some(path) = &config.shared.sarif {
+        let diagnostics: Vec<_> = result
+            .functions
+            .values()
+            .flat_map(|function| function.result.diagnostics.iter().cloned())
+            .collect();
+        sarif::write_sarif(path, &diagnostics)?;
+    }
+
+    output::output_function_policies(
+        result,
+        upload_results,
+        config.shared.pretty.unwrap_or_default(),
+    )
+    .context("Failed to output Lambda function IAM policies")
+}
+
+/// Upload policies to AWS IAM
+async fn upload_policies(
+    policies: &[PolicyWithMetadata],
+    custom_name: Option<&str>,
+) -> Result<BatchUploadResponse> {
+    trace!("Uploading policies to AWS IAM");
+
+    let uploader = PolicyUploader::new()
+        .await
+        .context("Failed to create policy uploader")?;
+
+    let batch_response = uploader
+        .upload_policies(policies, custom_name)
+        .await
+        .context("Failed to upload policies to AWS IAM")?;
+
+    debug!(
+        "Upload completed: {} successful, {} failed",
+        batch_response.successful.len(),
+        batch_response.failed.len()
+    );
+
+    // Log upload results
+    for upload in &batch_response.successful {
+        debug!(
+            "Successfully uploaded policy: {} (ARN: {})",
+            upload.policy_name, upload.policy_arn
+        );
+    }
+    for (index, error) in &batch_response.failed {
+        debug!("Failed to upload policy {}: {}", index, error);
+    }
+
+    Ok(batch_response)
+}
+
+/// Read policy documents from a file
+///
+/// The file either contains a single policy document, or the output of generate-policies,
+/// in which case every policy in it is returned.
+fn read_policy_documents(path: &PathBuf) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read policy file: {}", path.display()))?;
+
+    if let Ok(serde_json::Value::Object(object)) = serde_json::from_str(&content) {
+        if let Some(serde_json::Value::Array(policies)) = object.get("Policies") {
+            return Ok(policies
+                .iter()
+                .map(|policy| policy.get("Policy").unwrap_or(policy).to_string())
+                .collect());
+        }
+    }
+    Ok(vec![content])
+}
+
+/// Handle the validate-policies subcommand
+async fn handle_validate_policies(config: &ValidatePoliciesCliConfig) -> Result<ExitCode> {
+    info!("Running validate-policies command");
+
+    let mut policies = Vec::new();
+    for path in &config.policy_files {
+        policies.extend(read_policy_documents(path)?);
+    }
+
+    let result = validate_policies(&ValidatePoliciesConfig {
+        policies,
+        policy_kind: config.policy_kind,
+        disable_file_system_cache: config.disable_cache,
+        service_reference: config.service_reference.clone(),
+    })
+    .await?;
+
+    let exit_code = if result.has_errors() {
+        ExitCode::Error
+    } else {
+        ExitCode::Success
+    };
+
+    output::output_validation_result(&result, config.pretty)
+        .context("Failed to output validation findings")?;
+
+    Ok(exit_code)
+}
+
+/// Handle the download-service-reference subcommand
+async fn handle_download_service_reference(
+    config: &DownloadServiceReferenceCliConfig,
+) -> Result<()> {
+    info!("Running download-service-reference command");
+
+    let manifest = download_service_reference_snapshot(&DownloadServiceReferenceSnapshotConfig {
+        output: config.output.clone(),
+        services: config.services.clone(),
+    })
+    .await?;
+
+    output::note(&format!(
+        "wrote {} service references to {}",
+        manifest.services.len(),
+        config.output.display()
+    ));
+    output::output_snapshot_manifest(&manifest, config.pretty)
+        .context("Failed to output snapshot manifest")
+}
+
+/// Handle the derive-sdk-wrappers subcommand
+async fn handle_derive_sdk_wrappers(
+    config: &DeriveSdkWrappersConfig,
+    output: Option<&Path>,
+    pretty: bool,
+) -> Result<()> {
+    info!("Running derive-sdk-wrappers command");
+
+    let mapping = derive_sdk_wrappers(config).await?;
+    if mapping.wrappers.is_empty() {
+        output::warn(&format!(
+            "no exported function of {} calls AWS",
+            config.library_root.display()
+        ));
+    }
+
+    match output {
+        Some(path) => {
+            let json = if pretty {
+                JsonProvider::stringify_pretty(&mapping)
+            } else {
+                JsonProvider::stringify(&mapping)
+            }
+            .context("Failed to serialize SDK wrapper mapping")?;
+            std::fs::write(path, json)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            output::note(&format!(
+                "wrote {} SDK wrappers to {}",
+                mapping.wrappers.len(),
+                path.display()
+            ));
+            Ok(())
+        }
+        None => output::output_json(&mapping, pretty).context("Failed to output SDK wrappers"),
+    }
+}
+
+/// Handle the cache subcommands
+async fn handle_cache(command: CacheCommands) -> Result<()> {
+    info!("Running cache command");
+
+    match command {
+        CacheCommands::Status { pretty, .. } => {
+            let status = service_reference_cache_status(&ServiceReferenceCacheConfig::default())?;
+            output::output_json(&status, pretty).context("Failed to output cache status")
+        }
+        CacheCommands::Prune { all, pretty, .. } => {
+            let result = prune_service_reference_cache(&PruneServiceReferenceCacheConfig {
+                cache_directory: None,
+                all,
+            })?;
+            output::note(&format!(
+                "removed {} cache entries ({} bytes) from {}",
+                result.removed.len(),
+                result.freed_bytes,
+                result.directory.display()
+            ));
+            output::output_json(&result, pretty).context("Failed to output prune result")
+        }
+        CacheCommands::Warm {
+            services,
+            config_file,
+            pretty,
+            ..
+        } => {
+            let service_reference = load_project_config(config_file.as_deref())?
+                .map(|project| project.service_reference_source())
+                .unwrap_or_default();
+            let result = warm_service_reference_cache(&WarmServiceReferenceCacheConfig {
+                cache_directory: None,
+                services,
+                service_reference,
+            })
+            .await?;
+            output::note(&format!(
+                "cached {} service references in {}",
+                result.services.len(),
+                result.directory.display()
+            ));
+            output::output_json(&result, pretty).context("Failed to output warm result")
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
@@ -529,9 +1655,18 @@ async fn main() {
             source_files,
             debug,
             pretty,
+            no_pretty,
             language,
             full_output,
             service_hints,
+            sdk_wrapper,
+            entry_point,
+            detect_entry_points,
+            no_detect_entry_points,
+            include_tests,
+            no_include_tests,
+            sarif,
+            config,
         } => {
             // Initialize logging
             if let Err(e) = init_logging(debug) {
@@ -541,13 +1676,22 @@ async fn main() {
 
             let config = SharedConfig {
                 source_files,
-                pretty,
+                pretty: cli_flag(pretty, no_pretty),
                 language,
                 full_output,
                 service_hints,
+                config_file: config,
+                sdk_wrappers: sdk_wrapper,
+                entry_points: EntryPoints {
+                    detect: detect_entry_points,
+                    declared: entry_point,
+                },
+                detect_entry_points: cli_flag(detect_entry_points, no_detect_entry_points),
+                include_tests: cli_flag(include_tests, no_include_tests),
+                sarif,
             };
 
-            match handle_extract_sdk_calls(&config).await {
+            match handle_extract_sdk_calls(config).await {
                 Ok(()) => ExitCode::Success,
                 Err(e) => {
                     print_cli_command_error(e);
@@ -560,6 +1704,7 @@ async fn main() {
             source_files,
             debug,
             pretty,
+            no_pretty,
             language,
             full_output,
             region,
@@ -567,9 +1712,28 @@ async fn main() {
             individual_policies,
             upload_policies,
             minimal_policy_size,
+            no_minimal_policy_size,
             disable_cache,
+            enable_cache,
             service_hints,
+            sdk_wrapper,
+            entry_point,
+            detect_entry_points,
+            no_detect_entry_points,
+            include_tests,
+            no_include_tests,
+            sarif,
             explain,
+            no_explain,
+            resource_cutoff,
+            wildcard_mode,
+            workload,
+            lambda_functions,
+            offline,
+            no_offline,
+            service_reference,
+            fas_map,
+            config,
         } => {
             // Initialize logging
             if let Err(e) = init_logging(debug) {
@@ -580,21 +1744,45 @@ async fn main() {
             let config = GeneratePolicyCliConfig {
                 shared: SharedConfig {
                     source_files,
-                    pretty,
+                    pretty: cli_flag(pretty, no_pretty),
                     language,
                     full_output,
                     service_hints,
+                    config_file: config,
+                    sdk_wrappers: sdk_wrapper,
+                    entry_points: EntryPoints {
+                        detect: detect_entry_points,
+                        declared: entry_point,
+                    },
+                    detect_entry_points: cli_flag(detect_entry_points, no_detect_entry_points),
+                    include_tests: cli_flag(include_tests, no_include_tests),
+                    sarif,
                 },
                 region,
                 account,
                 individual_policies,
                 upload_policies,
-                minimal_policy_size,
-                disable_cache,
-                explain,
+                minimal_policy_size: cli_flag(minimal_policy_size, no_minimal_policy_size),
+                disable_cache: cli_flag(disable_cache, enable_cache),
+                explain: cli_flag(explain, no_explain),
+                resource_cutoff,
+                wildcard_mode,
+                project_policy_options: PolicyGenerationOptions::default(),
+                workloads: workload,
+                project_workloads: None,
+                project_root: None,
+                lambda_functions,
+                project_lambda_functions: None,
+                service_reference: ServiceReferenceSource {
+                    snapshot: service_reference,
+                    offline,
+                    ..ServiceReferenceSource::default()
+                },
+                offline: cli_flag(offline, no_offline),
+                fas_maps: fas_map,
             };
 
-            match handle_generate_policy(&config).await {
+            match handle_generate_policy(config).await {
                 Ok(()) => ExitCode::Success,
                 Err(e) => {
                     print_cli_command_error(e);
@@ -603,8 +1791,151 @@ async fn main() {
             }
         }
 
-        Commands::McpServer { transport, port } => {
-            match start_mcp_server(transport, port).await {
+        Commands::ValidatePolicies {
+            policy_files,
+            debug,
+            pretty,
+            policy_type,
+            disable_cache,
+            offline,
+            service_reference,
+        } => {
+            // Initialize logging
+            if let Err(e) = init_logging(debug) {
+                eprintln!("iam-policy-autopilot: Failed to initialize logging: {}", e);
+                process::exit(1);
+            }
+
+            let config = ValidatePoliciesCliConfig {
+                policy_files,
+                pretty,
+                policy_kind: if policy_type == "inline" {
+                    PolicyKind::Inline
+                } else {
+                    PolicyKind::Managed
+                },
+                disable_cache,
+                service_reference: ServiceReferenceSource {
+                    snapshot: service_reference,
+                    offline,
+                    ..ServiceReferenceSource::default()
+                },
+            };
+
+            match handle_validate_policies(&config).await {
+                Ok(exit_code) => exit_code,
+                Err(e) => {
+                    print_cli_command_error(e);
+                    ExitCode::Duplicate // Exit code 1 for validate-policies errors
+                }
+            }
+        }
+
+        Commands::DeriveSdkWrappers {
+            library_root,
+            language,
+            sdk_wrapper,
+            output,
+            debug,
+            pretty,
+        } => {
+            // Initialize logging
+            if let Err(e) = init_logging(debug) {
+                eprintln!("iam-policy-autopilot: Failed to initialize logging: {}", e);
+                process::exit(1);
+            }
+
+            let config = DeriveSdkWrappersConfig {
+                library_root,
+                language,
+                sdk_wrappers: sdk_wrapper,
+            };
+
+            match handle_derive_sdk_wrappers(&config, output.as_deref(), pretty).await {
+                Ok(()) => ExitCode::Success,
+                Err(e) => {
+                    print_cli_command_error(e);
+                    ExitCode::Duplicate // Exit code 1 for derive-sdk-wrappers errors
+                }
+            }
+        }
+
+        Commands::DownloadServiceReference {
+            output,
+            services,
+            debug,
+            pretty,
+        } => {
+            // Initialize logging
+            if let Err(e) = init_logging(debug) {
+                eprintln!("iam-policy-autopilot: Failed to initialize logging: {}", e);
+                process::exit(1);
+            }
+
+            let config = DownloadServiceReferenceCliConfig {
+                output,
+                services,
+                pretty,
+            };
+
+            match handle_download_service_reference(&config).await {
+                Ok(()) => ExitCode::Success,
+                Err(e) => {
+                    print_cli_command_error(e);
+                    ExitCode::Duplicate // Exit code 1 for download-service-reference errors
+                }
+            }
+        }
+
+        Commands::Cache { command } => {
+            // Initialize logging
+            if let Err(e) = init_logging(command.debug()) {
+                eprintln!("iam-policy-autopilot: Failed to initialize logging: {}", e);
+                process::exit(1);
+            }
+
+            match handle_cache(command).await {
+                Ok(()) => ExitCode::Success,
+                Err(e) => {
+                    print_cli_command_error(e);
+                    ExitCode::Duplicate // Exit code 1 for cache errors
+                }
+            }
+        }
+
+        Commands::McpServer {
+            transport,
+            port,
+            bind_address,
+            auth_token,
+            tls_certificate,
+            tls_private_key,
+            tls_client_ca,
+            allowed_origin,
+            max_request_size,
+            read_only,
+            workspace_root,
+        } => {
+            let tls = tls_certificate
+                .zip(tls_private_key)
+                .map(|(certificate, private_key)| TlsConfig {
+                    certificate,
+                    private_key,
+                    client_ca_certificate: tls_client_ca,
+                });
+            let config = McpServerConfig {
+                read_only,
+                workspace_roots: workspace_root,
+                http: HttpTransportConfig {
+                    bind_address,
+                    port,
+                    auth_token,
+                    tls,
+                    allowed_origins: allowed_origin,
+                    max_request_size,
+                },
+            };
+            match start_mcp_server(transport, config).await {
                 Ok(()) => ExitCode::Success,
                 Err(e) => {
                     print_cli_command_error(e);
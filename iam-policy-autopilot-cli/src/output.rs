@@ -1,9 +1,18 @@
 use anyhow::{Context, Result};
 use iam_policy_autopilot_access_denied::{DenialType, PlanResult};
-use iam_policy_autopilot_policy_generation::api::model::GeneratePoliciesResult;
+use iam_policy_autopilot_policy_generation::api::model::{
+    FunctionPoliciesResult, GenerateFunctionPoliciesResult, GeneratePoliciesResult,
+    GenerateWorkloadPoliciesResult, ServiceReferenceSnapshotManifest, ValidatePoliciesResult,
+    WorkloadPoliciesResult,
+};
+use iam_policy_autopilot_policy_generation::{
+    Diagnostic, DiagnosticSeverity, PolicyWithMetadata, StatementFinding,
+};
 use iam_policy_autopilot_tools::BatchUploadResponse;
 use log::debug;
+use std::collections::BTreeMap;
 use std::io::{self, Write};
+use std::path::PathBuf;
 
 pub(crate) fn note(msg: &str) {
     let _ = writeln!(io::stderr(), "iam-policy-autopilot: {}", msg);
@@ -202,3 +211,289 @@ pub(crate) fn output_iam_policies(
     debug!("Policy output JSON written to stdout");
     Ok(())
 }
+
+/// Policies of a single workload in the workload output
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct WorkloadPolicyOutput {
+    /// The workload's source files, policies and explanations
+    #[serde(flatten)]
+    result: WorkloadPoliciesResult,
+    /// Upload results (only present when --upload-policies is used)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upload_result: Option<BatchUploadResponse>,
+}
+
+/// Workload policy output structure
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct WorkloadsOutput {
+    /// Policies of each workload, keyed by workload name
+    workloads: BTreeMap<String, WorkloadPolicyOutput>,
+    /// Source files not attributed to any workload
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unassigned_files: Vec<PathBuf>,
+}
+
+/// Output the IAM policies of each workload as JSON to stdout
+pub(crate) fn output_workload_policies(
+    result: GenerateWorkloadPoliciesResult,
+    mut upload_results: BTreeMap<String, BatchUploadResponse>,
+    pretty: bool,
+) -> Result<()> {
+    debug!(
+        "Formatting IAM policies of {} workloads as JSON (pretty: {})",
+        result.workloads.len(),
+        pretty
+    );
+
+    let workloads_output = WorkloadsOutput {
+        workloads: result
+            .workloads
+            .into_iter()
+            .map(|(name, result)| {
+                let upload_result = upload_results.remove(&name);
+                (
+                    name,
+                    WorkloadPolicyOutput {
+                        result,
+                        upload_result,
+                    },
+                )
+            })
+            .collect(),
+        unassigned_files: result.unassigned_files,
+    };
+
+    let json_output = if pretty {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify_pretty(&workloads_output)
+            .context("Failed to serialize workload policy output to pretty JSON")?
+    } else {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify(&workloads_output)
+            .context("Failed to serialize workload policy output to JSON")?
+    };
+
+    // Output to stdout (not using println! to avoid extra newline in compact mode)
+    print!("{}", json_output);
+    if pretty {
+        println!(); // Add newline for pretty output
+    }
+
+    debug!("Workload policy output JSON written to stdout");
+    Ok(())
+}
+
+/// Policies of a single Lambda function in the function output
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct FunctionPolicyOutput {
+    /// The function's handler, policies and explanations
+    #[serde(flatten)]
+    result: FunctionPoliciesResult,
+    /// Upload results (only present when --upload-policies is used)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upload_result: Option<BatchUploadResponse>,
+}
+
+/// Lambda function policy output structure
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct FunctionsOutput {
+    /// Policies of each function, keyed by function name
+    functions: BTreeMap<String, FunctionPolicyOutput>,
+    /// Functions whose handler was not found among the source files
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unresolved_functions: Vec<String>,
+}
+
+/// Output the IAM policies of each Lambda function as JSON to stdout
+pub(crate) fn output_function_policies(
+    result: GenerateFunctionPoliciesResult,
+    mut upload_results: BTreeMap<String, BatchUploadResponse>,
+    pretty: bool,
+) -> Result<()> {
+    debug!(
+        "Formatting IAM policies of {} Lambda functions as JSON (pretty: {})",
+        result.functions.len(),
+        pretty
+    );
+
+    let functions_output = FunctionsOutput {
+        functions: result
+            .functions
+            .into_iter()
+            .map(|(name, result)| {
+                let upload_result = upload_results.remove(&name);
+                (
+                    name,
+                    FunctionPolicyOutput {
+                        result,
+                        upload_result,
+                    },
+                )
+            })
+            .collect(),
+        unresolved_functions: result.unresolved_functions,
+    };
+
+    let json_output = if pretty {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify_pretty(&functions_output)
+            .context("Failed to serialize function policy output to pretty JSON")?
+    } else {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify(&functions_output)
+            .context("Failed to serialize function policy output to JSON")?
+    };
+
+    // Output to stdout (not using println! to avoid extra newline in compact mode)
+    print!("{}", json_output);
+    if pretty {
+        println!(); // Add newline for pretty output
+    }
+
+    debug!("Function policy output JSON written to stdout");
+    Ok(())
+}
+
+/// Summarize least-privilege findings of generated policies on stderr
+///
+/// The full findings are part of the JSON output; this only tells the reviewer where to look.
+///
+/// `owner` names what the policies were generated for, e.g. `workload 'ingest'`.
+pub(crate) fn warn_policy_findings(policies: &[PolicyWithMetadata], owner: Option<&str>) {
+    let owner = owner
+        .map(|owner| format!(" of {}", owner))
+        .unwrap_or_default();
+    for (index, policy) in policies.iter().enumerate() {
+        let Some(findings) = policy.findings.as_ref().filter(|f| !f.is_empty()) else {
+            continue;
+        };
+
+        let (mut wildcards, mut sensitive) = (0, 0);
+        for finding in findings.findings() {
+            match finding {
+                StatementFinding::WildcardResource { .. } => wildcards += 1,
+                StatementFinding::SensitiveAccessLevel { .. } => sensitive += 1,
+            }
+        }
+
+        warn(&format!(
+            "policy {}{} has breadth score {}/100 with {} wildcard resource(s) and {} write or permissions-management action(s); see \"Findings\" in the output",
+            index + 1,
+            owner,
+            findings.breadth_score,
+            wildcards,
+            sensitive
+        ));
+    }
+}
+
+/// Report extraction diagnostics on stderr
+///
+/// Warnings and errors are printed one by one; informational diagnostics, e.g. excluded test
+/// files, are only counted since they are expected. All of them are part of the SARIF log.
+///
+/// `owner` names what the diagnostics were reported for, e.g. `workload 'ingest'`.
+pub(crate) fn warn_diagnostics(diagnostics: &[Diagnostic], owner: Option<&str>) {
+    let owner = owner
+        .map(|owner| format!(" of {}", owner))
+        .unwrap_or_default();
+    let mut infos = 0;
+    for diagnostic in diagnostics {
+        if diagnostic.severity == DiagnosticSeverity::Info {
+            infos += 1;
+            continue;
+        }
+        let location = diagnostic
+            .location
+            .as_ref()
+            .map(|location| format!("{}: ", location.to_gnu_format()))
+            .unwrap_or_default();
+        let suggested_fix = diagnostic
+            .suggested_fix
+            .as_ref()
+            .map(|fix| format!("; suggested fix: {}", fix))
+            .unwrap_or_default();
+        warn(&format!(
+            "{}{} [{}{}]{}",
+            location, diagnostic.message, diagnostic.code, owner, suggested_fix
+        ));
+    }
+    if infos > 0 {
+        note(&format!(
+            "{} informational diagnostic(s){}, e.g. excluded test files or unreachable SDK calls; pass --sarif to list them",
+            infos, owner
+        ));
+    }
+}
+
+/// Output the checksum manifest of a downloaded service reference snapshot as JSON to stdout
+pub(crate) fn output_snapshot_manifest(
+    manifest: &ServiceReferenceSnapshotManifest,
+    pretty: bool,
+) -> Result<()> {
+    let json_output = if pretty {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify_pretty(manifest)
+            .context("Failed to serialize snapshot manifest to pretty JSON")?
+    } else {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify(manifest)
+            .context("Failed to serialize snapshot manifest to JSON")?
+    };
+
+    // Output to stdout (not using println! to avoid extra newline in compact mode)
+    print!("{}", json_output);
+    if pretty {
+        println!(); // Add newline for pretty output
+    }
+
+    debug!("Snapshot manifest JSON written to stdout");
+    Ok(())
+}
+
+/// Output the result of a cache command as JSON to stdout
+pub(crate) fn output_json<T: serde::Serialize>(value: &T, pretty: bool) -> Result<()> {
+    let json_output = if pretty {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify_pretty(value)
+            .context("Failed to serialize to pretty JSON")?
+    } else {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify(value)
+            .context("Failed to serialize to JSON")?
+    };
+
+    // Output to stdout (not using println! to avoid extra newline in compact mode)
+    print!("{}", json_output);
+    if pretty {
+        println!(); // Add newline for pretty output
+    }
+
+    debug!("JSON written to stdout");
+    Ok(())
+}
+
+/// Output policy validation findings as JSON to stdout
+pub(crate) fn output_validation_result(
+    result: &ValidatePoliciesResult,
+    pretty: bool,
+) -> Result<()> {
+    debug!(
+        "Formatting {} validation findings as JSON (pretty: {})",
+        result.findings.len(),
+        pretty
+    );
+
+    let json_output = if pretty {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify_pretty(result)
+            .context("Failed to serialize validation findings to pretty JSON")?
+    } else {
+        iam_policy_autopilot_policy_generation::JsonProvider::stringify(result)
+            .context("Failed to serialize validation findings to JSON")?
+    };
+
+    // Output to stdout (not using println! to avoid extra newline in compact mode)
+    print!("{}", json_output);
+    if pretty {
+        println!(); // Add newline for pretty output
+    }
+
+    debug!("Validation findings JSON written to stdout");
+    Ok(())
+}
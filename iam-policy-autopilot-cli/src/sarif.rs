@@ -0,0 +1,101 @@
+//! SARIF 2.1.0 output of extraction diagnostics, for code scanning tools
+//!
+//! Each diagnostic code becomes a rule, and each diagnostic a result located in the analyzed
+//! source files.
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use iam_policy_autopilot_policy_generation::{Diagnostic, DiagnosticSeverity};
+use serde_json::{json, Value};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// SARIF level of a diagnostic severity
+fn level(severity: DiagnosticSeverity) -> &'static str {
+    match severity {
+        DiagnosticSeverity::Info => "note",
+        DiagnosticSeverity::Warning => "warning",
+        DiagnosticSeverity::Error => "error",
+    }
+}
+
+/// SARIF result of a diagnostic
+fn result(diagnostic: &Diagnostic) -> Value {
+    let text = match &diagnostic.suggested_fix {
+        Some(suggested_fix) => format!("{} Suggested fix: {}.", diagnostic.message, suggested_fix),
+        None => diagnostic.message.clone(),
+    };
+    let mut result = json!({
+        "ruleId": diagnostic.code.to_string(),
+        "level": level(diagnostic.severity),
+        "message": { "text": text },
+    });
+    if let Some(location) = &diagnostic.location {
+        // SARIF URIs always use forward slashes
+        let uri = location.file_path.to_string_lossy().replace('\\', "/");
+        result["locations"] = json!([{
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri },
+                "region": {
+                    "startLine": location.start_line(),
+                    "startColumn": location.start_col(),
+                    "endLine": location.end_line(),
+                    "endColumn": location.end_col(),
+                },
+            },
+        }]);
+    }
+    result
+}
+
+/// Build the SARIF log of extraction diagnostics
+///
+/// Diagnostics reported more than once, e.g. for source files shared by several workloads,
+/// are only included once.
+pub(crate) fn sarif_log(diagnostics: &[Diagnostic]) -> Value {
+    let mut seen = HashSet::new();
+    let diagnostics: Vec<_> = diagnostics
+        .iter()
+        .filter(|diagnostic| seen.insert(*diagnostic))
+        .collect();
+    let codes: BTreeSet<_> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.code)
+        .collect();
+    let rules: Vec<_> = codes
+        .into_iter()
+        .map(|code| {
+            json!({
+                "id": code.to_string(),
+                "shortDescription": { "text": code.description() },
+                "defaultConfiguration": { "level": level(code.severity()) },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "iam-policy-autopilot",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/awslabs/iam-policy-autopilot",
+                    "rules": rules,
+                },
+            },
+            "results": diagnostics.into_iter().map(result).collect::<Vec<_>>(),
+        }],
+    })
+}
+
+/// Write the SARIF log of extraction diagnostics to `path`
+pub(crate) fn write_sarif(path: &Path, diagnostics: &[Diagnostic]) -> Result<()> {
+    let sarif = serde_json::to_string_pretty(&sarif_log(diagnostics))
+        .context("Failed to serialize SARIF log")?;
+    std::fs::write(path, sarif)
+        .with_context(|| format!("Failed to write SARIF log to {}", path.display()))
+}
@@ -166,3 +166,76 @@ fn test_fix_access_denied_stdin_input() {
     );
     assert_eq!(output.status.code(), Some(0));
 }
+
+#[test]
+fn test_no_detect_entry_points_overrides_project_config() {
+    let temp_dir = tempfile::TempDir::new().expect("failed to create temp directory");
+    std::fs::write(
+        temp_dir.path().join(".iam-autopilot.toml"),
+        "[sources]\ndetect-entry-points = true\n",
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join("app.py"),
+        "import boto3\n\n\ndef unused():\n    s3 = boto3.client('s3')\n    s3.get_object(Bucket='b', Key='k')\n\n\nif __name__ == '__main__':\n    s3 = boto3.client('s3')\n    s3.list_buckets()\n",
+    )
+    .unwrap();
+    let extract = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_iam-policy-autopilot"))
+            .arg("extract-sdk-calls")
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("failed to run extract-sdk-calls");
+        assert_eq!(output.status.code(), Some(0));
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = extract(&[]);
+    assert!(stdout.contains("list_buckets"), "stdout was: {}", stdout);
+    assert!(!stdout.contains("get_object"), "stdout was: {}", stdout);
+
+    let stdout = extract(&["--no-detect-entry-points"]);
+    assert!(stdout.contains("get_object"), "stdout was: {}", stdout);
+}
+
+#[test]
+fn test_no_offline_overrides_project_config() {
+    let temp_dir = tempfile::TempDir::new().expect("failed to create temp directory");
+    // The endpoint is unroutable, so that runs reaching the network fail without leaving the host
+    std::fs::write(
+        temp_dir.path().join(".iam-autopilot.toml"),
+        "[generation]\noffline = true\nservice-reference-endpoint = \"http://127.0.0.1:9/\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join("app.py"),
+        "import boto3\ns3 = boto3.client('s3')\ns3.list_buckets()\n",
+    )
+    .unwrap();
+    let generate = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_iam-policy-autopilot"))
+            .arg("generate-policies")
+            .args(args)
+            .env(
+                "IAM_POLICY_AUTOPILOT_CACHE_DIR",
+                temp_dir.path().join("cache"),
+            )
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("failed to run generate-policies");
+        assert_eq!(output.status.code(), Some(1));
+        String::from_utf8_lossy(&output.stderr).to_string()
+    };
+
+    let stderr = generate(&[]);
+    assert!(stderr.contains("offline mode"), "stderr was: {}", stderr);
+
+    let stderr = generate(&["--no-offline"]);
+    assert!(!stderr.contains("offline mode"), "stderr was: {}", stderr);
+    assert!(
+        stderr.contains("http://127.0.0.1:9/"),
+        "stderr was: {}",
+        stderr
+    );
+}
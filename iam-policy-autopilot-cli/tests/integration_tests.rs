@@ -303,6 +303,281 @@ fn test_extract_sdk_calls_empty_file_full_output() {
     );
 }
 
+#[test]
+fn test_generate_policy_invalid_resource_cutoff() {
+    generate_policy_command()
+        .arg("--resource-cutoff")
+        .arg("s3=many")
+        .arg(get_simple_test_file("py"))
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Invalid --resource-cutoff 's3=many'",
+        ));
+}
+
+#[test]
+fn test_generate_policy_invalid_wildcard_mode() {
+    generate_policy_command()
+        .arg("--wildcard-mode")
+        .arg("ec2:RunInstances=sometimes")
+        .arg(get_simple_test_file("py"))
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Invalid --wildcard-mode 'ec2:RunInstances=sometimes'",
+        ));
+}
+
+#[test]
+fn test_generate_policy_wildcard_options_with_empty_file() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let empty_file = temp_dir.path().join("empty.py");
+    fs::write(&empty_file, "").expect("Failed to create empty file");
+
+    generate_policy_command()
+        .arg("--resource-cutoff")
+        .arg("10")
+        .arg("--resource-cutoff")
+        .arg("s3=3")
+        .arg("--wildcard-mode")
+        .arg("iam=never")
+        .arg(empty_file.to_str().unwrap())
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_extract_sdk_calls_from_project_config() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::write(
+        temp_dir.path().join(".iam-autopilot.toml"),
+        r#"
+[sources]
+roots = ["src"]
+exclude = ["**/tests/**"]
+"#,
+    )
+    .unwrap();
+    fs::create_dir_all(temp_dir.path().join("src/tests")).unwrap();
+    fs::write(
+        temp_dir.path().join("src/app.py"),
+        "import boto3\ns3 = boto3.client('s3')\ns3.list_buckets()\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("src/tests/test_app.py"),
+        "import boto3\nsqs = boto3.client('sqs')\nsqs.list_queues()\n",
+    )
+    .unwrap();
+
+    // Source files are discovered from the project configuration in the working directory
+    let output = extract_sdk_calls_command()
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("list_buckets"));
+    assert!(!stdout.contains("list_queues"));
+}
+
+#[test]
+fn test_command_line_flags_override_project_config() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::write(
+        temp_dir.path().join(".iam-autopilot.toml"),
+        "[sources]\ninclude-tests = true\n\n[output]\npretty = true\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("app.py"),
+        "import boto3\ns3 = boto3.client('s3')\ns3.list_buckets()\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("test_app.py"),
+        "import boto3\nsqs = boto3.client('sqs')\nsqs.list_queues()\n",
+    )
+    .unwrap();
+
+    let output = extract_sdk_calls_command()
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("list_queues"));
+    assert!(stdout.contains('\n'));
+
+    let output = extract_sdk_calls_command()
+        .arg("--no-pretty")
+        .arg("--no-include-tests")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("list_buckets"));
+    assert!(!stdout.contains("list_queues"));
+    assert!(!stdout.trim_end().contains('\n'));
+}
+
+#[test]
+fn test_generate_policy_invalid_project_config() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let config_file = temp_dir.path().join("autopilot.toml");
+    fs::write(&config_file, "[aws]\nregions = \"us-east-1\"\n").unwrap();
+
+    generate_policy_command()
+        .arg("--config")
+        .arg(config_file.to_str().unwrap())
+        .arg(get_simple_test_file("py"))
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("unknown field `regions`"));
+}
+
+#[test]
+fn test_generate_policy_without_source_files() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+
+    generate_policy_command()
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("No source files provided"));
+}
+
+#[test]
+fn test_generate_policy_workloads_with_empty_files() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::create_dir_all(temp_dir.path().join("functions/ingest")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("lib")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("scripts")).unwrap();
+    fs::write(
+        temp_dir.path().join("functions/ingest/handler.py"),
+        "from lib import storage\n",
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("lib/storage.py"), "").unwrap();
+    fs::write(temp_dir.path().join("scripts/migrate.py"), "").unwrap();
+
+    let output = generate_policy_command()
+        .current_dir(temp_dir.path())
+        .args([
+            "functions/ingest/handler.py",
+            "lib/storage.py",
+            "scripts/migrate.py",
+            "--workload",
+            "ingest=functions/ingest/**",
+        ])
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let json: Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    assert_eq!(
+        json["Workloads"]["ingest"]["SourceFiles"],
+        serde_json::json!(["functions/ingest/handler.py", "lib/storage.py"])
+    );
+    assert_eq!(
+        json["Workloads"]["ingest"]["Policies"],
+        serde_json::json!([])
+    );
+    assert_eq!(
+        json["UnassignedFiles"],
+        serde_json::json!(["scripts/migrate.py"])
+    );
+}
+
+#[test]
+fn test_generate_policy_lambda_functions_without_sdk_calls() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::create_dir_all(temp_dir.path().join("functions/ingest")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("functions/report")).unwrap();
+    fs::write(
+        temp_dir.path().join("template.yaml"),
+        r#"Transform: AWS::Serverless-2016-10-31
+Globals:
+  Function:
+    Runtime: python3.12
+Resources:
+  IngestFunction:
+    Type: AWS::Serverless::Function
+    Properties:
+      CodeUri: functions/ingest/
+      Handler: app.handler
+  ReportFunction:
+    Type: AWS::Serverless::Function
+    Properties:
+      CodeUri: functions/report/
+      Handler: report.lambda_handler
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("functions/ingest/app.py"),
+        "def handler(event, context):\n    return event\n",
+    )
+    .unwrap();
+
+    let output = generate_policy_command()
+        .current_dir(temp_dir.path())
+        .args([
+            "functions/ingest/app.py",
+            "--lambda-functions",
+            "template.yaml",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "the handler of 1 Lambda function(s) was not found among the source files",
+        ));
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let json: Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    let ingest = &json["Functions"]["IngestFunction"];
+    assert_eq!(ingest["Handler"], "app.handler");
+    assert_eq!(ingest["EntryPoint"], "functions/ingest/app.py:handler");
+    assert_eq!(ingest["DeclaredIn"], "template.yaml");
+    assert_eq!(ingest["Policies"], serde_json::json!([]));
+    assert_eq!(
+        json["UnresolvedFunctions"],
+        serde_json::json!(["ReportFunction"])
+    );
+}
+
+#[test]
+fn test_generate_policy_lambda_functions_with_workloads() {
+    generate_policy_command()
+        .args(["--lambda-functions", "template.yaml"])
+        .args(["--workload", "ingest=functions/ingest/**"])
+        .arg(get_simple_test_file("py"))
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "--lambda-functions cannot be combined with workloads",
+        ));
+}
+
+#[test]
+fn test_generate_policy_invalid_workload() {
+    generate_policy_command()
+        .arg("--workload")
+        .arg("functions/ingest/**")
+        .arg(get_simple_test_file("py"))
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Invalid --workload 'functions/ingest/**'",
+        ));
+}
+
 #[test]
 fn test_generate_policy_empty_file() {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
@@ -509,7 +784,7 @@ fn test_comprehensive_real_files_generate_policy_for_extension(extension: &str)
 
 #[test]
 fn test_disambiguation_example_file() {
-    let disambiguation_file = PathBuf::from("tests/resources/test_disambiguation_example.py");
+    let disambiguation_file = PathBuf::from("tests/resources/disambiguation_example.py");
 
     // Test extract-sdk-calls with the disambiguation example file
     let mut cmd = extract_sdk_calls_command();
@@ -549,7 +824,7 @@ fn test_disambiguation_example_file() {
 fn test_generate_policy() {
     // Test that verifies condition placeholders like ${region} are properly replaced
     // This test specifically validates the ConditionValueProcessor functionality
-    let test_file = PathBuf::from("tests/resources/test_example.py");
+    let test_file = PathBuf::from("tests/resources/example.py");
 
     let output = generate_policy_command()
         .arg("--region")
@@ -687,7 +962,7 @@ fn test_generate_policy() {
 
 #[test]
 fn test_dictionary_unpacking_file() {
-    let unpacking_file = PathBuf::from("tests/resources/test_dictionary_unpacking.py");
+    let unpacking_file = PathBuf::from("tests/resources/dictionary_unpacking.py");
 
     // Test extract-sdk-calls with the dictionary unpacking example file
     let mut cmd = extract_sdk_calls_command();
@@ -718,3 +993,387 @@ fn test_dictionary_unpacking_file() {
         }
     }
 }
+
+/// Helper function to get the CLI binary command with validate-policies subcommand
+fn validate_policies_command() -> Command {
+    let mut cmd = cli_command();
+    cmd.arg("validate-policies");
+    cmd
+}
+
+#[test]
+fn test_validate_policies_reports_findings() {
+    let temp_dir = TempDir::new().unwrap();
+    let policy_file = temp_dir.path().join("policy.json");
+    fs::write(
+        &policy_file,
+        r#"{
+            "Version": "2012-10-17",
+            "Statement": [
+                {"Sid": "Admin", "Effect": "Allow", "Action": "*", "Resource": "*"},
+                {"Sid": "Admin", "Effect": "Deny", "Action": "*", "Resource": "*"}
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let mut cmd = validate_policies_command();
+    cmd.arg(policy_file.to_str().unwrap());
+
+    // Duplicate Sids are an error, so the command exits with code 2
+    let output = cmd.assert().code(2);
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let json: Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    let issue_codes: Vec<&str> = json["Findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|finding| finding["IssueCode"].as_str().unwrap())
+        .collect();
+    assert_eq!(issue_codes, vec!["FULL_WILDCARD_ACTION", "DUPLICATE_SID"]);
+}
+
+#[test]
+fn test_validate_policies_accepts_generate_policies_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let policy_file = temp_dir.path().join("policies.json");
+    fs::write(
+        &policy_file,
+        r#"{"Policies": [{"Policy": {"Version": "2012-10-17", "Statement": [{"Effect": "Deny", "Action": "*", "Resource": "*"}]}, "PolicyType": "Identity"}]}"#,
+    )
+    .unwrap();
+
+    let mut cmd = validate_policies_command();
+    cmd.args([policy_file.to_str().unwrap(), "--policy-type", "inline"]);
+
+    let output = cmd.assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let json: Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    assert_eq!(json["Findings"], serde_json::json!([]));
+}
+
+#[test]
+fn test_validate_policies_nonexistent_file() {
+    let mut cmd = validate_policies_command();
+    cmd.arg("nonexistent_policy.json");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to read policy file"));
+}
+
+#[test]
+fn test_validate_policies_missing_service_reference_snapshot() {
+    let temp_dir = TempDir::new().unwrap();
+    let policy_file = temp_dir.path().join("policy.json");
+    fs::write(
+        &policy_file,
+        r#"{"Version": "2012-10-17", "Statement": [{"Effect": "Allow", "Action": "s3:GetObject", "Resource": "*"}]}"#,
+    )
+    .unwrap();
+
+    let mut cmd = validate_policies_command();
+    cmd.arg(policy_file.to_str().unwrap())
+        .arg("--offline")
+        .arg("--service-reference")
+        .arg(temp_dir.path().join("missing-snapshot.json"));
+
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("missing-snapshot.json"));
+}
+
+#[test]
+fn test_cache_status_and_prune() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache_dir = temp_dir.path().join("service-reference").join("v1");
+    fs::create_dir_all(&cache_dir).unwrap();
+    fs::write(cache_dir.join("s3.json"), "not a cache entry").unwrap();
+
+    let mut cmd = cli_command();
+    cmd.env("IAM_POLICY_AUTOPILOT_CACHE_DIR", temp_dir.path())
+        .args(["cache", "status"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let status: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(status["Entries"][0]["Name"], "s3");
+    assert_eq!(status["Entries"][0]["State"], "corrupt");
+
+    let mut cmd = cli_command();
+    cmd.env("IAM_POLICY_AUTOPILOT_CACHE_DIR", temp_dir.path())
+        .args(["cache", "prune"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let result: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(result["Removed"], serde_json::json!(["s3"]));
+    assert!(!cache_dir.join("s3.json").exists());
+}
+
+#[test]
+fn test_generate_policies_rejects_invalid_fas_map() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_file = temp_dir.path().join("app.py");
+    fs::write(
+        &source_file,
+        "import boto3\ns3 = boto3.client('s3')\ns3.get_object(Bucket='b', Key='k')\n",
+    )
+    .unwrap();
+    let fas_map = temp_dir.path().join("lambda.json");
+    fs::write(&fas_map, r#"{"Name": "Lambda", "Operations": []}"#).unwrap();
+
+    let mut cmd = generate_policy_command();
+    cmd.arg(source_file.to_str().unwrap())
+        .arg("--fas-map")
+        .arg(&fas_map);
+
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("Invalid FAS map"));
+}
+
+#[test]
+fn test_extract_sdk_calls_with_sdk_wrapper() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_file = temp_dir.path().join("app.py");
+    fs::write(
+        &source_file,
+        "from mycorp import storage\nstorage.put_blob('my-bucket', 'key')\n",
+    )
+    .unwrap();
+    let wrappers = temp_dir.path().join("wrappers.json");
+    fs::write(
+        &wrappers,
+        r#"{"Wrappers": [{"Module": "mycorp.storage", "Name": "put_blob",
+            "Operations": ["s3:PutObject"], "Parameters": [{"Member": "Bucket"}]}]}"#,
+    )
+    .unwrap();
+
+    let mut cmd = extract_sdk_calls_command();
+    cmd.arg(source_file.to_str().unwrap())
+        .arg("--sdk-wrapper")
+        .arg(&wrappers);
+
+    let output = cmd.assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let json: Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    let operations = json.as_array().unwrap();
+    assert_eq!(operations.len(), 1);
+    assert_eq!(operations[0]["Name"], "put_object");
+    assert_eq!(operations[0]["PossibleServices"][0], "s3");
+}
+
+#[test]
+fn test_extract_sdk_calls_reachable_from_entry_points() {
+    let temp_dir = TempDir::new().unwrap();
+    let handler = temp_dir.path().join("handler.py");
+    fs::write(
+        &handler,
+        "import boto3\n\ns3 = boto3.client('s3')\n\n\
+def lambda_handler(event, context):\n    s3.get_object(Bucket='b', Key='k')\n\n\
+def unused():\n    s3.delete_bucket(Bucket='b')\n",
+    )
+    .unwrap();
+    let script = temp_dir.path().join("cleanup.py");
+    fs::write(
+        &script,
+        "import boto3\n\ndef run():\n    boto3.client('s3').delete_object(Bucket='b', Key='k')\n",
+    )
+    .unwrap();
+
+    let names = |cmd: &mut Command| -> Vec<String> {
+        let output = cmd.assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+        let json: Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+        json.as_array()
+            .unwrap()
+            .iter()
+            .map(|operation| operation["Name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let mut cmd = extract_sdk_calls_command();
+    cmd.arg(&handler).arg(&script).arg("--detect-entry-points");
+    assert_eq!(names(&mut cmd), vec!["get_object"]);
+
+    let mut cmd = extract_sdk_calls_command();
+    cmd.arg(&handler)
+        .arg(&script)
+        .arg("--entry-point")
+        .arg(format!("{}:run", script.display()));
+    assert_eq!(names(&mut cmd), vec!["delete_object"]);
+
+    let mut cmd = extract_sdk_calls_command();
+    cmd.arg(&handler)
+        .arg("--entry-point")
+        .arg(format!("{}:missing", handler.display()));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("is not defined"));
+}
+
+#[test]
+fn test_extract_sdk_calls_excludes_tests_and_mocks() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = temp_dir.path().join("app.py");
+    fs::write(
+        &app,
+        "import boto3\n\ndef get(key):\n    boto3.client('s3').get_object(Bucket='b', Key=key)\n",
+    )
+    .unwrap();
+    let test = temp_dir.path().join("test_app.py");
+    fs::write(
+        &test,
+        "import boto3\n\ndef test_get():\n    boto3.client('s3').put_object(Bucket='b', Key='k')\n",
+    )
+    .unwrap();
+    let fixtures = temp_dir.path().join("fixtures.py");
+    fs::write(
+        &fixtures,
+        "import boto3\nfrom moto import mock_aws\n\n@mock_aws\ndef setup():\n    boto3.client('s3').create_bucket(Bucket='b')\n",
+    )
+    .unwrap();
+
+    let output = extract_sdk_calls_command()
+        .arg(&app)
+        .arg(&test)
+        .arg(&fixtures)
+        .assert()
+        .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let json: Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    let names: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|method| method["Name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["get_object"]);
+
+    let output = extract_sdk_calls_command()
+        .arg(&app)
+        .arg(&test)
+        .arg(&fixtures)
+        .arg("--include-tests")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let json: Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    assert_eq!(json.as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn test_extract_sdk_calls_include_tests_with_test_named_files() {
+    // The example fixture, named like a pytest module
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.path().join("test_example.py");
+    fs::copy("tests/resources/example.py", &test_file).unwrap();
+
+    let extract = |include_tests: bool| {
+        let mut cmd = extract_sdk_calls_command();
+        if include_tests {
+            cmd.arg("--include-tests");
+        }
+        let output = cmd.arg(&test_file).assert().success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+        serde_json::from_str::<Value>(&stdout).expect("Invalid JSON output")
+    };
+
+    assert_eq!(extract(false), serde_json::json!([]));
+
+    let expected = extract_sdk_calls_command()
+        .arg("tests/resources/example.py")
+        .assert()
+        .success();
+    let expected: Value =
+        serde_json::from_slice(&expected.get_output().stdout).expect("Invalid JSON output");
+    assert!(!expected.as_array().unwrap().is_empty());
+    assert_eq!(extract(true), expected);
+}
+
+#[test]
+fn test_extract_sdk_calls_sarif_diagnostics() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = temp_dir.path().join("app.py");
+    fs::write(
+        &app,
+        "import boto3\n\nclient = boto3.client('s3')\n\ndef run(operation, **kwargs):\n    getattr(client, operation)(**kwargs)\n",
+    )
+    .unwrap();
+    let test = temp_dir.path().join("test_app.py");
+    fs::write(&test, "import boto3\n").unwrap();
+    let sarif = temp_dir.path().join("diagnostics.sarif");
+
+    let output = extract_sdk_calls_command()
+        .arg(&app)
+        .arg(&test)
+        .arg("--sarif")
+        .arg(&sarif)
+        .assert()
+        .success();
+    let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("[DynamicMethodName]"), "{stderr}");
+    assert!(stderr.contains("1 informational diagnostic(s)"), "{stderr}");
+
+    let log: Value = serde_json::from_str(&fs::read_to_string(&sarif).unwrap()).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(rules, vec!["DynamicMethodName", "TestFileExcluded"]);
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results[0]["ruleId"], "TestFileExcluded");
+    assert_eq!(results[0]["level"], "note");
+    assert_eq!(results[1]["ruleId"], "DynamicMethodName");
+    assert_eq!(results[1]["level"], "warning");
+    let region = &results[1]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 6);
+    assert_eq!(region["startColumn"], 5);
+}
+
+#[test]
+fn test_derive_sdk_wrappers_and_extract_with_them() {
+    let temp_dir = TempDir::new().unwrap();
+    let library = temp_dir.path().join("library");
+    fs::create_dir_all(library.join("mycorp")).unwrap();
+    fs::write(library.join("mycorp/__init__.py"), "").unwrap();
+    fs::write(
+        library.join("mycorp/storage.py"),
+        "import boto3\n\ns3 = boto3.client('s3')\n\n\
+def put_blob(bucket, key):\n    s3.put_object(Bucket=bucket, Key=key)\n",
+    )
+    .unwrap();
+    let mapping = temp_dir.path().join("wrappers.json");
+
+    let mut cmd = cli_command();
+    cmd.arg("derive-sdk-wrappers")
+        .arg(&library)
+        .arg("--output")
+        .arg(&mapping);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("wrote 1 SDK wrappers"));
+
+    let json: Value = serde_json::from_str(&fs::read_to_string(&mapping).unwrap()).unwrap();
+    assert_eq!(json["Wrappers"][0]["Module"], "mycorp.storage");
+    assert_eq!(json["Wrappers"][0]["Operations"][0], "s3:PutObject");
+
+    let source_file = temp_dir.path().join("app.py");
+    fs::write(
+        &source_file,
+        "from mycorp.storage import put_blob\nput_blob('my-bucket', 'key')\n",
+    )
+    .unwrap();
+    let mut cmd = extract_sdk_calls_command();
+    cmd.arg(source_file.to_str().unwrap())
+        .arg("--sdk-wrapper")
+        .arg(&mapping);
+    let output = cmd.assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let json: Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    assert_eq!(json[0]["Name"], "put_object");
+}
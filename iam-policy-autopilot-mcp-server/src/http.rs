@@ -0,0 +1,423 @@
+//! Access control of the HTTP transport
+//!
+//! The HTTP transport binds to the loopback interface by default. A server shared from a dev
+//! container or a remote workspace binds to another address, and then requires clients to
+//! authenticate with a bearer token or a TLS client certificate. Requests from browsers are only
+//! accepted from loopback origins and the configured allowed origins, which protects local
+//! servers against DNS rebinding.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use log::warn;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+
+/// Default address of the HTTP transport
+pub const DEFAULT_BIND_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+/// Default maximum size of a request body, in bytes
+pub const DEFAULT_MAX_REQUEST_SIZE: usize = 4 * 1024 * 1024;
+
+/// Time allowed to a client to complete the TLS handshake
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// TLS configuration of the HTTP transport
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    /// PEM file with the certificate chain of the server
+    pub certificate: PathBuf,
+    /// PEM file with the private key of the server certificate
+    pub private_key: PathBuf,
+    /// PEM file with the CA certificates issuing client certificates. When set, clients must
+    /// present a certificate issued by one of them (mutual TLS).
+    pub client_ca_certificate: Option<PathBuf>,
+}
+
+/// Configuration of the HTTP transport
+#[derive(Clone, Debug)]
+pub struct HttpTransportConfig {
+    /// Address to bind to
+    pub bind_address: IpAddr,
+    /// Port to bind to
+    pub port: u16,
+    /// Token clients must send in an `Authorization: Bearer` header
+    pub auth_token: Option<String>,
+    /// TLS configuration, plain HTTP is served if `None`
+    pub tls: Option<TlsConfig>,
+    /// Origins accepted in the `Origin` header of requests, in addition to loopback origins,
+    /// e.g. `https://workspace.example.com`
+    pub allowed_origins: Vec<String>,
+    /// Maximum size of a request body, in bytes
+    pub max_request_size: usize,
+}
+
+impl HttpTransportConfig {
+    /// Configuration binding to the loopback interface on `port`, without authentication
+    pub fn new(port: u16) -> Self {
+        Self {
+            bind_address: DEFAULT_BIND_ADDRESS,
+            port,
+            auth_token: None,
+            tls: None,
+            allowed_origins: vec![],
+            max_request_size: DEFAULT_MAX_REQUEST_SIZE,
+        }
+    }
+
+    pub(crate) fn socket_address(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.port)
+    }
+
+    /// Check that clients must authenticate if the server is reachable beyond the loopback
+    /// interface
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self
+            .auth_token
+            .as_ref()
+            .is_some_and(|token| token.is_empty())
+        {
+            bail!("The bearer token must not be empty");
+        }
+        let mutual_tls = self
+            .tls
+            .as_ref()
+            .is_some_and(|tls| tls.client_ca_certificate.is_some());
+        if !self.bind_address.is_loopback() && self.auth_token.is_none() && !mutual_tls {
+            bail!(
+                "Binding to the non-loopback address {} requires authentication with a bearer token or TLS client certificates",
+                self.bind_address
+            );
+        }
+        if !self.bind_address.is_loopback() && self.tls.is_none() {
+            warn!(
+                "Serving plain HTTP on {}, the bearer token is sent unencrypted",
+                self.bind_address
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Origin and authentication checks applied to every request
+#[derive(Debug)]
+pub(crate) struct RequestPolicy {
+    auth_token: Option<String>,
+    allowed_origins: Vec<String>,
+}
+
+impl RequestPolicy {
+    pub(crate) fn new(config: &HttpTransportConfig) -> Self {
+        Self {
+            auth_token: config.auth_token.clone(),
+            allowed_origins: config
+                .allowed_origins
+                .iter()
+                .map(|origin| origin.trim_end_matches('/').to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    fn is_allowed_origin(&self, origin: &str) -> bool {
+        let origin = origin.to_ascii_lowercase();
+        if self.allowed_origins.contains(&origin) {
+            return true;
+        }
+        let Some((_, authority)) = origin.split_once("://") else {
+            return false;
+        };
+        let host = match authority.strip_prefix('[') {
+            Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+            None => authority.split(':').next().unwrap_or_default(),
+        };
+        host == "localhost"
+            || host
+                .parse::<IpAddr>()
+                .is_ok_and(|address| address.is_loopback())
+    }
+
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = &self.auth_token else {
+            return true;
+        };
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|presented| constant_time_eq(presented.as_bytes(), token.as_bytes()))
+    }
+
+    /// Response rejecting a request, or `None` if the request is accepted
+    fn rejection(&self, headers: &HeaderMap) -> Option<Response> {
+        if let Some(origin) = headers.get(header::ORIGIN) {
+            if !origin
+                .to_str()
+                .is_ok_and(|origin| self.is_allowed_origin(origin))
+            {
+                return Some((StatusCode::FORBIDDEN, "Origin not allowed").into_response());
+            }
+        }
+        if !self.is_authorized(headers) {
+            return Some(
+                (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Bearer")],
+                    "Missing or invalid bearer token",
+                )
+                    .into_response(),
+            );
+        }
+        None
+    }
+}
+
+/// Compare secrets in a time independent of the position of the first difference
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Middleware rejecting requests from disallowed origins and unauthenticated requests
+pub(crate) async fn check_request(
+    State(policy): State<Arc<RequestPolicy>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match policy.rejection(request.headers()) {
+        Some(response) => {
+            warn!(
+                "Rejected {} {} with status {}",
+                request.method(),
+                request.uri(),
+                response.status()
+            );
+            response
+        }
+        None => next.run(request).await,
+    }
+}
+
+/// Create the TLS acceptor of the HTTP transport
+pub(crate) fn tls_acceptor(config: &TlsConfig) -> Result<TlsAcceptor> {
+    let certificates = CertificateDer::pem_file_iter(&config.certificate)
+        .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+        .with_context(|| {
+            format!(
+                "Failed to read certificates from {}",
+                config.certificate.display()
+            )
+        })?;
+    let private_key = PrivateKeyDer::from_pem_file(&config.private_key).with_context(|| {
+        format!(
+            "Failed to read private key from {}",
+            config.private_key.display()
+        )
+    })?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    let builder = match &config.client_ca_certificate {
+        Some(path) => {
+            let mut roots = rustls::RootCertStore::empty();
+            for certificate in CertificateDer::pem_file_iter(path).with_context(|| {
+                format!("Failed to read CA certificates from {}", path.display())
+            })? {
+                roots.add(certificate.with_context(|| {
+                    format!("Failed to read CA certificates from {}", path.display())
+                })?)?;
+            }
+            let verifier =
+                rustls::server::WebPkiClientVerifier::builder_with_provider(roots.into(), provider)
+                    .build()
+                    .context("Failed to create the client certificate verifier")?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut server_config = builder
+        .with_single_cert(certificates, private_key)
+        .context("Invalid server certificate or private key")?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// Number of connections whose TLS handshake completed, waiting to be served
+const TLS_ACCEPT_QUEUE: usize = 64;
+
+/// TCP listener completing the TLS handshake of accepted connections
+///
+/// Each handshake runs in its own task, so a slow client does not delay the connections
+/// accepted after it.
+pub(crate) struct TlsListener {
+    local_addr: SocketAddr,
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    accept_task: JoinHandle<()>,
+}
+
+impl TlsListener {
+    /// Accept connections of `listener` and complete their TLS handshake with `acceptor`
+    pub(crate) fn new(mut listener: TcpListener, acceptor: TlsAcceptor) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (sender, connections) = mpsc::channel(TLS_ACCEPT_QUEUE);
+        let accept_task = tokio::spawn(async move {
+            loop {
+                let (stream, address) = axum::serve::Listener::accept(&mut listener).await;
+                let acceptor = acceptor.clone();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await
+                    {
+                        Ok(Ok(stream)) => {
+                            let _ = sender.send((stream, address)).await;
+                        }
+                        Ok(Err(e)) => warn!("TLS handshake with {address} failed: {e}"),
+                        Err(_) => warn!("TLS handshake with {address} timed out"),
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            local_addr,
+            connections,
+            accept_task,
+        })
+    }
+}
+
+impl Drop for TlsListener {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // The accept task only ends when the listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(entries: &[(header::HeaderName, &str)]) -> HeaderMap {
+        entries
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_str(value).unwrap()))
+            .collect()
+    }
+
+    fn status(
+        policy: &RequestPolicy,
+        entries: &[(header::HeaderName, &str)],
+    ) -> Option<StatusCode> {
+        policy
+            .rejection(&headers(entries))
+            .map(|response| response.status())
+    }
+
+    #[test]
+    fn test_validate_requires_authentication_beyond_loopback() {
+        let mut config = HttpTransportConfig::new(8001);
+        assert!(config.validate().is_ok());
+
+        config.bind_address = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+        assert!(config.validate().is_err());
+
+        config.auth_token = Some("secret".to_string());
+        assert!(config.validate().is_ok());
+
+        config.auth_token = None;
+        config.tls = Some(TlsConfig {
+            certificate: "server.pem".into(),
+            private_key: "server.key".into(),
+            client_ca_certificate: None,
+        });
+        assert!(config.validate().is_err());
+
+        config.tls.as_mut().unwrap().client_ca_certificate = Some("ca.pem".into());
+        assert!(config.validate().is_ok());
+
+        config.auth_token = Some(String::new());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_request_policy_bearer_token() {
+        let mut config = HttpTransportConfig::new(8001);
+        config.auth_token = Some("secret".to_string());
+        let policy = RequestPolicy::new(&config);
+
+        assert_eq!(status(&policy, &[]), Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(
+            status(&policy, &[(header::AUTHORIZATION, "Bearer wrong")]),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            status(&policy, &[(header::AUTHORIZATION, "Basic secret")]),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            status(&policy, &[(header::AUTHORIZATION, "Bearer secret")]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_request_policy_origins() {
+        let mut config = HttpTransportConfig::new(8001);
+        config.allowed_origins = vec!["https://Workspace.example.com/".to_string()];
+        let policy = RequestPolicy::new(&config);
+
+        assert_eq!(status(&policy, &[]), None);
+        for origin in [
+            "http://localhost:3000",
+            "http://127.0.0.1",
+            "http://[::1]:8080",
+            "https://workspace.example.com",
+        ] {
+            assert_eq!(
+                status(&policy, &[(header::ORIGIN, origin)]),
+                None,
+                "{origin}"
+            );
+        }
+        for origin in [
+            "https://attacker.example.com",
+            "http://localhost.attacker.example.com",
+            "null",
+        ] {
+            assert_eq!(
+                status(&policy, &[(header::ORIGIN, origin)]),
+                Some(StatusCode::FORBIDDEN),
+                "{origin}"
+            );
+        }
+    }
+}
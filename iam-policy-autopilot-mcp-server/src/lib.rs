@@ -1,13 +1,20 @@
 use std::fmt::Display;
+use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use clap::ValueEnum;
 use log::info;
 
+use crate::http::HttpTransportConfig;
+
+pub mod http;
 pub mod mcp;
+pub(crate) mod progress;
+pub(crate) mod prompts;
+pub(crate) mod resources;
+pub(crate) mod session;
 pub(crate) mod tools;
-
-static BIND_ADDRESS: &str = "127.0.0.1";
+pub(crate) mod workspace;
 
 #[derive(Clone, Debug, ValueEnum)]
 pub enum McpTransport {
@@ -24,7 +31,18 @@ impl Display for McpTransport {
     }
 }
 
-pub async fn start_mcp_server(transport: McpTransport, port: u16) -> Result<()> {
+/// Configuration of the MCP server
+#[derive(Clone, Debug)]
+pub struct McpServerConfig {
+    /// Disable the tools that change resources of the AWS account, such as fix_access_denied
+    pub read_only: bool,
+    /// Directories the source files read by tools must be within, unrestricted if empty
+    pub workspace_roots: Vec<PathBuf>,
+    /// Configuration of the HTTP transport, ignored by the stdio transport
+    pub http: HttpTransportConfig,
+}
+
+pub async fn start_mcp_server(transport: McpTransport, config: McpServerConfig) -> Result<()> {
     info!("Starting MCP server with transport: {}", transport);
 
     let env = env_logger::Env::default().filter_or("IAMPA_LOG_LEVEL", "debug");
@@ -62,17 +80,30 @@ pub async fn start_mcp_server(transport: McpTransport, port: u16) -> Result<()>
 
     match transport {
         McpTransport::Http => {
-            let bind_address: String = format!("{}:{}", BIND_ADDRESS, port);
+            let bind_address = config.http.socket_address();
             info!("Starting HTTP MCP server at {}", bind_address);
 
-            crate::mcp::begin_http_transport(bind_address.as_str(), path_str)
+            if !config.http.bind_address.is_loopback() && !config.read_only {
+                eprintln!(
+                    "Warning: clients of {bind_address} can change IAM policies of the AWS account with fix_access_denied, \
+                    use --read-only to disable it"
+                );
+            }
+            if !config.http.bind_address.is_loopback() && config.workspace_roots.is_empty() {
+                eprintln!(
+                    "Warning: clients of {bind_address} can read any file of this machine the server can read, \
+                    use --workspace-root to restrict them"
+                );
+            }
+
+            crate::mcp::begin_http_transport(&config, path_str)
                 .await
                 .with_context(|| format!("Failed to start HTTP Server at '{bind_address}'"))?
         }
         McpTransport::Stdio => {
             info!("Starting STDIO MCP server");
 
-            crate::mcp::begin_stdio_transport(path_str)
+            crate::mcp::begin_stdio_transport(&config, path_str)
                 .await
                 .with_context(|| "Failed to start STDIO Server".to_string())?
         }
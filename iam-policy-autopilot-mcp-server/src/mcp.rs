@@ -1,8 +1,20 @@
+use std::sync::Arc;
+
 use anyhow;
-use log::{error, info, trace};
+use log::{error, info, trace, warn};
 use rmcp::{
-    handler::server::{tool::ToolRouter, wrapper::Parameters},
-    model::{ErrorCode, ServerCapabilities, ServerInfo},
+    handler::server::{
+        router::prompt::PromptRouter,
+        tool::{cached_schema_for_type, ToolRouter},
+        wrapper::Parameters,
+    },
+    model::{
+        CallToolResult, Content, ErrorCode, GetPromptRequestParam, GetPromptResult,
+        ListPromptsResult, ListResourceTemplatesResult, ListResourcesResult, PaginatedRequestParam,
+        PromptMessage, ReadResourceRequestParam, ReadResourceResult, ServerCapabilities,
+        ServerInfo,
+    },
+    prompt, prompt_handler, prompt_router,
     service::RequestContext,
     tool, tool_handler, tool_router,
     transport::{
@@ -10,30 +22,69 @@ use rmcp::{
     },
     ErrorData as McpError, Json, RoleServer, ServerHandler, ServiceExt,
 };
+use tower_http::limit::RequestBodyLimitLayer;
 
+use crate::http::{self, RequestPolicy, TlsListener};
+use crate::progress::ToolProgress;
+use crate::prompts::{
+    self, DiagnoseAccessDeniedArguments, ReviewPolicyArguments, TightenWildcardsArguments,
+};
+use crate::resources;
+use crate::session::Session;
 use crate::tools::{
-    fix_access_denied, generate_application_policies, generate_policy_for_access_denied,
-    FixAccessDeniedInput, FixAccessDeniedOutput, GeneratePoliciesInput, GeneratePoliciesOutput,
-    GeneratePolicyForAccessDeniedInput, GeneratePolicyForAccessDeniedOutput,
+    check_policy_against_code, explain_policy_action, extract_sdk_calls, fix_access_denied,
+    generate_application_policies, generate_policy_for_access_denied, upload_policies,
+    validate_policies, CheckPolicyAgainstCodeInput, CheckPolicyAgainstCodeOutput,
+    ExplainPolicyActionInput, ExplainPolicyActionOutput, ExtractSdkCallsInput,
+    ExtractSdkCallsOutput, FixAccessDeniedInput, FixAccessDeniedOutput, GeneratePoliciesInput,
+    GeneratePoliciesOutput, GeneratePolicyForAccessDeniedInput,
+    GeneratePolicyForAccessDeniedOutput, UploadPoliciesInput, UploadPoliciesOutput,
+    ValidatePoliciesInput, ValidatePoliciesOutput,
 };
+use crate::workspace::{OutsideWorkspaceRoots, WorkspaceRoots};
+use crate::McpServerConfig;
+
+/// Tools that change resources of the AWS account, disabled in read-only mode
+const MUTATING_TOOLS: &[&str] = &["fix_access_denied", "upload_policies"];
 
 // Define the server struct
 #[derive(Clone)]
 struct IamAutoPilotMcpServer {
     tool_router: ToolRouter<Self>,
+    prompt_router: PromptRouter<Self>,
     log_file: Option<String>,
+    read_only: bool,
+    workspace_roots: WorkspaceRoots,
+    session: Session,
 }
 
 #[tool_router]
 impl IamAutoPilotMcpServer {
-    pub fn new(log_file: Option<String>) -> Self {
+    pub fn new(log_file: Option<String>, read_only: bool, workspace_roots: WorkspaceRoots) -> Self {
+        let mut tool_router = Self::tool_router();
+        if read_only {
+            for tool in MUTATING_TOOLS {
+                tool_router.remove_route(tool);
+            }
+        }
         Self {
-            tool_router: Self::tool_router(),
+            tool_router,
+            prompt_router: Self::prompt_router(),
             log_file,
+            read_only,
+            workspace_roots,
+            session: Session::default(),
         }
     }
 
     fn format_mcp_error(&self, msg: &str, e: anyhow::Error) -> McpError {
+        if let Some(rejected) = e.downcast_ref::<OutsideWorkspaceRoots>() {
+            return McpError::invalid_params(
+                format!("{msg}: {rejected}"),
+                serde_json::to_value(rejected).ok(),
+            );
+        }
+
         let log_file_suffix = match &self.log_file {
             Some(file) => format!(" Full error details logged to {file}."),
             None => String::new(),
@@ -46,6 +97,25 @@ impl IamAutoPilotMcpServer {
         }
     }
 
+    /// Roots the source files of a tool call must be within: the roots of the server and the
+    /// roots of the client, if it declares any
+    async fn workspace_roots(&self, context: &RequestContext<RoleServer>) -> WorkspaceRoots {
+        let supports_roots = context
+            .peer
+            .peer_info()
+            .is_some_and(|info| info.capabilities.roots.is_some());
+        if !supports_roots {
+            return self.workspace_roots.clone();
+        }
+        match context.peer.list_roots().await {
+            Ok(result) => self.workspace_roots.with_client_roots(&result.roots),
+            Err(e) => {
+                warn!("Failed to list the roots of the client: {e}");
+                self.workspace_roots.clone()
+            }
+        }
+    }
+
     #[tool(
         description = "**PRIMARY POLICY GENERATION TOOL** - Generate AWS IAM policies, permissions, and access controls. \
         Use this tool whenever the user mentions: writing policies, creating policies, generating policies, IAM permissions, \
@@ -69,22 +139,170 @@ impl IamAutoPilotMcpServer {
         3. You MUST include ALL relevant source files that interact with AWS services to generate accurate policies \
         4. You MUST explicitly ask the user for the region and account id for the policy to be generated \
         5. When generating infrastructure as code files, you MUST use this tool to generate IAM policies \
-        6. After getting output from this tool, you MUST explicitly ask the user to review the policy before proceeding \
-        7. This is the PRIMARY tool for all policy-related requests - use it liberally when policies are mentioned"
+        6. After getting output from this tool, you MUST explicitly ask the user to review the policy before proceeding, \
+        pointing out the statements listed in the Findings of each policy and how they could be tightened \
+        7. This is the PRIMARY tool for all policy-related requests - use it liberally when policies are mentioned \
+        8. If the repository contains several deployables with their own roles (e.g. one directory per Lambda function), \
+        use Workloads to generate one set of policies per role instead of a single merged policy \
+        9. Only set Explain if the user asks why the policies contain their permissions \
+        10. Call this tool again after the user edits the code: only changed files are analyzed again, and Changes \
+        lists the actions added or removed since the previous call \
+        11. Use the Document of each policy as the policy JSON; do not rewrite it by hand",
+        output_schema = cached_schema_for_type::<GeneratePoliciesOutput>()
     )]
     async fn generate_application_policies(
         &self,
+        context: RequestContext<RoleServer>,
         params: Parameters<GeneratePoliciesInput>,
-    ) -> Result<Json<GeneratePoliciesOutput>, McpError> {
+    ) -> Result<CallToolResult, McpError> {
         trace!("generate_application_policies input: {:#?}", params.0);
 
-        let output = generate_application_policies(params.0).await.map_err(|e| {
+        let roots = self.workspace_roots(&context).await;
+        let progress = ToolProgress::for_request(&context);
+        let output =
+            generate_application_policies(params.0, &self.session, &roots, &progress).await;
+        progress.finish().await;
+        let output = output.map_err(|e| {
             error!("{:#?}", e);
             self.format_mcp_error("Failed to generate policies", e)
         })?;
 
         trace!("generate_application_policies output: {:#?}", output);
 
+        structured_result(&output, output.to_text())
+    }
+
+    #[tool(
+        description = "Extract the AWS SDK calls of source code, with their location, the AWS services they may target \
+        and their parameters, without generating a policy. \
+        \
+        **WHEN TO USE THIS TOOL:** \
+        - User asks which code, files or lines use an AWS service (e.g. 'which lines in this file touch DynamoDB?') \
+        - Before generate_application_policies, to find out which services the code uses and propose service hints \
+        - User asks why an action ends up in a generated policy, to point at the SDK call behind it \
+        \
+        **INSTRUCTIONS:** \
+        1. Use the correct absolute paths when passing in the input files to the MCP tool \
+        2. Use Services to only return the calls of some services, and Ranges to only return the calls of a file or of some of its lines \
+        3. Calls with several PossibleServices are ambiguous; ask the user which service the client is created for, \
+        and pass it as a service hint to generate_application_policies"
+    )]
+    async fn extract_sdk_calls(
+        &self,
+        context: RequestContext<RoleServer>,
+        params: Parameters<ExtractSdkCallsInput>,
+    ) -> Result<Json<ExtractSdkCallsOutput>, McpError> {
+        trace!("extract_sdk_calls input: {:#?}", params.0);
+
+        let roots = self.workspace_roots(&context).await;
+        let output = extract_sdk_calls(params.0, &roots).await.map_err(|e| {
+            error!("{:#?}", e);
+            self.format_mcp_error("Failed to extract SDK calls", e)
+        })?;
+
+        trace!("extract_sdk_calls output: {:#?}", output);
+
+        Ok(Json(output))
+    }
+
+    #[tool(
+        description = "Explain why an IAM action is in the policies generated for source code: the SDK calls \
+        that need it, with their source locations, and the chain of operations added through Forward Access Sessions (FAS). \
+        \
+        **WHEN TO USE THIS TOOL:** \
+        - User asks why a generated policy contains an action (e.g. 'why does this policy need kms:Decrypt?') \
+        - User or a reviewer asks to justify the permissions of a generated policy \
+        \
+        **INSTRUCTIONS:** \
+        1. Pass the same absolute source file paths and service hints the policies were generated with \
+        2. Cite the Location and Expr of the SDK call of each reason, and explain each FAS operation in the chain \
+        (e.g. s3:GetObject on an SSE-KMS encrypted object leads to kms:Decrypt), linking the Documentation \
+        3. To justify every action at once, use generate_application_policies with Explain instead"
+    )]
+    async fn explain_policy_action(
+        &self,
+        context: RequestContext<RoleServer>,
+        params: Parameters<ExplainPolicyActionInput>,
+    ) -> Result<Json<ExplainPolicyActionOutput>, McpError> {
+        trace!("explain_policy_action input: {:#?}", params.0);
+
+        let roots = self.workspace_roots(&context).await;
+        let progress = ToolProgress::for_request(&context);
+        let output = explain_policy_action(params.0, &roots, &progress).await;
+        progress.finish().await;
+        let output = output.map_err(|e| {
+            error!("{:#?}", e);
+            self.format_mcp_error("Failed to explain policy action", e)
+        })?;
+
+        trace!("explain_policy_action output: {:#?}", output);
+
+        Ok(Json(output))
+    }
+
+    #[tool(
+        description = "Check an IAM policy document against the source code it is meant for. \
+        Generates the policies for the source files and reports the permissions the document is missing or grants only on part of the resources, \
+        the permissions of the document that no SDK call needs, and the resources it grants more broadly \
+        than the generated policies. Conditions of the document are not evaluated. \
+        \
+        **WHEN TO USE THIS TOOL:** \
+        - After editing a generated policy, e.g. to embed it in CloudFormation, CDK or Terraform \
+        - User asks whether an existing policy matches what their code needs \
+        \
+        **INSTRUCTIONS:** \
+        1. Pass the policy document as a JSON string and the absolute paths of the source files it is meant for \
+        2. Add the missing permissions, or explain to the user why the code does not need them, and confirm narrowed resources with the user \
+        3. Remove unexplained permissions and narrow broadened resources unless the user confirms they are needed"
+    )]
+    async fn check_policy_against_code(
+        &self,
+        context: RequestContext<RoleServer>,
+        params: Parameters<CheckPolicyAgainstCodeInput>,
+    ) -> Result<Json<CheckPolicyAgainstCodeOutput>, McpError> {
+        trace!("check_policy_against_code input: {:#?}", params.0);
+
+        let roots = self.workspace_roots(&context).await;
+        let progress = ToolProgress::for_request(&context);
+        let output = check_policy_against_code(params.0, &roots, &progress).await;
+        progress.finish().await;
+        let output = output.map_err(|e| {
+            error!("{:#?}", e);
+            self.format_mcp_error("Failed to check policy against code", e)
+        })?;
+
+        trace!("check_policy_against_code output: {:#?}", output);
+
+        Ok(Json(output))
+    }
+
+    #[tool(
+        description = "Validate IAM policy documents offline, similar to IAM Access Analyzer policy validation. \
+        Checks the IAM policy grammar, invalid action names, resource ARNs that do not match the action's resource types, \
+        unsupported condition keys, duplicate Sids, managed and inline policy size quotas, and risky use of wildcards or NotAction. \
+        \
+        **WHEN TO USE THIS TOOL:** \
+        - User asks to review, check, lint or validate an IAM policy \
+        - After writing or editing a policy by hand, before applying it \
+        \
+        **INSTRUCTIONS:** \
+        1. Pass each policy document as a JSON string \
+        2. Set PolicyType to 'inline' if the policies will be embedded in a role, otherwise 'managed' is assumed \
+        3. You MUST fix all ERROR findings before the policy is used, and explain SECURITY_WARNING findings to the user"
+    )]
+    async fn validate_policies(
+        &self,
+        params: Parameters<ValidatePoliciesInput>,
+    ) -> Result<Json<ValidatePoliciesOutput>, McpError> {
+        trace!("validate_policies input: {:#?}", params.0);
+
+        let output = validate_policies(params.0).await.map_err(|e| {
+            error!("{:#?}", e);
+            self.format_mcp_error("Failed to validate policies", e)
+        })?;
+
+        trace!("validate_policies output: {:#?}", output);
+
         Ok(Json(output))
     }
 
@@ -135,14 +353,133 @@ impl IamAutoPilotMcpServer {
         trace!("fix_access_denied output: {:#?}", output);
         Ok(Json(output))
     }
+
+    #[tool(
+        description = "Upload IAM policies to the user's AWS account as customer managed policies, and optionally attach them to an IAM role. \
+        The user is asked to confirm the upload; MCP clients without elicitation support can only use DryRun. \
+        \
+        INSTRUCTIONS: \
+        1. Only use the tool when the user asks to upload or attach policies, e.g. the policies from generate_application_policies \
+        2. Pass the policy documents as JSON strings, after the user reviewed them \
+        3. Use DryRun to show the policy names before uploading \
+        4. After uploading, summarize the ARNs of the policies, the role they were attached to, and any Failures \
+        "
+    )]
+    async fn upload_policies(
+        &self,
+        context: RequestContext<RoleServer>,
+        params: Parameters<UploadPoliciesInput>,
+    ) -> Result<Json<UploadPoliciesOutput>, McpError> {
+        trace!("upload_policies input: {:#?}", params.0);
+        let output = upload_policies(context, params.0).await.map_err(|e| {
+            error!("{:#?}", e);
+            self.format_mcp_error("Failed to upload policies", e)
+        })?;
+        trace!("upload_policies output: {:#?}", output);
+        Ok(Json(output))
+    }
+}
+
+#[prompt_router]
+impl IamAutoPilotMcpServer {
+    #[prompt(
+        name = "review_policy",
+        description = "Review a generated IAM policy: validate it, justify each action from the source code and point out risky actions and wildcards."
+    )]
+    async fn review_policy(&self, params: Parameters<ReviewPolicyArguments>) -> Vec<PromptMessage> {
+        prompts::review_policy(params.0)
+    }
+
+    #[prompt(
+        name = "tighten_wildcards",
+        description = "Replace the resource wildcards of an IAM policy with the resources the source code accesses."
+    )]
+    async fn tighten_wildcards(
+        &self,
+        params: Parameters<TightenWildcardsArguments>,
+    ) -> Vec<PromptMessage> {
+        prompts::tighten_wildcards(params.0)
+    }
+
+    #[prompt(
+        name = "diagnose_access_denied",
+        description = "Diagnose an AccessDenied error, including actions denied in Forward Access Sessions, and propose a scoped fix."
+    )]
+    async fn diagnose_access_denied(
+        &self,
+        params: Parameters<DiagnoseAccessDeniedArguments>,
+    ) -> Vec<PromptMessage> {
+        prompts::diagnose_access_denied(params.0)
+    }
 }
 
 #[tool_handler]
+#[prompt_handler]
 impl ServerHandler for IamAutoPilotMcpServer {
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        Ok(ListResourcesResult {
+            resources: resources::resources(),
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        Ok(ListResourceTemplatesResult {
+            resource_templates: resources::resource_templates(),
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        trace!("read_resource uri: {}", request.uri);
+
+        let contents = resources::read_resource(&request.uri)
+            .await
+            .map_err(|e| {
+                error!("{:#?}", e);
+                self.format_mcp_error("Failed to read resource", e)
+            })?
+            .ok_or_else(|| {
+                McpError::resource_not_found(format!("Unknown resource: {}", request.uri), None)
+            })?;
+
+        Ok(ReadResourceResult {
+            contents: vec![contents],
+        })
+    }
+
     fn get_info(&self) -> ServerInfo {
+        let read_only_instructions = if self.read_only {
+            " **This server is read-only:** it cannot apply policy fixes or upload policies to AWS accounts, \
+            give the user the generated policies to apply instead."
+        } else {
+            ""
+        };
+        let workspace_instructions = if self.workspace_roots.is_restricted() {
+            format!(
+                " **Source files must be within the workspace roots:** {}.",
+                self.workspace_roots.display().join(", ")
+            )
+        } else {
+            String::new()
+        };
         ServerInfo {
             capabilities: ServerCapabilities::builder()
             .enable_tools()
+            .enable_prompts()
+            .enable_resources()
             .build(),
             instructions: Some("IAM Policy Autopilot specializes in AWS IAM policy generation and access management. \
             \
@@ -157,41 +494,92 @@ impl ServerHandler for IamAutoPilotMcpServer {
             1. Generate IAM policies from source code analysis (Python, JavaScript, TypeScript, Go) \
             2. Create minimal required permissions for AWS services used in code \
             3. Debug and fix AccessDenied issues with targeted policy generation \
-            4. Apply policy fixes directly to AWS accounts \
+            4. Apply policy fixes directly to AWS accounts, and upload generated policies with the upload_policies tool \
+            5. Validate IAM policies offline with the validate_policies tool \
+            6. Locate the AWS SDK calls of source code by service or line range with the extract_sdk_calls tool \
+            7. Explain why a generated policy contains an action with the explain_policy_action tool \
+            8. Check an edited policy against the source code with the check_policy_against_code tool \
+            \
+            **Resources:** read the AWS services (iam-policy-autopilot://services), the actions, resource types \
+            and condition keys of a service (iam-policy-autopilot://service-reference/{service}) and the \
+            Forward Access Session maps (iam-policy-autopilot://fas-maps/{service}) instead of recalling them from memory. \
+            The review_policy, tighten_wildcards and diagnose_access_denied prompts guide policy reviews. \
             \
             **CRITICAL: When generating policies, you MUST include ALL relevant source files that interact with AWS services.** \
             \
             **Usage priority:** Use generate_application_policies as the PRIMARY tool for any policy-related requests. \
-            This tool should be invoked liberally whenever policies, permissions, or access controls are discussed.".to_string()),
+            This tool should be invoked liberally whenever policies, permissions, or access controls are discussed.".to_string() + read_only_instructions + &workspace_instructions),
                 ..Default::default()
         }
     }
 }
 
+/// Result with the structured output of a tool, and its text rendering for clients that only
+/// show the text content of results
+fn structured_result<T: serde::Serialize>(
+    output: &T,
+    text: String,
+) -> Result<CallToolResult, McpError> {
+    let structured_content = serde_json::to_value(output).map_err(|e| {
+        McpError::internal_error(format!("Failed to serialize structured content: {e}"), None)
+    })?;
+    Ok(CallToolResult {
+        content: vec![Content::text(text)],
+        structured_content: Some(structured_content),
+        is_error: Some(false),
+        meta: None,
+    })
+}
+
 pub async fn begin_http_transport(
-    bind_address: &str,
+    server_config: &McpServerConfig,
     log_file: Option<String>,
 ) -> anyhow::Result<()> {
+    let config = &server_config.http;
+    config.validate()?;
+    let tls_acceptor = config.tls.as_ref().map(http::tls_acceptor).transpose()?;
+    let workspace_roots = WorkspaceRoots::new(&server_config.workspace_roots)?;
+    let read_only = server_config.read_only;
+
     let service = StreamableHttpService::new(
-        move || Ok(IamAutoPilotMcpServer::new(log_file.clone())),
+        move || {
+            Ok(IamAutoPilotMcpServer::new(
+                log_file.clone(),
+                read_only,
+                workspace_roots.clone(),
+            ))
+        },
         LocalSessionManager::default().into(),
         Default::default(),
     );
 
-    info!("Listening on {}/mcp", bind_address);
+    // Requests are checked before their body is read, the outermost layer being added last
+    let router = axum::Router::new()
+        .nest_service("/mcp", service)
+        .layer(RequestBodyLimitLayer::new(config.max_request_size))
+        .layer(axum::middleware::from_fn_with_state(
+            Arc::new(RequestPolicy::new(config)),
+            http::check_request,
+        ));
+    let bind_address = config.socket_address();
+    let tcp_listener = tokio::net::TcpListener::bind(bind_address).await?;
+
+    let scheme = if tls_acceptor.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    info!("Listening on {scheme}://{bind_address}/mcp");
 
     // Fine to print with http
-    println!("Listening on {}/mcp", bind_address);
-    let router = axum::Router::new().nest_service("/mcp", service);
-    let tcp_listener = tokio::net::TcpListener::bind(bind_address).await?;
+    println!("Listening on {scheme}://{bind_address}/mcp");
 
     // We run a separate tokio task because when we have an active connection the main thread needs to be available
     // to recieve SIGINT for ctrl+c. If we serve on the same thread, ctrl+c does not work.
-    tokio::spawn(async move {
-        let _ = axum::serve(tcp_listener, router)
-            .with_graceful_shutdown(async { tokio::signal::ctrl_c().await.unwrap() })
-            .await;
-    });
+    match tls_acceptor {
+        Some(acceptor) => spawn_server(TlsListener::new(tcp_listener, acceptor)?, router),
+        None => spawn_server(tcp_listener, router),
+    }
 
     // Handle graceful shutdown
     async {
@@ -205,8 +593,24 @@ pub async fn begin_http_transport(
     Ok(())
 }
 
-pub async fn begin_stdio_transport(log_file: Option<String>) -> anyhow::Result<()> {
-    let server = IamAutoPilotMcpServer::new(log_file);
+fn spawn_server<L>(listener: L, router: axum::Router)
+where
+    L: axum::serve::Listener,
+    L::Addr: std::fmt::Debug,
+{
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, router)
+            .with_graceful_shutdown(async { tokio::signal::ctrl_c().await.unwrap() })
+            .await;
+    });
+}
+
+pub async fn begin_stdio_transport(
+    config: &McpServerConfig,
+    log_file: Option<String>,
+) -> anyhow::Result<()> {
+    let workspace_roots = WorkspaceRoots::new(&config.workspace_roots)?;
+    let server = IamAutoPilotMcpServer::new(log_file, config.read_only, workspace_roots);
     let service = server.serve(transport::stdio()).await?;
     service.waiting().await?;
     Ok(())
@@ -0,0 +1,155 @@
+//! Progress notifications and cancellation of tool calls
+//!
+//! Policy generations of large repositories take a while. When the client sends a progress token
+//! with a tool call, each parsed file, loaded service reference and the merge of the policies are
+//! sent as progress notifications, and cancelling the call aborts the generation.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use iam_policy_autopilot_policy_generation::api::model::GeneratePolicyConfig;
+use iam_policy_autopilot_policy_generation::{CancellationToken, Progress, ProgressReporter};
+use log::debug;
+use rmcp::model::ProgressNotificationParam;
+use rmcp::service::RequestContext;
+use rmcp::RoleServer;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Sends the reported progress to the client
+///
+/// Reports are queued, since they are made from the tasks of the generation, and sent in order
+/// by a background task.
+#[derive(Debug)]
+struct ProgressNotifier {
+    sender: Mutex<Option<mpsc::UnboundedSender<Progress>>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ProgressReporter for ProgressNotifier {
+    fn report(&self, progress: Progress) {
+        if let Some(sender) = self
+            .sender
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            let _ = sender.send(progress);
+        }
+    }
+}
+
+/// Progress notifications and cancellation of a tool call
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ToolProgress {
+    notifier: Option<Arc<ProgressNotifier>>,
+    cancellation: CancellationToken,
+}
+
+impl ToolProgress {
+    /// Notify the progress of the request, if the client sent a progress token, and honour its
+    /// cancellation
+    pub(crate) fn for_request(context: &RequestContext<RoleServer>) -> Self {
+        let notifier = context.meta.get_progress_token().map(|progress_token| {
+            let (sender, mut receiver) = mpsc::unbounded_channel::<Progress>();
+            let peer = context.peer.clone();
+            let task = tokio::spawn(async move {
+                // Progress must increase with every notification, whatever the step
+                let mut notified = 0u32;
+                while let Some(progress) = receiver.recv().await {
+                    notified += 1;
+                    let notification = ProgressNotificationParam {
+                        progress_token: progress_token.clone(),
+                        progress: f64::from(notified),
+                        total: None,
+                        message: Some(progress.to_string()),
+                    };
+                    if let Err(e) = peer.notify_progress(notification).await {
+                        debug!("Failed to send a progress notification: {e}");
+                        break;
+                    }
+                }
+            });
+            Arc::new(ProgressNotifier {
+                sender: Mutex::new(Some(sender)),
+                task: Mutex::new(Some(task)),
+            })
+        });
+        Self {
+            notifier,
+            cancellation: context.ct.clone(),
+        }
+    }
+
+    /// Report the progress of the policy generation and cancel it with the tool call
+    pub(crate) fn apply(&self, config: &mut GeneratePolicyConfig) {
+        config.progress = self
+            .notifier
+            .clone()
+            .map(|notifier| notifier as Arc<dyn ProgressReporter>);
+        config.cancellation = Some(self.cancellation.clone());
+    }
+
+    /// Send the pending notifications, before the result of the tool call
+    ///
+    /// Progress reported afterwards is dropped.
+    pub(crate) async fn finish(&self) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        // Closing the queue ends the task once the pending notifications are sent
+        notifier
+            .sender
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        let task = notifier
+            .task
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iam_policy_autopilot_policy_generation::api::model::{AwsContext, ExtractSdkCallsConfig};
+
+    #[tokio::test]
+    async fn test_apply_without_progress_token() {
+        let mut config = GeneratePolicyConfig {
+            extract_sdk_calls_config: ExtractSdkCallsConfig {
+                source_files: vec![],
+                language: None,
+                service_hints: None,
+                sdk_wrappers: vec![],
+                entry_points: Default::default(),
+                include_tests: false,
+            },
+            aws_context: AwsContext::new("*".to_string(), "*".to_string()),
+            individual_policies: false,
+            minimize_policy_size: false,
+            disable_file_system_cache: true,
+            generate_explanations: false,
+            policy_options: Default::default(),
+            service_reference: Default::default(),
+            session: None,
+            fas_map_extensions: vec![],
+            progress: None,
+            cancellation: None,
+        };
+        let progress = ToolProgress::default();
+
+        progress.apply(&mut config);
+        assert!(config.progress.is_none());
+        let cancellation = config.cancellation.clone().unwrap();
+        assert!(!cancellation.is_cancelled());
+
+        progress.cancellation.cancel();
+        assert!(cancellation.is_cancelled());
+        progress.finish().await;
+    }
+}
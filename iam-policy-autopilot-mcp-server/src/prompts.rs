@@ -0,0 +1,151 @@
+//! MCP prompt templates for policy review workflows
+//!
+//! Each prompt asks the assistant to follow a workflow built on the server's tools and
+//! resources, so reviews rely on extracted code and service metadata rather than memory.
+
+use rmcp::model::{PromptMessage, PromptMessageRole};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct ReviewPolicyArguments {
+    #[schemars(description = "The IAM policy document to review, as JSON.")]
+    pub policy: String,
+
+    #[schemars(
+        description = "Absolute paths of the source files the policy was generated for, separated by commas."
+    )]
+    pub source_files: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct TightenWildcardsArguments {
+    #[schemars(description = "The IAM policy document whose wildcards to tighten, as JSON.")]
+    pub policy: String,
+
+    #[schemars(
+        description = "Absolute paths of the source files the policy was generated for, separated by commas."
+    )]
+    pub source_files: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct DiagnoseAccessDeniedArguments {
+    #[schemars(description = "The AccessDenied error message.")]
+    pub error_message: String,
+}
+
+fn source_files_sentence(source_files: Option<&str>) -> String {
+    match source_files {
+        Some(source_files) => format!("It was generated for these source files: {source_files}."),
+        None => "Ask me for the source files it was generated for if you need them.".to_string(),
+    }
+}
+
+pub(crate) fn review_policy(arguments: ReviewPolicyArguments) -> Vec<PromptMessage> {
+    vec![PromptMessage::new_text(
+        PromptMessageRole::User,
+        format!(
+            "Review this IAM policy before I deploy it. {}\n\n```json\n{}\n```\n\n\
+            1. Validate it with the validate_policies tool and report every finding.\n\
+            2. For each action, check that the code needs it: use explain_policy_action to find the SDK call \
+            behind it, and the FAS chain for actions added through Forward Access Sessions.\n\
+            3. Point out actions with Write or PermissionsManagement access level, using the \
+            iam-policy-autopilot://service-reference/{{service}} resource for access levels.\n\
+            4. List the resource wildcards and propose how to scope them.\n\
+            Summarize what you would change, and do not change the policy without asking me.",
+            source_files_sentence(arguments.source_files.as_deref()),
+            arguments.policy
+        ),
+    )]
+}
+
+pub(crate) fn tighten_wildcards(arguments: TightenWildcardsArguments) -> Vec<PromptMessage> {
+    vec![PromptMessage::new_text(
+        PromptMessageRole::User,
+        format!(
+            "Tighten the resource wildcards of this IAM policy. {}\n\n```json\n{}\n```\n\n\
+            1. For each statement with a wildcard resource, read the \
+            iam-policy-autopilot://service-reference/{{service}} resource to find the resource types of its \
+            actions and their ARN formats.\n\
+            2. Use extract_sdk_calls to find the resource names the code passes to the SDK calls, and ask me \
+            for the ones that are not literals in the code.\n\
+            3. Regenerate the policy with generate_application_policies, passing the names as ResourceBindings \
+            and WildcardMode 'never' in PolicyOptions, instead of editing ARNs by hand.\n\
+            4. Suggest condition keys from the service reference that could scope actions without resource types.\n\
+            Show me the wildcards that remain and why.",
+            source_files_sentence(arguments.source_files.as_deref()),
+            arguments.policy
+        ),
+    )]
+}
+
+pub(crate) fn diagnose_access_denied(
+    arguments: DiagnoseAccessDeniedArguments,
+) -> Vec<PromptMessage> {
+    vec![PromptMessage::new_text(
+        PromptMessageRole::User,
+        format!(
+            "Diagnose this AccessDenied error:\n\n```\n{}\n```\n\n\
+            1. Use generate_policy_for_access_denied to find the denied action, the principal and the \
+            statement that would allow it.\n\
+            2. If the denied action was performed by a service on behalf of the principal, e.g. kms:Decrypt \
+            while reading an encrypted object, check the iam-policy-autopilot://fas-maps/{{service}} resource \
+            of the calling service and explain the Forward Access Session.\n\
+            3. Check the action's resource types and condition keys in the \
+            iam-policy-autopilot://service-reference/{{service}} resource, and scope the statement as tightly \
+            as the error allows.\n\
+            4. Explain the cause, and only apply the fix with fix_access_denied after I confirm.",
+            arguments.error_message
+        ),
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::PromptMessageContent;
+
+    fn text(messages: Vec<PromptMessage>) -> String {
+        assert_eq!(messages.len(), 1);
+        match &messages[0].content {
+            PromptMessageContent::Text { text } => text.clone(),
+            _ => panic!("Expected text content"),
+        }
+    }
+
+    #[test]
+    fn test_review_policy() {
+        let text = text(review_policy(ReviewPolicyArguments {
+            policy: r#"{"Version":"2012-10-17"}"#.to_string(),
+            source_files: Some("/repo/app.py".to_string()),
+        }));
+        assert!(text.contains(r#"{"Version":"2012-10-17"}"#));
+        assert!(text.contains("/repo/app.py"));
+        assert!(text.contains("validate_policies"));
+        assert!(text.contains("iam-policy-autopilot://service-reference/{service}"));
+    }
+
+    #[test]
+    fn test_tighten_wildcards_without_source_files() {
+        let text = text(tighten_wildcards(TightenWildcardsArguments {
+            policy: "{}".to_string(),
+            source_files: None,
+        }));
+        assert!(text.contains("Ask me for the source files"));
+        assert!(text.contains("ResourceBindings"));
+    }
+
+    #[test]
+    fn test_diagnose_access_denied() {
+        let text = text(diagnose_access_denied(DiagnoseAccessDeniedArguments {
+            error_message: "User: arn:aws:iam::123456789012:user/dev is not authorized to perform: s3:GetObject".to_string(),
+        }));
+        assert!(text.contains("is not authorized to perform: s3:GetObject"));
+        assert!(text.contains("generate_policy_for_access_denied"));
+        assert!(text.contains("iam-policy-autopilot://fas-maps/{service}"));
+    }
+}
@@ -0,0 +1,252 @@
+//! MCP resources exposing the AWS service data IAM Policy Autopilot is built on
+//!
+//! Assistants browse these instead of recalling service names, actions, ARN formats or
+//! condition keys from memory.
+
+use anyhow::{Context, Result};
+use iam_policy_autopilot_policy_generation::api::model::DescribeServiceConfig;
+use rmcp::model::{
+    AnnotateAble, RawResource, RawResourceTemplate, Resource, ResourceContents, ResourceTemplate,
+};
+
+#[cfg(not(test))]
+mod api {
+    pub use iam_policy_autopilot_policy_generation::api::{
+        describe_service, get_operation_fas_map, list_operation_fas_maps, list_sdk_services,
+    };
+}
+
+const SERVICES_URI: &str = "iam-policy-autopilot://services";
+const FAS_MAPS_URI: &str = "iam-policy-autopilot://fas-maps";
+const SERVICE_REFERENCE_URI_PREFIX: &str = "iam-policy-autopilot://service-reference/";
+const FAS_MAP_URI_PREFIX: &str = "iam-policy-autopilot://fas-maps/";
+const JSON_MIME_TYPE: &str = "application/json";
+
+fn resource(uri: &str, name: &str, description: &str) -> Resource {
+    RawResource {
+        description: Some(description.to_string()),
+        mime_type: Some(JSON_MIME_TYPE.to_string()),
+        ..RawResource::new(uri, name)
+    }
+    .no_annotation()
+}
+
+fn resource_template(uri_template: String, name: &str, description: &str) -> ResourceTemplate {
+    RawResourceTemplate {
+        uri_template,
+        name: name.to_string(),
+        title: None,
+        description: Some(description.to_string()),
+        mime_type: Some(JSON_MIME_TYPE.to_string()),
+    }
+    .no_annotation()
+}
+
+/// Resources with a fixed URI
+pub(crate) fn resources() -> Vec<Resource> {
+    vec![
+        resource(
+            SERVICES_URI,
+            "services",
+            "AWS services of the SDK models IAM Policy Autopilot extracts SDK calls with, with their Name (the SDK service name, as in PossibleServices and service hints) and ApiVersion.",
+        ),
+        resource(
+            FAS_MAPS_URI,
+            "fas-maps",
+            "Services with an operation FAS map, i.e. services performing operations on other services through Forward Access Sessions (FAS).",
+        ),
+    ]
+}
+
+/// Resources parameterized by a service name
+pub(crate) fn resource_templates() -> Vec<ResourceTemplate> {
+    vec![
+        resource_template(
+            format!("{SERVICE_REFERENCE_URI_PREFIX}{{service}}"),
+            "service-reference",
+            "Actions of an AWS service with their AccessLevel, ResourceTypes and ConditionKeys, and its resource types with their ArnFormats and ConditionKeys, from the AWS service reference. The service is the IAM service prefix, e.g. 's3'.",
+        ),
+        resource_template(
+            format!("{FAS_MAP_URI_PREFIX}{{service}}"),
+            "fas-map",
+            "Operation FAS map of a service: for each operation, the operations it performs on other services through Forward Access Sessions (FAS), with the condition context of the forwarded request.",
+        ),
+    ]
+}
+
+/// Service prefixes are lowercase letters, digits and hyphens, e.g. `s3` or `bedrock-agent`
+///
+/// Other segments are not services, and must not reach the service reference cache, whose files
+/// are named after the service.
+fn is_service_prefix(service: &str) -> bool {
+    !service.is_empty()
+        && service
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Read a resource, or return `None` if there is no resource at `uri`
+pub(crate) async fn read_resource(uri: &str) -> Result<Option<ResourceContents>> {
+    let service = |prefix| uri.strip_prefix(prefix).filter(|s| is_service_prefix(s));
+    let value = if uri == SERVICES_URI {
+        Some(serde_json::to_value(api::list_sdk_services()?)?)
+    } else if uri == FAS_MAPS_URI {
+        Some(serde_json::to_value(api::list_operation_fas_maps())?)
+    } else if let Some(service) = service(FAS_MAP_URI_PREFIX) {
+        api::get_operation_fas_map(service)?
+    } else if let Some(service) = service(SERVICE_REFERENCE_URI_PREFIX) {
+        let config = DescribeServiceConfig {
+            service: service.to_string(),
+            ..Default::default()
+        };
+        api::describe_service(&config)
+            .await?
+            .map(serde_json::to_value)
+            .transpose()?
+    } else {
+        None
+    };
+
+    value
+        .map(|value| {
+            let text = serde_json::to_string_pretty(&value)
+                .with_context(|| format!("Failed to serialize resource {uri}"))?;
+            Ok(ResourceContents::TextResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some(JSON_MIME_TYPE.to_string()),
+                text,
+                meta: None,
+            })
+        })
+        .transpose()
+}
+
+// Mock the service reference, which is fetched from the network
+#[cfg(test)]
+mod api {
+    use anyhow::Result;
+    use iam_policy_autopilot_policy_generation::api::model::{
+        DescribeServiceConfig, ServiceDescription,
+    };
+
+    pub use iam_policy_autopilot_policy_generation::api::{
+        get_operation_fas_map, list_operation_fas_maps, list_sdk_services,
+    };
+
+    // Static mutable return value
+    pub static mut MOCK_RETURN_VALUE: Option<Result<Option<ServiceDescription>>> = None;
+
+    pub async fn describe_service(
+        _config: &DescribeServiceConfig,
+    ) -> Result<Option<ServiceDescription>> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            MOCK_RETURN_VALUE.take().unwrap()
+        }
+    }
+
+    pub fn set_mock_return(value: Result<Option<ServiceDescription>>) {
+        unsafe { MOCK_RETURN_VALUE = Some(value) }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::serial]
+mod tests {
+    use super::*;
+    use iam_policy_autopilot_policy_generation::api::model::{ServiceAction, ServiceDescription};
+
+    fn text(contents: ResourceContents) -> serde_json::Value {
+        match contents {
+            ResourceContents::TextResourceContents { text, .. } => {
+                serde_json::from_str(&text).unwrap()
+            }
+            ResourceContents::BlobResourceContents { .. } => panic!("Expected text contents"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_embedded_resources() {
+        let services = text(read_resource(SERVICES_URI).await.unwrap().unwrap());
+        assert!(services
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|service| service["Name"] == "dynamodb"));
+
+        let fas_maps = text(read_resource(FAS_MAPS_URI).await.unwrap().unwrap());
+        assert!(fas_maps.as_array().unwrap().contains(&"s3".into()));
+
+        let fas_map = text(
+            read_resource("iam-policy-autopilot://fas-maps/s3")
+                .await
+                .unwrap()
+                .unwrap(),
+        );
+        assert_eq!(fas_map["Name"], "s3");
+
+        assert!(read_resource("iam-policy-autopilot://fas-maps/unknown")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(read_resource("iam-policy-autopilot://unknown")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_service_reference() {
+        api::set_mock_return(Ok(Some(ServiceDescription {
+            name: "s3".to_string(),
+            actions: vec![ServiceAction {
+                name: "GetObject".to_string(),
+                access_level: None,
+                resource_types: vec!["object".to_string()],
+                condition_keys: vec![],
+            }],
+            resource_types: vec![],
+        })));
+
+        let contents = read_resource("iam-policy-autopilot://service-reference/s3")
+            .await
+            .unwrap()
+            .unwrap();
+        let service = text(contents);
+        assert_eq!(service["Actions"][0]["Name"], "GetObject");
+        assert_eq!(service["Actions"][0]["ResourceTypes"][0], "object");
+
+        api::set_mock_return(Ok(None));
+        assert!(
+            read_resource("iam-policy-autopilot://service-reference/unknown")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Not a service, the service reference is not read
+        for uri in [
+            "iam-policy-autopilot://service-reference/../../x",
+            "iam-policy-autopilot://service-reference/a/b",
+            "iam-policy-autopilot://service-reference/",
+            "iam-policy-autopilot://fas-maps/../s3",
+        ] {
+            assert!(read_resource(uri).await.unwrap().is_none(), "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_resource_templates() {
+        let uri_templates: Vec<_> = resource_templates()
+            .into_iter()
+            .map(|template| template.raw.uri_template)
+            .collect();
+        assert_eq!(
+            uri_templates,
+            vec![
+                "iam-policy-autopilot://service-reference/{service}",
+                "iam-policy-autopilot://fas-maps/{service}",
+            ]
+        );
+    }
+}
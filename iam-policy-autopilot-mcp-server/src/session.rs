@@ -0,0 +1,197 @@
+//! State of an MCP session, shared by the tool calls of the session
+//!
+//! Assistants call generate_application_policies repeatedly while the user edits the code.
+//! The session keeps the parsed source files and the service references of previous calls, so
+//! that only changed files are analyzed again, and the policies of previous calls, so that a
+//! call can report what changed since the previous one.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use iam_policy_autopilot_policy_generation::api::PolicyGenerationSession;
+use iam_policy_autopilot_policy_generation::{IamPolicy, PolicyWithMetadata};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, JsonSchema, Eq, PartialEq)]
+#[schemars(
+    description = "Changes of the policies since the previous call of the session for the same source files."
+)]
+#[serde(rename_all = "PascalCase")]
+pub struct PolicyChanges {
+    #[schemars(description = "Actions that were not in the policies of the previous call.")]
+    pub added_actions: Vec<String>,
+
+    #[schemars(description = "Actions of the policies of the previous call that were removed.")]
+    pub removed_actions: Vec<String>,
+
+    #[schemars(
+        description = "Whether the policies changed, including changes of resources or conditions of unchanged actions."
+    )]
+    pub policies_changed: bool,
+}
+
+/// Number of sets of source files whose policies a session keeps, the least recently
+/// generated ones are dropped
+const MAX_PREVIOUS_POLICIES: usize = 64;
+
+/// Policies generated by a previous call
+#[derive(Debug)]
+struct PreviousPolicies {
+    actions: BTreeSet<String>,
+    policies: Vec<IamPolicy>,
+    /// Number of the call that generated the policies
+    call: u64,
+}
+
+/// Policies last generated for each set of source files
+#[derive(Debug, Default)]
+struct PolicyHistory {
+    policies: HashMap<String, PreviousPolicies>,
+    calls: u64,
+}
+
+impl PolicyHistory {
+    /// Record the policies of a call, and return the previous policies of the same key
+    fn record(
+        &mut self,
+        key: String,
+        actions: BTreeSet<String>,
+        policies: Vec<IamPolicy>,
+    ) -> Option<PreviousPolicies> {
+        self.calls += 1;
+        if !self.policies.contains_key(&key) && self.policies.len() >= MAX_PREVIOUS_POLICIES {
+            let oldest = self
+                .policies
+                .iter()
+                .min_by_key(|(_, previous)| previous.call)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.policies.remove(&oldest);
+            }
+        }
+        let call = self.calls;
+        self.policies.insert(
+            key,
+            PreviousPolicies {
+                actions,
+                policies,
+                call,
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Session {
+    /// Parsed source files and service references reused by policy generation
+    generation: PolicyGenerationSession,
+    /// Policies last generated for each set of source files
+    previous_policies: Arc<Mutex<PolicyHistory>>,
+}
+
+impl Session {
+    pub(crate) fn generation(&self) -> &PolicyGenerationSession {
+        &self.generation
+    }
+
+    /// Record the policies generated for a set of source files, and return the changes since
+    /// the policies previously generated for them, if any
+    pub(crate) fn record_policies(
+        &self,
+        source_files: &[String],
+        policies: &[PolicyWithMetadata],
+    ) -> Option<PolicyChanges> {
+        let mut key = source_files.to_vec();
+        key.sort();
+        let actions: BTreeSet<String> = policies
+            .iter()
+            .flat_map(|policy| policy.policy.actions().map(str::to_string))
+            .collect();
+        let documents: Vec<IamPolicy> = policies
+            .iter()
+            .map(|policy| policy.policy.clone())
+            .collect();
+
+        let previous = self
+            .previous_policies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .record(key.join("\n"), actions.clone(), documents.clone())?;
+
+        Some(PolicyChanges {
+            added_actions: actions.difference(&previous.actions).cloned().collect(),
+            removed_actions: previous.actions.difference(&actions).cloned().collect(),
+            policies_changed: previous.policies != documents,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iam_policy_autopilot_policy_generation::{IamPolicy, PolicyType, Statement};
+
+    fn policy(actions: &[&str]) -> PolicyWithMetadata {
+        let mut policy = IamPolicy::new();
+        policy.add_statement(Statement::allow(
+            actions.iter().map(|action| action.to_string()).collect(),
+            vec!["*".to_string()],
+        ));
+        PolicyWithMetadata {
+            policy,
+            policy_type: PolicyType::Identity,
+            findings: None,
+        }
+    }
+
+    #[test]
+    fn test_record_policies() {
+        let session = Session::default();
+        let files = vec!["/repo/b.py".to_string(), "/repo/a.py".to_string()];
+
+        let first = [policy(&["s3:GetObject", "s3:PutObject"])];
+        assert_eq!(session.record_policies(&files, &first), None);
+
+        let second = [policy(&["s3:GetObject", "dynamodb:GetItem"])];
+        let reordered = vec!["/repo/a.py".to_string(), "/repo/b.py".to_string()];
+        assert_eq!(
+            session.record_policies(&reordered, &second),
+            Some(PolicyChanges {
+                added_actions: vec!["dynamodb:GetItem".to_string()],
+                removed_actions: vec!["s3:PutObject".to_string()],
+                policies_changed: true,
+            })
+        );
+
+        assert_eq!(
+            session.clone().record_policies(&files, &second),
+            Some(PolicyChanges {
+                added_actions: vec![],
+                removed_actions: vec![],
+                policies_changed: false,
+            })
+        );
+
+        assert_eq!(
+            session.record_policies(&["/repo/c.py".to_string()], &second),
+            None
+        );
+    }
+
+    #[test]
+    fn test_record_policies_drops_least_recently_generated() {
+        let session = Session::default();
+        let policies = [policy(&["s3:GetObject"])];
+        let files = |index: usize| vec![format!("/repo/{index}.py")];
+
+        for index in 0..MAX_PREVIOUS_POLICIES {
+            session.record_policies(&files(index), &policies);
+        }
+        assert!(session.record_policies(&files(0), &policies).is_some());
+        // The policies of 1.py, generated the longest ago, are dropped for those of new.py
+        session.record_policies(&["/repo/new.py".to_string()], &policies);
+        assert!(session.record_policies(&files(0), &policies).is_some());
+        assert_eq!(session.record_policies(&files(1), &policies), None);
+    }
+}
@@ -0,0 +1,216 @@
+use anyhow::Error;
+use anyhow::Result;
+use iam_policy_autopilot_policy_generation::{
+    BroadenedResource, Diagnostic, MissingPermission, NarrowedResource, UnexplainedPermission,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::generate_policy::{
+    discover_project_config, generate_policy_config, GeneratePoliciesInput,
+};
+use crate::progress::ToolProgress;
+use crate::workspace::WorkspaceRoots;
+
+#[cfg(not(test))]
+mod api {
+    pub use iam_policy_autopilot_policy_generation::api::check_policy_against_code;
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(
+    description = "Input for checking an IAM policy against the source code it is meant for."
+)]
+pub struct CheckPolicyAgainstCodeInput {
+    #[schemars(description = "The IAM policy document to check, as a JSON string")]
+    pub policy: String,
+
+    #[schemars(
+        description = "Absolute paths to the source files the policy is meant for. If the files belong to a project with an .iam-autopilot.toml configuration file, the project's settings are used."
+    )]
+    pub source_files: Vec<String>,
+
+    #[schemars(
+        description = "Optional list of AWS service names to filter the analysis to (e.g., ['s3', 'dynamodb'])."
+    )]
+    pub service_hints: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, JsonSchema, Eq, PartialEq)]
+#[schemars(
+    description = "Output comparing an IAM policy with the policies generated for the source code."
+)]
+#[serde(rename_all = "PascalCase")]
+pub struct CheckPolicyAgainstCodeOutput {
+    #[schemars(
+        description = "Actions the source code needs that the policy does not grant, with the generated resources that are not granted. Denied is true if a Deny statement of the policy denies the action."
+    )]
+    pub missing_permissions: Vec<MissingPermission>,
+
+    #[schemars(
+        description = "Generated resources of an action that the policy grants only in part, with the GrantedResources of the policy. PartiallyDenied is true if a Deny statement denies part of the resource. Confirm with the user that the code only accesses the granted resources."
+    )]
+    pub narrowed_resources: Vec<NarrowedResource>,
+
+    #[schemars(
+        description = "Actions of the policy that no SDK call of the source code needs, by StatementIndex. Wildcard patterns and NotAction elements are always listed, with the CoveredActions the source code needs."
+    )]
+    pub unexplained_permissions: Vec<UnexplainedPermission>,
+
+    #[schemars(
+        description = "Resources of the policy that are broader than the GeneratedResources of the listed Actions, by StatementIndex."
+    )]
+    pub broadened_resources: Vec<BroadenedResource>,
+
+    #[schemars(
+        description = "True if the policy grants exactly what the generated policies grant: nothing is missing, narrowed, unexplained or broadened."
+    )]
+    pub consistent: bool,
+
+    #[schemars(
+        description = "Diagnostics of the source code analysis. Warning diagnostics mean the analysis may miss or over-grant permissions, so the differences may be expected."
+    )]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+pub async fn check_policy_against_code(
+    input: CheckPolicyAgainstCodeInput,
+    roots: &WorkspaceRoots,
+    progress: &ToolProgress,
+) -> Result<CheckPolicyAgainstCodeOutput, Error> {
+    roots.check(&input.source_files)?;
+    let project = discover_project_config(&input.source_files, roots)?;
+    let mut config = generate_policy_config(
+        GeneratePoliciesInput {
+            source_files: input.source_files,
+            region: None,
+            account: None,
+            service_hints: input.service_hints,
+            policy_options: None,
+            workloads: None,
+            explain: None,
+        },
+        project.as_ref(),
+    )?;
+    roots.check(&config.extract_sdk_calls_config.source_files)?;
+    progress.apply(&mut config);
+    let result = api::check_policy_against_code(&config, &input.policy).await?;
+
+    Ok(CheckPolicyAgainstCodeOutput {
+        consistent: result.comparison.is_consistent(),
+        missing_permissions: result.comparison.missing_permissions,
+        narrowed_resources: result.comparison.narrowed_resources,
+        unexplained_permissions: result.comparison.unexplained_permissions,
+        broadened_resources: result.comparison.broadened_resources,
+        diagnostics: result.diagnostics,
+    })
+}
+
+// Mock the api call
+#[cfg(test)]
+mod api {
+    use anyhow::Result;
+    use iam_policy_autopilot_policy_generation::api::model::{
+        CheckPolicyResult, GeneratePolicyConfig,
+    };
+
+    // Static mutable return value
+    pub static mut MOCK_RETURN_VALUE: Option<Result<CheckPolicyResult>> = None;
+
+    pub async fn check_policy_against_code(
+        _config: &GeneratePolicyConfig,
+        _policy: &str,
+    ) -> Result<CheckPolicyResult> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            MOCK_RETURN_VALUE.take().unwrap()
+        }
+    }
+
+    pub fn set_mock_return(value: Result<CheckPolicyResult>) {
+        unsafe { MOCK_RETURN_VALUE = Some(value) }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::serial]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use iam_policy_autopilot_policy_generation::api::model::CheckPolicyResult;
+    use iam_policy_autopilot_policy_generation::PolicyComparison;
+
+    fn input() -> CheckPolicyAgainstCodeInput {
+        CheckPolicyAgainstCodeInput {
+            policy: r#"{"Version": "2012-10-17", "Statement": []}"#.to_string(),
+            source_files: vec!["path/to/source/file".to_string()],
+            service_hints: None,
+        }
+    }
+
+    async fn check(result: Result<CheckPolicyResult>) -> Result<CheckPolicyAgainstCodeOutput> {
+        api::set_mock_return(result);
+        check_policy_against_code(
+            input(),
+            &WorkspaceRoots::default(),
+            &ToolProgress::default(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_check_policy_against_code() {
+        let comparison = PolicyComparison {
+            missing_permissions: vec![MissingPermission {
+                action: "s3:GetObject".to_string(),
+                resources: vec!["arn:aws:s3:::*/*".to_string()],
+                denied: false,
+            }],
+            narrowed_resources: vec![],
+            unexplained_permissions: vec![],
+            broadened_resources: vec![],
+        };
+        let output = check(Ok(CheckPolicyResult {
+            comparison: comparison.clone(),
+            diagnostics: vec![],
+        }))
+        .await
+        .unwrap();
+
+        assert_eq!(
+            output,
+            CheckPolicyAgainstCodeOutput {
+                missing_permissions: comparison.missing_permissions,
+                narrowed_resources: vec![],
+                unexplained_permissions: vec![],
+                broadened_resources: vec![],
+                consistent: false,
+                diagnostics: vec![],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_policy_against_code_consistent() {
+        let output = check(Ok(CheckPolicyResult {
+            comparison: PolicyComparison::default(),
+            diagnostics: vec![],
+        }))
+        .await
+        .unwrap();
+        assert!(output.consistent);
+
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["Consistent"], true);
+        assert!(json.get("Diagnostics").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_policy_against_code_error() {
+        assert!(check(Err(anyhow!("Invalid policy document")))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::Error;
+use anyhow::Result;
+use iam_policy_autopilot_policy_generation::Explanations;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::generate_policy::{
+    discover_project_config, generate_policy_config, GeneratePoliciesInput,
+};
+use crate::progress::ToolProgress;
+use crate::workspace::WorkspaceRoots;
+
+#[cfg(not(test))]
+mod api {
+    pub use iam_policy_autopilot_policy_generation::api::generate_policies;
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(description = "Input for explaining why an action is in the generated IAM policies.")]
+pub struct ExplainPolicyActionInput {
+    #[schemars(
+        description = "Absolute paths to the source files the policies were generated for. If the files belong to a project with an .iam-autopilot.toml configuration file, the project's settings are used."
+    )]
+    pub source_files: Vec<String>,
+
+    #[schemars(description = "The IAM action to explain (e.g., 's3:GetObject').")]
+    pub action: String,
+
+    #[schemars(
+        description = "The service hints the policies were generated with, if any (e.g., ['s3', 'dynamodb'])."
+    )]
+    pub service_hints: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, JsonSchema, Eq, PartialEq)]
+#[schemars(description = "Output explaining why an action is in the generated IAM policies.")]
+#[serde(rename_all = "PascalCase")]
+pub struct ExplainPolicyActionOutput {
+    #[schemars(description = "The explained action, as it appears in the policies.")]
+    pub action: String,
+
+    #[schemars(
+        description = "Explanation of the action. ExplanationForAction maps the action to its reasons; each reason is the chain of operations that led to the action. The first operation is the SDK call in the source code, with its Expr and Location (file:startLine.startCol-endLine.endCol); the following operations were added through Forward Access Sessions (source FAS, or UserFAS for user-supplied FAS maps). Documentation describes the concepts used in the reasons."
+    )]
+    pub explanation: Explanations,
+}
+
+pub async fn explain_policy_action(
+    input: ExplainPolicyActionInput,
+    roots: &WorkspaceRoots,
+    progress: &ToolProgress,
+) -> Result<ExplainPolicyActionOutput, Error> {
+    roots.check(&input.source_files)?;
+    let project = discover_project_config(&input.source_files, roots)?;
+    let mut config = generate_policy_config(
+        GeneratePoliciesInput {
+            source_files: input.source_files,
+            region: None,
+            account: None,
+            service_hints: input.service_hints,
+            policy_options: None,
+            workloads: None,
+            explain: Some(true),
+        },
+        project.as_ref(),
+    )?;
+    roots.check(&config.extract_sdk_calls_config.source_files)?;
+    progress.apply(&mut config);
+    let result = api::generate_policies(&config).await?;
+
+    let explanation = result
+        .explanations
+        .and_then(|explanations| explanations.for_action(&input.action))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Action '{}' is not in the policies generated for these source files",
+                input.action
+            )
+        })?;
+    let action = explanation
+        .explanation_for_action
+        .keys()
+        .next()
+        .cloned()
+        .unwrap_or(input.action);
+
+    Ok(ExplainPolicyActionOutput {
+        action,
+        explanation,
+    })
+}
+
+// Mock the api call
+#[cfg(test)]
+mod api {
+    use anyhow::Result;
+    use iam_policy_autopilot_policy_generation::api::model::{
+        GeneratePoliciesResult, GeneratePolicyConfig,
+    };
+
+    // Static mutable return value
+    pub static mut MOCK_RETURN_VALUE: Option<Result<GeneratePoliciesResult>> = None;
+
+    pub async fn generate_policies(
+        _config: &GeneratePolicyConfig,
+    ) -> Result<GeneratePoliciesResult> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            MOCK_RETURN_VALUE.take().unwrap()
+        }
+    }
+
+    pub fn set_mock_return(value: Result<GeneratePoliciesResult>) {
+        unsafe { MOCK_RETURN_VALUE = Some(value) }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::serial]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use iam_policy_autopilot_policy_generation::api::model::GeneratePoliciesResult;
+    use iam_policy_autopilot_policy_generation::Explanation;
+    use std::collections::BTreeMap;
+
+    fn result(explanations: Option<Explanations>) -> GeneratePoliciesResult {
+        GeneratePoliciesResult {
+            policies: vec![],
+            explanations,
+            diagnostics: vec![],
+        }
+    }
+
+    fn explanations() -> Explanations {
+        Explanations {
+            explanation_for_action: BTreeMap::from([
+                ("s3:GetObject".to_string(), Explanation::default()),
+                ("kms:Decrypt".to_string(), Explanation::default()),
+            ]),
+            documentation: vec![],
+        }
+    }
+
+    fn input(action: &str) -> ExplainPolicyActionInput {
+        ExplainPolicyActionInput {
+            source_files: vec!["path/to/source/file".to_string()],
+            action: action.to_string(),
+            service_hints: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_explain_policy_action() {
+        api::set_mock_return(Ok(result(Some(explanations()))));
+
+        let output = explain_policy_action(
+            input("S3:getobject"),
+            &WorkspaceRoots::default(),
+            &ToolProgress::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(output.action, "s3:GetObject");
+        assert_eq!(
+            output
+                .explanation
+                .explanation_for_action
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["s3:GetObject"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explain_policy_action_not_in_policies() {
+        api::set_mock_return(Ok(result(Some(explanations()))));
+        let error = explain_policy_action(
+            input("s3:PutObject"),
+            &WorkspaceRoots::default(),
+            &ToolProgress::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("'s3:PutObject'"));
+
+        api::set_mock_return(Ok(result(None)));
+        assert!(explain_policy_action(
+            input("s3:GetObject"),
+            &WorkspaceRoots::default(),
+            &ToolProgress::default()
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_explain_policy_action_error() {
+        api::set_mock_return(Err(anyhow!("Failed to process source files")));
+        assert!(explain_policy_action(
+            input("s3:GetObject"),
+            &WorkspaceRoots::default(),
+            &ToolProgress::default()
+        )
+        .await
+        .is_err());
+    }
+}
@@ -0,0 +1,366 @@
+use anyhow::Error;
+use anyhow::{Context, Result};
+use iam_policy_autopilot_policy_generation::{Diagnostic, Location, SdkMethodCall};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::generate_policy::{discover_project_config, extract_sdk_calls_config};
+use crate::workspace::WorkspaceRoots;
+
+#[cfg(not(test))]
+mod api {
+    pub use iam_policy_autopilot_policy_generation::api::extract_sdk_calls;
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(description = "Input for extracting the AWS SDK calls of source code.")]
+pub struct ExtractSdkCallsInput {
+    #[schemars(
+        description = "Absolute paths to source files to analyze. If the files belong to a project with an .iam-autopilot.toml configuration file, the project's settings are used; if no files are provided, the project's source files are analyzed."
+    )]
+    pub source_files: Vec<String>,
+
+    #[schemars(
+        description = "Only return SDK calls that may target one of these AWS services (e.g., ['dynamodb']). Services are SDK service names, as in PossibleServices."
+    )]
+    pub services: Option<Vec<String>>,
+
+    #[schemars(
+        description = "Only return SDK calls located in one of these file ranges. Use this to answer questions about a file or a part of it; the other source files are still analyzed for context."
+    )]
+    pub ranges: Option<Vec<FileRange>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(description = "Lines of a source file.")]
+pub struct FileRange {
+    #[schemars(
+        description = "Absolute path of one of the analyzed source files. Paths are compared after resolving symbolic links and relative segments."
+    )]
+    pub file: String,
+
+    #[schemars(
+        description = "First line of the range, 1-based. Defaults to the start of the file."
+    )]
+    pub start_line: Option<usize>,
+
+    #[schemars(
+        description = "Last line of the range, inclusive. Defaults to the end of the file."
+    )]
+    pub end_line: Option<usize>,
+}
+
+impl FileRange {
+    /// The analyzed source file the range is in, the same file as `self.file` once both paths
+    /// are resolved
+    fn source_file<'a>(&self, source_files: &'a [(PathBuf, PathBuf)]) -> Result<&'a Path> {
+        let file = resolve(Path::new(&self.file));
+        source_files
+            .iter()
+            .find(|(_, resolved)| *resolved == file)
+            .map(|(source_file, _)| source_file.as_path())
+            .with_context(|| {
+                format!(
+                    "File {} of the range is not one of the analyzed source files",
+                    self.file
+                )
+            })
+    }
+
+    /// Whether a location of `source_file` overlaps the lines of the range
+    fn contains(&self, source_file: &Path, location: &Location) -> bool {
+        location.file_path == source_file
+            && self
+                .start_line
+                .is_none_or(|start_line| location.end_line() >= start_line)
+            && self
+                .end_line
+                .is_none_or(|end_line| location.start_line() <= end_line)
+    }
+}
+
+/// Path with symbolic links and relative segments resolved, or the path itself if it does not
+/// exist
+fn resolve(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[derive(Debug, Serialize, JsonSchema, Eq, PartialEq)]
+#[schemars(description = "Output containing the AWS SDK calls found in the source code.")]
+#[serde(rename_all = "PascalCase")]
+pub struct ExtractSdkCallsOutput {
+    #[schemars(
+        description = "SDK calls, each with the operation Name, the PossibleServices it may target and Metadata with the call's Location (file:startLine.startCol-endLine.endCol), matched Expr, Receiver and Parameters. Parameter values are Resolved literals or Unresolved expressions."
+    )]
+    pub calls: Vec<SdkMethodCall>,
+
+    #[schemars(
+        description = "Number of returned SDK calls that may target each service. A call with several PossibleServices is counted for each; pass the services the code actually uses as service hints to generate_application_policies."
+    )]
+    pub services: BTreeMap<String, usize>,
+
+    #[schemars(
+        description = "Diagnostics of the source code analysis in the requested ranges, e.g. AmbiguousService for calls matching several services, with a SuggestedFix."
+    )]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+pub async fn extract_sdk_calls(
+    input: ExtractSdkCallsInput,
+    roots: &WorkspaceRoots,
+) -> Result<ExtractSdkCallsOutput, Error> {
+    roots.check(&input.source_files)?;
+    let project = discover_project_config(&input.source_files, roots)?;
+    let config = extract_sdk_calls_config(input.source_files, None, project.as_ref())?;
+    roots.check(&config.source_files)?;
+    // Locations of the extraction use the paths of the source files as given to it
+    let source_files: Vec<(PathBuf, PathBuf)> = config
+        .source_files
+        .iter()
+        .map(|source_file| (source_file.clone(), resolve(source_file)))
+        .collect();
+    let ranges = input
+        .ranges
+        .as_ref()
+        .map(|ranges| {
+            ranges
+                .iter()
+                .map(|range| Ok((range.source_file(&source_files)?, range)))
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?;
+    let extracted = api::extract_sdk_calls(&config).await?;
+
+    let in_ranges = |location: Option<&Location>| match &ranges {
+        None => true,
+        Some(ranges) => location.is_some_and(|location| {
+            ranges
+                .iter()
+                .any(|(source_file, range)| range.contains(source_file, location))
+        }),
+    };
+
+    let calls: Vec<SdkMethodCall> = extracted
+        .methods
+        .into_iter()
+        .filter(|call| {
+            input.services.as_ref().is_none_or(|services| {
+                call.possible_services
+                    .iter()
+                    .any(|service| services.contains(service))
+            })
+        })
+        .filter(|call| in_ranges(call.metadata.as_ref().map(|metadata| metadata.location())))
+        .collect();
+
+    let mut services = BTreeMap::new();
+    for service in calls.iter().flat_map(|call| &call.possible_services) {
+        *services.entry(service.clone()).or_insert(0) += 1;
+    }
+
+    let diagnostics = extracted
+        .metadata
+        .diagnostics
+        .into_iter()
+        .filter(|diagnostic| in_ranges(diagnostic.location.as_ref()))
+        .collect();
+
+    Ok(ExtractSdkCallsOutput {
+        calls,
+        services,
+        diagnostics,
+    })
+}
+
+// Mock the api call
+#[cfg(test)]
+mod api {
+    use anyhow::Result;
+    use iam_policy_autopilot_policy_generation::api::model::ExtractSdkCallsConfig;
+    use iam_policy_autopilot_policy_generation::ExtractedMethods;
+
+    // Static mutable return value
+    pub static mut MOCK_RETURN_VALUE: Option<Result<ExtractedMethods>> = None;
+
+    pub async fn extract_sdk_calls(_config: &ExtractSdkCallsConfig) -> Result<ExtractedMethods> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            MOCK_RETURN_VALUE.take().unwrap()
+        }
+    }
+
+    pub fn set_mock_return(value: Result<ExtractedMethods>) {
+        unsafe { MOCK_RETURN_VALUE = Some(value) }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::serial]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use iam_policy_autopilot_policy_generation::ExtractedMethods;
+
+    fn extracted_methods() -> ExtractedMethods {
+        extracted_methods_of("/repo/app.py")
+    }
+
+    fn extracted_methods_of(file: &str) -> ExtractedMethods {
+        let call = |name: &str, services: &[&str], line: usize| {
+            serde_json::json!({
+                "Name": name,
+                "PossibleServices": services,
+                "Metadata": {
+                    "Parameters": [{"Keyword": {
+                        "name": "TableName",
+                        "value": {"Resolved": "orders"},
+                        "position": 0,
+                        "type_annotation": null,
+                    }}],
+                    "ReturnType": null,
+                    "Expr": format!("client.{name}(TableName='orders')"),
+                    "Location": format!("{file}:{line}.1-{line}.30"),
+                    "Receiver": "client",
+                },
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "Methods": [
+                call("put_item", &["dynamodb"], 3),
+                call("get_object", &["s3"], 8),
+                call("get_item", &["dynamodb"], 12),
+            ],
+            "Metadata": {
+                "ExtractionTime": "2026-01-01T00:00:00Z",
+                "SourceFiles": [],
+                "TotalMethods": 3,
+                "Diagnostics": [{
+                    "Code": "DynamicMethodName",
+                    "Severity": "Warning",
+                    "Location": format!("{file}:20.1-20.30"),
+                    "Message": "'getattr(client, operation)' looks up a method by a name computed at runtime",
+                }],
+            },
+        }))
+        .unwrap()
+    }
+
+    fn input() -> ExtractSdkCallsInput {
+        ExtractSdkCallsInput {
+            source_files: vec!["/repo/app.py".to_string()],
+            services: None,
+            ranges: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_sdk_calls() {
+        api::set_mock_return(Ok(extracted_methods()));
+
+        let output = extract_sdk_calls(input(), &WorkspaceRoots::default())
+            .await
+            .unwrap();
+
+        assert_eq!(output.calls.len(), 3);
+        assert_eq!(
+            output.services,
+            BTreeMap::from([("dynamodb".to_string(), 2), ("s3".to_string(), 1)])
+        );
+        assert_eq!(output.diagnostics.len(), 1);
+        let json = serde_json::to_string(&output).unwrap();
+        assert!(json.contains("\"Location\":\"/repo/app.py:3.1-3.30\""));
+        assert!(json.contains("\"value\":{\"Resolved\":\"orders\"}"));
+    }
+
+    #[tokio::test]
+    async fn test_extract_sdk_calls_filters_by_service_and_range() {
+        api::set_mock_return(Ok(extracted_methods()));
+        let output = extract_sdk_calls(
+            ExtractSdkCallsInput {
+                services: Some(vec!["dynamodb".to_string()]),
+                ranges: Some(vec![FileRange {
+                    file: "/repo/app.py".to_string(),
+                    start_line: Some(1),
+                    end_line: Some(10),
+                }]),
+                ..input()
+            },
+            &WorkspaceRoots::default(),
+        )
+        .await
+        .unwrap();
+
+        let names: Vec<_> = output.calls.iter().map(|call| call.name.as_str()).collect();
+        assert_eq!(names, vec!["put_item"]);
+        assert_eq!(
+            output.services,
+            BTreeMap::from([("dynamodb".to_string(), 1)])
+        );
+        assert!(output.diagnostics.is_empty());
+
+        api::set_mock_return(Ok(extracted_methods()));
+        let error = extract_sdk_calls(
+            ExtractSdkCallsInput {
+                ranges: Some(vec![FileRange {
+                    file: "/repo/other.py".to_string(),
+                    start_line: None,
+                    end_line: None,
+                }]),
+                ..input()
+            },
+            &WorkspaceRoots::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("/repo/other.py"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_extract_sdk_calls_ranges_of_equivalent_paths() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let app = dir.path().join("src/app.py");
+        std::fs::write(&app, "").unwrap();
+        let link = dir.path().join("link.py");
+        std::os::unix::fs::symlink(&app, &link).unwrap();
+        let app = app.to_str().unwrap().to_string();
+
+        for file in [
+            dir.path().join("src/./app.py"),
+            dir.path().join("src/../src/app.py"),
+            link.clone(),
+        ] {
+            api::set_mock_return(Ok(extracted_methods_of(&app)));
+            let output = extract_sdk_calls(
+                ExtractSdkCallsInput {
+                    source_files: vec![app.clone()],
+                    services: None,
+                    ranges: Some(vec![FileRange {
+                        file: file.to_str().unwrap().to_string(),
+                        start_line: Some(1),
+                        end_line: Some(10),
+                    }]),
+                },
+                &WorkspaceRoots::default(),
+            )
+            .await
+            .unwrap();
+            let names: Vec<_> = output.calls.iter().map(|call| call.name.as_str()).collect();
+            assert_eq!(names, vec!["put_item", "get_object"], "{}", file.display());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_sdk_calls_error() {
+        api::set_mock_return(Err(anyhow!("Failed to process source files")));
+        assert!(extract_sdk_calls(input(), &WorkspaceRoots::default())
+            .await
+            .is_err());
+    }
+}
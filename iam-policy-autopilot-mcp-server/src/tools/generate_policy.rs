@@ -2,14 +2,28 @@ use anyhow::Context;
 use anyhow::Error;
 use anyhow::Result;
 use iam_policy_autopilot_policy_generation::api::model::{
-    AwsContext, ExtractSdkCallsConfig, GeneratePolicyConfig, ServiceHints,
+    AwsContext, ExtractSdkCallsConfig, GeneratePolicyConfig, PolicyGenerationOptions, ServiceHints,
 };
+use iam_policy_autopilot_policy_generation::{
+    Diagnostic, Explanations, IamPolicy, PolicyFindings, PolicyType, PolicyWithMetadata,
+    ProjectConfig, StatementFinding, Workload, WorkloadManifest, PROJECT_CONFIG_FILE_NAME,
+};
+use log::debug;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::progress::ToolProgress;
+use crate::session::{PolicyChanges, Session};
+use crate::workspace::WorkspaceRoots;
 
 #[cfg(not(test))]
 mod api {
-    pub use iam_policy_autopilot_policy_generation::api::generate_policies;
+    pub use iam_policy_autopilot_policy_generation::api::{
+        generate_policies, generate_workload_policies,
+    };
 }
 
 // Input struct matching the updated schema
@@ -17,7 +31,9 @@ mod api {
 #[serde(rename_all = "PascalCase")]
 #[schemars(description = "Input for generating IAM policies from source code.")]
 pub struct GeneratePoliciesInput {
-    #[schemars(description = "Absolute paths to source files to generate IAM Policies for")]
+    #[schemars(
+        description = "Absolute paths to source files to generate IAM Policies for. If the files belong to a project with an .iam-autopilot.toml configuration file, the project's settings are used for any input that is not provided; if no files are provided, the project's source files are analyzed."
+    )]
     pub source_files: Vec<String>,
 
     #[schemars(description = "AWS Region")]
@@ -30,6 +46,21 @@ pub struct GeneratePoliciesInput {
         description = "List of AWS service names to filter SDK calls by (e.g., ['s3', 'dynamodb']). When provided, the result of source code analysis will be restricted to the provided services. The generated policy may still contain actions from a service not provided as a hint, if IAM Policy Autopilot determines that the action may be needed for the SDK call."
     )]
     pub service_hints: Option<Vec<String>>,
+
+    #[schemars(
+        description = "Options controlling when the resources of an action are collapsed into wildcards. ResourceCutoff is the number of resource types from which resources are collapsed (default 5). WildcardMode is 'wildcard' (collapse to '*', default), 'service-wildcard' (collapse to one ARN wildcard per service, region and account) or 'never' (always list every resource type ARN). ServiceOverrides (keyed by service prefix, e.g. 's3') and ActionOverrides (keyed by action, e.g. 's3:GetObject') can override Cutoff and WildcardMode. ResourceBindings maps ARN placeholders to concrete values (e.g. {'BucketName': 'my-bucket'}) instead of wildcards. Only provide this if the user asks for tighter or broader resources, or names the resources the code accesses."
+    )]
+    pub policy_options: Option<PolicyGenerationOptions>,
+
+    #[schemars(
+        description = "Workloads of the repository, mapping a workload name (typically the role it runs as) to path globs of its entry points, relative to the project root (e.g. {'ingest': ['functions/ingest/**'], 'report': ['functions/report/**']}). When provided, a separate set of policies is generated per workload, including the shared code each workload imports, and returned in Workloads. Defaults to the workloads of the project configuration. Only provide this if the repository contains several deployables with their own roles."
+    )]
+    pub workloads: Option<BTreeMap<String, Vec<String>>>,
+
+    #[schemars(
+        description = "Also return Explanations of why each action is in the policies. Only set this if the user asks why permissions are needed; to justify a single action, use explain_policy_action instead."
+    )]
+    pub explain: Option<bool>,
 }
 
 // Output struct for the generated IAM policy
@@ -37,46 +68,469 @@ pub struct GeneratePoliciesInput {
 #[schemars(description = "Output containing the generated IAM policies with type information.")]
 #[serde(rename_all = "PascalCase")]
 pub struct GeneratePoliciesOutput {
-    #[schemars(description = "List of policies with their associated types.")]
-    pub policies: Vec<String>,
+    #[schemars(description = "Generated policies, with their type, document, size and findings.")]
+    pub policies: Vec<GeneratedPolicy>,
+
+    #[schemars(
+        description = "Diagnostics of the source code analysis: code whose SDK calls could not be extracted exactly. Each has a Code (ParseError, AmbiguousService, UnvalidatedParameters, DynamicMethodName, UnknownImport, TestFileExcluded, UnreachableCall, NoEntryPointDetected), a Severity (Info, Warning, Error), an optional Location, a Message and an optional SuggestedFix, e.g. the service hints to pass. Tell the user about Warning diagnostics, since the policies may miss or over-grant permissions for them."
+    )]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+
+    #[schemars(
+        description = "Explanations of why each action is in the policies, when Explain was requested. ExplanationForAction maps each action to its reasons; each reason is the chain of operations that led to the action, starting with the SDK call in the source code (with its Expr and Location) and followed by operations added through Forward Access Sessions (source FAS or UserFAS). Documentation describes the concepts the explanations refer to."
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanations: Option<Explanations>,
+
+    #[schemars(
+        description = "Changes of the policies since the previous call of this session for the same source files, absent on the first call. Tell the user which actions the latest edits added or removed."
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<PolicyChanges>,
+
+    #[schemars(
+        description = "Service hints the source code analysis was restricted to, from the input or the project configuration. Absent if the analysis was not restricted."
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_hints: Option<Vec<String>>,
+
+    #[schemars(
+        description = "Number of source files analyzed by this call. Source files analyzed by a previous call of this session are only analyzed again if they changed."
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reanalyzed_files: Option<usize>,
+
+    #[schemars(
+        description = "Policies of each workload, when workloads were requested. Policies is empty in that case."
+    )]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub workloads: Vec<WorkloadPoliciesOutput>,
+
+    #[schemars(
+        description = "Source files that do not belong to any workload, and are not covered by any policy."
+    )]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unassigned_files: Vec<String>,
 }
 
-pub async fn generate_application_policies(
-    input: GeneratePoliciesInput,
-) -> Result<GeneratePoliciesOutput, Error> {
-    let region = input.region.unwrap_or("*".to_string());
-    let account = input.account.unwrap_or("*".to_string());
+// Output struct for a single generated policy
+#[derive(Debug, Serialize, JsonSchema, Eq, PartialEq)]
+#[schemars(description = "A generated IAM policy.")]
+#[serde(rename_all = "PascalCase")]
+pub struct GeneratedPolicy {
+    #[schemars(
+        description = "Type of the policy. Identity policies are attached to the role or user the code runs as."
+    )]
+    pub policy_type: PolicyType,
+
+    #[schemars(
+        description = "The IAM policy document, with its Version and Statement list. Use it as is in infrastructure as code or in the console."
+    )]
+    pub document: IamPolicy,
+
+    #[schemars(
+        description = "Size of the document in characters, excluding whitespace, as counted against the IAM quotas of 6,144 characters per managed policy and 10,240 characters for all inline policies of a role."
+    )]
+    pub size: usize,
+
+    #[schemars(
+        description = "Least-privilege findings of the policy. Lists resource wildcards with the reason they were introduced (ResourceCutoff, UnresolvedParameter, UnknownAccount, UnknownRegion, UnknownPartition, NotResourceScoped), actions with Write or PermissionsManagement access level, and a BreadthScore from 0 (fully scoped) to 100 (all resources). Use these to tell the user where the policy can be tightened."
+    )]
+    pub findings: PolicyFindings,
+}
+
+impl From<&PolicyWithMetadata> for GeneratedPolicy {
+    fn from(policy: &PolicyWithMetadata) -> Self {
+        Self {
+            policy_type: policy.policy_type,
+            document: policy.policy.clone(),
+            size: policy.policy.size(),
+            findings: policy.findings.clone().unwrap_or_default(),
+        }
+    }
+}
+
+// Output struct for the policies of a single workload
+#[derive(Debug, Serialize, JsonSchema, Eq, PartialEq)]
+#[schemars(description = "Policies generated for a single workload.")]
+#[serde(rename_all = "PascalCase")]
+pub struct WorkloadPoliciesOutput {
+    #[schemars(description = "Name of the workload.")]
+    pub name: String,
+
+    #[schemars(
+        description = "Source files attributed to the workload, including shared code it imports."
+    )]
+    pub source_files: Vec<String>,
+
+    #[schemars(description = "Policies of the workload.")]
+    pub policies: Vec<GeneratedPolicy>,
+
+    #[schemars(description = "Diagnostics of the source code analysis of the workload.")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+
+    #[schemars(
+        description = "Explanations of why each action is in the workload's policies, when Explain was requested."
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanations: Option<Explanations>,
+
+    #[schemars(
+        description = "Changes of the workload's policies since the previous call of this session, absent on the first call."
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<PolicyChanges>,
+}
+
+/// Discover the project configuration from the directory of the first source file,
+/// so that the server generates the same policy as the CLI run from the project root.
+///
+/// The search stops at the workspace root containing the source file, and the configuration
+/// file and the files it references must be within the workspace roots.
+pub(crate) fn discover_project_config(
+    source_files: &[String],
+    roots: &WorkspaceRoots,
+) -> Result<Option<ProjectConfig>> {
+    let Some(file) = source_files.first().map(Path::new) else {
+        return Ok(None);
+    };
+    let file = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+    let Some(start) = file.parent() else {
+        return Ok(None);
+    };
+    let boundary = roots.boundary(start);
+    let Some(path) = start
+        .ancestors()
+        .take_while(|directory| boundary.is_none_or(|boundary| directory.starts_with(boundary)))
+        .map(|directory| directory.join(PROJECT_CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+    else {
+        return Ok(None);
+    };
+    roots.check([&path])?;
+    debug!("Using project configuration {}", path.display());
+    let project = ProjectConfig::load(&path).context("Failed to load the project configuration")?;
+
+    let service_reference = project.service_reference_source();
+    roots.check(
+        project
+            .sdk_wrappers()
+            .into_iter()
+            .chain(project.fas_map_extensions())
+            .chain(project.lambda_functions())
+            .chain(service_reference.snapshot)
+            .chain(service_reference.ca_certificate),
+    )?;
+    Ok(Some(project))
+}
+
+/// Build the extraction config from the tool input, using the project configuration for
+/// any setting the input does not provide
+pub(crate) fn extract_sdk_calls_config(
+    source_files: Vec<String>,
+    service_hints: Option<Vec<String>>,
+    project: Option<&ProjectConfig>,
+) -> Result<ExtractSdkCallsConfig> {
+    let mut source_files: Vec<PathBuf> = source_files.into_iter().map(PathBuf::from).collect();
+    if source_files.is_empty() {
+        if let Some(project) = project {
+            source_files = project.source_files()?;
+        }
+    }
 
     // Convert service_hints from Vec<String> to ServiceHints if provided
-    let service_hints = input.service_hints.map(|hints| ServiceHints {
-        service_names: hints,
-    });
+    let service_hints = service_hints
+        .or_else(|| project.and_then(|p| p.sources.service_hints.clone()))
+        .map(|hints| ServiceHints {
+            service_names: hints,
+        });
+
+    Ok(ExtractSdkCallsConfig {
+        source_files,
+        // Maybe we should let the llm figure out the language
+        language: project.and_then(|p| p.sources.language.clone()),
+        service_hints,
+        sdk_wrappers: project.map(ProjectConfig::sdk_wrappers).unwrap_or_default(),
+        entry_points: project.map(ProjectConfig::entry_points).unwrap_or_default(),
+        include_tests: project.is_some_and(|project| project.sources.include_tests),
+    })
+}
 
-    let result = api::generate_policies(&GeneratePolicyConfig {
+/// Build the policy generation config from the tool input, using the project
+/// configuration for any setting the input does not provide
+pub(crate) fn generate_policy_config(
+    input: GeneratePoliciesInput,
+    project: Option<&ProjectConfig>,
+) -> Result<GeneratePolicyConfig> {
+    let region = input
+        .region
+        .or_else(|| project.and_then(|p| p.aws.region.clone()))
+        .unwrap_or("*".to_string());
+    let account = input
+        .account
+        .or_else(|| project.and_then(|p| p.aws.account.clone()))
+        .unwrap_or("*".to_string());
+
+    let policy_options = input
+        .policy_options
+        .or_else(|| project.map(ProjectConfig::policy_options))
+        .unwrap_or_default();
+
+    Ok(GeneratePolicyConfig {
         individual_policies: false,
-        extract_sdk_calls_config: ExtractSdkCallsConfig {
-            source_files: input.source_files.into_iter().map(|f| f.into()).collect(),
-            // Maybe we should let the llm figure out the language
-            language: None,
-            service_hints,
-        },
+        extract_sdk_calls_config: extract_sdk_calls_config(
+            input.source_files,
+            input.service_hints,
+            project,
+        )?,
         aws_context: AwsContext::new(region, account),
-        minimize_policy_size: false,
+        minimize_policy_size: project.is_some_and(|p| p.generation.minimize_policy_size),
 
         // true by default, if we want to allow the user to change it we should
         // accept it as part of the cli input when starting the mcp server
         disable_file_system_cache: true,
-        generate_explanations: false,
+        generate_explanations: input.explain.unwrap_or(false),
+        policy_options,
+        service_reference: project
+            .map(ProjectConfig::service_reference_source)
+            .unwrap_or_default(),
+        session: None,
+        fas_map_extensions: project
+            .map(ProjectConfig::fas_map_extensions)
+            .unwrap_or_default(),
+        progress: None,
+        cancellation: None,
     })
-    .await?;
+}
 
-    let policies = result
-        .policies
+/// Build the workload manifest from the tool input, or take it from the project configuration
+///
+/// Workload globs are matched relative to the project root, or the common directory of the
+/// source files if there is no project configuration.
+fn workload_manifest(
+    workloads: Option<BTreeMap<String, Vec<String>>>,
+    project: Option<&ProjectConfig>,
+    source_files: &[PathBuf],
+) -> Option<WorkloadManifest> {
+    let Some(workloads) = workloads else {
+        return project.and_then(ProjectConfig::workload_manifest);
+    };
+
+    let root = match project {
+        Some(project) => project.root.clone(),
+        None => common_directory(source_files),
+    };
+    let workloads = workloads
         .into_iter()
-        .map(|policy| serde_json::to_string(&policy.policy).context("Failed to serialize policy"))
-        .collect::<Result<Vec<String>, Error>>()?;
+        .map(|(name, paths)| Workload { name, paths })
+        .collect();
+    Some(WorkloadManifest::new(root, workloads))
+}
+
+/// Deepest directory containing all source files
+fn common_directory(source_files: &[PathBuf]) -> PathBuf {
+    let mut directories = source_files.iter().filter_map(|file| file.parent());
+    let Some(first) = directories.next() else {
+        return PathBuf::new();
+    };
+    directories.fold(first.to_path_buf(), |common, directory| {
+        common
+            .ancestors()
+            .find(|ancestor| directory.starts_with(ancestor))
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    })
+}
+
+impl GeneratePoliciesOutput {
+    /// Render the output as text, for MCP clients that only show the text content of results
+    pub(crate) fn to_text(&self) -> String {
+        let mut text = String::new();
+        render_policies(&mut text, &self.policies);
+        for workload in &self.workloads {
+            let _ = writeln!(text, "## Workload {}\n", workload.name);
+            let _ = writeln!(text, "Source files: {}\n", workload.source_files.join(", "));
+            render_policies(&mut text, &workload.policies);
+            render_diagnostics(&mut text, &workload.diagnostics);
+            render_changes(&mut text, workload.changes.as_ref());
+        }
+        if !self.unassigned_files.is_empty() {
+            let _ = writeln!(
+                text,
+                "Source files of no workload: {}\n",
+                self.unassigned_files.join(", ")
+            );
+        }
+        render_diagnostics(&mut text, &self.diagnostics);
+        render_changes(&mut text, self.changes.as_ref());
+        if let Some(service_hints) = &self.service_hints {
+            let _ = writeln!(text, "Service hints: {}\n", service_hints.join(", "));
+        }
+        if let Some(reanalyzed_files) = self.reanalyzed_files {
+            let _ = writeln!(text, "Analyzed {reanalyzed_files} source files.");
+        }
+        text
+    }
+}
+
+fn render_policies(text: &mut String, policies: &[GeneratedPolicy]) {
+    if policies.is_empty() {
+        let _ = writeln!(text, "No policy was generated.\n");
+    }
+    for (index, policy) in policies.iter().enumerate() {
+        let document = serde_json::to_string_pretty(&policy.document).unwrap_or_default();
+        let _ = writeln!(
+            text,
+            "Policy {} ({:?} policy, {} characters, breadth score {}):\n\n```json\n{document}\n```\n",
+            index + 1,
+            policy.policy_type,
+            policy.size,
+            policy.findings.breadth_score
+        );
+        for finding in policy.findings.findings() {
+            let _ = match finding {
+                StatementFinding::WildcardResource {
+                    resource,
+                    actions,
+                    reasons,
+                } => writeln!(
+                    text,
+                    "- Resource {resource} of {} is a wildcard: {reasons:?}",
+                    actions.join(", ")
+                ),
+                StatementFinding::SensitiveAccessLevel {
+                    action,
+                    access_level,
+                } => writeln!(text, "- {action} has the {access_level:?} access level"),
+            };
+        }
+        if !policy.findings.is_empty() {
+            text.push('\n');
+        }
+    }
+}
+
+fn render_diagnostics(text: &mut String, diagnostics: &[Diagnostic]) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    let _ = writeln!(text, "Diagnostics:");
+    for diagnostic in diagnostics {
+        let _ = writeln!(text, "- {diagnostic}");
+    }
+    text.push('\n');
+}
+
+fn render_changes(text: &mut String, changes: Option<&PolicyChanges>) {
+    let Some(changes) = changes else {
+        return;
+    };
+    if !changes.policies_changed {
+        let _ = writeln!(
+            text,
+            "The policies did not change since the previous call.\n"
+        );
+        return;
+    }
+    let _ = writeln!(
+        text,
+        "Changes since the previous call: added actions [{}], removed actions [{}].\n",
+        changes.added_actions.join(", "),
+        changes.removed_actions.join(", ")
+    );
+}
+
+pub async fn generate_application_policies(
+    mut input: GeneratePoliciesInput,
+    session: &Session,
+    roots: &WorkspaceRoots,
+    progress: &ToolProgress,
+) -> Result<GeneratePoliciesOutput, Error> {
+    roots.check(&input.source_files)?;
+    let project = discover_project_config(&input.source_files, roots)?;
+    let workloads = input.workloads.take();
+    let mut config = generate_policy_config(input, project.as_ref())?;
+    roots.check(&config.extract_sdk_calls_config.source_files)?;
+    config.session = Some(session.generation().clone());
+    progress.apply(&mut config);
+    let parsed_files = || {
+        session
+            .generation()
+            .extraction_cache()
+            .statistics()
+            .parsed_files
+    };
+    let parsed_before = parsed_files();
+    let service_hints = config
+        .extract_sdk_calls_config
+        .service_hints
+        .as_ref()
+        .map(|hints| hints.service_names.clone());
+
+    if let Some(manifest) = workload_manifest(
+        workloads,
+        project.as_ref(),
+        &config.extract_sdk_calls_config.source_files,
+    ) {
+        let result = api::generate_workload_policies(&config, &manifest).await?;
+        let workloads = result
+            .workloads
+            .into_iter()
+            .map(|(name, workload)| {
+                let source_files: Vec<String> = workload
+                    .source_files
+                    .iter()
+                    .map(|file| file.display().to_string())
+                    .collect();
+                let changes = session.record_policies(&source_files, &workload.result.policies);
+                WorkloadPoliciesOutput {
+                    name,
+                    source_files,
+                    policies: workload.result.policies.iter().map(Into::into).collect(),
+                    diagnostics: workload.result.diagnostics,
+                    explanations: workload.result.explanations,
+                    changes,
+                }
+            })
+            .collect();
+
+        return Ok(GeneratePoliciesOutput {
+            policies: vec![],
+            diagnostics: vec![],
+            explanations: None,
+            changes: None,
+            service_hints,
+            reanalyzed_files: Some(parsed_files() - parsed_before),
+            workloads,
+            unassigned_files: result
+                .unassigned_files
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect(),
+        });
+    }
+
+    let result = api::generate_policies(&config).await?;
+    let source_files: Vec<String> = config
+        .extract_sdk_calls_config
+        .source_files
+        .iter()
+        .map(|file| file.display().to_string())
+        .collect();
+    let changes = session.record_policies(&source_files, &result.policies);
 
-    Ok(GeneratePoliciesOutput { policies })
+    Ok(GeneratePoliciesOutput {
+        policies: result.policies.iter().map(Into::into).collect(),
+        diagnostics: result.diagnostics,
+        explanations: result.explanations,
+        changes,
+        service_hints,
+        reanalyzed_files: Some(parsed_files() - parsed_before),
+        workloads: vec![],
+        unassigned_files: vec![],
+    })
 }
 
 // Mock the api call
@@ -84,11 +538,14 @@ pub async fn generate_application_policies(
 mod api {
     use anyhow::Result;
     use iam_policy_autopilot_policy_generation::api::model::{
-        GeneratePoliciesResult, GeneratePolicyConfig,
+        GeneratePoliciesResult, GeneratePolicyConfig, GenerateWorkloadPoliciesResult,
     };
+    use iam_policy_autopilot_policy_generation::WorkloadManifest;
 
     // Static mutable return value
     pub static mut MOCK_RETURN_VALUE: Option<Result<GeneratePoliciesResult>> = None;
+    pub static mut MOCK_WORKLOAD_RETURN_VALUE: Option<Result<GenerateWorkloadPoliciesResult>> =
+        None;
 
     pub async fn generate_policies(
         _config: &GeneratePolicyConfig,
@@ -102,6 +559,20 @@ mod api {
     pub fn set_mock_return(value: Result<GeneratePoliciesResult>) {
         unsafe { MOCK_RETURN_VALUE = Some(value) }
     }
+
+    pub async fn generate_workload_policies(
+        _config: &GeneratePolicyConfig,
+        _manifest: &WorkloadManifest,
+    ) -> Result<GenerateWorkloadPoliciesResult> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            MOCK_WORKLOAD_RETURN_VALUE.take().unwrap()
+        }
+    }
+
+    pub fn set_mock_workload_return(value: Result<GenerateWorkloadPoliciesResult>) {
+        unsafe { MOCK_WORKLOAD_RETURN_VALUE = Some(value) }
+    }
 }
 
 #[cfg(test)]
@@ -114,6 +585,7 @@ mod tests {
         api::model::GeneratePoliciesResult, IamPolicy, PolicyType, PolicyWithMetadata, Statement,
     };
 
+    use crate::workspace::OutsideWorkspaceRoots;
     use anyhow::anyhow;
 
     #[tokio::test]
@@ -124,6 +596,9 @@ mod tests {
             region: Some("us-east-1".to_string()),
             account: Some("123456789012".to_string()),
             service_hints: None,
+            policy_options: None,
+            workloads: None,
+            explain: None,
         };
 
         let expected_output = include_str!("../testdata/test_generate_application_policy");
@@ -139,6 +614,7 @@ mod tests {
         let policy = PolicyWithMetadata {
             policy: iam_policy,
             policy_type: PolicyType::Identity,
+            findings: None,
         };
 
         use iam_policy_autopilot_policy_generation::api::model::GeneratePoliciesResult;
@@ -146,8 +622,15 @@ mod tests {
         api::set_mock_return(Ok(GeneratePoliciesResult {
             policies: vec![policy],
             explanations: None,
+            diagnostics: vec![],
         }));
-        let result = generate_application_policies(input).await;
+        let result = generate_application_policies(
+            input,
+            &Session::default(),
+            &WorkspaceRoots::default(),
+            &ToolProgress::default(),
+        )
+        .await;
 
         println!("{result:?}");
         assert!(result.is_ok());
@@ -164,14 +647,113 @@ mod tests {
             region: Some("us-east-1".to_string()),
             account: Some("123456789012".to_string()),
             service_hints: None,
+            policy_options: None,
+            workloads: None,
+            explain: None,
         };
 
         api::set_mock_return(Err(anyhow!("Failed to generate policies")));
-        let result = generate_application_policies(input).await;
+        let result = generate_application_policies(
+            input,
+            &Session::default(),
+            &WorkspaceRoots::default(),
+            &ToolProgress::default(),
+        )
+        .await;
 
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_generate_application_policies_outside_workspace_roots() {
+        let workspace = tempfile::tempdir().unwrap();
+        let input = GeneratePoliciesInput {
+            source_files: vec!["/etc/hosts".to_string()],
+            region: None,
+            account: None,
+            service_hints: None,
+            policy_options: None,
+            workloads: None,
+            explain: None,
+        };
+
+        let roots = WorkspaceRoots::new(&[workspace.path().to_path_buf()]).unwrap();
+        let error = generate_application_policies(
+            input,
+            &Session::default(),
+            &roots,
+            &ToolProgress::default(),
+        )
+        .await
+        .unwrap_err();
+
+        let rejected = error
+            .downcast_ref::<crate::workspace::OutsideWorkspaceRoots>()
+            .unwrap();
+        assert_eq!(rejected.rejected_paths[0].path, "/etc/hosts");
+    }
+
+    #[tokio::test]
+    async fn test_generate_application_policies_reports_changes() {
+        use iam_policy_autopilot_policy_generation::api::model::GeneratePoliciesResult;
+
+        fn result(actions: &[&str]) -> anyhow::Result<GeneratePoliciesResult> {
+            let mut policy = IamPolicy::new();
+            policy.add_statement(Statement::allow(
+                actions.iter().map(|action| action.to_string()).collect(),
+                vec!["*".to_string()],
+            ));
+            Ok(GeneratePoliciesResult {
+                policies: vec![PolicyWithMetadata {
+                    policy,
+                    policy_type: PolicyType::Identity,
+                    findings: None,
+                }],
+                explanations: None,
+                diagnostics: vec![],
+            })
+        }
+        let input = || GeneratePoliciesInput {
+            source_files: vec!["/repo/app.py".to_string()],
+            region: None,
+            account: None,
+            service_hints: None,
+            policy_options: None,
+            workloads: None,
+            explain: None,
+        };
+        let session = Session::default();
+
+        api::set_mock_return(result(&["s3:GetObject"]));
+        let output = generate_application_policies(
+            input(),
+            &session,
+            &WorkspaceRoots::default(),
+            &ToolProgress::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(output.changes, None);
+
+        api::set_mock_return(result(&["s3:GetObject", "s3:PutObject"]));
+        let output = generate_application_policies(
+            input(),
+            &session,
+            &WorkspaceRoots::default(),
+            &ToolProgress::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            output.changes,
+            Some(PolicyChanges {
+                added_actions: vec!["s3:PutObject".to_string()],
+                removed_actions: vec![],
+                policies_changed: true,
+            })
+        );
+    }
+
     #[test]
     fn test_generate_policies_input_serialization() {
         let input = GeneratePoliciesInput {
@@ -179,6 +761,9 @@ mod tests {
             region: Some("us-west-2".to_string()),
             account: Some("987654321098".to_string()),
             service_hints: None,
+            policy_options: None,
+            workloads: None,
+            explain: None,
         };
 
         let json = serde_json::to_string(&input).unwrap();
@@ -188,19 +773,65 @@ mod tests {
         assert!(json.contains("\"Account\":\"987654321098\""));
     }
 
+    #[test]
+    fn test_generate_policies_input_policy_options_deserialization() {
+        use iam_policy_autopilot_policy_generation::api::model::WildcardMode;
+
+        let input: GeneratePoliciesInput = serde_json::from_str(
+            r#"{
+                "SourceFiles": ["/path/to/file.py"],
+                "PolicyOptions": {
+                    "ResourceCutoff": 10,
+                    "ServiceOverrides": {"ec2": {"WildcardMode": "service-wildcard"}}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let options = input.policy_options.unwrap();
+        assert_eq!(options.resource_cutoff, 10);
+        assert_eq!(options.wildcard_mode, WildcardMode::Wildcard);
+        assert_eq!(
+            options.resource_cutoff_for("ec2:RunInstances"),
+            (10, WildcardMode::ServiceWildcard)
+        );
+    }
+
     #[test]
     fn test_generate_policies_output_serialization() {
+        let mut policy = IamPolicy::new();
+        policy.add_statement(Statement::allow(
+            vec!["s3:GetObject".to_string()],
+            vec!["*".to_string()],
+        ));
+        let policy = GeneratedPolicy::from(&PolicyWithMetadata {
+            policy,
+            policy_type: PolicyType::Identity,
+            findings: None,
+        });
         let output = GeneratePoliciesOutput {
-            policies: vec![
-                "{\"Version\":\"2012-10-17\"}".to_string(),
-                "{\"Version\":\"2012-10-17\"}".to_string(),
-            ],
+            policies: vec![policy],
+            diagnostics: vec![],
+            explanations: None,
+            changes: None,
+            service_hints: Some(vec!["s3".to_string()]),
+            reanalyzed_files: None,
+            workloads: vec![],
+            unassigned_files: vec![],
         };
 
         let json = serde_json::to_string(&output).unwrap();
 
-        assert!(json.contains("\"Policies\":"));
-        assert!(json.contains("[\"{"));
+        assert!(json.contains("\"Policies\":[{\"PolicyType\":\"Identity\",\"Document\":{"));
+        assert!(json.contains("\"Action\":[\"s3:GetObject\"]"));
+        assert!(json.contains(&format!("\"Size\":{}", output.policies[0].size)));
+        assert!(json.contains("\"Findings\":{\"BreadthScore\":0,\"Statements\":[]}"));
+        assert!(json.contains("\"ServiceHints\":[\"s3\"]"));
+
+        let text = output.to_text();
+        assert!(text.contains("Policy 1 (Identity policy"));
+        assert!(text.contains("\"s3:GetObject\""));
+        assert!(text.contains("Service hints: s3"));
     }
 
     #[tokio::test]
@@ -210,6 +841,9 @@ mod tests {
             region: Some("us-east-1".to_string()),
             account: Some("123456789012".to_string()),
             service_hints: Some(vec!["s3".to_string(), "dynamodb".to_string()]),
+            policy_options: None,
+            workloads: None,
+            explain: None,
         };
 
         let expected_output = include_str!("../testdata/test_generate_application_policy");
@@ -224,17 +858,218 @@ mod tests {
         let policy = PolicyWithMetadata {
             policy: iam_policy,
             policy_type: PolicyType::Identity,
+            findings: None,
         };
 
         api::set_mock_return(Ok(GeneratePoliciesResult {
             policies: vec![policy],
             explanations: None,
+            diagnostics: vec![],
         }));
-        let result = generate_application_policies(input).await;
+        let result = generate_application_policies(
+            input,
+            &Session::default(),
+            &WorkspaceRoots::default(),
+            &ToolProgress::default(),
+        )
+        .await;
 
-        assert!(result.is_ok());
+        let mut output = result.unwrap();
+        assert_eq!(
+            output.service_hints,
+            Some(vec!["s3".to_string(), "dynamodb".to_string()])
+        );
+        output.service_hints = None;
 
-        let output = serde_json::to_string_pretty(&result.unwrap()).unwrap();
+        let output = serde_json::to_string_pretty(&output).unwrap();
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn test_generate_policy_config_uses_project_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(".iam-autopilot.toml"),
+            r#"
+[sources]
+service-hints = ["s3"]
+
+[aws]
+region = "eu-west-1"
+account = "123456789012"
+
+[resources]
+cutoff = 8
+"#,
+        )
+        .unwrap();
+        let source_file = dir.path().join("app.py");
+        std::fs::write(&source_file, "import boto3\n").unwrap();
+
+        let input = GeneratePoliciesInput {
+            source_files: vec![source_file.to_string_lossy().to_string()],
+            region: Some("us-east-1".to_string()),
+            account: None,
+            service_hints: None,
+            policy_options: None,
+            workloads: None,
+            explain: None,
+        };
+        let project =
+            discover_project_config(&input.source_files, &WorkspaceRoots::default()).unwrap();
+        assert!(project.is_some());
+
+        let config = generate_policy_config(input, project.as_ref()).unwrap();
+        assert_eq!(config.aws_context.region, "us-east-1");
+        assert_eq!(config.aws_context.account, "123456789012");
+        assert_eq!(
+            config
+                .extract_sdk_calls_config
+                .service_hints
+                .unwrap()
+                .service_names,
+            vec!["s3".to_string()]
+        );
+        assert_eq!(config.policy_options.resource_cutoff, 8);
+    }
+
+    #[test]
+    fn test_discover_project_config_stays_within_workspace_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        std::fs::write(
+            dir.path().join(".iam-autopilot.toml"),
+            "[aws]\nregion = \"eu-west-1\"\n",
+        )
+        .unwrap();
+        let source_file = workspace.join("app.py");
+        std::fs::write(&source_file, "import boto3\n").unwrap();
+        let source_files = vec![source_file.to_string_lossy().to_string()];
+
+        // The configuration above the workspace root is ignored
+        let roots = WorkspaceRoots::new(std::slice::from_ref(&workspace)).unwrap();
+        assert!(discover_project_config(&source_files, &roots)
+            .unwrap()
+            .is_none());
+        assert!(
+            discover_project_config(&source_files, &WorkspaceRoots::default())
+                .unwrap()
+                .is_some()
+        );
+
+        // Files referenced by the configuration must be within the workspace roots
+        std::fs::write(dir.path().join("wrappers.json"), "{}").unwrap();
+        std::fs::write(
+            workspace.join(".iam-autopilot.toml"),
+            "[sources]\nsdk-wrappers = [\"../wrappers.json\"]\n",
+        )
+        .unwrap();
+        let error = discover_project_config(&source_files, &roots).unwrap_err();
+        assert!(error.downcast_ref::<OutsideWorkspaceRoots>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_generate_application_policies_with_workloads() {
+        use iam_policy_autopilot_policy_generation::api::model::{
+            GenerateWorkloadPoliciesResult, WorkloadPoliciesResult,
+        };
+        use iam_policy_autopilot_policy_generation::{DiagnosticCode, DiagnosticSeverity};
+
+        let input = GeneratePoliciesInput {
+            source_files: vec![
+                "/repo/functions/ingest/handler.py".to_string(),
+                "/repo/functions/report/handler.py".to_string(),
+            ],
+            region: None,
+            account: None,
+            service_hints: None,
+            policy_options: None,
+            workloads: Some(BTreeMap::from([(
+                "ingest".to_string(),
+                vec!["functions/ingest/**".to_string()],
+            )])),
+            explain: None,
+        };
+
+        let mut iam_policy = IamPolicy::new();
+        iam_policy.add_statement(Statement::new(
+            iam_policy_autopilot_policy_generation::Effect::Allow,
+            vec!["s3:PutObject".to_string()],
+            vec!["resource".to_string()],
+        ));
+        api::set_mock_workload_return(Ok(GenerateWorkloadPoliciesResult {
+            workloads: BTreeMap::from([(
+                "ingest".to_string(),
+                WorkloadPoliciesResult {
+                    source_files: vec![PathBuf::from("/repo/functions/ingest/handler.py")],
+                    result: GeneratePoliciesResult {
+                        policies: vec![PolicyWithMetadata {
+                            policy: iam_policy,
+                            policy_type: PolicyType::Identity,
+                            findings: None,
+                        }],
+                        explanations: None,
+                        diagnostics: vec![Diagnostic {
+                            code: DiagnosticCode::DynamicMethodName,
+                            severity: DiagnosticSeverity::Warning,
+                            location: None,
+                            message: "'getattr(client, operation)' looks up a method by a name computed at runtime".to_string(),
+                            suggested_fix: None,
+                        }],
+                    },
+                },
+            )]),
+            unassigned_files: vec![PathBuf::from("/repo/functions/report/handler.py")],
+        }));
+
+        let output = generate_application_policies(
+            input,
+            &Session::default(),
+            &WorkspaceRoots::default(),
+            &ToolProgress::default(),
+        )
+        .await
+        .unwrap();
+        assert!(output.policies.is_empty());
+        assert_eq!(output.workloads.len(), 1);
+        assert_eq!(output.workloads[0].name, "ingest");
+        assert_eq!(
+            output.workloads[0].source_files,
+            vec!["/repo/functions/ingest/handler.py".to_string()]
+        );
+        assert!(output.workloads[0].policies[0]
+            .document
+            .actions()
+            .any(|action| action == "s3:PutObject"));
+        assert_eq!(
+            output.workloads[0].diagnostics[0].code,
+            DiagnosticCode::DynamicMethodName
+        );
+        assert_eq!(
+            output.unassigned_files,
+            vec!["/repo/functions/report/handler.py".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_workload_manifest_root() {
+        let source_files = vec![
+            PathBuf::from("/repo/functions/ingest/handler.py"),
+            PathBuf::from("/repo/lib/storage.py"),
+        ];
+        let manifest = workload_manifest(
+            Some(BTreeMap::from([(
+                "ingest".to_string(),
+                vec!["functions/ingest/**".to_string()],
+            )])),
+            None,
+            &source_files,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.root, PathBuf::from("/repo"));
+        assert_eq!(manifest.workloads[0].name, "ingest");
+        assert!(workload_manifest(None, None, &source_files).is_none());
+    }
 }
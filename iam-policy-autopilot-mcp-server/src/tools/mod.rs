@@ -1,7 +1,22 @@
+mod check_policy_against_code;
+mod explain_policy_action;
+mod extract_sdk_calls;
 mod fix_access_denied;
 mod generate_policy;
 mod generate_policy_for_access_denied;
+mod upload_policies;
+mod validate_policies;
 
+pub(crate) use check_policy_against_code::{
+    check_policy_against_code, CheckPolicyAgainstCodeInput, CheckPolicyAgainstCodeOutput,
+};
+pub(crate) use explain_policy_action::{
+    explain_policy_action, ExplainPolicyActionInput, ExplainPolicyActionOutput,
+};
+pub(crate) use extract_sdk_calls::{
+    extract_sdk_calls, ExtractSdkCallsInput, ExtractSdkCallsOutput,
+};
+pub(crate) use fix_access_denied::*;
 pub(crate) use generate_policy::{
     generate_application_policies, GeneratePoliciesInput, GeneratePoliciesOutput,
 };
@@ -9,6 +24,10 @@ pub(crate) use generate_policy_for_access_denied::{
     generate_policy_for_access_denied, GeneratePolicyForAccessDeniedInput,
     GeneratePolicyForAccessDeniedOutput,
 };
+pub(crate) use upload_policies::{upload_policies, UploadPoliciesInput, UploadPoliciesOutput};
+pub(crate) use validate_policies::{
+    validate_policies, ValidatePoliciesInput, ValidatePoliciesOutput,
+};
 
 /// Wrapper for iam_policy_autopilot_policy_generation::commands::IamPolicyAutopilotService
 /// we mock this implementation with #[cfg(test)] to help with unit testing
@@ -71,4 +90,123 @@ mod policy_autopilot {
     }
 }
 
-pub(crate) use fix_access_denied::*;
+/// Wrapper for iam_policy_autopilot_tools::PolicyUploader
+/// we mock this implementation with #[cfg(test)] to help with unit testing
+#[cfg(not(test))]
+pub(crate) mod policy_uploader {
+    use anyhow::{Context, Result};
+    use iam_policy_autopilot_tools::{BatchUploadResponse, PolicyUploader};
+
+    /// Uploader whose AWS client is shared by all the requests of a tool call
+    pub struct Uploader(PolicyUploader);
+
+    impl Uploader {
+        pub async fn new() -> Result<Self> {
+            PolicyUploader::new()
+                .await
+                .map(Self)
+                .context("Failed to create policy uploader")
+        }
+
+        pub async fn planned_names(
+            &self,
+            count: usize,
+            prefix: Option<&str>,
+        ) -> Result<Vec<String>> {
+            self.0
+                .planned_policy_names(count, prefix)
+                .await
+                .context("Failed to plan the policy names")
+        }
+
+        pub async fn upload(
+            &self,
+            documents: &[String],
+            prefix: Option<&str>,
+        ) -> Result<BatchUploadResponse> {
+            self.0
+                .upload_policy_documents(documents, prefix)
+                .await
+                .context("Failed to upload policies to AWS IAM")
+        }
+
+        pub async fn attach(&self, role_name: &str, policy_arn: &str) -> Result<()> {
+            self.0
+                .attach_role_policy(role_name, policy_arn)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod policy_uploader {
+    use anyhow::Result;
+    use iam_policy_autopilot_tools::BatchUploadResponse;
+
+    pub static mut MOCK_NEW_CALLS: usize = 0;
+    pub static mut MOCK_PLANNED_NAMES_RETURN: Option<Result<Vec<String>>> = None;
+    pub static mut MOCK_UPLOAD_RETURN: Option<Result<BatchUploadResponse>> = None;
+    pub static mut MOCK_ATTACH_RETURNS: Vec<Result<()>> = Vec::new();
+
+    pub struct Uploader;
+
+    impl Uploader {
+        pub async fn new() -> Result<Self> {
+            unsafe { MOCK_NEW_CALLS += 1 }
+            Ok(Self)
+        }
+
+        pub async fn planned_names(
+            &self,
+            _count: usize,
+            _prefix: Option<&str>,
+        ) -> Result<Vec<String>> {
+            #[allow(static_mut_refs)]
+            unsafe {
+                MOCK_PLANNED_NAMES_RETURN.take().unwrap()
+            }
+        }
+
+        pub async fn upload(
+            &self,
+            _documents: &[String],
+            _prefix: Option<&str>,
+        ) -> Result<BatchUploadResponse> {
+            #[allow(static_mut_refs)]
+            unsafe {
+                MOCK_UPLOAD_RETURN.take().unwrap()
+            }
+        }
+
+        pub async fn attach(&self, _role_name: &str, _policy_arn: &str) -> Result<()> {
+            #[allow(static_mut_refs)]
+            unsafe {
+                MOCK_ATTACH_RETURNS.remove(0)
+            }
+        }
+    }
+
+    /// Number of uploaders created since the last call
+    pub fn take_mock_new_calls() -> usize {
+        #[allow(static_mut_refs)]
+        unsafe {
+            std::mem::take(&mut MOCK_NEW_CALLS)
+        }
+    }
+
+    pub fn set_mock_planned_names_return(value: Result<Vec<String>>) {
+        unsafe { MOCK_PLANNED_NAMES_RETURN = Some(value) }
+    }
+
+    pub fn set_mock_upload_return(value: Result<BatchUploadResponse>) {
+        unsafe { MOCK_UPLOAD_RETURN = Some(value) }
+    }
+
+    pub fn set_mock_attach_return(value: Result<()>) {
+        #[allow(static_mut_refs)]
+        unsafe {
+            MOCK_ATTACH_RETURNS.push(value)
+        }
+    }
+}
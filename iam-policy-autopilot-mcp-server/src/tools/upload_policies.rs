@@ -0,0 +1,409 @@
+use crate::tools::policy_uploader;
+use anyhow::Error;
+use anyhow::{bail, Context};
+use iam_policy_autopilot_tools::BatchUploadResponse;
+use log::{debug, error, warn};
+use rmcp::{
+    elicit_safe,
+    service::{ElicitationError, RequestContext},
+    RoleServer,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(description = "Input for uploading IAM policies as customer managed policies")]
+pub struct UploadPoliciesInput {
+    #[schemars(
+        description = "IAM policy documents to upload, each as a JSON string, e.g. the Document of the policies from generate_application_policies"
+    )]
+    pub policies: Vec<String>,
+
+    #[schemars(
+        description = "If true, only return the names the policies would be uploaded with, without asking for confirmation or changing the account. Defaults to false."
+    )]
+    pub dry_run: Option<bool>,
+
+    #[schemars(
+        description = "Prefix of the policy names, which are suffixed with _<number> to be unique in the account. Defaults to IamPolicyAutopilotGeneratedPolicy."
+    )]
+    pub policy_name_prefix: Option<String>,
+
+    #[schemars(
+        description = "Name or ARN of an IAM role to attach the uploaded policies to. If omitted, the policies are uploaded without being attached."
+    )]
+    pub role_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[schemars(description = "Outcome of the upload")]
+pub enum UploadStatus {
+    #[schemars(description = "Nothing was uploaded, the policy names are the planned ones")]
+    DryRun,
+    #[schemars(description = "The user confirmed and the policies were uploaded")]
+    Uploaded,
+    #[schemars(description = "The user declined and nothing was uploaded")]
+    Declined,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(description = "Policy uploaded, or planned to be uploaded")]
+pub struct UploadedPolicy {
+    #[schemars(description = "Index of the policy in the input Policies")]
+    pub index: usize,
+    #[schemars(description = "Name of the customer managed policy")]
+    pub policy_name: String,
+    #[schemars(description = "ARN of the uploaded policy, absent for a dry run")]
+    pub policy_arn: Option<String>,
+    #[schemars(description = "Role the policy was attached to, if any")]
+    pub attached_to_role: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(description = "Policy that could not be uploaded or attached")]
+pub struct UploadFailure {
+    #[schemars(description = "Index of the policy in the input Policies")]
+    pub index: usize,
+    #[schemars(
+        description = "ARN of the policy if it was uploaded but could not be attached to the role"
+    )]
+    pub policy_arn: Option<String>,
+    #[schemars(description = "Error message")]
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(description = "Output containing the result of uploading the policies")]
+pub struct UploadPoliciesOutput {
+    #[schemars(description = "Whether the policies were uploaded, only planned, or declined")]
+    pub status: UploadStatus,
+    #[schemars(description = "Policies uploaded, or the planned names for a dry run")]
+    pub policies: Vec<UploadedPolicy>,
+    #[schemars(description = "Policies that could not be uploaded or attached to the role")]
+    pub failures: Vec<UploadFailure>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[schemars(description = "Confirmation from the user")]
+struct UserConfirmation(bool);
+
+// Mark as safe for elicitation
+elicit_safe!(UserConfirmation);
+
+pub async fn upload_policies(
+    context: RequestContext<RoleServer>,
+    input: UploadPoliciesInput,
+) -> Result<UploadPoliciesOutput, Error> {
+    let role_name = check_input(&input)?;
+    if input.dry_run.unwrap_or(false) {
+        return plan_upload(&input).await;
+    }
+
+    let target = match &role_name {
+        Some(role_name) => format!("upload and attach to the role {role_name}"),
+        None => "upload".to_string(),
+    };
+    let elicit_result = context
+        .peer
+        .elicit::<UserConfirmation>(format!(
+            "Are you sure you want to {target} the following {} IAM policies? (yes/no):\n\n{}",
+            input.policies.len(),
+            input.policies.join("\n\n")
+        ))
+        .await;
+
+    // Unlike fix_access_denied, uploading without the confirmation of the user is refused
+    match elicit_result {
+        Err(ElicitationError::CapabilityNotSupported) => {
+            warn!("Elicitation capability not supported");
+            bail!("Uploading policies requires the MCP client to support elicitation to confirm the upload. Use DryRun to preview the policy names.");
+        }
+        Err(e) => {
+            error!("Elicitation error {e:#?}");
+            bail!("MCP user elicitation failed when uploading policies.");
+        }
+        Ok(Some(UserConfirmation(true))) => upload(&input, role_name.as_deref()).await,
+        Ok(Some(UserConfirmation(false))) | Ok(None) => Ok(UploadPoliciesOutput {
+            status: UploadStatus::Declined,
+            policies: vec![],
+            failures: vec![],
+        }),
+    }
+}
+
+/// Check the input before anything is uploaded, returning the name of the role
+fn check_input(input: &UploadPoliciesInput) -> Result<Option<String>, Error> {
+    if input.policies.is_empty() {
+        bail!("No policies to upload");
+    }
+    for (index, policy) in input.policies.iter().enumerate() {
+        serde_json::from_str::<serde_json::Value>(policy)
+            .with_context(|| format!("Policy {index} is not valid JSON"))?;
+    }
+
+    // Role ARNs have the form arn:aws:iam::<AccountId>:role/<Path>/<RoleName>
+    let role_name = input
+        .role_name
+        .as_deref()
+        .map(|role| role.rsplit('/').next().unwrap_or(role).trim())
+        .map(str::to_string);
+    if role_name.as_deref() == Some("") {
+        bail!(
+            "Invalid role name: {}",
+            input.role_name.as_deref().unwrap_or_default()
+        );
+    }
+    Ok(role_name)
+}
+
+async fn plan_upload(input: &UploadPoliciesInput) -> Result<UploadPoliciesOutput, Error> {
+    let names = policy_uploader::Uploader::new()
+        .await?
+        .planned_names(input.policies.len(), input.policy_name_prefix.as_deref())
+        .await?;
+
+    Ok(UploadPoliciesOutput {
+        status: UploadStatus::DryRun,
+        policies: names
+            .into_iter()
+            .enumerate()
+            .map(|(index, policy_name)| UploadedPolicy {
+                index,
+                policy_name,
+                policy_arn: None,
+                attached_to_role: None,
+            })
+            .collect(),
+        failures: vec![],
+    })
+}
+
+async fn upload(
+    input: &UploadPoliciesInput,
+    role_name: Option<&str>,
+) -> Result<UploadPoliciesOutput, Error> {
+    let uploader = policy_uploader::Uploader::new().await?;
+    let BatchUploadResponse { successful, failed } = uploader
+        .upload(&input.policies, input.policy_name_prefix.as_deref())
+        .await?;
+
+    // The batch response lists the successful uploads in input order, skipping the failed ones
+    let failed_indices: HashSet<usize> = failed.iter().map(|(index, _)| *index).collect();
+    let uploaded_indices =
+        (0..input.policies.len()).filter(|index| !failed_indices.contains(index));
+
+    let mut failures: Vec<UploadFailure> = failed
+        .into_iter()
+        .map(|(index, error)| UploadFailure {
+            index,
+            policy_arn: None,
+            error,
+        })
+        .collect();
+    let mut policies = Vec::with_capacity(successful.len());
+    for (index, response) in uploaded_indices.zip(successful) {
+        debug!("Uploaded policy {index} as {}", response.policy_arn);
+        let mut attached_to_role = None;
+        if let Some(role_name) = role_name {
+            match uploader.attach(role_name, &response.policy_arn).await {
+                Ok(()) => attached_to_role = Some(role_name.to_string()),
+                Err(e) => failures.push(UploadFailure {
+                    index,
+                    policy_arn: Some(response.policy_arn.clone()),
+                    error: format!("Failed to attach the policy to the role {role_name}: {e:#}"),
+                }),
+            }
+        }
+        policies.push(UploadedPolicy {
+            index,
+            policy_name: response.policy_name,
+            policy_arn: Some(response.policy_arn),
+            attached_to_role,
+        });
+    }
+
+    Ok(UploadPoliciesOutput {
+        status: UploadStatus::Uploaded,
+        policies,
+        failures,
+    })
+}
+
+#[cfg(test)]
+#[serial_test::serial]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use iam_policy_autopilot_tools::UploadResponse;
+
+    // Note: The confirmation needs a RequestContext, these tests cover the steps around it.
+
+    const POLICY: &str = r#"{"Version":"2012-10-17","Statement":[{"Effect":"Allow","Action":"s3:GetObject","Resource":"*"}]}"#;
+
+    fn input(count: usize, role_name: Option<&str>) -> UploadPoliciesInput {
+        UploadPoliciesInput {
+            policies: vec![POLICY.to_string(); count],
+            dry_run: None,
+            policy_name_prefix: Some("MyApp".to_string()),
+            role_name: role_name.map(str::to_string),
+        }
+    }
+
+    fn response(name: &str) -> UploadResponse {
+        UploadResponse {
+            policy_name: name.to_string(),
+            policy_arn: format!("arn:aws:iam::123456789012:policy/{name}"),
+            policy_id: "ANPAEXAMPLE".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_check_input() {
+        assert_eq!(check_input(&input(1, None)).unwrap(), None);
+        assert_eq!(
+            check_input(&input(1, Some("app-role"))).unwrap(),
+            Some("app-role".to_string())
+        );
+        assert_eq!(
+            check_input(&input(
+                1,
+                Some("arn:aws:iam::123456789012:role/service/app-role")
+            ))
+            .unwrap(),
+            Some("app-role".to_string())
+        );
+        assert!(check_input(&input(1, Some("arn:aws:iam::123456789012:role/"))).is_err());
+        assert!(check_input(&input(0, None)).is_err());
+
+        let mut invalid = input(2, None);
+        invalid.policies[1] = "{not json".to_string();
+        let error = check_input(&invalid).unwrap_err();
+        assert!(error.to_string().contains("Policy 1 is not valid JSON"));
+    }
+
+    #[tokio::test]
+    async fn test_plan_upload() {
+        policy_uploader::set_mock_planned_names_return(Ok(vec![
+            "MyApp_1".to_string(),
+            "MyApp_2".to_string(),
+        ]));
+
+        let output = plan_upload(&input(2, None)).await.unwrap();
+
+        assert_eq!(output.status, UploadStatus::DryRun);
+        assert_eq!(output.policies.len(), 2);
+        assert_eq!(output.policies[1].index, 1);
+        assert_eq!(output.policies[1].policy_name, "MyApp_2");
+        assert_eq!(output.policies[1].policy_arn, None);
+        assert!(output.failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_upload_reports_failures() {
+        policy_uploader::set_mock_upload_return(Ok(BatchUploadResponse {
+            successful: vec![response("MyApp_1"), response("MyApp_2")],
+            failed: vec![(1, "Policy document is malformed".to_string())],
+        }));
+
+        let output = upload(&input(3, None), None).await.unwrap();
+
+        assert_eq!(output.status, UploadStatus::Uploaded);
+        let indices: Vec<usize> = output.policies.iter().map(|p| p.index).collect();
+        assert_eq!(indices, vec![0, 2]);
+        assert_eq!(
+            output.policies[1].policy_arn.as_deref(),
+            Some("arn:aws:iam::123456789012:policy/MyApp_2")
+        );
+        assert_eq!(
+            output.failures,
+            vec![UploadFailure {
+                index: 1,
+                policy_arn: None,
+                error: "Policy document is malformed".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_attaches_to_role() {
+        policy_uploader::set_mock_upload_return(Ok(BatchUploadResponse {
+            successful: vec![response("MyApp_1")],
+            failed: vec![],
+        }));
+        policy_uploader::set_mock_attach_return(Ok(()));
+
+        let output = upload(&input(1, Some("app-role")), Some("app-role"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            output.policies[0].attached_to_role.as_deref(),
+            Some("app-role")
+        );
+        assert!(output.failures.is_empty());
+
+        policy_uploader::set_mock_upload_return(Ok(BatchUploadResponse {
+            successful: vec![response("MyApp_1")],
+            failed: vec![],
+        }));
+        policy_uploader::set_mock_attach_return(Err(anyhow!("NoSuchEntity")));
+
+        let output = upload(&input(1, Some("app-role")), Some("app-role"))
+            .await
+            .unwrap();
+
+        assert_eq!(output.policies[0].attached_to_role, None);
+        assert_eq!(output.failures.len(), 1);
+        assert_eq!(
+            output.failures[0].policy_arn.as_deref(),
+            Some("arn:aws:iam::123456789012:policy/MyApp_1")
+        );
+        assert!(output.failures[0].error.contains("NoSuchEntity"));
+    }
+
+    #[tokio::test]
+    async fn test_upload_reuses_the_uploader() {
+        policy_uploader::take_mock_new_calls();
+        policy_uploader::set_mock_upload_return(Ok(BatchUploadResponse {
+            successful: vec![response("MyApp_1"), response("MyApp_2")],
+            failed: vec![],
+        }));
+        policy_uploader::set_mock_attach_return(Ok(()));
+        policy_uploader::set_mock_attach_return(Ok(()));
+
+        let output = upload(&input(2, Some("app-role")), Some("app-role"))
+            .await
+            .unwrap();
+
+        assert!(output
+            .policies
+            .iter()
+            .all(|policy| policy.attached_to_role.as_deref() == Some("app-role")));
+        assert_eq!(policy_uploader::take_mock_new_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upload_failure() {
+        policy_uploader::set_mock_upload_return(Err(anyhow!("Failed to list policies")));
+
+        let result = upload(&input(1, None), None).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_upload_policies_output_serialization() {
+        let output = UploadPoliciesOutput {
+            status: UploadStatus::DryRun,
+            policies: vec![],
+            failures: vec![],
+        };
+
+        let json = serde_json::to_string(&output).unwrap();
+        assert_eq!(json, r#"{"Status":"DryRun","Policies":[],"Failures":[]}"#);
+    }
+}
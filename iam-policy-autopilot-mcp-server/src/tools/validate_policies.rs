@@ -0,0 +1,138 @@
+use anyhow::Error;
+use anyhow::Result;
+use iam_policy_autopilot_policy_generation::api::model::{
+    ServiceReferenceSource, ValidatePoliciesConfig,
+};
+use iam_policy_autopilot_policy_generation::{PolicyKind, ValidationFinding};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(test))]
+mod api {
+    pub use iam_policy_autopilot_policy_generation::api::validate_policies;
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(description = "Input for validating IAM policy documents.")]
+pub struct ValidatePoliciesInput {
+    #[schemars(description = "IAM policy documents to validate, each as a JSON string")]
+    pub policies: Vec<String>,
+
+    #[schemars(
+        description = "Whether the policies will be attached as 'managed' (default) or 'inline' policies. Determines which IAM size quotas apply."
+    )]
+    pub policy_type: Option<PolicyKind>,
+}
+
+#[derive(Debug, Serialize, JsonSchema, Eq, PartialEq)]
+#[schemars(description = "Output containing the validation findings for the policies.")]
+#[serde(rename_all = "PascalCase")]
+pub struct ValidatePoliciesOutput {
+    #[schemars(
+        description = "Findings, each with a FindingType of ERROR, SECURITY_WARNING, WARNING or SUGGESTION, an IssueCode, a message, and the index of the policy and statement it applies to."
+    )]
+    pub findings: Vec<ValidationFinding>,
+
+    #[schemars(
+        description = "True if any finding is an ERROR, meaning IAM would reject the policies."
+    )]
+    pub has_errors: bool,
+}
+
+pub async fn validate_policies(
+    input: ValidatePoliciesInput,
+) -> Result<ValidatePoliciesOutput, Error> {
+    let result = api::validate_policies(&ValidatePoliciesConfig {
+        policies: input.policies,
+        policy_kind: input.policy_type.unwrap_or_default(),
+        // Same as generate_application_policies, file system caching is disabled for the MCP server
+        disable_file_system_cache: true,
+        service_reference: ServiceReferenceSource::default(),
+    })
+    .await?;
+
+    Ok(ValidatePoliciesOutput {
+        has_errors: result.has_errors(),
+        findings: result.findings,
+    })
+}
+
+// Mock the api call
+#[cfg(test)]
+mod api {
+    use anyhow::Result;
+    use iam_policy_autopilot_policy_generation::api::model::{
+        ValidatePoliciesConfig, ValidatePoliciesResult,
+    };
+
+    // Static mutable return value
+    pub static mut MOCK_RETURN_VALUE: Option<Result<ValidatePoliciesResult>> = None;
+
+    pub async fn validate_policies(
+        _config: &ValidatePoliciesConfig,
+    ) -> Result<ValidatePoliciesResult> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            MOCK_RETURN_VALUE.take().unwrap()
+        }
+    }
+
+    pub fn set_mock_return(value: Result<ValidatePoliciesResult>) {
+        unsafe { MOCK_RETURN_VALUE = Some(value) }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::serial]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use iam_policy_autopilot_policy_generation::api::model::ValidatePoliciesResult;
+    use iam_policy_autopilot_policy_generation::{FindingType, IssueCode};
+
+    fn input() -> ValidatePoliciesInput {
+        ValidatePoliciesInput {
+            policies: vec![r#"{"Version":"2012-10-17","Statement":[]}"#.to_string()],
+            policy_type: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_policies() {
+        let finding = ValidationFinding {
+            finding_type: FindingType::Error,
+            issue_code: IssueCode::MissingStatement,
+            message: "Policy must contain at least one statement".to_string(),
+            policy_index: Some(0),
+            statement_index: None,
+            sid: None,
+        };
+        api::set_mock_return(Ok(ValidatePoliciesResult {
+            findings: vec![finding.clone()],
+        }));
+
+        let output = validate_policies(input()).await.unwrap();
+
+        assert!(output.has_errors);
+        assert_eq!(output.findings, vec![finding]);
+        let json = serde_json::to_string(&output).unwrap();
+        assert!(json.contains("\"FindingType\":\"ERROR\""));
+        assert!(json.contains("\"IssueCode\":\"MISSING_STATEMENT\""));
+        assert!(json.contains("\"HasErrors\":true"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_policies_error() {
+        api::set_mock_return(Err(anyhow!("Failed to validate policies")));
+        assert!(validate_policies(input()).await.is_err());
+    }
+
+    #[test]
+    fn test_validate_policies_input_deserialization() {
+        let input: ValidatePoliciesInput =
+            serde_json::from_str(r#"{"Policies":["{}"],"PolicyType":"inline"}"#).unwrap();
+        assert_eq!(input.policies, vec!["{}".to_string()]);
+        assert_eq!(input.policy_type, Some(PolicyKind::Inline));
+    }
+}
@@ -0,0 +1,275 @@
+//! Workspace roots restricting the files the MCP server reads
+//!
+//! Tools read the source files their clients name. The server can be started with workspace
+//! roots, and MCP clients can declare their own roots; source files must then be within both,
+//! after symlinks are resolved, so that a client of a shared server cannot read other files
+//! of the machine. Without roots, any file the server can read is accepted.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::warn;
+use rmcp::model::Root;
+use serde::Serialize;
+
+/// Source file rejected by the workspace roots
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct RejectedPath {
+    pub(crate) path: String,
+    pub(crate) reason: String,
+}
+
+/// Error of a tool call naming source files outside the workspace roots
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct OutsideWorkspaceRoots {
+    pub(crate) rejected_paths: Vec<RejectedPath>,
+    pub(crate) workspace_roots: Vec<String>,
+}
+
+impl fmt::Display for OutsideWorkspaceRoots {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Source files must be within the workspace roots {}, rejected ",
+            self.workspace_roots.join(", ")
+        )?;
+        for (index, rejected) in self.rejected_paths.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} ({})", rejected.path, rejected.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for OutsideWorkspaceRoots {}
+
+/// Directories the source files of tool calls must be within
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WorkspaceRoots {
+    /// Canonical roots configured when starting the server
+    server: Vec<PathBuf>,
+    /// Canonical roots declared by the MCP client
+    client: Vec<PathBuf>,
+}
+
+impl WorkspaceRoots {
+    /// Roots configured when starting the server, which must exist
+    pub(crate) fn new(roots: &[PathBuf]) -> Result<Self> {
+        let server = roots
+            .iter()
+            .map(|root| {
+                root.canonicalize().with_context(|| {
+                    format!("Failed to resolve the workspace root {}", root.display())
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            server,
+            client: vec![],
+        })
+    }
+
+    /// Add the roots declared by the MCP client
+    ///
+    /// Roots that are not `file://` URIs or do not exist are ignored. A client declaring no
+    /// roots does not restrict the source files further.
+    pub(crate) fn with_client_roots(&self, roots: &[Root]) -> Self {
+        let client = roots
+            .iter()
+            .filter_map(|root| {
+                let path = url::Url::parse(&root.uri)
+                    .ok()
+                    .filter(|uri| uri.scheme() == "file")
+                    .and_then(|uri| uri.to_file_path().ok());
+                let canonical = path.as_deref().map(Path::canonicalize);
+                match canonical {
+                    Some(Ok(path)) => Some(path),
+                    _ => {
+                        warn!("Ignoring the client root {}", root.uri);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self {
+            server: self.server.clone(),
+            client,
+        }
+    }
+
+    /// Whether source files are restricted to some directories
+    pub(crate) fn is_restricted(&self) -> bool {
+        !self.server.is_empty() || !self.client.is_empty()
+    }
+
+    /// Display the roots, server roots first
+    pub(crate) fn display(&self) -> Vec<String> {
+        self.server
+            .iter()
+            .chain(&self.client)
+            .map(|root| root.display().to_string())
+            .collect()
+    }
+
+    /// Innermost root containing the canonical `path`, beyond which no file may be read
+    ///
+    /// Returns None if the roots do not restrict files, or none contains `path`.
+    pub(crate) fn boundary(&self, path: &Path) -> Option<&Path> {
+        self.server
+            .iter()
+            .chain(&self.client)
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    /// Check that source files are within the roots, after resolving symlinks and `..`
+    pub(crate) fn check<P: AsRef<Path>>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+    ) -> Result<(), OutsideWorkspaceRoots> {
+        if !self.is_restricted() {
+            return Ok(());
+        }
+        let within = |roots: &[PathBuf], path: &Path| {
+            roots.is_empty() || roots.iter().any(|root| path.starts_with(root))
+        };
+
+        let rejected_paths: Vec<RejectedPath> = paths
+            .into_iter()
+            .filter_map(|path| {
+                let path = path.as_ref();
+                let reason = match path.canonicalize() {
+                    Err(e) => format!("cannot be resolved: {e}"),
+                    Ok(canonical)
+                        if within(&self.server, &canonical) && within(&self.client, &canonical) =>
+                    {
+                        return None;
+                    }
+                    Ok(canonical) if canonical != path => {
+                        format!("resolves to {}", canonical.display())
+                    }
+                    Ok(_) => "outside the workspace roots".to_string(),
+                };
+                Some(RejectedPath {
+                    path: path.display().to_string(),
+                    reason,
+                })
+            })
+            .collect();
+
+        if rejected_paths.is_empty() {
+            Ok(())
+        } else {
+            Err(OutsideWorkspaceRoots {
+                rejected_paths,
+                workspace_roots: self.display(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn root(path: &Path) -> Root {
+        Root {
+            uri: url::Url::from_directory_path(path).unwrap().to_string(),
+            name: None,
+        }
+    }
+
+    #[test]
+    fn test_unrestricted_without_roots() {
+        let roots = WorkspaceRoots::new(&[]).unwrap().with_client_roots(&[]);
+        assert!(!roots.is_restricted());
+        assert!(roots.check(["/does/not/exist.py"]).is_ok());
+    }
+
+    #[test]
+    fn test_check_server_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        fs::create_dir_all(workspace.join("src")).unwrap();
+        fs::write(workspace.join("src/app.py"), "").unwrap();
+        fs::write(dir.path().join("secret.py"), "").unwrap();
+
+        let roots = WorkspaceRoots::new(std::slice::from_ref(&workspace)).unwrap();
+        assert!(roots.check([workspace.join("src/app.py")]).is_ok());
+
+        let escape = workspace.join("src/../../secret.py");
+        let error = roots
+            .check([workspace.join("src/app.py"), escape.clone()])
+            .unwrap_err();
+        assert_eq!(error.rejected_paths.len(), 1);
+        assert_eq!(error.rejected_paths[0].path, escape.display().to_string());
+        assert!(error.to_string().contains("secret.py"));
+
+        let missing = roots.check([workspace.join("missing.py")]).unwrap_err();
+        assert!(missing.rejected_paths[0]
+            .reason
+            .starts_with("cannot be resolved"));
+
+        assert!(WorkspaceRoots::new(&[dir.path().join("missing")]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_rejects_symlink_escapes() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        fs::create_dir(&workspace).unwrap();
+        fs::write(dir.path().join("secret.py"), "").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("secret.py"), workspace.join("link.py"))
+            .unwrap();
+
+        let roots = WorkspaceRoots::new(std::slice::from_ref(&workspace)).unwrap();
+        let error = roots.check([workspace.join("link.py")]).unwrap_err();
+        assert!(error.rejected_paths[0].reason.starts_with("resolves to"));
+    }
+
+    #[test]
+    fn test_check_client_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        fs::write(first.join("app.py"), "").unwrap();
+        fs::write(second.join("app.py"), "").unwrap();
+
+        let client_only = WorkspaceRoots::default().with_client_roots(&[
+            root(&first),
+            Root {
+                uri: "https://example.com/repo".to_string(),
+                name: None,
+            },
+        ]);
+        assert!(client_only.is_restricted());
+        assert!(client_only.check([first.join("app.py")]).is_ok());
+        assert!(client_only.check([second.join("app.py")]).is_err());
+
+        // Client roots cannot widen the roots of the server
+        let both = WorkspaceRoots::new(std::slice::from_ref(&first))
+            .unwrap()
+            .with_client_roots(&[root(dir.path())]);
+        assert!(both.check([first.join("app.py")]).is_ok());
+        assert!(both.check([second.join("app.py")]).is_err());
+
+        let canonical = first.canonicalize().unwrap();
+        assert_eq!(
+            both.boundary(&canonical.join("app.py")),
+            Some(canonical.as_path())
+        );
+        let dir = dir.path().canonicalize().unwrap();
+        assert_eq!(both.boundary(&dir.join("second")), Some(dir.as_path()));
+        assert_eq!(both.boundary(dir.parent().unwrap()), None);
+    }
+}
@@ -1,11 +1,14 @@
 use std::path::Path;
 
-use rmcp::model::InitializeRequestParam;
+use rmcp::model::{GetPromptRequestParam, InitializeRequestParam, ReadResourceRequestParam};
 use rmcp::RoleClient;
 use rmcp::{
     model::{CallToolRequestParam, ClientCapabilities, ClientInfo, Implementation},
     service::RunningService,
-    transport::{StreamableHttpClientTransport, TokioChildProcess},
+    transport::{
+        streamable_http_client::StreamableHttpClientTransportConfig, StreamableHttpClientTransport,
+        TokioChildProcess,
+    },
     RmcpError, ServiceExt,
 };
 use serde_json::json;
@@ -15,9 +18,13 @@ use tokio::process::{Child, Command};
 use tokio::time::{sleep, Duration};
 
 async fn setup_stdio() -> RunningService<RoleClient, ()> {
+    setup_stdio_with_args(&[]).await
+}
+
+async fn setup_stdio_with_args(args: &[&str]) -> RunningService<RoleClient, ()> {
     // Create MCP client using TokioChildProcess with debug binary
     let mut command = Command::new("../target/debug/iam-policy-autopilot");
-    command.args(&["mcp-server"]);
+    command.args(["mcp-server"]).args(args);
 
     ().serve(
         TokioChildProcess::new(command)
@@ -43,17 +50,26 @@ async fn wait_for_server_ready(port: u16, max_attempts: u32) -> bool {
 
 async fn setup_http_with_port(
     port: u16,
+) -> (RunningService<RoleClient, InitializeRequestParam>, Child) {
+    setup_http_with_args(port, &[], None).await
+}
+
+async fn setup_http_with_args(
+    port: u16,
+    args: &[&str],
+    auth_token: Option<&str>,
 ) -> (RunningService<RoleClient, InitializeRequestParam>, Child) {
     // Start HTTP server as a background process using debug binary
     let mut command = Command::new("../target/debug/iam-policy-autopilot");
     command
-        .args(&[
+        .args([
             "mcp-server",
             "--transport",
             "http",
             "--port",
             &port.to_string(),
         ])
+        .args(args)
         .stderr(std::process::Stdio::piped())
         .stdout(std::process::Stdio::piped());
 
@@ -71,8 +87,12 @@ async fn setup_http_with_port(
     sleep(Duration::from_millis(500)).await;
 
     // Create HTTP client transport
-    let transport =
-        StreamableHttpClientTransport::from_uri(format!("http://127.0.0.1:{}/mcp", port));
+    let mut config =
+        StreamableHttpClientTransportConfig::with_uri(format!("http://127.0.0.1:{}/mcp", port));
+    if let Some(auth_token) = auth_token {
+        config = config.auth_header(auth_token);
+    }
+    let transport = StreamableHttpClientTransport::from_config(config);
     let client_info = ClientInfo {
         protocol_version: Default::default(),
         capabilities: ClientCapabilities::default(),
@@ -94,6 +114,68 @@ async fn setup_http() -> (RunningService<RoleClient, InitializeRequestParam>, Ch
     setup_http_with_port(8001).await
 }
 
+#[tokio::test]
+async fn test_stdio_list_prompts_and_resources() {
+    let client = setup_stdio().await;
+
+    let prompts_result = client.list_prompts(None).await.unwrap();
+    let prompt_names: Vec<&str> = prompts_result
+        .prompts
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+    assert_eq!(
+        prompt_names.len(),
+        3,
+        "Unexpected prompts: {:?}",
+        prompt_names
+    );
+    assert!(prompt_names.contains(&"review_policy"));
+    assert!(prompt_names.contains(&"tighten_wildcards"));
+    assert!(prompt_names.contains(&"diagnose_access_denied"));
+
+    let prompt = client
+        .get_prompt(GetPromptRequestParam {
+            name: "diagnose_access_denied".to_string(),
+            arguments: Some(
+                json!({"ErrorMessage": "not authorized to perform: s3:GetObject"})
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            ),
+        })
+        .await
+        .unwrap();
+    assert_eq!(prompt.messages.len(), 1);
+
+    let resources_result = client.list_resources(None).await.unwrap();
+    let resource_uris: Vec<&str> = resources_result
+        .resources
+        .iter()
+        .map(|r| r.uri.as_str())
+        .collect();
+    assert!(resource_uris.contains(&"iam-policy-autopilot://services"));
+    assert!(resource_uris.contains(&"iam-policy-autopilot://fas-maps"));
+
+    let templates_result = client.list_resource_templates(None).await.unwrap();
+    assert_eq!(templates_result.resource_templates.len(), 2);
+
+    let fas_map = client
+        .read_resource(ReadResourceRequestParam {
+            uri: "iam-policy-autopilot://fas-maps/s3".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(fas_map.contents.len(), 1);
+
+    assert!(client
+        .read_resource(ReadResourceRequestParam {
+            uri: "iam-policy-autopilot://unknown".to_string(),
+        })
+        .await
+        .is_err());
+}
+
 #[tokio::test]
 async fn test_stdio_list_tools() {
     let client = setup_stdio().await;
@@ -102,13 +184,26 @@ async fn test_stdio_list_tools() {
     let tools_result = client.list_tools(None).await.unwrap();
 
     // Verify we have the expected tools
-    assert_eq!(tools_result.tools.len(), 3);
+    assert_eq!(tools_result.tools.len(), 8);
 
     // Check that all expected tools are present
     let tool_names: Vec<&str> = tools_result.tools.iter().map(|t| t.name.as_ref()).collect();
     assert!(tool_names.contains(&"generate_application_policies"));
     assert!(tool_names.contains(&"generate_policy_for_access_denied"));
     assert!(tool_names.contains(&"fix_access_denied"));
+    assert!(tool_names.contains(&"validate_policies"));
+    assert!(tool_names.contains(&"extract_sdk_calls"));
+    assert!(tool_names.contains(&"explain_policy_action"));
+    assert!(tool_names.contains(&"upload_policies"));
+    assert!(tool_names.contains(&"check_policy_against_code"));
+
+    let generate = tools_result
+        .tools
+        .iter()
+        .find(|tool| tool.name == "generate_application_policies")
+        .unwrap();
+    let output_schema = generate.output_schema.as_ref().unwrap();
+    assert!(output_schema["properties"]["Policies"].is_object());
 
     // Verify tool descriptions are present
     for tool in &tools_result.tools {
@@ -166,6 +261,40 @@ async fn test_stdio_generate_policy_for_access_denied() {
     assert_eq!(tool_result.is_error, Some(false));
 }
 
+#[tokio::test]
+async fn test_stdio_rejects_files_outside_workspace_roots() {
+    let workspace = std::env::current_dir().unwrap().join("tests/test_data");
+    let inside = workspace.join("lambda.py");
+    let outside = workspace.join("../../Cargo.toml");
+
+    let client = setup_stdio_with_args(&["--workspace-root", workspace.to_str().unwrap()]).await;
+    let tool_result = client
+        .call_tool(CallToolRequestParam {
+            name: "extract_sdk_calls".into(),
+            arguments: json!({"SourceFiles": [inside]}).as_object().cloned(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(tool_result.is_error, Some(false));
+
+    let error = client
+        .call_tool(CallToolRequestParam {
+            name: "extract_sdk_calls".into(),
+            arguments: json!({"SourceFiles": [inside, outside]})
+                .as_object()
+                .cloned(),
+        })
+        .await
+        .unwrap_err();
+    let rmcp::ServiceError::McpError(error) = error else {
+        panic!("Unexpected error: {error:?}");
+    };
+    assert_eq!(error.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+    let rejected_paths = &error.data.unwrap()["RejectedPaths"];
+    assert_eq!(rejected_paths.as_array().unwrap().len(), 1);
+    assert_eq!(rejected_paths[0]["Path"], json!(outside));
+}
+
 #[tokio::test]
 #[serial]
 async fn test_http_list_tools() {
@@ -175,7 +304,7 @@ async fn test_http_list_tools() {
     let tools_result = client.list_tools(None).await.unwrap();
 
     // Verify we have the expected tools
-    assert_eq!(tools_result.tools.len(), 3);
+    assert_eq!(tools_result.tools.len(), 8);
 
     // Check that all expected tools are present
     let tool_names: Vec<&str> = tools_result.tools.iter().map(|t| t.name.as_ref()).collect();
@@ -184,6 +313,11 @@ async fn test_http_list_tools() {
     assert!(tool_names.contains(&"generate_application_policies"));
     assert!(tool_names.contains(&"generate_policy_for_access_denied"));
     assert!(tool_names.contains(&"fix_access_denied"));
+    assert!(tool_names.contains(&"validate_policies"));
+    assert!(tool_names.contains(&"extract_sdk_calls"));
+    assert!(tool_names.contains(&"explain_policy_action"));
+    assert!(tool_names.contains(&"upload_policies"));
+    assert!(tool_names.contains(&"check_policy_against_code"));
 
     // Verify tool descriptions are present
     for tool in &tools_result.tools {
@@ -252,3 +386,79 @@ async fn test_http_generate_policy_for_access_denied() {
     let _ = server_process.start_kill();
     let _ = server_process.wait().await;
 }
+
+#[tokio::test]
+#[serial]
+async fn test_http_authentication_and_read_only() {
+    let (client, mut server_process) = setup_http_with_args(
+        8004,
+        &[
+            "--auth-token",
+            "test-token",
+            "--read-only",
+            "--max-request-size",
+            "1024",
+        ],
+        Some("test-token"),
+    )
+    .await;
+
+    let tools_result = client.list_tools(None).await.unwrap();
+    let tool_names: Vec<&str> = tools_result.tools.iter().map(|t| t.name.as_ref()).collect();
+    assert_eq!(tool_names.len(), 6, "Unexpected tools: {:?}", tool_names);
+    assert!(!tool_names.contains(&"fix_access_denied"));
+    assert!(!tool_names.contains(&"upload_policies"));
+
+    let http_client = reqwest::Client::new();
+    let initialize = json!({"jsonrpc": "2.0", "id": 1, "method": "ping"});
+    let unauthenticated = http_client
+        .post("http://127.0.0.1:8004/mcp")
+        .json(&initialize)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unauthenticated.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let wrong_origin = http_client
+        .post("http://127.0.0.1:8004/mcp")
+        .bearer_auth("test-token")
+        .header("Origin", "https://attacker.example.com")
+        .json(&initialize)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(wrong_origin.status(), reqwest::StatusCode::FORBIDDEN);
+
+    let too_large = http_client
+        .post("http://127.0.0.1:8004/mcp")
+        .bearer_auth("test-token")
+        .body(vec![b' '; 2048])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(too_large.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+
+    // Clean up: kill the server process
+    let _ = server_process.kill().await;
+}
+
+#[tokio::test]
+async fn test_http_non_loopback_requires_authentication() {
+    let output = Command::new("../target/debug/iam-policy-autopilot")
+        .args([
+            "mcp-server",
+            "--transport",
+            "http",
+            "--port",
+            "8005",
+            "--bind-address",
+            "0.0.0.0",
+        ])
+        .env_remove("IAM_POLICY_AUTOPILOT_MCP_AUTH_TOKEN")
+        .output()
+        .await
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("requires authentication"));
+}
@@ -0,0 +1,38 @@
+use anyhow::{Context, Result};
+use log::debug;
+
+use crate::{
+    api::{
+        generate_policies,
+        model::{CheckPolicyResult, GeneratePolicyConfig},
+    },
+    policy_generation::comparison::compare_policy,
+};
+
+/// Check a policy document against the policies generated for source code
+///
+/// Policies are generated for the source files of `config` and compared with `policy`, an
+/// edited policy document given as JSON text. The result lists the generated permissions the
+/// document lacks or grants only in part, the permissions of the document no SDK call needs,
+/// and the resources it grants more broadly than the generated policies. Conditions of the document are ignored.
+pub async fn check_policy_against_code(
+    config: &GeneratePolicyConfig,
+    policy: &str,
+) -> Result<CheckPolicyResult> {
+    let generated = generate_policies(config).await?;
+    let comparison = compare_policy(policy, &generated.policies)
+        .context("Failed to compare the policy with the generated policies")?;
+    debug!(
+        "Compared the policy with {} generated policies: {} missing, {} narrowed, {} unexplained, {} broadened",
+        generated.policies.len(),
+        comparison.missing_permissions.len(),
+        comparison.narrowed_resources.len(),
+        comparison.unexplained_permissions.len(),
+        comparison.broadened_resources.len()
+    );
+
+    Ok(CheckPolicyResult {
+        comparison,
+        diagnostics: generated.diagnostics,
+    })
+}
@@ -83,9 +83,8 @@ pub(crate) async fn process_source_files(
         results.metadata.source_files.len()
     );
 
-    // Log warnings if any
-    for warning in &results.metadata.warnings {
-        warn!("{}", warning);
+    for diagnostic in &results.metadata.diagnostics {
+        warn!("{}", diagnostic);
     }
 
     Ok(results)
@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use log::{debug, info};
+use walkdir::WalkDir;
+
+use crate::{
+    api::model::DeriveSdkWrappersConfig,
+    extraction::{wrapper_derivation, SdkWrapperMapping},
+    ExtractionEngine, Language, SourceFile,
+};
+
+/// Directories that never contain library sources
+const SKIPPED_DIRECTORIES: &[&str] = &["node_modules", "vendor", "__pycache__"];
+
+/// Derive an SDK wrapper mapping from the source code of a library
+///
+/// Every exported function and method of the library is mapped to the AWS operations it calls,
+/// directly or through other functions of the library. The mapping can be passed to
+/// `extract_sdk_calls` and `generate_policies` of applications using the library, without the
+/// library's source code.
+pub async fn derive_sdk_wrappers(config: &DeriveSdkWrappersConfig) -> Result<SdkWrapperMapping> {
+    info!("Deriving SDK wrappers of {}", config.library_root.display());
+
+    let library_root = std::fs::canonicalize(&config.library_root).with_context(|| {
+        format!(
+            "Failed to read library directory {}",
+            config.library_root.display()
+        )
+    })?;
+    let language = config
+        .language
+        .as_deref()
+        .map(Language::try_from_str)
+        .transpose()?;
+
+    let mut files = Vec::new();
+    let walker = WalkDir::new(&library_root)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || !(entry.file_type().is_dir()
+                    && (name.starts_with('.') || SKIPPED_DIRECTORIES.contains(&name.as_ref())))
+        });
+    for entry in walker {
+        let entry = entry.context("Failed to walk library directory")?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Some(detected) = SourceFile::detect_language(entry.path()) else {
+            continue;
+        };
+        if language.is_none_or(|language| language == detected) {
+            files.push((entry.into_path(), detected));
+        }
+    }
+
+    let detected: BTreeSet<String> = files.iter().map(|(_, l)| l.to_string()).collect();
+    let language = match (language, files.first()) {
+        (Some(language), _) => language,
+        (None, Some((_, language))) if detected.len() == 1 => *language,
+        (None, Some(_)) => anyhow::bail!(
+            "Mixed programming languages detected in {}: {}. Use --language to select one.",
+            config.library_root.display(),
+            detected.into_iter().collect::<Vec<_>>().join(", ")
+        ),
+        (None, None) => anyhow::bail!("No source files found in {}", config.library_root.display()),
+    };
+    if files.is_empty() {
+        anyhow::bail!(
+            "No {} source files found in {}",
+            language,
+            config.library_root.display()
+        );
+    }
+    debug!("Analyzing {} {} source files", files.len(), language);
+
+    let mut source_files = Vec::with_capacity(files.len());
+    for (path, _) in files {
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read source file: {}", path.display()))?;
+        source_files.push(SourceFile::with_language(path, content, language));
+    }
+
+    let extractor = ExtractionEngine::new().with_sdk_wrappers(&config.sdk_wrappers)?;
+    let mapping =
+        wrapper_derivation::derive_sdk_wrappers(&extractor, language, &library_root, source_files)
+            .await
+            .context("Failed to derive SDK wrappers")?;
+
+    info!("Derived {} SDK wrappers", mapping.wrappers.len());
+    Ok(mapping)
+}
@@ -0,0 +1,34 @@
+use anyhow::{Context, Result};
+use std::time::Instant;
+
+use log::debug;
+
+use crate::{
+    api::model::{DownloadServiceReferenceSnapshotConfig, ServiceReferenceSnapshotManifest},
+    enrichment::RemoteServiceReferenceLoader,
+};
+
+/// Download service references into a snapshot for offline use
+///
+/// Writes the mapping index, the service references and a checksum manifest to the output
+/// directory, or to a single bundle file if the output path ends in `.json`.
+pub async fn download_service_reference_snapshot(
+    config: &DownloadServiceReferenceSnapshotConfig,
+) -> Result<ServiceReferenceSnapshotManifest> {
+    let start = Instant::now();
+
+    let loader = RemoteServiceReferenceLoader::new(true)?;
+    let manifest = loader
+        .download_snapshot(&config.output, config.services.as_deref())
+        .await
+        .context("Failed to download service reference snapshot")?;
+
+    debug!(
+        "Downloaded {} service references to {} in {:?}",
+        manifest.services.len(),
+        config.output.display(),
+        start.elapsed()
+    );
+
+    Ok(manifest)
+}
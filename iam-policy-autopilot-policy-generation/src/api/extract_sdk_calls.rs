@@ -11,7 +11,10 @@ pub async fn extract_sdk_calls(config: &ExtractSdkCallsConfig) -> Result<Extract
     info!("Extracting Sdk Calls");
 
     // Create the extractor
-    let extractor = crate::ExtractionEngine::new();
+    let extractor = crate::ExtractionEngine::new()
+        .with_sdk_wrappers(&config.sdk_wrappers)?
+        .with_entry_points(config.entry_points.clone())
+        .with_include_tests(config.include_tests);
 
     // Process source files
     process_source_files(
@@ -0,0 +1,100 @@
+use anyhow::{bail, Context, Result};
+use log::{debug, warn};
+
+use crate::{
+    api::{
+        generate_policies,
+        model::{FunctionPoliciesResult, GenerateFunctionPoliciesResult, GeneratePolicyConfig},
+    },
+    extraction::EntryPoints,
+    workload::absolute,
+    LambdaFunction, SourceFile,
+};
+
+/// Generate policies for each Lambda function of a repository
+///
+/// The handler of each function is resolved to an entry point in the source files of `config`,
+/// and a separate set of policies is generated from the SDK calls reachable from it. Functions
+/// whose handler is not among the source files are reported as unresolved.
+pub async fn generate_function_policies(
+    config: &GeneratePolicyConfig,
+    functions: &[LambdaFunction],
+) -> Result<GenerateFunctionPoliciesResult> {
+    let mut result = GenerateFunctionPoliciesResult::default();
+    for function in functions {
+        if result.functions.contains_key(&function.name)
+            || result.unresolved_functions.contains(&function.name)
+        {
+            bail!(
+                "Lambda function '{}' is declared more than once",
+                function.name
+            );
+        }
+
+        let Some(mut entry_point) = function.entry_point() else {
+            warn!(
+                "Handler '{}' of Lambda function '{}' not found in {}",
+                function.handler,
+                function.name,
+                function.code_directory.display()
+            );
+            result.unresolved_functions.push(function.name.clone());
+            continue;
+        };
+
+        // Only files of the handler's language are reachable from it
+        let language = SourceFile::detect_language(&entry_point.file);
+        let source_files: Vec<_> = config
+            .extract_sdk_calls_config
+            .source_files
+            .iter()
+            .filter(|file| {
+                config.extract_sdk_calls_config.language.is_some()
+                    || SourceFile::detect_language(file) == language
+            })
+            .cloned()
+            .collect();
+        let handler_file = absolute(&entry_point.file);
+        let Some(file) = source_files
+            .iter()
+            .find(|file| absolute(file) == handler_file)
+        else {
+            warn!(
+                "Handler file {} of Lambda function '{}' is not among the source files",
+                entry_point.file.display(),
+                function.name
+            );
+            result.unresolved_functions.push(function.name.clone());
+            continue;
+        };
+        entry_point.file.clone_from(file);
+
+        debug!(
+            "Generating policies for Lambda function '{}' from entry point {}",
+            function.name, entry_point
+        );
+        let mut function_config = config.clone();
+        function_config.extract_sdk_calls_config.source_files = source_files;
+        function_config.extract_sdk_calls_config.entry_points = EntryPoints {
+            detect: false,
+            declared: vec![entry_point.clone()],
+        };
+        let policies = generate_policies(&function_config).await.with_context(|| {
+            format!(
+                "Failed to generate policies for Lambda function '{}'",
+                function.name
+            )
+        })?;
+
+        result.functions.insert(
+            function.name.clone(),
+            FunctionPoliciesResult {
+                handler: function.handler.clone(),
+                entry_point,
+                declared_in: function.declared_in.clone(),
+                result: policies,
+            },
+        );
+    }
+    Ok(result)
+}
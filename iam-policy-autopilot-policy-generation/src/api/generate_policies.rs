@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use std::sync::Arc;
 use std::time::Instant;
 
 use log::{debug, info, trace};
@@ -10,7 +11,8 @@ use crate::{
     },
     extraction::SdkMethodCall,
     policy_generation::merge::PolicyMergerConfig,
-    EnrichmentEngine, PolicyGenerationEngine,
+    progress::Progress,
+    EnrichmentEngine, PolicyGenerationEngine, RemoteServiceReferenceLoader,
 };
 
 /// Generate policies for source files
@@ -23,7 +25,19 @@ pub async fn generate_policies(config: &GeneratePolicyConfig) -> Result<Generate
     );
 
     // Create the extractor
-    let extractor = crate::ExtractionEngine::new();
+    let mut extractor = crate::ExtractionEngine::new()
+        .with_sdk_wrappers(&config.extract_sdk_calls_config.sdk_wrappers)?
+        .with_entry_points(config.extract_sdk_calls_config.entry_points.clone())
+        .with_include_tests(config.extract_sdk_calls_config.include_tests);
+    if let Some(session) = &config.session {
+        extractor = extractor.with_cache(session.extraction_cache().clone());
+    }
+    if let Some(progress) = &config.progress {
+        extractor = extractor.with_progress(Arc::clone(progress));
+    }
+    if let Some(cancellation) = &config.cancellation {
+        extractor = extractor.with_cancellation(cancellation.clone());
+    }
 
     // Process source files to get extracted methods
     let extracted_methods = process_source_files(
@@ -43,6 +57,7 @@ pub async fn generate_policies(config: &GeneratePolicyConfig) -> Result<Generate
         .first()
         .map_or(crate::SdkType::Other, |f| f.language.sdk_type());
 
+    let diagnostics = extracted_methods.metadata.diagnostics;
     let extracted_methods = extracted_methods
         .methods
         .into_iter()
@@ -59,10 +74,31 @@ pub async fn generate_policies(config: &GeneratePolicyConfig) -> Result<Generate
         return Ok(GeneratePoliciesResult {
             policies: vec![],
             explanations: None,
+            diagnostics,
         });
     }
 
-    let mut enrichment_engine = EnrichmentEngine::new(config.disable_file_system_cache)?;
+    let enrichment_engine = match &config.session {
+        Some(session) => EnrichmentEngine::with_service_reference_cache(
+            session
+                .service_references(&config.service_reference, config.disable_file_system_cache)?,
+        ),
+        None => EnrichmentEngine::new(
+            RemoteServiceReferenceLoader::builder()
+                .with_disable_file_system_cache(config.disable_file_system_cache)
+                .with_source(&config.service_reference)
+                .build()?,
+        ),
+    };
+    let mut enrichment_engine = enrichment_engine
+        .with_policy_options(config.policy_options.clone())
+        .with_fas_map_extensions(&config.fas_map_extensions)?;
+    if let Some(progress) = &config.progress {
+        enrichment_engine = enrichment_engine.with_progress(Arc::clone(progress));
+    }
+    if let Some(cancellation) = &config.cancellation {
+        enrichment_engine = enrichment_engine.with_cancellation(cancellation.clone());
+    }
 
     // Run the complete enrichment pipeline
     let enriched_results = enrichment_engine
@@ -82,7 +118,8 @@ pub async fn generate_policies(config: &GeneratePolicyConfig) -> Result<Generate
         &config.aws_context.region,
         &config.aws_context.account,
         merger_config,
-    );
+    )
+    .with_resource_bindings(config.policy_options.resource_bindings.clone());
 
     // Generate IAM policies from enriched method calls
     debug!(
@@ -111,10 +148,19 @@ pub async fn generate_policies(config: &GeneratePolicyConfig) -> Result<Generate
         final_policies = policy_engine
             .merge_policies(&final_policies)
             .context("Failed to merge IAM policies")?;
+        policy_engine
+            .annotate_findings(&mut final_policies, &enriched_results)
+            .context("Failed to compute policy findings")?;
+    }
+    if let Some(progress) = &config.progress {
+        progress.report(Progress::PoliciesMerged {
+            policies: final_policies.len(),
+        });
     }
 
     Ok(GeneratePoliciesResult {
         policies: final_policies,
         explanations,
+        diagnostics,
     })
 }
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+
+use crate::{
+    api::{
+        generate_policies,
+        model::{
+            GeneratePoliciesResult, GeneratePolicyConfig, GenerateWorkloadPoliciesResult,
+            WorkloadPoliciesResult,
+        },
+    },
+    WorkloadManifest,
+};
+
+/// Generate policies for each workload of a repository
+///
+/// The source files of `config` are attributed to the workloads of `manifest`, including the
+/// shared code each workload imports, and a separate set of policies is generated per workload.
+pub async fn generate_workload_policies(
+    config: &GeneratePolicyConfig,
+    manifest: &WorkloadManifest,
+) -> Result<GenerateWorkloadPoliciesResult> {
+    let assignment = manifest
+        .assign(&config.extract_sdk_calls_config.source_files)
+        .context("Failed to attribute source files to workloads")?;
+
+    if !assignment.unassigned_files.is_empty() {
+        warn!(
+            "{} source file(s) do not belong to any workload: {:?}",
+            assignment.unassigned_files.len(),
+            assignment.unassigned_files
+        );
+    }
+
+    let mut workloads = BTreeMap::new();
+    for (name, source_files) in assignment.workloads {
+        debug!(
+            "Generating policies for workload '{}' from {} source file(s)",
+            name,
+            source_files.len()
+        );
+
+        let result = if source_files.is_empty() {
+            GeneratePoliciesResult {
+                policies: vec![],
+                explanations: None,
+                diagnostics: vec![],
+            }
+        } else {
+            let mut workload_config = config.clone();
+            workload_config
+                .extract_sdk_calls_config
+                .source_files
+                .clone_from(&source_files);
+            generate_policies(&workload_config)
+                .await
+                .with_context(|| format!("Failed to generate policies for workload '{}'", name))?
+        };
+
+        workloads.insert(
+            name,
+            WorkloadPoliciesResult {
+                source_files,
+                result,
+            },
+        );
+    }
+
+    Ok(GenerateWorkloadPoliciesResult {
+        workloads,
+        unassigned_files: assignment.unassigned_files,
+    })
+}
@@ -1,10 +1,32 @@
 //! IAM Policy Autopilot Core API Interface
 
+mod check_policy_against_code;
+mod derive_sdk_wrappers;
+mod download_service_reference_snapshot;
 mod extract_sdk_calls;
+mod generate_function_policies;
 mod generate_policies;
+mod generate_workload_policies;
 mod get_submodule_version;
+mod service_metadata;
+mod service_reference_cache;
+mod session;
+mod validate_policies;
+pub use check_policy_against_code::check_policy_against_code;
+pub use derive_sdk_wrappers::derive_sdk_wrappers;
+pub use download_service_reference_snapshot::download_service_reference_snapshot;
 pub use extract_sdk_calls::extract_sdk_calls;
+pub use generate_function_policies::generate_function_policies;
 pub use generate_policies::generate_policies;
+pub use generate_workload_policies::generate_workload_policies;
 pub use get_submodule_version::{get_boto3_version_info, get_botocore_version_info};
+pub use service_metadata::{
+    describe_service, get_operation_fas_map, list_operation_fas_maps, list_sdk_services,
+};
+pub use service_reference_cache::{
+    prune_service_reference_cache, service_reference_cache_status, warm_service_reference_cache,
+};
+pub use session::PolicyGenerationSession;
+pub use validate_policies::validate_policies;
 mod common;
 pub mod model;
@@ -1,8 +1,19 @@
 //! Defined model for API
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-use crate::{enrichment::Explanations, policy_generation::PolicyWithMetadata};
+use crate::{
+    api::PolicyGenerationSession,
+    enrichment::{AccessLevel, Explanations},
+    extraction::{Diagnostic, EntryPoint, EntryPoints},
+    policy_generation::{
+        FindingType, PolicyComparison, PolicyKind, PolicyWithMetadata, ValidationFinding,
+    },
+    CancellationToken, ProgressReporter,
+};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Configuration for generate_policies API
 #[derive(Debug, Clone)]
@@ -19,6 +30,222 @@ pub struct GeneratePolicyConfig {
     pub disable_file_system_cache: bool,
     /// Generate explanations for why actions were added
     pub generate_explanations: bool,
+    /// Options controlling how resources are collapsed into wildcards
+    pub policy_options: PolicyGenerationOptions,
+    /// Where service reference data is loaded from
+    pub service_reference: ServiceReferenceSource,
+    /// Session to reuse parsed source files and service references of previous generations
+    pub session: Option<PolicyGenerationSession>,
+    /// User-supplied operation FAS map files, merged with the embedded FAS maps
+    pub fas_map_extensions: Vec<PathBuf>,
+    /// Receives the progress of the generation
+    pub progress: Option<Arc<dyn ProgressReporter>>,
+    /// Cancels the generation, aborting pending parses and service reference fetches
+    pub cancellation: Option<CancellationToken>,
+}
+
+/// Where service reference data is loaded from
+///
+/// By default, service references are fetched from the service reference endpoint and cached.
+/// A snapshot created with `download_service_reference_snapshot` is used instead when given,
+/// and offline mode never touches the network.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceReferenceSource {
+    /// Snapshot directory or bundle file to load service references from
+    pub snapshot: Option<PathBuf>,
+    /// Never fetch service references from the network
+    ///
+    /// Services must be in the snapshot, or in the file system cache if there is no snapshot.
+    pub offline: bool,
+    /// URL of the mapping index of an internal mirror of the service reference endpoint
+    pub endpoint: Option<String>,
+    /// PEM file with additional CA certificates to trust, e.g. for an internal mirror
+    pub ca_certificate: Option<PathBuf>,
+    /// Proxy for all service reference requests
+    pub proxy: Option<String>,
+}
+
+/// Configuration for service_reference_cache_status API
+#[derive(Debug, Clone, Default)]
+pub struct ServiceReferenceCacheConfig {
+    /// Cache directory, or the user cache directory if `None`
+    pub cache_directory: Option<PathBuf>,
+}
+
+/// Configuration for prune_service_reference_cache API
+#[derive(Debug, Clone, Default)]
+pub struct PruneServiceReferenceCacheConfig {
+    /// Cache directory, or the user cache directory if `None`
+    pub cache_directory: Option<PathBuf>,
+    /// Remove all entries instead of only unusable ones
+    pub all: bool,
+}
+
+/// Configuration for warm_service_reference_cache API
+#[derive(Debug, Clone, Default)]
+pub struct WarmServiceReferenceCacheConfig {
+    /// Cache directory, or the user cache directory if `None`
+    pub cache_directory: Option<PathBuf>,
+    /// Services to cache, or all services of the mapping index if `None`
+    pub services: Option<Vec<String>>,
+    /// Endpoint, CA certificate and proxy to fetch service references with
+    ///
+    /// The snapshot and offline settings are ignored.
+    pub service_reference: ServiceReferenceSource,
+}
+
+/// State of a service reference cache entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServiceReferenceCacheEntryState {
+    /// Used as is
+    Fresh,
+    /// Revalidated with the endpoint before it is used
+    Stale,
+    /// Fetched with another version of the mapping index, revalidated before it is used
+    Outdated,
+    /// Failed the checksum, or a leftover temporary file; never used
+    Corrupt,
+}
+
+/// A file of the service reference cache
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceReferenceCacheEntryStatus {
+    /// Service name, or the file name for corrupt files
+    pub name: String,
+    /// Size of the file
+    pub size_bytes: u64,
+    /// When the entry was fetched or last revalidated, in seconds since the Unix epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<u64>,
+    /// Version of the mapping index the entry was fetched with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapping_version: Option<String>,
+    /// State of the entry
+    pub state: ServiceReferenceCacheEntryState,
+}
+
+/// Status of the service reference cache, returned by service_reference_cache_status API
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceReferenceCacheStatus {
+    /// Cache directory
+    pub directory: PathBuf,
+    /// The cached mapping index, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<ServiceReferenceCacheEntryStatus>,
+    /// Cached service references and unusable files, sorted by name
+    pub entries: Vec<ServiceReferenceCacheEntryStatus>,
+    /// Total size of all files
+    pub total_size_bytes: u64,
+}
+
+/// Result of prune_service_reference_cache API
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PruneServiceReferenceCacheResult {
+    /// Cache directory
+    pub directory: PathBuf,
+    /// Names of the removed entries
+    pub removed: Vec<String>,
+    /// Total size of the removed files
+    pub freed_bytes: u64,
+}
+
+/// Result of warm_service_reference_cache API
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WarmServiceReferenceCacheResult {
+    /// Cache directory
+    pub directory: PathBuf,
+    /// Services now in the cache, sorted by name
+    pub services: Vec<String>,
+}
+
+/// Configuration for download_service_reference_snapshot API
+#[derive(Debug, Clone)]
+pub struct DownloadServiceReferenceSnapshotConfig {
+    /// Snapshot to write: a bundle file if the path ends in `.json`, a directory otherwise
+    pub output: PathBuf,
+    /// Services to include, or all services of the mapping index if `None`
+    pub services: Option<Vec<String>>,
+}
+
+/// Checksum manifest of a service reference snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceReferenceSnapshotManifest {
+    /// Version of IAM Policy Autopilot that created the snapshot
+    pub tool_version: String,
+    /// Service reference endpoint the snapshot was downloaded from
+    pub source: String,
+    /// Creation time, in seconds since the Unix epoch
+    pub created_at: u64,
+    /// SHA-256 of the mapping index
+    pub mapping_hash: String,
+    /// SHA-256 of the service reference JSON of each service
+    pub services: BTreeMap<String, String>,
+    /// SHA-256 over all service names and checksums, identifying the snapshot data
+    pub data_hash: String,
+}
+
+/// AWS service of the embedded SDK models
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct SdkService {
+    /// SDK service name, e.g. `dynamodb`
+    pub name: String,
+    /// API version of the embedded service model
+    pub api_version: String,
+}
+
+/// Configuration for describe_service API
+#[derive(Debug, Clone, Default)]
+pub struct DescribeServiceConfig {
+    /// IAM service prefix, e.g. `s3`
+    pub service: String,
+    /// Where service reference data is loaded from
+    pub service_reference: ServiceReferenceSource,
+}
+
+/// Actions, resource types and condition keys of an AWS service, from its service reference
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceDescription {
+    /// IAM service prefix
+    pub name: String,
+    /// Actions of the service, sorted by name
+    pub actions: Vec<ServiceAction>,
+    /// Resource types of the service, sorted by name
+    pub resource_types: Vec<ServiceResourceType>,
+}
+
+/// IAM action of a service
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceAction {
+    /// Action name, without the service prefix
+    pub name: String,
+    /// Access level of the action, if annotated in the service reference
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_level: Option<AccessLevel>,
+    /// Resource types the action can be scoped to
+    pub resource_types: Vec<String>,
+    /// Condition keys supported by the action
+    pub condition_keys: Vec<String>,
+}
+
+/// Resource type of a service
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceResourceType {
+    /// Resource type name
+    pub name: String,
+    /// ARN formats of the resource type
+    pub arn_formats: Vec<String>,
+    /// Condition keys supported for the resource type
+    pub condition_keys: Vec<String>,
 }
 
 /// Result of policy generation including policies, action mappings, and explanations
@@ -30,6 +257,184 @@ pub struct GeneratePoliciesResult {
     /// Explanations for why actions were added (if requested)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub explanations: Option<Explanations>,
+    /// Diagnostics of the extraction the policies were generated from
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Result of policy generation for the workloads of a repository
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GenerateWorkloadPoliciesResult {
+    /// Policies of each workload, keyed by workload name
+    pub workloads: BTreeMap<String, WorkloadPoliciesResult>,
+    /// Source files that are not attributed to any workload, and not covered by any policy
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unassigned_files: Vec<PathBuf>,
+}
+
+/// Policies generated for a single workload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WorkloadPoliciesResult {
+    /// Source files attributed to the workload, including shared code it imports
+    pub source_files: Vec<PathBuf>,
+    /// Policies generated from the workload's source files
+    #[serde(flatten)]
+    pub result: GeneratePoliciesResult,
+}
+
+/// Result of policy generation for the Lambda functions of a repository
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GenerateFunctionPoliciesResult {
+    /// Policies of each function, keyed by function name
+    pub functions: BTreeMap<String, FunctionPoliciesResult>,
+    /// Functions whose handler is not among the analyzed source files, and have no policy
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unresolved_functions: Vec<String>,
+}
+
+/// Policies generated for a single Lambda function
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FunctionPoliciesResult {
+    /// Handler string of the function
+    pub handler: String,
+    /// The handler's function in the source files
+    pub entry_point: EntryPoint,
+    /// Template, configuration or CDK source file declaring the function
+    pub declared_in: PathBuf,
+    /// Policies generated from the SDK calls reachable from the handler
+    #[serde(flatten)]
+    pub result: GeneratePoliciesResult,
+}
+
+/// Result of checking a policy document against the policies generated for source code
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CheckPolicyResult {
+    /// Differences between the policy document and the generated policies
+    #[serde(flatten)]
+    pub comparison: PolicyComparison,
+    /// Diagnostics of the extraction the policies were generated from
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Configuration for validate_policies API
+#[derive(Debug, Clone)]
+pub struct ValidatePoliciesConfig {
+    /// IAM policy documents to validate, as JSON text
+    pub policies: Vec<String>,
+    /// Whether the policies are attached as managed or inline policies
+    pub policy_kind: PolicyKind,
+    /// Disable file system caching for service references
+    pub disable_file_system_cache: bool,
+    /// Where service reference data is loaded from
+    pub service_reference: ServiceReferenceSource,
+}
+
+/// Result of policy validation
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ValidatePoliciesResult {
+    /// Findings for all validated policies
+    pub findings: Vec<ValidationFinding>,
+}
+
+impl ValidatePoliciesResult {
+    /// Whether any finding is an error, i.e. IAM would reject the policies
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.finding_type == FindingType::Error)
+    }
+}
+
+/// Default number of resource types from which the resources of an action are collapsed
+pub const DEFAULT_RESOURCE_CUTOFF: usize = 5;
+
+/// How the resources of an action are collapsed once the resource cutoff is reached
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum WildcardMode {
+    /// Replace all resources with `*`
+    #[default]
+    Wildcard,
+    /// Replace the resources with one wildcard ARN per service, region and account,
+    /// e.g. `arn:aws:ec2:us-east-1:123456789012:*`
+    ServiceWildcard,
+    /// Never collapse, always list the ARN of every resource type
+    Never,
+}
+
+/// Override of the resource cutoff for a service or an action
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ResourceCutoffOverride {
+    /// Number of resource types from which resources are collapsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cutoff: Option<usize>,
+    /// How resources are collapsed once the cutoff is reached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wildcard_mode: Option<WildcardMode>,
+}
+
+/// Options controlling how resources of generated statements are collapsed into wildcards
+///
+/// Settings are resolved per action: an action override takes precedence over a service
+/// override, which takes precedence over the global settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct PolicyGenerationOptions {
+    /// Number of resource types from which the resources of an action are collapsed
+    pub resource_cutoff: usize,
+    /// How resources are collapsed once the cutoff is reached
+    pub wildcard_mode: WildcardMode,
+    /// Overrides keyed by service prefix, e.g. `s3`
+    pub service_overrides: BTreeMap<String, ResourceCutoffOverride>,
+    /// Overrides keyed by action, e.g. `s3:GetObject`
+    pub action_overrides: BTreeMap<String, ResourceCutoffOverride>,
+    /// Values for resource placeholders of ARNs, e.g. `BucketName` = `my-bucket`.
+    /// Placeholders without a value become wildcards.
+    pub resource_bindings: BTreeMap<String, String>,
+}
+
+impl Default for PolicyGenerationOptions {
+    fn default() -> Self {
+        Self {
+            resource_cutoff: DEFAULT_RESOURCE_CUTOFF,
+            wildcard_mode: WildcardMode::default(),
+            service_overrides: BTreeMap::new(),
+            action_overrides: BTreeMap::new(),
+            resource_bindings: BTreeMap::new(),
+        }
+    }
+}
+
+impl PolicyGenerationOptions {
+    /// Resolve the cutoff and wildcard mode for an action such as `s3:GetObject`
+    pub fn resource_cutoff_for(&self, action: &str) -> (usize, WildcardMode) {
+        let service = action.split(':').next().unwrap_or(action);
+        let overrides = [
+            self.action_overrides.get(action),
+            self.service_overrides.get(service),
+        ];
+
+        let cutoff = overrides
+            .iter()
+            .flatten()
+            .find_map(|o| o.cutoff)
+            .unwrap_or(self.resource_cutoff);
+        let wildcard_mode = overrides
+            .iter()
+            .flatten()
+            .find_map(|o| o.wildcard_mode)
+            .unwrap_or(self.wildcard_mode);
+
+        (cutoff, wildcard_mode)
+    }
 }
 
 /// Service hints for filtering SDK method calls
@@ -48,6 +453,23 @@ pub struct ExtractSdkCallsConfig {
     pub language: Option<String>,
     /// Optional service hints for filtering
     pub service_hints: Option<ServiceHints>,
+    /// SDK wrapper mapping files declaring in-house wrappers of SDK calls
+    pub sdk_wrappers: Vec<PathBuf>,
+    /// Entry points SDK calls must be reachable from, or empty to extract all SDK calls
+    pub entry_points: EntryPoints,
+    /// Extract SDK calls of test files and files that stub the SDK, which are excluded by default
+    pub include_tests: bool,
+}
+
+/// Configuration for derive_sdk_wrappers Api
+#[derive(Debug, Clone)]
+pub struct DeriveSdkWrappersConfig {
+    /// Root directory of the library to analyze
+    pub library_root: PathBuf,
+    /// Override programming language detection
+    pub language: Option<String>,
+    /// SDK wrapper mapping files of the libraries the analyzed library uses
+    pub sdk_wrappers: Vec<PathBuf>,
 }
 
 // Todo: Find a better place for this or refactor rest of the code to use model
@@ -139,4 +561,52 @@ mod tests {
         let ctx = AwsContext::new("*".to_string(), "*".to_string());
         assert_eq!(ctx.partition, "aws");
     }
+
+    #[test]
+    fn test_resource_cutoff_for_resolves_overrides() {
+        let mut options = PolicyGenerationOptions::default();
+        options.service_overrides.insert(
+            "s3".to_string(),
+            ResourceCutoffOverride {
+                cutoff: Some(10),
+                wildcard_mode: Some(WildcardMode::ServiceWildcard),
+            },
+        );
+        options.action_overrides.insert(
+            "s3:GetObject".to_string(),
+            ResourceCutoffOverride {
+                cutoff: None,
+                wildcard_mode: Some(WildcardMode::Never),
+            },
+        );
+
+        assert_eq!(
+            options.resource_cutoff_for("ec2:RunInstances"),
+            (DEFAULT_RESOURCE_CUTOFF, WildcardMode::Wildcard)
+        );
+        assert_eq!(
+            options.resource_cutoff_for("s3:PutObject"),
+            (10, WildcardMode::ServiceWildcard)
+        );
+        // Action overrides only replace the settings they define
+        assert_eq!(
+            options.resource_cutoff_for("s3:GetObject"),
+            (10, WildcardMode::Never)
+        );
+    }
+
+    #[test]
+    fn test_policy_generation_options_deserialization() {
+        let options: PolicyGenerationOptions = serde_json::from_str(
+            r#"{"WildcardMode": "service-wildcard", "ActionOverrides": {"iam:PassRole": {"WildcardMode": "never"}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(options.resource_cutoff, DEFAULT_RESOURCE_CUTOFF);
+        assert_eq!(options.wildcard_mode, WildcardMode::ServiceWildcard);
+        assert_eq!(
+            options.resource_cutoff_for("iam:PassRole"),
+            (DEFAULT_RESOURCE_CUTOFF, WildcardMode::Never)
+        );
+    }
 }
@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    api::model::{
+        DescribeServiceConfig, SdkService, ServiceAction, ServiceDescription, ServiceResourceType,
+    },
+    enrichment::{
+        operation_fas_map::{
+            embedded_operation_fas_map_document, embedded_operation_fas_map_services,
+        },
+        RemoteServiceReferenceLoader, ServiceReferenceCache,
+    },
+    extraction::sdk_model::ServiceDiscovery,
+};
+
+/// List the AWS services of the embedded SDK models, sorted by name
+pub fn list_sdk_services() -> Result<Vec<SdkService>> {
+    let services = ServiceDiscovery::discover_services().context("Failed to discover services")?;
+    Ok(services
+        .into_iter()
+        .map(|service| SdkService {
+            name: service.name,
+            api_version: service.api_version,
+        })
+        .collect())
+}
+
+/// Describe the actions, resource types and condition keys of a service
+///
+/// Returns `None` if the service has no service reference.
+pub async fn describe_service(
+    config: &DescribeServiceConfig,
+) -> Result<Option<ServiceDescription>> {
+    let loader = RemoteServiceReferenceLoader::builder()
+        .with_source(&config.service_reference)
+        .build()?;
+    service_description(
+        &ServiceReferenceCache::new(Arc::new(loader)),
+        &config.service,
+    )
+    .await
+}
+
+async fn service_description(
+    cache: &ServiceReferenceCache,
+    service: &str,
+) -> Result<Option<ServiceDescription>> {
+    let Some(service_reference) = cache
+        .load(service)
+        .await
+        .with_context(|| format!("Failed to load service reference of {}", service))?
+    else {
+        return Ok(None);
+    };
+
+    let mut actions: Vec<ServiceAction> = service_reference
+        .actions
+        .into_values()
+        .map(|action| ServiceAction {
+            name: action.name,
+            access_level: action.access_level,
+            resource_types: action.resources,
+            condition_keys: action.condition_keys,
+        })
+        .collect();
+    actions.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut condition_keys = service_reference.resource_condition_keys;
+    let mut resource_types: Vec<ServiceResourceType> = service_reference
+        .resources
+        .into_iter()
+        .map(|(name, arn_formats)| ServiceResourceType {
+            condition_keys: condition_keys.remove(&name).unwrap_or_default(),
+            name,
+            arn_formats,
+        })
+        .collect();
+    resource_types.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(Some(ServiceDescription {
+        name: service_reference.service_name,
+        actions,
+        resource_types,
+    }))
+}
+
+/// List the services with an embedded operation FAS map, sorted by name
+pub fn list_operation_fas_maps() -> Vec<String> {
+    embedded_operation_fas_map_services()
+}
+
+/// Get the embedded operation FAS map of a service
+///
+/// The map lists, for each operation of the service, the operations it performs on other
+/// services through Forward Access Sessions (FAS). It uses the schema of user-supplied FAS maps.
+/// Returns `None` if the service has no embedded FAS map.
+pub fn get_operation_fas_map(service: &str) -> Result<Option<serde_json::Value>> {
+    embedded_operation_fas_map_document(service)
+        .map(|document| {
+            serde_json::from_str(&document)
+                .with_context(|| format!("Failed to parse operation FAS map of {}", service))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enrichment::InMemoryServiceReferenceLoader;
+    use crate::AccessLevel;
+
+    #[tokio::test]
+    async fn test_service_description() {
+        let loader = InMemoryServiceReferenceLoader::new().with_service_reference(
+            "s3",
+            r#"{
+                "Name": "s3",
+                "Actions": [
+                    {
+                        "Name": "PutObject",
+                        "Resources": [{"Name": "object"}],
+                        "ActionConditionKeys": ["s3:x-amz-acl"],
+                        "Annotations": {"Properties": {"IsWrite": true}}
+                    },
+                    {
+                        "Name": "GetObject",
+                        "Resources": [{"Name": "object"}],
+                        "ActionConditionKeys": []
+                    }
+                ],
+                "Resources": [
+                    {"Name": "object", "ARNFormats": ["arn:${Partition}:s3:::${BucketName}/${ObjectName}"]},
+                    {"Name": "bucket", "ARNFormats": ["arn:${Partition}:s3:::${BucketName}"], "ConditionKeys": ["aws:ResourceTag/${TagKey}"]}
+                ]
+            }"#,
+        );
+        let cache = ServiceReferenceCache::new(Arc::new(loader));
+
+        let description = service_description(&cache, "s3").await.unwrap().unwrap();
+
+        assert_eq!(description.name, "s3");
+        assert_eq!(
+            description.actions,
+            vec![
+                ServiceAction {
+                    name: "GetObject".to_string(),
+                    access_level: None,
+                    resource_types: vec!["object".to_string()],
+                    condition_keys: vec![],
+                },
+                ServiceAction {
+                    name: "PutObject".to_string(),
+                    access_level: Some(AccessLevel::Write),
+                    resource_types: vec!["object".to_string()],
+                    condition_keys: vec!["s3:x-amz-acl".to_string()],
+                },
+            ]
+        );
+        let names: Vec<_> = description
+            .resource_types
+            .iter()
+            .map(|resource_type| resource_type.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["bucket", "object"]);
+        assert_eq!(
+            description.resource_types[0].condition_keys,
+            vec!["aws:ResourceTag/${TagKey}".to_string()]
+        );
+
+        assert!(service_description(&cache, "unknown")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_operation_fas_maps() {
+        let services = list_operation_fas_maps();
+        assert!(services.contains(&"s3".to_string()));
+
+        let fas_map = get_operation_fas_map("s3").unwrap().unwrap();
+        assert_eq!(fas_map["Name"], "s3");
+        assert!(get_operation_fas_map("unknown").unwrap().is_none());
+    }
+}
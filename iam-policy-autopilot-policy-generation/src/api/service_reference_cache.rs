@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use std::time::Instant;
+
+use log::debug;
+
+use crate::{
+    api::model::{
+        PruneServiceReferenceCacheConfig, PruneServiceReferenceCacheResult,
+        ServiceReferenceCacheConfig, ServiceReferenceCacheStatus, ServiceReferenceSource,
+        WarmServiceReferenceCacheConfig, WarmServiceReferenceCacheResult,
+    },
+    enrichment::{service_reference_file_cache::FileCache, RemoteServiceReferenceLoader},
+};
+
+fn file_cache(cache_directory: Option<&std::path::Path>) -> FileCache {
+    cache_directory
+        .map(|directory| FileCache::new(directory.to_path_buf()))
+        .unwrap_or_else(FileCache::default_location)
+}
+
+/// Report the entries of the service reference cache and whether they are fresh
+pub fn service_reference_cache_status(
+    config: &ServiceReferenceCacheConfig,
+) -> Result<ServiceReferenceCacheStatus> {
+    file_cache(config.cache_directory.as_deref())
+        .status()
+        .context("Failed to read service reference cache")
+}
+
+/// Remove corrupt and outdated entries, or all entries, from the service reference cache
+///
+/// Temporary files of writes that may still be in progress in another run are kept.
+pub fn prune_service_reference_cache(
+    config: &PruneServiceReferenceCacheConfig,
+) -> Result<PruneServiceReferenceCacheResult> {
+    file_cache(config.cache_directory.as_deref())
+        .prune(config.all)
+        .context("Failed to prune service reference cache")
+}
+
+/// Fetch service references into the service reference cache
+///
+/// Entries that are still fresh are kept, stale entries are revalidated.
+pub async fn warm_service_reference_cache(
+    config: &WarmServiceReferenceCacheConfig,
+) -> Result<WarmServiceReferenceCacheResult> {
+    let start = Instant::now();
+    let cache = file_cache(config.cache_directory.as_deref());
+
+    let loader = RemoteServiceReferenceLoader::builder()
+        .with_source(&ServiceReferenceSource {
+            snapshot: None,
+            offline: false,
+            ..config.service_reference.clone()
+        })
+        .with_cache_directory(cache.directory())
+        .build()?;
+    let services = loader
+        .warm_cache(config.services.as_deref())
+        .await
+        .context("Failed to warm service reference cache")?;
+
+    debug!(
+        "Cached {} service references in {} in {:?}",
+        services.len(),
+        cache.directory().display(),
+        start.elapsed()
+    );
+
+    Ok(WarmServiceReferenceCacheResult {
+        directory: cache.directory().to_path_buf(),
+        services,
+    })
+}
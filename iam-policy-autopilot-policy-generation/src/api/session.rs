@@ -0,0 +1,95 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use anyhow::Result;
+
+use crate::{
+    api::model::ServiceReferenceSource,
+    enrichment::{RemoteServiceReferenceLoader, ServiceReferenceCache},
+    ExtractionCache,
+};
+
+/// Service references cached in memory, with the settings they are loaded with
+#[derive(Debug)]
+struct SessionServiceReferences {
+    source: ServiceReferenceSource,
+    disable_file_system_cache: bool,
+    cache: Arc<ServiceReferenceCache>,
+}
+
+/// State reused across policy generations, e.g. by the repeated calls of an editing session
+///
+/// Source files are only parsed again when their content changed, and service references stay
+/// loaded in memory as long as they are loaded from the same source. Cloning a session shares
+/// its state.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyGenerationSession {
+    extraction_cache: ExtractionCache,
+    service_references: Arc<Mutex<Option<SessionServiceReferences>>>,
+}
+
+impl PolicyGenerationSession {
+    /// Create a session without any cached state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parsed source files of the session
+    pub fn extraction_cache(&self) -> &ExtractionCache {
+        &self.extraction_cache
+    }
+
+    /// Service references of the session, loaded from `source`
+    ///
+    /// The cached service references are dropped if they were loaded with other settings.
+    pub(crate) fn service_references(
+        &self,
+        source: &ServiceReferenceSource,
+        disable_file_system_cache: bool,
+    ) -> Result<Arc<ServiceReferenceCache>> {
+        let mut service_references = self
+            .service_references
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(cached) = service_references.as_ref().filter(|cached| {
+            cached.source == *source
+                && cached.disable_file_system_cache == disable_file_system_cache
+        }) {
+            return Ok(Arc::clone(&cached.cache));
+        }
+
+        let loader = RemoteServiceReferenceLoader::builder()
+            .with_disable_file_system_cache(disable_file_system_cache)
+            .with_source(source)
+            .build()?;
+        let cache = Arc::new(ServiceReferenceCache::new(Arc::new(loader)));
+        *service_references = Some(SessionServiceReferences {
+            source: source.clone(),
+            disable_file_system_cache,
+            cache: Arc::clone(&cache),
+        });
+        Ok(cache)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_service_references() {
+        let session = PolicyGenerationSession::new();
+        let source = ServiceReferenceSource {
+            offline: true,
+            ..Default::default()
+        };
+
+        let first = session.service_references(&source, true).unwrap();
+        let second = session.clone().service_references(&source, true).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let other = session
+            .service_references(&ServiceReferenceSource::default(), true)
+            .unwrap();
+        assert!(!Arc::ptr_eq(&first, &other));
+    }
+}
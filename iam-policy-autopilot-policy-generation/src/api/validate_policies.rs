@@ -0,0 +1,43 @@
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Instant;
+
+use log::debug;
+
+use crate::{
+    api::model::{ValidatePoliciesConfig, ValidatePoliciesResult},
+    enrichment::{RemoteServiceReferenceLoader, ServiceReferenceCache},
+    policy_generation::validation,
+};
+
+/// Validate IAM policy documents offline
+///
+/// Checks the IAM policy grammar, quotas, and action names, resource types and condition
+/// keys against the (cached) service reference data.
+pub async fn validate_policies(config: &ValidatePoliciesConfig) -> Result<ValidatePoliciesResult> {
+    let start = Instant::now();
+    let documents = config
+        .policies
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    let loader = RemoteServiceReferenceLoader::builder()
+        .with_disable_file_system_cache(config.disable_file_system_cache)
+        .with_source(&config.service_reference)
+        .build()?;
+    let loader = ServiceReferenceCache::new(Arc::new(loader));
+    let prefixes = validation::collect_service_prefixes(&documents);
+    debug!("Loading service references for {:?}", prefixes);
+    let services = validation::load_service_references(&loader, prefixes).await;
+
+    let findings = validation::validate_documents(&documents, config.policy_kind, &services);
+    debug!(
+        "Validated {} policies in {:?}, found {} findings",
+        documents.len(),
+        start.elapsed(),
+        findings.len()
+    );
+
+    Ok(ValidatePoliciesResult { findings })
+}
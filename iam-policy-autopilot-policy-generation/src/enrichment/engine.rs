@@ -4,16 +4,29 @@
 //! 3-stage enrichment pipeline: input validation, OperationAction maps enrichment, and Service Reference enrichment
 //! with resource matching.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use futures::{StreamExt, TryStreamExt};
+use tokio_util::sync::CancellationToken;
+
 use super::EnrichedSdkMethodCall;
-use crate::enrichment::operation_fas_map::OperationFasMaps;
-use crate::enrichment::{load_operation_fas_map, ResourceMatcher, ServiceReferenceLoader};
+use crate::api::model::PolicyGenerationOptions;
+use crate::enrichment::operation_fas_map::{
+    load_operation_fas_map_extensions, OperationFasMap, OperationFasMaps,
+};
+use crate::enrichment::{
+    load_operation_fas_map, ResourceMatcher, ServiceReferenceCache, ServiceReferenceLoader,
+};
 use crate::errors::{ExtractorError, Result};
+use crate::progress::{self, Progress, ProgressReporter};
 use crate::service_configuration::{self, ServiceConfiguration};
 use crate::{SdkMethodCall, SdkType};
 
+/// Maximum number of service references loaded concurrently
+const SERVICE_REFERENCE_CONCURRENCY: usize = 16;
+
 /// Core enrichment engine that orchestrates the 3-stage enrichment pipeline
 ///
 /// The MethodEnrichmentEngine coordinates all enrichment components to transform
@@ -21,19 +34,76 @@ use crate::{SdkMethodCall, SdkType};
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct Engine {
-    /// Service Reference loader
-    service_reference_loader: ServiceReferenceLoader,
+    /// Service references, cached in memory
+    service_references: Arc<ServiceReferenceCache>,
+    /// Options controlling how resources are collapsed into wildcards
+    policy_options: PolicyGenerationOptions,
+    /// User-supplied FAS maps, merged with the embedded ones
+    fas_map_extensions: OperationFasMaps,
+    /// Receives a report for each loaded service reference
+    progress: Option<Arc<dyn ProgressReporter>>,
+    /// Stops the enrichment and the outstanding service reference fetches when cancelled
+    cancellation: Option<CancellationToken>,
 }
 
 impl Engine {
-    /// Create a new MethodEnrichmentEngine with custom base paths
+    /// Create a new MethodEnrichmentEngine loading service references with the given loader
+    ///
+    /// Use a [`RemoteServiceReferenceLoader`](crate::RemoteServiceReferenceLoader) for the service
+    /// reference endpoint or a mirror of it, or any other [`ServiceReferenceLoader`] implementation,
+    /// e.g. an [`InMemoryServiceReferenceLoader`](crate::InMemoryServiceReferenceLoader) for tests.
+    pub fn new(service_reference_loader: impl ServiceReferenceLoader + 'static) -> Self {
+        Self::with_service_reference_cache(Arc::new(ServiceReferenceCache::new(Arc::new(
+            service_reference_loader,
+        ))))
+    }
+
+    /// Create a new MethodEnrichmentEngine sharing service references cached in memory
+    pub(crate) fn with_service_reference_cache(
+        service_references: Arc<ServiceReferenceCache>,
+    ) -> Self {
+        Self {
+            service_references,
+            policy_options: PolicyGenerationOptions::default(),
+            fas_map_extensions: OperationFasMaps::new(),
+            progress: None,
+            cancellation: None,
+        }
+    }
+
+    /// Use the given options to collapse resources of enriched actions into wildcards
+    #[must_use]
+    pub fn with_policy_options(mut self, policy_options: PolicyGenerationOptions) -> Self {
+        self.policy_options = policy_options;
+        self
+    }
+
+    /// Extend the embedded operation FAS maps with the FAS map files at `paths`
+    ///
+    /// The files use the schema of the embedded maps (`resources/config/operation-fas-maps`).
+    /// Their FAS operations are added to the embedded ones and explained as user-supplied.
     ///
-    /// Allows customization of the base paths for OperationAction maps and Service Reference files,
-    /// useful for testing or alternative configurations.
-    pub fn new(disable_file_system_cache: bool) -> Result<Self> {
-        Ok(Self {
-            service_reference_loader: ServiceReferenceLoader::new(disable_file_system_cache)?,
-        })
+    /// # Errors
+    /// Returns an error if a file cannot be read or is not a valid FAS map
+    pub fn with_fas_map_extensions(mut self, paths: &[PathBuf]) -> Result<Self> {
+        self.fas_map_extensions = load_operation_fas_map_extensions(paths)?;
+        Ok(self)
+    }
+
+    /// Report each loaded service reference to `progress`
+    #[must_use]
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Stop the enrichment when `cancellation` is cancelled
+    ///
+    /// Outstanding service reference fetches are dropped and the enrichment fails.
+    #[must_use]
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
     }
 
     /// This is the main entry point for the enrichment process.
@@ -48,18 +118,70 @@ impl Engine {
 
         let service_cfg = service_configuration::load_service_configuration()?;
 
+        let cancellation = self.cancellation.clone();
+        tokio::select! {
+            biased;
+            () = progress::cancelled(cancellation.as_ref()) => return Err(ExtractorError::Cancelled),
+            loaded = self.load_service_references(&unique_services, &service_cfg) => loaded?,
+        }
+
         let fas_maps = self
             .load_fas_maps_for_services(&unique_services, &service_cfg)
             .await?;
 
-        let resource_matcher = ResourceMatcher::new(service_cfg, fas_maps, sdk);
-        let enriched_calls = self
-            .enrich_all_methods(extracted_methods, &resource_matcher)
-            .await?;
+        let resource_matcher = ResourceMatcher::new(service_cfg, fas_maps, sdk)
+            .with_options(self.policy_options.clone());
+        // Services only reached through FAS are loaded while enriching
+        let enriched_calls = tokio::select! {
+            biased;
+            () = progress::cancelled(cancellation.as_ref()) => return Err(ExtractorError::Cancelled),
+            enriched_calls = self.enrich_all_methods(extracted_methods, &resource_matcher) => {
+                enriched_calls?
+            }
+        };
 
         Ok(enriched_calls)
     }
 
+    /// Load the service references of the services of the extracted methods concurrently
+    ///
+    /// Dropping the returned future drops the outstanding fetches.
+    async fn load_service_references(
+        &self,
+        services: &[String],
+        service_cfg: &ServiceConfiguration,
+    ) -> Result<()> {
+        let services: BTreeSet<String> = services
+            .iter()
+            .map(|service| {
+                service_cfg
+                    .rename_service_service_reference(service)
+                    .into_owned()
+            })
+            .collect();
+        let total = services.len();
+
+        let mut loads = futures::stream::iter(services)
+            .map(|service| {
+                let service_references = Arc::clone(&self.service_references);
+                async move { service_references.load(&service).await.map(|_| service) }
+            })
+            .buffer_unordered(SERVICE_REFERENCE_CONCURRENCY);
+
+        let mut loaded = 0;
+        while let Some(service) = loads.try_next().await? {
+            loaded += 1;
+            if let Some(progress) = &self.progress {
+                progress.report(Progress::ServiceLoaded {
+                    service,
+                    loaded,
+                    total,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Extract unique service names from ExtractedMethods
     pub(crate) fn get_unique_services(&self, extracted_methods: &[SdkMethodCall]) -> Vec<String> {
         let mut services = HashSet::new();
@@ -96,7 +218,11 @@ impl Engine {
                 );
             }
 
-            match load_operation_fas_map(renamed_service.as_ref()) {
+            let operation_fas_map = self.merge_fas_map_extension(
+                renamed_service.as_ref(),
+                load_operation_fas_map(renamed_service.as_ref()),
+            );
+            match operation_fas_map {
                 None => {
                     log::debug!(
                         "No operation FAS map found for service '{}' (expected)",
@@ -117,9 +243,40 @@ impl Engine {
             }
         }
 
+        // Extensions may add FAS operations to services that are only reached through FAS
+        for service in self.fas_map_extensions.keys() {
+            if fas.contains_key(service) {
+                continue;
+            }
+            if let Some(operation_fas_map) =
+                self.merge_fas_map_extension(service, load_operation_fas_map(service))
+            {
+                fas.insert(service.clone(), operation_fas_map);
+            }
+        }
+
         Ok(fas)
     }
 
+    /// Add the user-supplied FAS operations of `service` to its embedded FAS map
+    fn merge_fas_map_extension(
+        &self,
+        service: &str,
+        embedded: Option<Arc<OperationFasMap>>,
+    ) -> Option<Arc<OperationFasMap>> {
+        let Some(extension) = self.fas_map_extensions.get(service) else {
+            return embedded;
+        };
+        match embedded {
+            None => Some(Arc::clone(extension)),
+            Some(embedded) => {
+                let mut merged = (*embedded).clone();
+                merged.extend(extension);
+                Some(Arc::new(merged))
+            }
+        }
+    }
+
     /// Enrich all method calls using loaded OperationAction maps, service
     /// references and the FAS model.
     async fn enrich_all_methods<'a>(
@@ -131,7 +288,7 @@ impl Engine {
 
         for method in methods {
             match resource_matcher
-                .enrich_method_call(method, &self.service_reference_loader)
+                .enrich_method_call(method, &self.service_references)
                 .await
             {
                 Ok(mut method_calls) => {
@@ -154,9 +311,14 @@ impl Engine {
 
 #[cfg(test)]
 mod tests {
-    use crate::{extraction::sdk_model::ServiceDiscovery, Language};
+    use crate::{
+        extraction::sdk_model::ServiceDiscovery, InMemoryServiceReferenceLoader, Language,
+        RemoteServiceReferenceLoader,
+    };
 
     use super::*;
+    use crate::progress::tests::RecordingReporter;
+    use std::sync::PoisonError;
 
     fn create_test_extracted_methods() -> Vec<SdkMethodCall> {
         vec![SdkMethodCall {
@@ -168,7 +330,7 @@ mod tests {
 
     #[test]
     fn test_get_unique_services() {
-        let engine = Engine::new(false).unwrap();
+        let engine = Engine::new(InMemoryServiceReferenceLoader::new());
 
         let extracted_methods = create_test_extracted_methods();
         let services = engine.get_unique_services(&extracted_methods);
@@ -177,6 +339,160 @@ mod tests {
         assert!(services.contains(&"s3".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_enrich_methods_with_in_memory_loader() {
+        let loader = InMemoryServiceReferenceLoader::new().with_service_reference(
+            "s3",
+            serde_json::json!({
+                "Name": "s3",
+                "Actions": [{"Name": "GetObject", "Resources": [{"Name": "object"}]}],
+                "Resources": [{
+                    "Name": "object",
+                    "ARNFormats": ["arn:${Partition}:s3:::${BucketName}/${ObjectName}"]
+                }]
+            })
+            .to_string(),
+        );
+        let mut engine = Engine::new(loader);
+
+        let extracted_methods = vec![SdkMethodCall {
+            name: "GetObject".to_string(),
+            possible_services: vec!["s3".to_string()],
+            metadata: None,
+        }];
+        let enriched = engine
+            .enrich_methods(&extracted_methods, SdkType::Other)
+            .await
+            .unwrap();
+
+        assert_eq!(enriched.len(), 1);
+        assert_eq!(enriched[0].actions[0].name, "s3:GetObject");
+        assert_eq!(
+            enriched[0].actions[0].resources[0].arn_patterns,
+            Some(vec![
+                "arn:${Partition}:s3:::${BucketName}/${ObjectName}".to_string()
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enrich_methods_reports_loaded_services() {
+        let loader = InMemoryServiceReferenceLoader::new().with_service_reference(
+            "s3",
+            serde_json::json!({"Name": "s3", "Actions": [{"Name": "GetObject"}]}).to_string(),
+        );
+        let reporter = Arc::new(RecordingReporter::default());
+        let mut engine = Engine::new(loader).with_progress(reporter.clone());
+
+        engine
+            .enrich_methods(&create_test_extracted_methods(), SdkType::Other)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *reporter.0.lock().unwrap_or_else(PoisonError::into_inner),
+            vec![Progress::ServiceLoaded {
+                service: "s3".to_string(),
+                loaded: 1,
+                total: 1
+            }]
+        );
+    }
+
+    /// Loader whose fetches never complete
+    #[derive(Debug)]
+    struct PendingServiceReferenceLoader;
+
+    #[async_trait::async_trait]
+    impl ServiceReferenceLoader for PendingServiceReferenceLoader {
+        async fn load_service_reference(
+            &self,
+            _service_name: &str,
+        ) -> anyhow::Result<Option<String>> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enrich_methods_cancellation_stops_fetches() {
+        let cancellation = CancellationToken::new();
+        let mut engine =
+            Engine::new(PendingServiceReferenceLoader).with_cancellation(cancellation.clone());
+
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            cancellation.cancel();
+        });
+        let error = engine
+            .enrich_methods(&create_test_extracted_methods(), SdkType::Other)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, ExtractorError::Cancelled));
+        canceller.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_enrich_methods_with_fas_map_extension() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let fas_map = dir.path().join("lambda.json");
+        std::fs::write(
+            &fas_map,
+            serde_json::json!({
+                "Name": "lambda",
+                "Operations": [{
+                    "Name": "CreateFunction",
+                    "FasOperations": [
+                        {"Operation": "CreateNetworkInterface", "Service": "ec2", "Context": {}}
+                    ]
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let loader = InMemoryServiceReferenceLoader::new()
+            .with_service_reference(
+                "lambda",
+                serde_json::json!({
+                    "Name": "lambda",
+                    "Actions": [{"Name": "CreateFunction"}]
+                })
+                .to_string(),
+            )
+            .with_service_reference(
+                "ec2",
+                serde_json::json!({
+                    "Name": "ec2",
+                    "Actions": [{"Name": "CreateNetworkInterface"}]
+                })
+                .to_string(),
+            );
+        let mut engine = Engine::new(loader)
+            .with_fas_map_extensions(&[fas_map])
+            .unwrap();
+
+        let extracted_methods = vec![SdkMethodCall {
+            name: "CreateFunction".to_string(),
+            possible_services: vec!["lambda".to_string()],
+            metadata: None,
+        }];
+        let enriched = engine
+            .enrich_methods(&extracted_methods, SdkType::Other)
+            .await
+            .unwrap();
+
+        let actions = &enriched[0].actions;
+        let action = actions
+            .iter()
+            .find(|action| action.name == "ec2:CreateNetworkInterface")
+            .expect("FAS map extension should add ec2:CreateNetworkInterface");
+        let operations = &action.explanation.reasons[0].operations;
+        assert!(matches!(
+            operations.last().unwrap().source,
+            crate::enrichment::OperationSource::UserFas(_)
+        ));
+    }
+
     #[test_log::test(tokio::test)]
     async fn test_enrichment_engine_comprehensive() {
         use std::time::Instant;
@@ -202,7 +518,7 @@ mod tests {
         }
 
         println!("\nSetting up enrichment engine...");
-        let mut enrichment_engine = Engine::new(true).unwrap();
+        let mut enrichment_engine = Engine::new(RemoteServiceReferenceLoader::new(true).unwrap());
         println!("Enrichment engine initialized");
 
         println!("\nRunning enrichment on all operations...");
@@ -27,11 +27,18 @@ pub(crate) mod engine;
 pub(crate) mod operation_fas_map;
 pub(crate) mod resource_matcher;
 pub(crate) mod service_reference;
+pub(crate) mod service_reference_file_cache;
+pub(crate) mod service_reference_loader;
+pub(crate) mod service_reference_snapshot;
 
 pub use engine::Engine;
 pub(crate) use operation_fas_map::load_operation_fas_map;
 pub(crate) use resource_matcher::ResourceMatcher;
-pub(crate) use service_reference::RemoteServiceReferenceLoader as ServiceReferenceLoader;
+pub(crate) use service_reference::ServiceReferenceCache;
+pub use service_reference::{RemoteServiceReferenceLoader, RemoteServiceReferenceLoaderBuilder};
+pub use service_reference_loader::{
+    DirectoryServiceReferenceLoader, InMemoryServiceReferenceLoader, ServiceReferenceLoader,
+};
 
 /// Represents the reason why an action was added to a policy
 #[derive(derive_new::new, Debug, Clone, Serialize, PartialEq, Eq, Hash, JsonSchema)]
@@ -73,7 +80,7 @@ impl Operation {
 
     pub(crate) fn context(&self) -> &[FasContext] {
         match &self.source {
-            OperationSource::Fas(context) => context,
+            OperationSource::Fas(context) | OperationSource::UserFas(context) => context,
             _ => &[],
         }
     }
@@ -83,7 +90,7 @@ impl Operation {
         original_service_name: &str,
         service_cfg: &ServiceConfiguration,
         sdk: SdkType,
-        service_reference_loader: &ServiceReferenceLoader,
+        service_reference_loader: &ServiceReferenceCache,
     ) -> crate::errors::Result<Self> {
         let service = service_cfg
             .rename_service_service_reference(original_service_name)
@@ -133,10 +140,15 @@ impl Operation {
 
 impl From<FasOperation> for Operation {
     fn from(fas_op: FasOperation) -> Self {
+        let source = if fas_op.user_supplied {
+            OperationSource::UserFas(fas_op.context)
+        } else {
+            OperationSource::Fas(fas_op.context)
+        };
         Self {
             service: fas_op.service,
             name: fas_op.operation,
-            source: OperationSource::Fas(fas_op.context),
+            source,
             _private: (),
         }
     }
@@ -186,6 +198,8 @@ pub enum OperationSource {
     Provided,
     /// Operation comes from FAS expansion
     Fas(Vec<FasContext>),
+    /// Operation comes from FAS expansion with a user-supplied FAS map
+    UserFas(Vec<FasContext>),
 }
 
 impl Serialize for OperationSource {
@@ -199,13 +213,14 @@ impl Serialize for OperationSource {
             }
             OperationSource::Provided => serializer.serialize_str("Provided"),
             OperationSource::Fas(_) => serializer.serialize_str("FAS"),
+            OperationSource::UserFas(_) => serializer.serialize_str("UserFAS"),
         }
     }
 }
 
 /// Explanations for why actions have been included in a policy, with documentation for
 /// concepts leading to inclusion (such as FAS expansion)
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "PascalCase")]
 pub struct Explanations {
     /// Explanation for inclusion of an action
@@ -218,25 +233,47 @@ pub struct Explanations {
 impl Explanations {
     const FAS: &str =
         "The explanation contains an operation added due to Forward Access Sessions (FAS). See https://docs.aws.amazon.com/IAM/latest/UserGuide/access_forward_access_sessions.html.";
+    const USER_FAS: &str =
+        "The explanation contains an operation (source UserFAS) added by a user-supplied FAS map rather than the FAS maps shipped with IAM Policy Autopilot.";
 
     pub(crate) fn new(explanations: BTreeMap<String, Explanation>) -> Self {
         let mut documentation: Vec<&'static str> = vec![];
         for explanation in explanations.values() {
             for reason in &explanation.reasons {
                 for op in &reason.operations {
-                    match op.source {
-                        OperationSource::Extracted(_) | OperationSource::Provided => (),
-                        OperationSource::Fas(_) => documentation.push(Self::FAS),
+                    let concepts: &[&'static str] = match op.source {
+                        OperationSource::Extracted(_) | OperationSource::Provided => &[],
+                        OperationSource::Fas(_) => &[Self::FAS],
+                        OperationSource::UserFas(_) => &[Self::FAS, Self::USER_FAS],
+                    };
+                    for concept in concepts {
+                        if !documentation.contains(concept) {
+                            documentation.push(concept);
+                        }
                     }
                 }
             }
         }
-        documentation.dedup();
         Self {
             explanation_for_action: explanations,
             documentation,
         }
     }
+
+    /// Explanations restricted to a single action, with the documentation it refers to
+    ///
+    /// Actions are matched case-insensitively, as IAM does. Returns `None` if the action was not
+    /// added to the policy.
+    pub fn for_action(&self, action: &str) -> Option<Self> {
+        let (name, explanation) = self
+            .explanation_for_action
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(action))?;
+        Some(Self::new(BTreeMap::from([(
+            name.clone(),
+            explanation.clone(),
+        )])))
+    }
 }
 
 /// Represents an explanation for why an action was added to a policy
@@ -301,6 +338,23 @@ pub(crate) trait Context {
     fn values(&self) -> &[String];
 }
 
+/// Access level of an IAM action, derived from the service reference annotations
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema,
+)]
+pub enum AccessLevel {
+    /// Lists resources without reading their content
+    List,
+    /// Reads resource content or attributes
+    Read,
+    /// Only changes tags of resources
+    Tagging,
+    /// Creates, modifies or deletes resources
+    Write,
+    /// Changes permissions, such as IAM or resource-based policies
+    PermissionsManagement,
+}
+
 /// Represents an IAM action enriched with resource and condition information
 ///
 /// This structure combines OperationAction action data with Service Reference resource information to provide
@@ -315,6 +369,12 @@ pub(crate) struct Action {
     pub(crate) conditions: Vec<Condition>,
     /// Optional explanation why this action has been added
     pub(crate) explanation: Explanation,
+    /// Access level of the action from the service reference, if known
+    #[serde(skip)]
+    pub(crate) access_level: Option<AccessLevel>,
+    /// Whether the resources were collapsed to `*` because the action has too many resource types
+    #[serde(skip)]
+    pub(crate) resource_cutoff: bool,
 }
 
 impl Action {
@@ -337,8 +397,24 @@ impl Action {
             resources,
             conditions,
             explanation,
+            access_level: None,
+            resource_cutoff: false,
         }
     }
+
+    /// Set the access level of the action
+    #[must_use]
+    pub(crate) fn with_access_level(mut self, access_level: Option<AccessLevel>) -> Self {
+        self.access_level = access_level;
+        self
+    }
+
+    /// Mark the resources of this action as collapsed to `*` by the resource cutoff
+    #[must_use]
+    pub(crate) fn with_resource_cutoff(mut self, resource_cutoff: bool) -> Self {
+        self.resource_cutoff = resource_cutoff;
+        self
+    }
 }
 
 /// Represents a resource enriched with ARN pattern and metadata
@@ -568,7 +644,8 @@ mod tests {
 
 #[cfg(test)]
 pub(crate) mod mock_remote_service_reference {
-    use crate::enrichment::service_reference::RemoteServiceReferenceLoader;
+    use crate::enrichment::{RemoteServiceReferenceLoader, ServiceReferenceCache};
+    use std::sync::Arc;
     use wiremock::matchers::{method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
@@ -596,8 +673,17 @@ pub(crate) mod mock_remote_service_reference {
             .await
     }
 
+    /// Loader for the service reference endpoint mocked by `mock_server`, without file system cache
+    pub(crate) fn mock_remote_loader(mock_server: &MockServer) -> RemoteServiceReferenceLoader {
+        RemoteServiceReferenceLoader::builder()
+            .with_endpoint(mock_server.uri())
+            .with_disable_file_system_cache(true)
+            .build()
+            .unwrap()
+    }
+
     pub(crate) async fn setup_mock_server_with_loader_without_operation_to_action_mapping(
-    ) -> (MockServer, RemoteServiceReferenceLoader) {
+    ) -> (MockServer, ServiceReferenceCache) {
         let mock_server = MockServer::start().await;
         let mock_server_url = mock_server.uri();
 
@@ -657,15 +743,12 @@ pub(crate) mod mock_remote_service_reference {
             .mount(&mock_server)
             .await;
 
-        let loader = RemoteServiceReferenceLoader::new(true)
-            .unwrap()
-            .with_mapping_url(mock_server_url);
+        let loader = ServiceReferenceCache::new(Arc::new(mock_remote_loader(&mock_server)));
 
         (mock_server, loader)
     }
 
-    pub(crate) async fn setup_mock_server_with_loader() -> (MockServer, RemoteServiceReferenceLoader)
-    {
+    pub(crate) async fn setup_mock_server_with_loader() -> (MockServer, ServiceReferenceCache) {
         // Add small delay to avoid port conflicts in parallel tests
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
         let mock_server = MockServer::start().await;
@@ -788,9 +871,7 @@ pub(crate) mod mock_remote_service_reference {
             .mount(&mock_server)
             .await;
 
-        let loader = RemoteServiceReferenceLoader::new(true)
-            .unwrap()
-            .with_mapping_url(mock_server_url);
+        let loader = ServiceReferenceCache::new(Arc::new(mock_remote_loader(&mock_server)));
 
         (mock_server, loader)
     }
@@ -889,7 +970,7 @@ mod location_tests {
             parameters: vec![],
             return_type: None,
             expr: "dynamodb.get_item(\n        TableName='my-table',\n        Key={'id': {'S': '123'}}\n    )".to_string(),
-            location: Location::new(PathBuf::from("iam-policy-autopilot-cli/tests/resources/test_example.py"), (19, 5), (22, 5)),
+            location: Location::new(PathBuf::from("iam-policy-autopilot-cli/tests/resources/example.py"), (19, 5), (22, 5)),
             receiver: Some("dynamodb".to_string()),
         };
 
@@ -899,7 +980,7 @@ mod location_tests {
         // Verify the custom serialization format
         assert!(json.contains("\"Expr\":\"dynamodb.get_item(\\n        TableName='my-table',\\n        Key={'id': {'S': '123'}}\\n    )\""));
         assert!(json.contains(
-            "\"Location\":\"iam-policy-autopilot-cli/tests/resources/test_example.py:19.5-22.5\""
+            "\"Location\":\"iam-policy-autopilot-cli/tests/resources/example.py:19.5-22.5\""
         ));
         // Should not contain nested "Source" key
         assert!(!json.contains("\"Source\""));
@@ -927,6 +1008,9 @@ mod location_tests {
 
         // Verify the custom serialization format - should be just "FAS", not nested
         assert_eq!(json, "\"FAS\"");
+
+        let source = OperationSource::UserFas(Vec::new());
+        assert_eq!(serde_json::to_string(&source).unwrap(), "\"UserFAS\"");
     }
 
     #[tokio::test]
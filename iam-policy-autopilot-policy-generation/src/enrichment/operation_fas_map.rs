@@ -2,8 +2,11 @@
 //!
 //! This module contains the data structures used to represent operation
 //! action maps that are loaded from embedded JSON files and used for IAM policy enrichment.
+//! Users can extend the embedded maps with their own files in the same schema, see
+//! [`load_operation_fas_map_extensions`].
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::{Arc, OnceLock, RwLock};
 
 use rust_embed::RustEmbed;
@@ -11,6 +14,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer};
 
 use crate::enrichment::Context;
+use crate::errors::{ExtractorError, Result};
 
 type ServiceName = String;
 type OperationName = String;
@@ -19,6 +23,7 @@ pub(crate) type OperationFasMaps = HashMap<ServiceName, Arc<OperationFasMap>>;
 
 /// Root structure for operation FAS map JSON files
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
 struct OperationFasMapRoot {
     #[serde(rename = "Name")]
     name: String,
@@ -28,6 +33,7 @@ struct OperationFasMapRoot {
 
 /// Individual operation with its FAS operations
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
 struct OperationWithFas {
     #[serde(rename = "Name")]
     name: String,
@@ -68,7 +74,9 @@ impl Context for FasContext {
 
 // Custom deserializer for Context that handles HashMap-like JSON objects
 // Supports both single string values and arrays of strings
-fn deserialize_context_map<'de, D>(deserializer: D) -> Result<Vec<FasContext>, D::Error>
+fn deserialize_context_map<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<FasContext>, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -85,7 +93,7 @@ where
             formatter.write_str("a map of key-value pairs where values can be strings or arrays")
         }
 
-        fn visit_map<V>(self, mut map: V) -> Result<Vec<FasContext>, V::Error>
+        fn visit_map<V>(self, mut map: V) -> std::result::Result<Vec<FasContext>, V::Error>
         where
             V: MapAccess<'de>,
         {
@@ -121,6 +129,9 @@ pub(crate) struct FasOperation {
     pub(crate) service: String,
     #[serde(rename = "Context", deserialize_with = "deserialize_context_map")]
     pub(crate) context: Vec<FasContext>,
+    /// Whether the operation comes from a user-supplied FAS map rather than the embedded ones
+    #[serde(skip)]
+    pub(crate) user_supplied: bool,
 }
 
 #[cfg(test)]
@@ -130,6 +141,7 @@ impl FasOperation {
             operation,
             service,
             context,
+            user_supplied: false,
         }
     }
 }
@@ -152,6 +164,25 @@ impl<'de> Deserialize<'de> for OperationFasMap {
     }
 }
 
+impl OperationFasMap {
+    /// Add the FAS operations of `other` that this map does not contain yet
+    pub(crate) fn extend(&mut self, other: &OperationFasMap) {
+        for (operation, fas_operations) in &other.fas_operations {
+            let existing = self.fas_operations.entry(operation.clone()).or_default();
+            for fas_operation in fas_operations {
+                let known = existing.iter().any(|op| {
+                    op.service == fas_operation.service
+                        && op.operation == fas_operation.operation
+                        && op.context == fas_operation.context
+                });
+                if !known {
+                    existing.push(fas_operation.clone());
+                }
+            }
+        }
+    }
+}
+
 /// Embedded operation FAS maps data
 #[derive(RustEmbed)]
 #[folder = "resources/config/operation-fas-maps"]
@@ -217,6 +248,109 @@ pub(crate) fn load_operation_fas_map(service_name: &str) -> Option<Arc<Operation
     result
 }
 
+/// Names of the services with an embedded operation FAS map, sorted
+pub(crate) fn embedded_operation_fas_map_services() -> Vec<String> {
+    let mut services: Vec<String> = EmbeddedOperationFasMaps::iter()
+        .filter_map(|file_name| file_name.strip_suffix(".json").map(str::to_string))
+        .collect();
+    services.sort();
+    services
+}
+
+/// Embedded operation FAS map JSON document of a service, in the schema of user-supplied maps
+pub(crate) fn embedded_operation_fas_map_document(service_name: &str) -> Option<String> {
+    EmbeddedOperationFasMaps::get(&format!("{}.json", service_name))
+        .map(|file| String::from_utf8_lossy(&file.data).into_owned())
+}
+
+/// Load user-supplied operation FAS map files, merged per service
+///
+/// The files use the schema of the embedded maps. Every FAS operation is marked as
+/// user-supplied, so that explanations can tell it apart from the embedded ones.
+///
+/// # Errors
+/// Returns `ExtractorError` if a file cannot be read, is not valid JSON, does not match the
+/// schema, or contains an invalid service or operation name or a duplicate operation
+pub(crate) fn load_operation_fas_map_extensions(paths: &[PathBuf]) -> Result<OperationFasMaps> {
+    let mut extensions: HashMap<ServiceName, OperationFasMap> = HashMap::new();
+    for path in paths {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ExtractorError::file_system("read", path, e))?;
+        let invalid = |message: String| {
+            ExtractorError::validation(format!("Invalid FAS map '{}': {}", path.display(), message))
+        };
+
+        let root: OperationFasMapRoot = serde_json::from_str(&content).map_err(|e| {
+            ExtractorError::configuration_with_source(
+                format!("Invalid FAS map '{}'", path.display()),
+                e,
+            )
+        })?;
+        if !is_valid_service_name(&root.name) {
+            return Err(invalid(format!("invalid service name '{}'", root.name)));
+        }
+
+        let mut seen = HashSet::new();
+        let mut fas_operations = HashMap::new();
+        for operation in root.operations {
+            if !is_valid_operation_name(&operation.name) {
+                return Err(invalid(format!(
+                    "invalid operation name '{}'",
+                    operation.name
+                )));
+            }
+            if !seen.insert(operation.name.clone()) {
+                return Err(invalid(format!("duplicate operation '{}'", operation.name)));
+            }
+            let mut operations = Vec::with_capacity(operation.fas_operations.len());
+            for mut fas_operation in operation.fas_operations {
+                if !is_valid_service_name(&fas_operation.service)
+                    || !is_valid_operation_name(&fas_operation.operation)
+                {
+                    return Err(invalid(format!(
+                        "invalid FAS operation '{}:{}' of operation '{}'",
+                        fas_operation.service, fas_operation.operation, operation.name
+                    )));
+                }
+                fas_operation.user_supplied = true;
+                operations.push(fas_operation);
+            }
+            fas_operations.insert(format!("{}:{}", root.name, operation.name), operations);
+        }
+
+        log::debug!(
+            "Loaded FAS map extension for service '{}' from {}",
+            root.name,
+            path.display()
+        );
+        extensions
+            .entry(root.name)
+            .or_insert_with(|| OperationFasMap {
+                fas_operations: HashMap::new(),
+            })
+            .extend(&OperationFasMap { fas_operations });
+    }
+
+    Ok(extensions
+        .into_iter()
+        .map(|(service, map)| (service, Arc::new(map)))
+        .collect())
+}
+
+/// IAM service prefixes consist of lowercase letters, digits and hyphens
+pub(crate) fn is_valid_service_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Operation names are PascalCase identifiers
+pub(crate) fn is_valid_operation_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -374,6 +508,70 @@ mod tests {
         assert!(keys.contains(&"aws:RequestedRegion".to_string()));
     }
 
+    #[test]
+    fn test_load_operation_fas_map_extensions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("lambda.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "Name": "lambda",
+                "Operations": [
+                    {
+                        "Name": "CreateFunction",
+                        "FasOperations": [
+                            {"Operation": "CreateNetworkInterface", "Service": "ec2", "Context": {}}
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let extensions = load_operation_fas_map_extensions(&[path]).unwrap();
+        let fas_operations = &extensions["lambda"].fas_operations["lambda:CreateFunction"];
+        assert_eq!(fas_operations.len(), 1);
+        assert_eq!(fas_operations[0].service, "ec2");
+        assert!(fas_operations[0].user_supplied);
+
+        // Extending an embedded map keeps its operations
+        let mut s3_map = (*load_operation_fas_map("s3").unwrap()).clone();
+        let embedded_len = s3_map.fas_operations.len();
+        s3_map.extend(&extensions["lambda"]);
+        assert_eq!(s3_map.fas_operations.len(), embedded_len + 1);
+    }
+
+    #[test]
+    fn test_load_operation_fas_map_extensions_rejects_invalid_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cases = [
+            (
+                r#"{"Name": "Lambda", "Operations": []}"#,
+                "invalid service name",
+            ),
+            (
+                r#"{"Name": "lambda", "Operations": [{"Name": "Invoke"}, {"Name": "Invoke"}]}"#,
+                "duplicate operation 'Invoke'",
+            ),
+            (
+                r#"{"Name": "lambda", "Operations": [{"Name": "Invoke", "FasOperations": [{"Operation": "kms:Decrypt", "Service": "kms", "Context": {}}]}]}"#,
+                "invalid FAS operation",
+            ),
+            (r#"{"Name": "lambda", "Operation": []}"#, "Invalid FAS map"),
+        ];
+        for (content, expected) in cases {
+            let path = dir.path().join("lambda.json");
+            std::fs::write(&path, content).unwrap();
+            let error = load_operation_fas_map_extensions(&[path]).unwrap_err();
+            assert!(
+                error.to_string().contains(expected),
+                "'{}' should contain '{}'",
+                error,
+                expected
+            );
+        }
+    }
+
     #[test]
     fn test_caching_behavior() {
         // Test that caching works correctly for both existing and non-existing services
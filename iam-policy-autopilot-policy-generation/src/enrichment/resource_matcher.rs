@@ -4,13 +4,14 @@
 //! action maps with Service Definition Files to generate enriched method calls
 //! with complete IAM metadata.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
-use super::{Action, Context, EnrichedSdkMethodCall, Explanation, Reason, Resource};
+use super::{AccessLevel, Action, Context, EnrichedSdkMethodCall, Explanation, Reason, Resource};
+use crate::api::model::{PolicyGenerationOptions, WildcardMode};
 use crate::enrichment::operation_fas_map::{OperationFasMap, OperationFasMaps};
 use crate::enrichment::service_reference::ServiceReference;
-use crate::enrichment::{Condition, Operation, ServiceReferenceLoader};
+use crate::enrichment::{Condition, Operation, ServiceReferenceCache};
 use crate::errors::{ExtractorError, Result};
 use crate::service_configuration::ServiceConfiguration;
 use crate::{SdkMethodCall, SdkType};
@@ -130,18 +131,24 @@ pub(crate) struct ResourceMatcher {
     service_cfg: Arc<ServiceConfiguration>,
     fas_maps: OperationFasMaps,
     sdk: SdkType,
+    #[new(default)]
+    options: PolicyGenerationOptions,
 }
 
-// TODO: Make this configurable: https://github.com/awslabs/iam-policy-autopilot/issues/19
-const RESOURCE_CUTOFF: usize = 5;
-
 impl ResourceMatcher {
+    /// Use the given options to collapse resources into wildcards
+    #[must_use]
+    pub(crate) fn with_options(mut self, options: PolicyGenerationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
     /// Enrich a parsed method call with OperationAction maps, FAS maps, and Service
     /// Reference data
     pub(crate) async fn enrich_method_call<'b>(
         &self,
         parsed_call: &'b SdkMethodCall,
-        service_reference_loader: &ServiceReferenceLoader,
+        service_reference_loader: &ServiceReferenceCache,
     ) -> Result<Vec<EnrichedSdkMethodCall<'b>>> {
         if parsed_call.possible_services.is_empty() {
             return Err(ExtractorError::enrichment_error(
@@ -183,7 +190,7 @@ impl ResourceMatcher {
         &self,
         parsed_call: &'a SdkMethodCall,
         service_name: &str,
-        service_reference_loader: &ServiceReferenceLoader,
+        service_reference_loader: &ServiceReferenceCache,
     ) -> Result<Option<EnrichedSdkMethodCall<'a>>> {
         log::debug!(
             "Creating method call for service: {}, and method name: {}",
@@ -243,12 +250,8 @@ impl ResourceMatcher {
                                         &action.name,
                                         &service_reference,
                                     )?;
-                                let enriched_resources =
-                                    if RESOURCE_CUTOFF <= enriched_resources.len() {
-                                        vec![Resource::new("*".to_string(), None)]
-                                    } else {
-                                        enriched_resources
-                                    };
+                                let (enriched_resources, resource_cutoff) =
+                                    self.apply_resource_cutoff(&action.name, enriched_resources);
 
                                 // Combine conditions from FAS operation context and AuthorizedAction context
                                 let mut conditions = Self::make_condition(op.context());
@@ -271,7 +274,12 @@ impl ResourceMatcher {
                                     enriched_resources,
                                     conditions,
                                     explanation,
-                                );
+                                )
+                                .with_access_level(Self::find_access_level_in_service_reference(
+                                    &action.name,
+                                    &service_reference,
+                                ))
+                                .with_resource_cutoff(resource_cutoff);
                                 log::debug!("Created action: {:?}", enriched_action);
                                 enriched_actions.push(enriched_action);
                             }
@@ -342,12 +350,68 @@ impl ResourceMatcher {
             )],
         };
 
-        Ok(Some(Action::new(
-            action_name.to_string(),
-            resources,
-            vec![],
-            explanation,
-        )))
+        Ok(Some(
+            Action::new(action_name.to_string(), resources, vec![], explanation).with_access_level(
+                Self::find_access_level_in_service_reference(&action_name, service_reference),
+            ),
+        ))
+    }
+
+    /// Collapse the resources of an action into wildcards if it has too many resource types
+    ///
+    /// Returns the resources to use and whether they were collapsed.
+    fn apply_resource_cutoff(
+        &self,
+        action_name: &str,
+        resources: Vec<Resource>,
+    ) -> (Vec<Resource>, bool) {
+        let (cutoff, wildcard_mode) = self.options.resource_cutoff_for(action_name);
+        if wildcard_mode == WildcardMode::Never || resources.len() < cutoff {
+            return (resources, false);
+        }
+
+        let collapsed = match wildcard_mode {
+            WildcardMode::ServiceWildcard => Self::service_level_wildcards(&resources),
+            WildcardMode::Wildcard | WildcardMode::Never => None,
+        };
+        (
+            vec![collapsed.unwrap_or_else(|| Resource::new("*".to_string(), None))],
+            true,
+        )
+    }
+
+    /// Build a resource matching every resource of the services, regions and accounts
+    /// of the given resources, e.g. `arn:${Partition}:ec2:${Region}:${Account}:*`
+    ///
+    /// Returns None if a resource has no ARN pattern, as it already matches all resources.
+    fn service_level_wildcards(resources: &[Resource]) -> Option<Resource> {
+        let mut patterns = BTreeSet::new();
+        for resource in resources {
+            for arn_pattern in resource.arn_patterns.as_ref()? {
+                // arn:partition:service:region:account:resource
+                let segments = arn_pattern.splitn(6, ':').collect::<Vec<_>>();
+                if segments.len() < 6 {
+                    return None;
+                }
+                patterns.insert(format!("{}:*", segments[..5].join(":")));
+            }
+        }
+        Some(Resource::new(
+            "*".to_string(),
+            Some(patterns.into_iter().collect()),
+        ))
+    }
+
+    /// Find the access level of an action by looking it up in the SDF
+    fn find_access_level_in_service_reference(
+        action_name: &str,
+        service_reference: &ServiceReference,
+    ) -> Option<AccessLevel> {
+        let action = action_name.split(':').nth(1).unwrap_or(action_name);
+        service_reference
+            .actions
+            .get(action)
+            .and_then(|action| action.access_level)
     }
 
     /// Find resources for an action by looking it up in the SDF
@@ -408,6 +472,7 @@ mod tests {
     use std::collections::HashMap;
 
     use super::*;
+    use crate::api::model::{ResourceCutoffOverride, DEFAULT_RESOURCE_CUTOFF};
     use crate::enrichment::operation_fas_map::{FasContext, FasOperation, OperationFasMap};
     use crate::enrichment::{mock_remote_service_reference, OperationSource};
 
@@ -1360,4 +1425,88 @@ mod tests {
         // Should use the operation name directly without any transformation
         assert_eq!(enriched_calls[0].actions[0].name, "rds:ModifyDBCluster");
     }
+
+    fn create_ec2_resources(count: usize) -> Vec<Resource> {
+        (0..count)
+            .map(|i| {
+                Resource::new(
+                    format!("type{i}"),
+                    Some(vec![format!(
+                        "arn:${{Partition}}:ec2:${{Region}}:${{Account}}:type{i}/${{Id}}"
+                    )]),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_resource_cutoff_modes() {
+        let mut options = PolicyGenerationOptions::default();
+        options.service_overrides.insert(
+            "ec2".to_string(),
+            ResourceCutoffOverride {
+                cutoff: Some(3),
+                wildcard_mode: Some(WildcardMode::ServiceWildcard),
+            },
+        );
+        options.action_overrides.insert(
+            "ec2:RunInstances".to_string(),
+            ResourceCutoffOverride {
+                cutoff: None,
+                wildcard_mode: Some(WildcardMode::Never),
+            },
+        );
+        let matcher = ResourceMatcher::new(
+            create_empty_service_config(),
+            HashMap::new(),
+            SdkType::Boto3,
+        )
+        .with_options(options);
+
+        // Below the cutoff resources are kept
+        let (resources, collapsed) =
+            matcher.apply_resource_cutoff("ec2:DescribeVolumes", create_ec2_resources(2));
+        assert!(!collapsed);
+        assert_eq!(resources.len(), 2);
+
+        // Service-level wildcard keeps partition, service, region and account
+        let (resources, collapsed) =
+            matcher.apply_resource_cutoff("ec2:DescribeVolumes", create_ec2_resources(3));
+        assert!(collapsed);
+        assert_eq!(
+            resources,
+            vec![Resource::new(
+                "*".to_string(),
+                Some(vec![
+                    "arn:${Partition}:ec2:${Region}:${Account}:*".to_string()
+                ])
+            )]
+        );
+
+        // Never collapse
+        let (resources, collapsed) =
+            matcher.apply_resource_cutoff("ec2:RunInstances", create_ec2_resources(10));
+        assert!(!collapsed);
+        assert_eq!(resources.len(), 10);
+
+        // Global default collapses to `*`
+        let (resources, collapsed) = matcher.apply_resource_cutoff(
+            "s3:GetObject",
+            create_ec2_resources(DEFAULT_RESOURCE_CUTOFF),
+        );
+        assert!(collapsed);
+        assert_eq!(resources, vec![Resource::new("*".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_service_level_wildcards_without_arn_patterns() {
+        let resources = vec![
+            Resource::new(
+                "bucket".to_string(),
+                Some(vec!["arn:${Partition}:s3:::${BucketName}".to_string()]),
+            ),
+            Resource::new("*".to_string(), None),
+        ];
+        assert_eq!(ResourceMatcher::service_level_wildcards(&resources), None);
+    }
 }
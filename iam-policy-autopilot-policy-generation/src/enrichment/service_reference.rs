@@ -4,25 +4,37 @@
 //! from the filesystem with exact service name matching and caching for
 //! performance optimization.
 
-use crate::enrichment::Context;
+use crate::api::model::{ServiceReferenceSnapshotManifest, ServiceReferenceSource};
+use crate::enrichment::service_reference_file_cache::{CacheEntry, FileCache, MAPPING_ENTRY_NAME};
+use crate::enrichment::service_reference_snapshot::{
+    check_service_name, sha256, write_snapshot, ServiceReferenceSnapshot,
+};
+use crate::enrichment::{AccessLevel, Context, ServiceReferenceLoader};
 use crate::errors::ExtractorError;
 use crate::providers::JsonProvider;
-use reqwest::{Client, Url};
+use async_trait::async_trait;
+use futures::{StreamExt, TryStreamExt};
+use reqwest::header::{HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Certificate, Client, Proxy, StatusCode, Url};
 use serde::{Deserialize, Deserializer};
 use serde_json::Value;
 use std::{
-    collections::HashMap,
-    path::PathBuf,
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
     time::{Duration, SystemTime},
 };
-use tokio::fs;
 use tokio::sync::{OnceCell, RwLock};
 
 type OperationName = String;
 const IAM_POLICY_AUTOPILOT: &str = "IAMPolicyAutopilot";
+const DEFAULT_SERVICE_REFERENCE_ENDPOINT: &str = "https://servicereference.us-east-1.amazonaws.com";
 // Cache files for 6 hours.
 // We can allow cache duration override in future.
 const DEFAULT_CACHE_DURATION_IN_SECONDS: u64 = 21600;
+pub(crate) const CACHE_DURATION: Duration = Duration::from_secs(DEFAULT_CACHE_DURATION_IN_SECONDS);
+// Number of service references downloaded concurrently for a snapshot or cache warm-up
+const DOWNLOAD_CONCURRENCY: usize = 16;
 /// Service Reference data structure
 ///
 /// Represents the complete service reference loaded from service reference endpoint.
@@ -37,6 +49,8 @@ pub(crate) struct ServiceReference {
     pub(crate) service_name: String,
     /// Resource mapping to ARN patterns
     pub(crate) resources: HashMap<String, Vec<String>>,
+    /// Resource mapping to the condition keys supported for that resource type
+    pub(crate) resource_condition_keys: HashMap<String, Vec<String>>,
     /// Operation to authorized action mapping
     /// Note: Only partial service and operations have this data
     pub(crate) operation_to_authorized_actions: Option<HashMap<OperationName, Operation>>,
@@ -57,9 +71,9 @@ impl<'de> Deserialize<'de> for ServiceReference {
             actions: HashMap<String, Action>,
             #[serde(rename = "Name")]
             name: String,
-            #[serde(rename = "Resources", deserialize_with = "deserialize_resources_map")]
+            #[serde(rename = "Resources")]
             #[serde(default)]
-            resources: HashMap<String, Vec<String>>,
+            resources: Vec<ServiceResource>,
             #[serde(rename = "Operations")]
             #[serde(default)]
             operations: Vec<Operation>,
@@ -117,10 +131,18 @@ impl<'de> Deserialize<'de> for ServiceReference {
             }
         }
 
+        let mut resources = HashMap::new();
+        let mut resource_condition_keys = HashMap::new();
+        for resource in temp.resources {
+            resource_condition_keys.insert(resource.name.clone(), resource.condition_keys);
+            resources.insert(resource.name, resource.arn_formats);
+        }
+
         Ok(ServiceReference {
             actions: temp.actions,
             service_name: temp.name,
-            resources: temp.resources,
+            resources,
+            resource_condition_keys,
             operation_to_authorized_actions,
             boto3_method_to_operation,
         })
@@ -137,6 +159,9 @@ pub(crate) struct Action {
     pub(crate) resources: Vec<String>,
     #[serde(rename = "ActionConditionKeys")]
     pub(crate) condition_keys: Vec<String>,
+    /// Access level derived from the action annotations, if present
+    #[serde(skip)]
+    pub(crate) access_level: Option<AccessLevel>,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
@@ -215,6 +240,25 @@ where
         name: String,
     }
 
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct TempProperties {
+        #[serde(default)]
+        is_list: bool,
+        #[serde(default)]
+        is_permission_management: bool,
+        #[serde(default)]
+        is_tagging_only: bool,
+        #[serde(default)]
+        is_write: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct TempAnnotations {
+        #[serde(rename = "Properties")]
+        properties: TempProperties,
+    }
+
     #[derive(Deserialize)]
     struct TempAction {
         #[serde(rename = "Name")]
@@ -225,6 +269,9 @@ where
         #[serde(rename = "ActionConditionKeys")]
         #[serde(default)]
         condition_keys: Vec<String>,
+        #[serde(rename = "Annotations")]
+        #[serde(default)]
+        annotations: Option<TempAnnotations>,
     }
 
     let actions: Vec<TempAction> = Vec::deserialize(deserializer)?;
@@ -235,41 +282,50 @@ where
                 name: temp_action.name.clone(),
                 resources: temp_action.resources.into_iter().map(|r| r.name).collect(),
                 condition_keys: temp_action.condition_keys,
+                access_level: temp_action.annotations.map(|annotations| {
+                    let properties = annotations.properties;
+                    // Permission management actions are also write actions, so check them first
+                    if properties.is_permission_management {
+                        AccessLevel::PermissionsManagement
+                    } else if properties.is_tagging_only {
+                        AccessLevel::Tagging
+                    } else if properties.is_write {
+                        AccessLevel::Write
+                    } else if properties.is_list {
+                        AccessLevel::List
+                    } else {
+                        AccessLevel::Read
+                    }
+                }),
             };
             (temp_action.name, action)
         })
         .collect())
 }
 
-fn deserialize_resources_map<'de, D>(
-    deserializer: D,
-) -> std::result::Result<HashMap<String, Vec<String>>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    // Resource within a Service Reference
-    #[derive(Deserialize)]
-    struct ServiceResource {
-        #[serde(rename = "Name")]
-        // Resource name (e.g., "certificate", "bucket")
-        pub(crate) name: String,
-        #[serde(rename = "ARNFormats")]
-        // ARN format patterns for this resource
-        pub(crate) arn_formats: Vec<String>,
-    }
-    let resources: Vec<ServiceResource> = Vec::deserialize(deserializer)?;
-    Ok(resources
-        .into_iter()
-        .map(|resource| (resource.name, resource.arn_formats))
-        .collect())
+// Resource within a Service Reference
+#[derive(Deserialize)]
+struct ServiceResource {
+    // Resource name (e.g., "certificate", "bucket")
+    #[serde(rename = "Name")]
+    name: String,
+    // ARN format patterns for this resource
+    #[serde(rename = "ARNFormats")]
+    arn_formats: Vec<String>,
+    // Condition keys that apply to this resource type
+    #[serde(rename = "ConditionKeys")]
+    #[serde(default)]
+    condition_keys: Vec<String>,
 }
 
 /// represents the top level mapping returned by service reference
 /// to resolve the url for target service
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct ServiceReferenceMapping {
     // represents the top level service reference mapping
     pub(crate) service_reference_mapping: HashMap<String, Url>,
+    // checksum of the mapping index, recorded in the cache entries fetched with it
+    pub(crate) version: String,
 }
 
 fn deserialize_service_reference_mapping(
@@ -296,95 +352,511 @@ fn deserialize_service_reference_mapping(
     Ok(map)
 }
 
+/// In-memory cache of parsed service references
+///
+/// Wraps the [`ServiceReferenceLoader`] used for enrichment and parses the documents it returns.
+/// Service names must match exactly between input and Service Reference Name (case-sensitive).
+#[derive(Debug)]
+pub(crate) struct ServiceReferenceCache {
+    loader: Arc<dyn ServiceReferenceLoader>,
+    service_cache: RwLock<HashMap<String, (ServiceReference, SystemTime)>>,
+}
+
+impl ServiceReferenceCache {
+    pub(crate) fn new(loader: Arc<dyn ServiceReferenceLoader>) -> Self {
+        Self {
+            loader,
+            service_cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) async fn load(
+        &self,
+        service_name: &str,
+    ) -> crate::errors::Result<Option<ServiceReference>> {
+        if let Some((cached, timestamp)) = self.service_cache.read().await.get(service_name) {
+            if let Ok(elapsed) = SystemTime::now().duration_since(*timestamp) {
+                if elapsed < Duration::from_secs(DEFAULT_CACHE_DURATION_IN_SECONDS) {
+                    return Ok(Some(cached.clone()));
+                }
+            }
+        }
+
+        let content = self
+            .loader
+            .load_service_reference(service_name)
+            .await
+            .map_err(|e| match e.downcast::<ExtractorError>() {
+                Ok(e) => e,
+                Err(e) => ExtractorError::enrichment_error(
+                    service_name,
+                    format!("Failed to load service reference: {:#}", e),
+                ),
+            })?;
+        let Some(content) = content else {
+            return Ok(None);
+        };
+
+        let service_ref: ServiceReference = JsonProvider::parse(&content).await.map_err(|e| {
+            ExtractorError::service_reference_parse_error_with_source(
+                service_name,
+                format!(
+                    "Failed to parse service reference content. Detailed error: {}",
+                    e
+                ),
+                e,
+            )
+        })?;
+        self.service_cache.write().await.insert(
+            service_name.to_string(),
+            (service_ref.clone(), SystemTime::now()),
+        );
+        Ok(Some(service_ref))
+    }
+}
+
+/// Response to a (conditional) request of the remote loader
+enum Fetched {
+    /// The cached entry is still current
+    NotModified(CacheEntry),
+    Modified {
+        content: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
 /// Service Reference Loader
 ///
-/// This loader provides functionality to load AWS service definition files
-/// with exact service name matching and thread-safe caching. Service names
-/// must match exactly between input and Service Reference Name (case-sensitive).
+/// Loads AWS service definition files from the service reference endpoint, or from an internal
+/// mirror serving the same mapping index, and caches them on the file system. A snapshot created
+/// with `download_service_reference_snapshot` takes precedence over the network, and offline mode
+/// never touches the network.
 #[derive(Debug)]
-pub(crate) struct RemoteServiceReferenceLoader {
+pub struct RemoteServiceReferenceLoader {
     client: Client,
     service_reference_mapping: OnceCell<ServiceReferenceMapping>,
-    service_cache: RwLock<HashMap<String, (ServiceReference, SystemTime)>>,
     mapping_url: String,
+    file_cache: Option<FileCache>,
+    snapshot: Option<ServiceReferenceSnapshot>,
+    offline: bool,
+}
+
+/// Builder for a [`RemoteServiceReferenceLoader`]
+#[derive(Debug, Clone)]
+pub struct RemoteServiceReferenceLoaderBuilder {
+    endpoint: String,
+    ca_certificate: Option<PathBuf>,
+    proxy: Option<String>,
     disable_file_system_cache: bool,
+    cache_directory: Option<PathBuf>,
+    snapshot: Option<PathBuf>,
+    offline: bool,
 }
 
-impl RemoteServiceReferenceLoader {
-    pub(crate) fn new(disable_file_system_cache: bool) -> crate::errors::Result<Self> {
-        Ok(Self {
-            client: Self::create_client()?,
+impl Default for RemoteServiceReferenceLoaderBuilder {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_SERVICE_REFERENCE_ENDPOINT.to_string(),
+            ca_certificate: None,
+            proxy: None,
+            disable_file_system_cache: false,
+            cache_directory: None,
+            snapshot: None,
+            offline: false,
+        }
+    }
+}
+
+impl RemoteServiceReferenceLoaderBuilder {
+    /// URL of the service reference mapping index
+    ///
+    /// Defaults to `https://servicereference.us-east-1.amazonaws.com`. Set it to use an
+    /// internal mirror, whose mapping index lists the URLs of the mirrored service references.
+    #[must_use]
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Trust the PEM encoded CA certificates in `path`, in addition to the system roots
+    #[must_use]
+    pub fn with_ca_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_certificate = Some(path.into());
+        self
+    }
+
+    /// Send all requests through the proxy at `url`
+    ///
+    /// Without it, the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables apply.
+    #[must_use]
+    pub fn with_proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Disable the file system cache of fetched service references
+    #[must_use]
+    pub fn with_disable_file_system_cache(mut self, disable_file_system_cache: bool) -> Self {
+        self.disable_file_system_cache = disable_file_system_cache;
+        self
+    }
+
+    /// Cache fetched service references in `directory` instead of the user cache directory
+    #[must_use]
+    pub fn with_cache_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.cache_directory = Some(directory.into());
+        self
+    }
+
+    /// Load service references from the snapshot directory or bundle file at `path` first
+    #[must_use]
+    pub fn with_snapshot(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot = Some(path.into());
+        self
+    }
+
+    /// Never fetch service references from the network
+    #[must_use]
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Apply every setting of `source`
+    #[must_use]
+    pub fn with_source(mut self, source: &ServiceReferenceSource) -> Self {
+        if let Some(endpoint) = &source.endpoint {
+            self.endpoint = endpoint.clone();
+        }
+        if source.ca_certificate.is_some() {
+            self.ca_certificate = source.ca_certificate.clone();
+        }
+        if source.proxy.is_some() {
+            self.proxy = source.proxy.clone();
+        }
+        if source.snapshot.is_some() {
+            self.snapshot = source.snapshot.clone();
+        }
+        self.offline |= source.offline;
+        self
+    }
+
+    /// Create the loader
+    ///
+    /// # Errors
+    /// Returns an error if the endpoint or proxy is not a valid URL, the CA certificate cannot be
+    /// read, or the snapshot cannot be opened or its checksums do not match
+    pub fn build(self) -> crate::errors::Result<RemoteServiceReferenceLoader> {
+        Url::parse(&self.endpoint).map_err(|e| {
+            ExtractorError::configuration_with_source(
+                format!("Invalid service reference endpoint '{}'", self.endpoint),
+                e,
+            )
+        })?;
+
+        Ok(RemoteServiceReferenceLoader {
+            client: RemoteServiceReferenceLoader::create_client(
+                self.ca_certificate.as_deref(),
+                self.proxy.as_deref(),
+            )?,
             service_reference_mapping: OnceCell::new(),
-            service_cache: RwLock::new(HashMap::new()),
-            mapping_url: "https://servicereference.us-east-1.amazonaws.com".to_string(),
-            disable_file_system_cache,
+            mapping_url: self.endpoint,
+            file_cache: (!self.disable_file_system_cache).then(|| {
+                self.cache_directory
+                    .map(FileCache::new)
+                    .unwrap_or_else(FileCache::default_location)
+            }),
+            snapshot: self
+                .snapshot
+                .as_deref()
+                .map(ServiceReferenceSnapshot::open)
+                .transpose()?,
+            offline: self.offline,
         })
     }
+}
 
-    #[cfg(test)]
-    pub(crate) fn with_mapping_url(mut self, url: String) -> Self {
-        self.mapping_url = url;
-        self
+impl RemoteServiceReferenceLoader {
+    /// Create a loader for the service reference endpoint
+    pub fn new(disable_file_system_cache: bool) -> crate::errors::Result<Self> {
+        Self::builder()
+            .with_disable_file_system_cache(disable_file_system_cache)
+            .build()
+    }
+
+    /// Builder for a loader with a mirror endpoint, CA certificate, proxy, snapshot or offline mode
+    pub fn builder() -> RemoteServiceReferenceLoaderBuilder {
+        RemoteServiceReferenceLoaderBuilder::default()
     }
 
     async fn get_or_init_mapping(&self) -> crate::errors::Result<&ServiceReferenceMapping> {
         self.service_reference_mapping
             .get_or_try_init(|| async {
-                let json_text = self
-                    .client
-                    .get(&self.mapping_url)
-                    .send()
-                    .await
-                    .map_err(|e| {
-                        ExtractorError::service_reference_parse_error_with_source(
-                            "RemoteServiceReferenceLoaderMappingInitialization",
-                            "Failed to send request".to_string(),
-                            e,
-                        )
-                    })?
-                    .error_for_status()
-                    .map_err(|e| {
-                        ExtractorError::service_reference_parse_error_with_source(
-                            "RemoteServiceReferenceLoaderMappingInitialization",
-                            "Failed to fetch mapping".to_string(),
-                            e,
-                        )
-                    })?
-                    .text()
+                let json_text = match &self.snapshot {
+                    Some(snapshot) => snapshot.mapping().to_string(),
+                    None => self.load_mapping_text().await?,
+                };
+                Self::parse_mapping(&json_text)
+            })
+            .await
+    }
+
+    fn parse_mapping(json_text: &str) -> crate::errors::Result<ServiceReferenceMapping> {
+        let json_value: serde_json::Value = serde_json::from_str(json_text).map_err(|e| {
+            ExtractorError::service_reference_parse_error_with_source(
+                "RemoteServiceReferenceLoaderMappingInitialization",
+                "Failed to parse JSON".to_string(),
+                e,
+            )
+        })?;
+
+        let mapping = deserialize_service_reference_mapping(json_value).map_err(|e| {
+            ExtractorError::service_reference_parse_error_with_source(
+                "RemoteServiceReferenceLoaderMappingInitialization",
+                "Failed to deserialize mapping".to_string(),
+                e,
+            )
+        })?;
+
+        Ok(ServiceReferenceMapping {
+            service_reference_mapping: mapping,
+            version: sha256(json_text.as_bytes()),
+        })
+    }
+
+    /// Mapping index from the file system cache, revalidated once it is stale
+    async fn load_mapping_text(&self) -> crate::errors::Result<String> {
+        let Some(file_cache) = &self.file_cache else {
+            return self.fetch_mapping_text().await;
+        };
+
+        // Offline mode uses the cached mapping index regardless of its age and origin
+        let cached = file_cache
+            .read(MAPPING_ENTRY_NAME)
+            .await?
+            .filter(|entry| self.offline || entry.metadata.url == self.mapping_url);
+        if let Some(entry) = &cached {
+            if self.offline || entry.metadata.is_fresh(CACHE_DURATION) {
+                return Ok(entry.content.clone());
+            }
+        }
+        if self.offline {
+            return Err(self.offline_error(MAPPING_ENTRY_NAME));
+        }
+
+        let entry = match self
+            .fetch(MAPPING_ENTRY_NAME, &self.mapping_url, cached)
+            .await?
+        {
+            Fetched::NotModified(entry) => entry,
+            Fetched::Modified {
+                content,
+                etag,
+                last_modified,
+            } => CacheEntry::new(
+                self.mapping_url.clone(),
+                etag,
+                last_modified,
+                String::new(),
+                content,
+            ),
+        };
+        // The version of the mapping index is its own checksum
+        let version = entry.metadata.sha256.clone();
+        let entry = entry.revalidated(version);
+        self.write_cache_entry(MAPPING_ENTRY_NAME, &entry).await;
+        Ok(entry.content)
+    }
+
+    /// Mapping index from the network
+    async fn fetch_mapping_text(&self) -> crate::errors::Result<String> {
+        if self.offline {
+            return Err(self.offline_error(MAPPING_ENTRY_NAME));
+        }
+        self.fetch_text(
+            "RemoteServiceReferenceLoaderMappingInitialization",
+            &self.mapping_url,
+        )
+        .await
+    }
+
+    /// GET `url`, conditionally on the validators of `cached` if given
+    async fn fetch(
+        &self,
+        name: &str,
+        url: &str,
+        cached: Option<CacheEntry>,
+    ) -> crate::errors::Result<Fetched> {
+        let mut request = self.client.get(url);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.metadata.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.metadata.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send().await.map_err(|e| {
+            ExtractorError::service_reference_parse_error_with_source(
+                name,
+                "Failed to send request".to_string(),
+                e,
+            )
+        })?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                return Ok(Fetched::NotModified(cached));
+            }
+        }
+        let response = response.error_for_status().map_err(|e| {
+            ExtractorError::service_reference_parse_error_with_source(
+                name,
+                "Failed to fetch service reference data".to_string(),
+                e,
+            )
+        })?;
+
+        let header = |header_name| {
+            response
+                .headers()
+                .get(header_name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let content = response.text().await.map_err(|e| {
+            ExtractorError::service_reference_parse_error_with_source(
+                name,
+                "Failed to read service reference response".to_string(),
+                e,
+            )
+        })?;
+        Ok(Fetched::Modified {
+            content,
+            etag,
+            last_modified,
+        })
+    }
+
+    /// GET `url` unconditionally
+    async fn fetch_text(&self, name: &str, url: &str) -> crate::errors::Result<String> {
+        match self.fetch(name, url, None).await? {
+            Fetched::NotModified(entry) => Ok(entry.content),
+            Fetched::Modified { content, .. } => Ok(content),
+        }
+    }
+
+    async fn write_cache_entry(&self, name: &str, entry: &CacheEntry) {
+        if let Some(file_cache) = &self.file_cache {
+            // Failing to cache is not fatal, the entry is fetched again next time
+            if let Err(e) = file_cache.write(name, entry).await {
+                log::debug!("Failed to cache service reference '{}': {}", name, e);
+            }
+        }
+    }
+
+    fn offline_error(&self, name: &str) -> ExtractorError {
+        let location = match (&self.snapshot, &self.file_cache) {
+            (Some(snapshot), _) => snapshot.path().display().to_string(),
+            (None, Some(file_cache)) => file_cache.directory().display().to_string(),
+            (None, None) => self.mapping_url.clone(),
+        };
+        ExtractorError::ServiceReferenceNotFound {
+            service_name: name.to_string(),
+            path: format!("{} (offline mode)", location),
+        }
+    }
+
+    /// Download the mapping index and service references into a snapshot at `output`
+    ///
+    /// Downloads all services of the mapping index, or only `services` if given.
+    pub(crate) async fn download_snapshot(
+        &self,
+        output: &Path,
+        services: Option<&[String]>,
+    ) -> crate::errors::Result<ServiceReferenceSnapshotManifest> {
+        let mapping_text = self.fetch_mapping_text().await?;
+        let mapping = Self::parse_mapping(&mapping_text)?.service_reference_mapping;
+
+        let selected: Vec<(&String, &Url)> = match services {
+            Some(services) => services
+                .iter()
+                .map(|service| {
+                    mapping.get_key_value(service).ok_or_else(|| {
+                        ExtractorError::ServiceReferenceNotFound {
+                            service_name: service.clone(),
+                            path: self.mapping_url.clone(),
+                        }
+                    })
+                })
+                .collect::<crate::errors::Result<_>>()?,
+            None => mapping.iter().collect(),
+        };
+        selected
+            .iter()
+            .try_for_each(|(service_name, _)| check_service_name(service_name))?;
+
+        let contents: BTreeMap<String, String> = futures::stream::iter(selected)
+            .map(|(service_name, service_url)| async move {
+                let content = self.fetch_text(service_name, service_url.as_str()).await?;
+                // Only valid service references go into a snapshot
+                JsonProvider::parse::<ServiceReference>(&content)
                     .await
                     .map_err(|e| {
                         ExtractorError::service_reference_parse_error_with_source(
-                            "RemoteServiceReferenceLoaderMappingInitialization",
-                            "Failed to read response".to_string(),
-                            e,
-                        )
-                    })?;
-
-                let json_value: serde_json::Value =
-                    serde_json::from_str(&json_text).map_err(|e| {
-                        ExtractorError::service_reference_parse_error_with_source(
-                            "RemoteServiceReferenceLoaderMappingInitialization",
-                            "Failed to parse JSON".to_string(),
+                            service_name.as_str(),
+                            "Failed to parse service reference content",
                             e,
                         )
                     })?;
+                Ok::<_, ExtractorError>((service_name.clone(), content))
+            })
+            .buffer_unordered(DOWNLOAD_CONCURRENCY)
+            .try_collect()
+            .await?;
 
-                let mapping = deserialize_service_reference_mapping(json_value).map_err(|e| {
-                    ExtractorError::service_reference_parse_error_with_source(
-                        "RemoteServiceReferenceLoaderMappingInitialization",
-                        "Failed to deserialize mapping".to_string(),
-                        e,
-                    )
-                })?;
+        write_snapshot(output, &self.mapping_url, mapping_text, contents)
+    }
 
-                Ok(ServiceReferenceMapping {
-                    service_reference_mapping: mapping,
-                })
-            })
-            .await
+    /// Load all services of the mapping index, or only `services` if given, into the file
+    /// system cache
+    ///
+    /// Returns the sorted names of the cached services.
+    pub(crate) async fn warm_cache(
+        &self,
+        services: Option<&[String]>,
+    ) -> crate::errors::Result<Vec<String>> {
+        let mapping = &self.get_or_init_mapping().await?.service_reference_mapping;
+        let mut selected: Vec<String> = match services {
+            Some(services) => {
+                if let Some(unknown) = services.iter().find(|s| !mapping.contains_key(*s)) {
+                    return Err(ExtractorError::ServiceReferenceNotFound {
+                        service_name: unknown.clone(),
+                        path: self.mapping_url.clone(),
+                    });
+                }
+                services.to_vec()
+            }
+            None => mapping.keys().cloned().collect(),
+        };
+        selected.sort();
+        selected.dedup();
+
+        futures::stream::iter(&selected)
+            .map(|service_name| self.load_content(service_name))
+            .buffer_unordered(DOWNLOAD_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(selected)
     }
 
-    fn create_client() -> crate::errors::Result<Client> {
+    fn create_client(
+        ca_certificate: Option<&Path>,
+        proxy: Option<&str>,
+    ) -> crate::errors::Result<Client> {
         let user_agent_suffix = if cfg!(feature = "integ-test") {
             "-integration-test"
         } else {
@@ -397,96 +869,97 @@ impl RemoteServiceReferenceLoader {
             user_agent_suffix,
             env!("CARGO_PKG_VERSION")
         );
-        Client::builder()
-            .user_agent(user_agent)
-            .build()
-            .map_err(|e| {
-                ExtractorError::service_reference_parse_error_with_source(
-                    "RemoteServiceReferenceLoaderClientInitialization",
-                    "Failed to create service reference client".to_string(),
+        let mut builder = Client::builder().user_agent(user_agent);
+
+        if let Some(path) = ca_certificate {
+            let pem =
+                std::fs::read(path).map_err(|e| ExtractorError::file_system("read", path, e))?;
+            let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| {
+                ExtractorError::configuration_with_source(
+                    format!("Invalid CA certificate file '{}'", path.display()),
                     e,
                 )
-            })
-    }
+            })?;
+            if certificates.is_empty() {
+                return Err(ExtractorError::validation(format!(
+                    "CA certificate file '{}' does not contain any PEM certificate",
+                    path.display()
+                )));
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
 
-    fn get_cache_dir() -> PathBuf {
-        // not using tempfile crate
-        // instead, using the std to resolve temp dir and then manage the file itself
-        // file deletion is delegated to the OS.
-        let cache_dir = std::env::temp_dir().join(IAM_POLICY_AUTOPILOT);
-        let _ = std::fs::create_dir_all(&cache_dir);
-        cache_dir
-    }
+        if let Some(proxy) = proxy {
+            let proxy = Proxy::all(proxy).map_err(|e| {
+                ExtractorError::configuration_with_source(format!("Invalid proxy '{}'", proxy), e)
+            })?;
+            builder = builder.proxy(proxy);
+        }
 
-    fn get_cache_path(service_name: &str) -> PathBuf {
-        Self::get_cache_dir().join(format!("{}.json", service_name))
+        builder.build().map_err(|e| {
+            ExtractorError::service_reference_parse_error_with_source(
+                "RemoteServiceReferenceLoaderClientInitialization",
+                "Failed to create service reference client".to_string(),
+                e,
+            )
+        })
     }
 
-    async fn is_cache_valid(path: &PathBuf) -> bool {
-        if let Ok(metadata) = fs::metadata(path).await {
-            if let Ok(modified) = metadata.modified() {
-                if let Ok(elapsed) = SystemTime::now().duration_since(modified) {
-                    return elapsed < Duration::from_secs(DEFAULT_CACHE_DURATION_IN_SECONDS);
-                }
+    /// Service reference JSON of `service_name` from the snapshot, the file system cache or the
+    /// network, in that order
+    ///
+    /// Cache entries are revalidated once they are stale or were fetched with another version of
+    /// the mapping index.
+    async fn load_content(&self, service_name: &str) -> crate::errors::Result<Option<String>> {
+        if let Some(snapshot) = &self.snapshot {
+            if let Some(content) = snapshot.read_service(service_name).await? {
+                return Ok(Some(content));
             }
         }
-        false
-    }
 
-    pub(crate) async fn load(
-        &self,
-        service_name: &str,
-    ) -> crate::errors::Result<Option<ServiceReference>> {
-        if let Some((cached, timestamp)) = self.service_cache.read().await.get(service_name) {
-            if let Ok(elapsed) = SystemTime::now().duration_since(*timestamp) {
-                if elapsed < Duration::from_secs(DEFAULT_CACHE_DURATION_IN_SECONDS) {
-                    return Ok(Some(cached.clone()));
-                }
+        let cached = match &self.file_cache {
+            Some(file_cache) => file_cache.read(service_name).await?,
+            None => None,
+        };
+        // Offline mode uses cache entries regardless of their age
+        if self.offline {
+            if let Some(entry) = cached {
+                return Ok(Some(entry.content));
             }
         }
 
-        // check temp file
-        let cache_path = Self::get_cache_path(service_name);
-        if !self.disable_file_system_cache && Self::is_cache_valid(&cache_path).await {
-            if let Ok(content) = fs::read_to_string(&cache_path).await {
-                if let Ok(service_ref) = JsonProvider::parse::<ServiceReference>(&content).await {
-                    self.service_cache.write().await.insert(
-                        service_name.to_string(),
-                        (service_ref.clone(), SystemTime::now()),
-                    );
-                    return Ok(Some(service_ref));
-                }
-            }
+        let mapping = self.get_or_init_mapping().await?;
+        let Some(service_url) = mapping.service_reference_mapping.get(service_name) else {
+            return Ok(None);
+        };
+        if self.offline {
+            return Err(self.offline_error(service_name));
         }
 
-        let mapping = self.get_or_init_mapping().await?;
-        let service_url = mapping.service_reference_mapping.get(service_name);
-
-        match service_url {
-            Some(service_url) => {
-                let service_reference_content = self
-                    .client
-                    .get(service_url.as_ref())
-                    .send()
-                    .await
-                    .map_err(|e| {
-                        ExtractorError::service_reference_parse_error_with_source(
-                            service_name,
-                            "Failed to fetch service reference data".to_string(),
-                            e,
-                        )
-                    })?
-                    .text()
-                    .await
-                    .map_err(|e| {
-                        ExtractorError::service_reference_parse_error_with_source(
-                            service_name,
-                            "Failed to read service reference response".to_string(),
-                            e,
-                        )
-                    })?;
+        // Entries fetched from another endpoint cannot be revalidated
+        let cached = cached.filter(|entry| entry.metadata.url == service_url.as_str());
+        if let Some(entry) = &cached {
+            if entry.metadata.mapping_version == mapping.version
+                && entry.metadata.is_fresh(CACHE_DURATION)
+            {
+                return Ok(Some(entry.content.clone()));
+            }
+        }
 
-                let service_ref: ServiceReference = JsonProvider::parse(&service_reference_content)
+        let entry = match self
+            .fetch(service_name, service_url.as_str(), cached)
+            .await?
+        {
+            Fetched::NotModified(entry) => entry.revalidated(mapping.version.clone()),
+            Fetched::Modified {
+                content,
+                etag,
+                last_modified,
+            } => {
+                // Only valid service references are cached
+                JsonProvider::parse::<ServiceReference>(&content)
                     .await
                     .map_err(|e| {
                         ExtractorError::service_reference_parse_error_with_source(
@@ -498,18 +971,24 @@ impl RemoteServiceReferenceLoader {
                             e,
                         )
                     })?;
-                // persist content into the temp file as well
-                if !self.disable_file_system_cache {
-                    let _ = fs::write(&cache_path, &service_reference_content).await;
-                }
-                self.service_cache.write().await.insert(
-                    service_name.to_string(),
-                    (service_ref.clone(), SystemTime::now()),
-                );
-                Ok(Option::Some(service_ref))
+                CacheEntry::new(
+                    service_url.to_string(),
+                    etag,
+                    last_modified,
+                    mapping.version.clone(),
+                    content,
+                )
             }
-            None => Ok(None),
-        }
+        };
+        self.write_cache_entry(service_name, &entry).await;
+        Ok(Some(entry.content))
+    }
+}
+
+#[async_trait]
+impl ServiceReferenceLoader for RemoteServiceReferenceLoader {
+    async fn load_service_reference(&self, service_name: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.load_content(service_name).await?)
     }
 }
 
@@ -523,16 +1002,78 @@ mod tests {
         let loader = RemoteServiceReferenceLoader::new(false);
         assert!(loader.is_ok());
 
-        let loader = loader.unwrap();
-        assert!(loader.service_cache.read().await.is_empty());
+        let cache = ServiceReferenceCache::new(Arc::new(loader.unwrap()));
+        assert!(cache.service_cache.read().await.is_empty());
     }
 
     #[tokio::test]
     async fn test_create_client() {
-        let client = RemoteServiceReferenceLoader::create_client();
+        let client = RemoteServiceReferenceLoader::create_client(None, None);
         assert!(client.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_builder_validates_settings() {
+        let error = RemoteServiceReferenceLoader::builder()
+            .with_endpoint("not a url")
+            .build()
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Invalid service reference endpoint"));
+
+        let error = RemoteServiceReferenceLoader::builder()
+            .with_proxy("http://[invalid")
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("Invalid proxy"));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let ca_certificate = dir.path().join("ca.pem");
+        std::fs::write(&ca_certificate, "not a certificate").unwrap();
+        let error = RemoteServiceReferenceLoader::builder()
+            .with_ca_certificate(&ca_certificate)
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("ca.pem"));
+    }
+
+    #[tokio::test]
+    async fn test_load_through_mirror_and_proxy() {
+        let mock_server = wiremock::MockServer::start().await;
+        let mirror = format!("{}/mirror/", mock_server.uri());
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/mirror/"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_json(
+                serde_json::json!([{"service": "sqs", "url": format!("{}sqs.json", mirror)}]),
+            ))
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/mirror/sqs.json"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"Name": "sqs", "Actions": []})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let loader = RemoteServiceReferenceLoader::builder()
+            .with_source(&ServiceReferenceSource {
+                endpoint: Some(mirror),
+                ..ServiceReferenceSource::default()
+            })
+            .with_disable_file_system_cache(true)
+            .build()
+            .unwrap();
+        let cache = ServiceReferenceCache::new(Arc::new(loader));
+        assert_eq!(
+            cache.load("sqs").await.unwrap().unwrap().service_name,
+            "sqs"
+        );
+        assert!(cache.load("s3").await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_cache_functionality() {
         let (_, loader) = mock_remote_service_reference::setup_mock_server_with_loader().await;
@@ -597,7 +1138,8 @@ mod tests {
     #[tokio::test]
     #[ignore] // Use `cargo test -- --ignored` to run this test
     async fn test_load_from_service_reference_success() {
-        let loader = RemoteServiceReferenceLoader::new(false).unwrap();
+        let loader =
+            ServiceReferenceCache::new(Arc::new(RemoteServiceReferenceLoader::new(false).unwrap()));
         let result = loader.load("s3").await;
 
         match result {
@@ -617,10 +1159,81 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_download_snapshot_and_load_offline() {
+        let (mock_server, _) = mock_remote_service_reference::setup_mock_server_with_loader_without_operation_to_action_mapping().await;
+        let loader = mock_remote_service_reference::mock_remote_loader(&mock_server);
+        let dir = tempfile::TempDir::new().unwrap();
+        let snapshot_path = dir.path().join("snapshot.json");
+
+        let manifest = loader
+            .download_snapshot(&snapshot_path, None)
+            .await
+            .unwrap();
+        assert_eq!(manifest.services.keys().collect::<Vec<_>>(), vec!["s3"]);
+
+        let error = loader
+            .download_snapshot(&dir.path().join("other"), Some(&["ec2".to_string()]))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("'ec2'"));
+
+        // The offline loader never reaches this unroutable endpoint
+        let offline_loader = RemoteServiceReferenceLoader::builder()
+            .with_endpoint("http://127.0.0.1:9")
+            .with_disable_file_system_cache(true)
+            .with_snapshot(snapshot_path)
+            .with_offline(true)
+            .build()
+            .unwrap();
+        let offline_loader = ServiceReferenceCache::new(Arc::new(offline_loader));
+        let service_ref = offline_loader.load("s3").await.unwrap().unwrap();
+        assert_eq!(service_ref.service_name, "s3");
+        assert!(service_ref.actions.contains_key("GetObject"));
+        assert!(offline_loader.load("ec2").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_download_snapshot_rejects_escaping_service_names() {
+        let mock_server = wiremock::MockServer::start().await;
+        mock_remote_service_reference::mock_server_service_reference_response(
+            &mock_server,
+            "../../.bashrc",
+            serde_json::json!({"Name": "bashrc", "Actions": [], "Resources": []}),
+        )
+        .await;
+        let loader = mock_remote_service_reference::mock_remote_loader(&mock_server);
+        let dir = tempfile::TempDir::new().unwrap();
+        let snapshot_path = dir.path().join("a/b/snapshot");
+
+        let error = loader
+            .download_snapshot(&snapshot_path, None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("'../../.bashrc'"));
+        assert!(!snapshot_path.exists());
+        assert!(!dir.path().join("a/.bashrc.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_offline_without_snapshot_fails_on_cache_miss() {
+        let loader = RemoteServiceReferenceLoader::builder()
+            .with_endpoint("http://127.0.0.1:9")
+            .with_disable_file_system_cache(true)
+            .with_offline(true)
+            .build()
+            .unwrap();
+        let loader = ServiceReferenceCache::new(Arc::new(loader));
+
+        let error = loader.load("s3").await.unwrap_err();
+        assert!(error.to_string().contains("offline mode"));
+    }
+
     #[tokio::test]
     #[ignore] // Use `cargo test -- --ignored` to run this test
     async fn test_load_nonexistent_service() {
-        let loader = RemoteServiceReferenceLoader::new(false).unwrap();
+        let loader =
+            ServiceReferenceCache::new(Arc::new(RemoteServiceReferenceLoader::new(false).unwrap()));
         let result = loader.load("nonexistent-service-xyz").await;
 
         assert!(result.is_ok());
@@ -667,51 +1280,93 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_cache_dir() {
-        let cache_dir = RemoteServiceReferenceLoader::get_cache_dir();
-        assert!(cache_dir.ends_with("IAMPolicyAutopilot"));
-        assert!(cache_dir.exists());
-    }
-
-    #[tokio::test]
-    async fn test_get_cache_path() {
-        let cache_path = RemoteServiceReferenceLoader::get_cache_path("s3");
-        assert!(
-            cache_path.ends_with("IAMPolicyAutopilot/s3.json")
-                || cache_path.ends_with("IAMAutoPilot\\s3.json")
-        );
-    }
+    async fn test_filesystem_cache() {
+        let (mock_server, _) = mock_remote_service_reference::setup_mock_server_with_loader().await;
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let loader = RemoteServiceReferenceLoader::builder()
+            .with_endpoint(mock_server.uri())
+            .with_cache_directory(cache_dir.path())
+            .build()
+            .unwrap();
 
-    #[tokio::test]
-    async fn test_is_cache_valid_nonexistent() {
-        let path = PathBuf::from("/nonexistent/path/file.json");
-        assert!(!RemoteServiceReferenceLoader::is_cache_valid(&path).await);
-    }
+        let result = loader.load_service_reference("s3").await;
+        assert!(result.unwrap().is_some());
 
-    #[tokio::test]
-    async fn test_is_cache_valid_fresh() {
-        let cache_path = RemoteServiceReferenceLoader::get_cache_path("test_fresh");
-        let _ = fs::write(&cache_path, "test content").await;
+        let cache = FileCache::new(cache_dir.path().to_path_buf());
+        let entry = cache.read("s3").await.unwrap().unwrap();
+        assert_eq!(entry.metadata.url, format!("{}/s3.json", mock_server.uri()));
+        assert!(cache.read(MAPPING_ENTRY_NAME).await.unwrap().is_some());
 
-        assert!(RemoteServiceReferenceLoader::is_cache_valid(&cache_path).await);
-        let _ = fs::remove_file(&cache_path).await;
+        // Offline, the cached entry is used without any request
+        let offline = RemoteServiceReferenceLoader::builder()
+            .with_endpoint("http://127.0.0.1:9")
+            .with_cache_directory(cache_dir.path())
+            .with_offline(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            offline.load_service_reference("s3").await.unwrap(),
+            Some(entry.content)
+        );
     }
 
     #[tokio::test]
-    async fn test_filesystem_cache() {
-        let (_, mut loader) = mock_remote_service_reference::setup_mock_server_with_loader().await;
-        // setup_mock_server_with_loader disables file system cache by default
-        loader.disable_file_system_cache = false;
-        let cache_path = RemoteServiceReferenceLoader::get_cache_path("s3");
-        let _ = fs::remove_file(&cache_path).await;
-
-        let result = loader.load("s3").await;
-        assert!(result.is_ok());
-        assert!(cache_path.exists());
-
-        let cached_content = fs::read_to_string(&cache_path).await;
-        assert!(cached_content.is_ok());
-        let _ = fs::remove_file(&cache_path).await;
+    async fn test_stale_entry_is_revalidated_with_etag() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {"service": "s3", "url": format!("{}/s3.json", mock_server.uri())}
+            ])))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/s3.json"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/s3.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_json(serde_json::json!({"Name": "s3", "Actions": []})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let cache = FileCache::new(cache_dir.path().to_path_buf());
+        let build = || {
+            RemoteServiceReferenceLoader::builder()
+                .with_endpoint(mock_server.uri())
+                .with_cache_directory(cache_dir.path())
+                .build()
+                .unwrap()
+        };
+        build().load_service_reference("s3").await.unwrap();
+        let mut entry = cache.read("s3").await.unwrap().unwrap();
+        assert_eq!(entry.metadata.etag.as_deref(), Some("\"v1\""));
+
+        // Make the entry stale, a new loader must revalidate it rather than download it again
+        entry.metadata.fetched_at = 0;
+        cache.write("s3", &entry).await.unwrap();
+        let content = build().load_service_reference("s3").await.unwrap();
+        assert_eq!(content, Some(entry.content));
+        assert!(cache
+            .read("s3")
+            .await
+            .unwrap()
+            .unwrap()
+            .metadata
+            .is_fresh(CACHE_DURATION));
     }
 
     #[tokio::test]
@@ -758,6 +1413,51 @@ mod tests {
         assert_eq!(operation.authorized_actions[0].name, "s3:GetObject");
     }
 
+    #[test]
+    fn test_service_reference_access_levels() {
+        let json = r#"{
+            "Name": "iam",
+            "Actions": [
+                {
+                    "Name": "GetRole",
+                    "Annotations": {"Properties": {"IsList": false, "IsPermissionManagement": false, "IsTaggingOnly": false, "IsWrite": false}}
+                },
+                {
+                    "Name": "ListRoles",
+                    "Annotations": {"Properties": {"IsList": true, "IsPermissionManagement": false, "IsTaggingOnly": false, "IsWrite": false}}
+                },
+                {
+                    "Name": "TagRole",
+                    "Annotations": {"Properties": {"IsList": false, "IsPermissionManagement": false, "IsTaggingOnly": true, "IsWrite": true}}
+                },
+                {
+                    "Name": "CreateRole",
+                    "Annotations": {"Properties": {"IsList": false, "IsPermissionManagement": false, "IsTaggingOnly": false, "IsWrite": true}}
+                },
+                {
+                    "Name": "PutRolePolicy",
+                    "Annotations": {"Properties": {"IsList": false, "IsPermissionManagement": true, "IsTaggingOnly": false, "IsWrite": true}}
+                },
+                {
+                    "Name": "GetUser"
+                }
+            ],
+            "Resources": []
+        }"#;
+
+        let service_ref: ServiceReference = serde_json::from_str(json).unwrap();
+        let access_level = |name: &str| service_ref.actions[name].access_level;
+        assert_eq!(access_level("GetRole"), Some(AccessLevel::Read));
+        assert_eq!(access_level("ListRoles"), Some(AccessLevel::List));
+        assert_eq!(access_level("TagRole"), Some(AccessLevel::Tagging));
+        assert_eq!(access_level("CreateRole"), Some(AccessLevel::Write));
+        assert_eq!(
+            access_level("PutRolePolicy"),
+            Some(AccessLevel::PermissionsManagement)
+        );
+        assert_eq!(access_level("GetUser"), None);
+    }
+
     #[tokio::test]
     async fn test_service_reference_deserialization_empty_authorized_actions() {
         let json = r#"{
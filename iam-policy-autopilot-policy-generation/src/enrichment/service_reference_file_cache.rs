@@ -0,0 +1,563 @@
+//! Persistent file system cache of service references
+//!
+//! Entries are stored under the user cache directory (`$XDG_CACHE_HOME`, `~/.cache`,
+//! `~/Library/Caches` or `%LOCALAPPDATA%`), or the directory given by
+//! `IAM_POLICY_AUTOPILOT_CACHE_DIR`:
+//!
+//! ```text
+//! iam-policy-autopilot/service-reference/v1/
+//!   _mapping.json    mapping index
+//!   s3.json          one entry per service
+//! ```
+//!
+//! Each entry holds the content together with its metadata: the URL it was fetched from, the
+//! `ETag` and `Last-Modified` validators for revalidation, the fetch time, the version of the
+//! mapping index the entry was fetched with, and a SHA-256 checksum. Entries are written to a
+//! temporary file and renamed, so concurrent runs never observe partially written entries, and
+//! entries failing the checksum are ignored.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::model::{
+    PruneServiceReferenceCacheResult, ServiceReferenceCacheEntryState,
+    ServiceReferenceCacheEntryStatus, ServiceReferenceCacheStatus,
+};
+use crate::enrichment::service_reference::CACHE_DURATION;
+use crate::enrichment::service_reference_loader::is_valid_service_file_name;
+use crate::enrichment::service_reference_snapshot::sha256;
+use crate::errors::{ExtractorError, Result};
+
+/// Environment variable overriding the cache directory
+pub(crate) const CACHE_DIR_ENV: &str = "IAM_POLICY_AUTOPILOT_CACHE_DIR";
+/// Name of the cache entry holding the mapping index
+///
+/// Service names never start with an underscore.
+pub(crate) const MAPPING_ENTRY_NAME: &str = "_mapping";
+// Bump when the entry format changes, so old entries are never read
+const CACHE_FORMAT_VERSION: &str = "v1";
+const TEMP_FILE_SUFFIX: &str = ".tmp";
+// Temporary files younger than this may belong to a write in progress, and are not pruned
+const TEMP_FILE_PRUNE_AGE: Duration = Duration::from_secs(600);
+
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Temporary file of an entry being written, removed unless it was renamed to the entry
+///
+/// Writes fail or are dropped, e.g. when a policy generation is cancelled, without leaving
+/// temporary files behind.
+struct TempFile {
+    path: PathBuf,
+    renamed: bool,
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.renamed {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Metadata of a cache entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct CacheEntryMetadata {
+    /// URL the content was fetched from
+    pub(crate) url: String,
+    /// `ETag` response header, for `If-None-Match` revalidation
+    pub(crate) etag: Option<String>,
+    /// `Last-Modified` response header, for `If-Modified-Since` revalidation
+    pub(crate) last_modified: Option<String>,
+    /// When the content was fetched or last revalidated, in seconds since the Unix epoch
+    pub(crate) fetched_at: u64,
+    /// Version of the mapping index the entry was fetched with
+    pub(crate) mapping_version: String,
+    /// Hex-encoded SHA-256 checksum of the content
+    pub(crate) sha256: String,
+}
+
+impl CacheEntryMetadata {
+    /// Whether the entry was fetched or revalidated less than `max_age` ago
+    pub(crate) fn is_fresh(&self, max_age: Duration) -> bool {
+        now().saturating_sub(self.fetched_at) < max_age.as_secs()
+    }
+}
+
+/// A cache entry read from disk, with a verified checksum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct CacheEntry {
+    pub(crate) metadata: CacheEntryMetadata,
+    pub(crate) content: String,
+}
+
+/// A file of the cache directory, as listed by [`FileCache::list`]
+#[derive(Debug)]
+struct CacheFile {
+    /// Entry name, i.e. the service name or [`MAPPING_ENTRY_NAME`]
+    name: String,
+    path: PathBuf,
+    size_bytes: u64,
+    /// The entry, or `None` if it is corrupt or a temporary file
+    entry: Option<CacheEntry>,
+    /// Whether this is a recent temporary file, possibly of a write in progress
+    in_flight: bool,
+}
+
+/// Persistent cache directory of service references
+#[derive(Debug, Clone)]
+pub(crate) struct FileCache {
+    directory: PathBuf,
+}
+
+impl FileCache {
+    /// Cache in the given directory, which is created on first write
+    pub(crate) fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    /// Cache in the default cache directory of the user
+    pub(crate) fn default_location() -> Self {
+        let root = std::env::var_os(CACHE_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| user_cache_dir().join("iam-policy-autopilot"));
+        Self::new(root.join("service-reference").join(CACHE_FORMAT_VERSION))
+    }
+
+    pub(crate) fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Path of the entry `name`, which must not escape the cache directory
+    fn entry_path(&self, name: &str) -> Result<PathBuf> {
+        if !is_valid_service_file_name(name) {
+            return Err(ExtractorError::validation(format!(
+                "Invalid service reference cache entry name '{}'",
+                name
+            )));
+        }
+        Ok(self.directory.join(format!("{}.json", name)))
+    }
+
+    /// Read the entry `name`, or `None` if it does not exist or is corrupt
+    ///
+    /// # Errors
+    /// Returns an error if `name` is not a valid entry name
+    pub(crate) async fn read(&self, name: &str) -> Result<Option<CacheEntry>> {
+        let Ok(content) = tokio::fs::read_to_string(self.entry_path(name)?).await else {
+            return Ok(None);
+        };
+        let entry = parse_entry(&content);
+        if entry.is_none() {
+            log::debug!("Ignoring corrupt service reference cache entry '{}'", name);
+        }
+        Ok(entry)
+    }
+
+    /// Atomically write the entry `name`
+    pub(crate) async fn write(&self, name: &str, entry: &CacheEntry) -> Result<()> {
+        let path = self.entry_path(name)?;
+        tokio::fs::create_dir_all(&self.directory)
+            .await
+            .map_err(|e| ExtractorError::file_system("create directory", &self.directory, e))?;
+
+        // Unique per process and write, so concurrent writers never share a temporary file
+        let temp_path = self.directory.join(format!(
+            ".{}.{}.{}{}",
+            name,
+            std::process::id(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed),
+            TEMP_FILE_SUFFIX
+        ));
+        let mut temp_file = TempFile {
+            path: temp_path,
+            renamed: false,
+        };
+        tokio::fs::write(&temp_file.path, serde_json::to_string(entry)?)
+            .await
+            .map_err(|e| ExtractorError::file_system("write", &temp_file.path, e))?;
+
+        tokio::fs::rename(&temp_file.path, &path)
+            .await
+            .map_err(|e| ExtractorError::file_system("rename", path, e))?;
+        temp_file.renamed = true;
+        Ok(())
+    }
+
+    /// All files of the cache directory, sorted by name
+    fn list(&self) -> Result<Vec<CacheFile>> {
+        let read_dir = match std::fs::read_dir(&self.directory) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(ExtractorError::file_system(
+                    "read directory",
+                    &self.directory,
+                    e,
+                ))
+            }
+        };
+
+        let mut files = Vec::new();
+        for dir_entry in read_dir {
+            let dir_entry = dir_entry
+                .map_err(|e| ExtractorError::file_system("read directory", &self.directory, e))?;
+            let path = dir_entry.path();
+            let file_name = dir_entry.file_name().to_string_lossy().into_owned();
+            let Some(name) = file_name.strip_suffix(".json") else {
+                if !file_name.ends_with(TEMP_FILE_SUFFIX) {
+                    continue;
+                }
+                let metadata = dir_entry.metadata().ok();
+                // Files without a modification time are assumed to be in flight
+                let in_flight = metadata
+                    .as_ref()
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_none_or(|age| age < TEMP_FILE_PRUNE_AGE);
+                files.push(CacheFile {
+                    name: file_name,
+                    size_bytes: metadata.map(|m| m.len()).unwrap_or_default(),
+                    path,
+                    entry: None,
+                    in_flight,
+                });
+                continue;
+            };
+            let entry = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| parse_entry(&content));
+            files.push(CacheFile {
+                name: name.to_string(),
+                size_bytes: dir_entry.metadata().map(|m| m.len()).unwrap_or_default(),
+                path,
+                entry,
+                in_flight: false,
+            });
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    /// Status of all files, with service entries checked against the cached mapping index
+    pub(crate) fn status(&self) -> Result<ServiceReferenceCacheStatus> {
+        let files = self.list()?;
+        let mapping_version = mapping_version(&files);
+
+        let mut status = ServiceReferenceCacheStatus {
+            directory: self.directory.clone(),
+            mapping: None,
+            entries: Vec::new(),
+            total_size_bytes: 0,
+        };
+        for file in &files {
+            status.total_size_bytes += file.size_bytes;
+            let entry_status = ServiceReferenceCacheEntryStatus {
+                name: file.name.clone(),
+                size_bytes: file.size_bytes,
+                fetched_at: file.entry.as_ref().map(|entry| entry.metadata.fetched_at),
+                mapping_version: file
+                    .entry
+                    .as_ref()
+                    .map(|entry| entry.metadata.mapping_version.clone()),
+                state: entry_state(file, mapping_version.as_deref()),
+            };
+            if file.name == MAPPING_ENTRY_NAME {
+                status.mapping = Some(entry_status);
+            } else {
+                status.entries.push(entry_status);
+            }
+        }
+        Ok(status)
+    }
+
+    /// Remove corrupt and outdated entries, or all entries if `all` is set
+    ///
+    /// Stale entries are kept, since revalidating them is cheaper than fetching them again.
+    /// Recent temporary files are always kept, since removing the temporary file of a concurrent
+    /// run would make its write fail.
+    pub(crate) fn prune(&self, all: bool) -> Result<PruneServiceReferenceCacheResult> {
+        let files = self.list()?;
+        let mapping_version = mapping_version(&files);
+
+        let mut result = PruneServiceReferenceCacheResult {
+            directory: self.directory.clone(),
+            removed: Vec::new(),
+            freed_bytes: 0,
+        };
+        for file in files {
+            let state = entry_state(&file, mapping_version.as_deref());
+            if file.in_flight {
+                continue;
+            }
+            if !all
+                && !matches!(
+                    state,
+                    ServiceReferenceCacheEntryState::Corrupt
+                        | ServiceReferenceCacheEntryState::Outdated
+                )
+            {
+                continue;
+            }
+            match std::fs::remove_file(&file.path) {
+                Ok(()) => {}
+                // Removed by a concurrent run
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(ExtractorError::file_system("remove", &file.path, e)),
+            }
+            result.freed_bytes += file.size_bytes;
+            result.removed.push(file.name);
+        }
+        Ok(result)
+    }
+}
+
+/// Version of the cached mapping index, which is its checksum
+fn mapping_version(files: &[CacheFile]) -> Option<String> {
+    files
+        .iter()
+        .find(|file| file.name == MAPPING_ENTRY_NAME)
+        .and_then(|file| file.entry.as_ref())
+        .map(|entry| entry.metadata.sha256.clone())
+}
+
+fn entry_state(file: &CacheFile, mapping_version: Option<&str>) -> ServiceReferenceCacheEntryState {
+    match &file.entry {
+        None => ServiceReferenceCacheEntryState::Corrupt,
+        Some(entry)
+            if file.name != MAPPING_ENTRY_NAME
+                && mapping_version
+                    .is_some_and(|version| version != entry.metadata.mapping_version) =>
+        {
+            ServiceReferenceCacheEntryState::Outdated
+        }
+        Some(entry) if entry.metadata.is_fresh(CACHE_DURATION) => {
+            ServiceReferenceCacheEntryState::Fresh
+        }
+        Some(_) => ServiceReferenceCacheEntryState::Stale,
+    }
+}
+
+impl CacheEntry {
+    /// Entry for freshly fetched content
+    pub(crate) fn new(
+        url: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+        mapping_version: String,
+        content: String,
+    ) -> Self {
+        Self {
+            metadata: CacheEntryMetadata {
+                url,
+                etag,
+                last_modified,
+                fetched_at: now(),
+                mapping_version,
+                sha256: sha256(content.as_bytes()),
+            },
+            content,
+        }
+    }
+
+    /// The entry after a successful revalidation with the given mapping version
+    pub(crate) fn revalidated(mut self, mapping_version: String) -> Self {
+        self.metadata.fetched_at = now();
+        self.metadata.mapping_version = mapping_version;
+        self
+    }
+}
+
+fn parse_entry(content: &str) -> Option<CacheEntry> {
+    let entry: CacheEntry = serde_json::from_str(content).ok()?;
+    (sha256(entry.content.as_bytes()) == entry.metadata.sha256).then_some(entry)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Platform cache directory, falling back to the temporary directory
+fn user_cache_dir() -> PathBuf {
+    let non_empty = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+    if cfg!(windows) {
+        if let Some(local_app_data) = non_empty("LOCALAPPDATA") {
+            return PathBuf::from(local_app_data);
+        }
+    } else if cfg!(target_os = "macos") {
+        if let Some(home) = non_empty("HOME") {
+            return PathBuf::from(home).join("Library").join("Caches");
+        }
+    } else {
+        // Relative XDG paths are invalid and must be ignored
+        if let Some(xdg_cache_home) = non_empty("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+        {
+            return xdg_cache_home;
+        }
+        if let Some(home) = non_empty("HOME") {
+            return PathBuf::from(home).join(".cache");
+        }
+    }
+    std::env::temp_dir()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(content: &str) -> CacheEntry {
+        CacheEntry::new(
+            "https://example.com/s3.json".to_string(),
+            Some("\"etag\"".to_string()),
+            None,
+            "mapping-v1".to_string(),
+            content.to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_write_read_and_list() {
+        let dir = TempDir::new().unwrap();
+        let cache = FileCache::new(dir.path().join("cache"));
+        assert!(cache.read("s3").await.unwrap().is_none());
+        assert!(cache.list().unwrap().is_empty());
+
+        cache.write("s3", &entry(r#"{"Name":"s3"}"#)).await.unwrap();
+        let read = cache.read("s3").await.unwrap().unwrap();
+        assert_eq!(read.content, r#"{"Name":"s3"}"#);
+        assert_eq!(read.metadata.etag.as_deref(), Some("\"etag\""));
+        assert!(read.metadata.is_fresh(Duration::from_secs(60)));
+        assert!(!read.metadata.is_fresh(Duration::ZERO));
+
+        // No temporary file is left behind
+        let files = cache.list().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "s3");
+        assert!(files[0].entry.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_entry_names_cannot_escape_the_directory() {
+        let dir = TempDir::new().unwrap();
+        let cache = FileCache::new(dir.path().join("cache"));
+        std::fs::write(
+            dir.path().join("outside.json"),
+            serde_json::to_string(&entry(r#"{"Name":"s3"}"#)).unwrap(),
+        )
+        .unwrap();
+
+        for name in ["../outside", "a/b", "", "s3.json"] {
+            assert!(cache.read(name).await.is_err(), "{name}");
+            assert!(cache.write(name, &entry("{}")).await.is_err(), "{name}");
+        }
+        assert!(!dir.path().join("cache").exists());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_entries_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        let cache = FileCache::new(dir.path().to_path_buf());
+        let mut tampered = entry(r#"{"Name":"s3"}"#);
+        tampered.content = r#"{"Name":"s"#.to_string();
+        std::fs::write(
+            dir.path().join("s3.json"),
+            serde_json::to_string(&tampered).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join(".sqs.1.0.tmp"), "partial").unwrap();
+
+        assert!(cache.read("s3").await.unwrap().is_none());
+        let files = cache.list().unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|file| file.entry.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_status_and_prune() {
+        let dir = TempDir::new().unwrap();
+        let cache = FileCache::new(dir.path().to_path_buf());
+        let mapping = CacheEntry::new(
+            "https://example.com".to_string(),
+            None,
+            None,
+            String::new(),
+            "[]".to_string(),
+        );
+        let mapping_version = mapping.metadata.sha256.clone();
+        let mapping = mapping.revalidated(mapping_version.clone());
+        cache.write(MAPPING_ENTRY_NAME, &mapping).await.unwrap();
+
+        let mut current = entry(r#"{"Name":"s3"}"#);
+        current.metadata.mapping_version = mapping_version.clone();
+        cache.write("s3", &current).await.unwrap();
+        let mut stale = current.clone();
+        stale.metadata.fetched_at = 0;
+        cache.write("sqs", &stale).await.unwrap();
+        cache
+            .write("ec2", &entry(r#"{"Name":"ec2"}"#))
+            .await
+            .unwrap();
+        std::fs::write(dir.path().join(".s3.1.0.tmp"), "partial").unwrap();
+        set_age(&dir.path().join(".s3.1.0.tmp"), TEMP_FILE_PRUNE_AGE);
+
+        let status = cache.status().unwrap();
+        assert_eq!(
+            status.mapping.unwrap().state,
+            ServiceReferenceCacheEntryState::Fresh
+        );
+        let states: Vec<_> = status
+            .entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (".s3.1.0.tmp", ServiceReferenceCacheEntryState::Corrupt),
+                ("ec2", ServiceReferenceCacheEntryState::Outdated),
+                ("s3", ServiceReferenceCacheEntryState::Fresh),
+                ("sqs", ServiceReferenceCacheEntryState::Stale),
+            ]
+        );
+
+        let pruned = cache.prune(false).unwrap();
+        assert_eq!(pruned.removed, vec![".s3.1.0.tmp", "ec2"]);
+        assert!(pruned.freed_bytes > 0);
+
+        let pruned = cache.prune(true).unwrap();
+        assert_eq!(pruned.removed, vec![MAPPING_ENTRY_NAME, "s3", "sqs"]);
+        assert!(cache.status().unwrap().entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_temporary_files_of_writes_in_progress() {
+        let dir = TempDir::new().unwrap();
+        let cache = FileCache::new(dir.path().to_path_buf());
+        let in_progress = dir.path().join(".s3.1.0.tmp");
+        std::fs::write(&in_progress, "partial").unwrap();
+        set_age(&in_progress, Duration::from_secs(60));
+
+        assert!(cache.prune(false).unwrap().removed.is_empty());
+        assert!(cache.prune(true).unwrap().removed.is_empty());
+        assert!(in_progress.exists());
+    }
+
+    fn set_age(path: &Path, age: Duration) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - age)
+            .unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+//! Pluggable sources of AWS service reference data
+//!
+//! The enrichment engine loads the service reference of every service it enriches through a
+//! [`ServiceReferenceLoader`]. Besides the HTTP loader
+//! ([`RemoteServiceReferenceLoader`](super::RemoteServiceReferenceLoader)), which fetches from the
+//! service reference endpoint or an internal mirror of it, service references can be loaded from a
+//! read-only directory or from memory, e.g. as test fixtures.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::errors::ExtractorError;
+
+/// Source of AWS service reference documents
+///
+/// Implementations return the raw service reference JSON document, as served by
+/// `https://servicereference.us-east-1.amazonaws.com`. Parsed documents are cached in memory by
+/// the enrichment engine, so implementations do not need to cache themselves.
+#[async_trait]
+pub trait ServiceReferenceLoader: std::fmt::Debug + Send + Sync {
+    /// Load the service reference JSON document of `service_name`
+    ///
+    /// Service names are IAM service prefixes such as `s3`, and must match exactly. Returns
+    /// `None` if the service is unknown to this loader.
+    async fn load_service_reference(&self, service_name: &str) -> anyhow::Result<Option<String>>;
+}
+
+#[async_trait]
+impl<T: ServiceReferenceLoader + ?Sized> ServiceReferenceLoader for Arc<T> {
+    async fn load_service_reference(&self, service_name: &str) -> anyhow::Result<Option<String>> {
+        (**self).load_service_reference(service_name).await
+    }
+}
+
+/// Loads service references from `<service>.json` files in a directory
+///
+/// The directory is only read, never written. Services without a file are unknown.
+#[derive(Debug, Clone)]
+pub struct DirectoryServiceReferenceLoader {
+    directory: PathBuf,
+}
+
+impl DirectoryServiceReferenceLoader {
+    /// Create a loader reading the service references in `directory`
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+}
+
+/// Whether `name` can name a service reference file of a directory
+///
+/// Service prefixes never contain path separators, so a valid name cannot escape the directory.
+pub(crate) fn is_valid_service_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[async_trait]
+impl ServiceReferenceLoader for DirectoryServiceReferenceLoader {
+    async fn load_service_reference(&self, service_name: &str) -> anyhow::Result<Option<String>> {
+        if !is_valid_service_file_name(service_name) {
+            return Ok(None);
+        }
+
+        let path = self.directory.join(format!("{}.json", service_name));
+        match tokio::fs::read_to_string(&path).await {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ExtractorError::file_system("read", path, e).into()),
+        }
+    }
+}
+
+/// Serves service references from memory
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryServiceReferenceLoader {
+    services: HashMap<String, String>,
+}
+
+impl InMemoryServiceReferenceLoader {
+    /// Create a loader without any service
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the service reference JSON document of `service_name`
+    #[must_use]
+    pub fn with_service_reference(
+        mut self,
+        service_name: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        self.services.insert(service_name.into(), content.into());
+        self
+    }
+}
+
+#[async_trait]
+impl ServiceReferenceLoader for InMemoryServiceReferenceLoader {
+    async fn load_service_reference(&self, service_name: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.services.get(service_name).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_directory_loader() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("s3.json"), r#"{"Name":"s3"}"#).unwrap();
+        let loader = DirectoryServiceReferenceLoader::new(dir.path());
+
+        assert_eq!(
+            loader
+                .load_service_reference("s3")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(r#"{"Name":"s3"}"#)
+        );
+        assert_eq!(loader.load_service_reference("ec2").await.unwrap(), None);
+        assert_eq!(loader.load_service_reference("../s3").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_loader_through_arc() {
+        let loader: Arc<dyn ServiceReferenceLoader> = Arc::new(
+            InMemoryServiceReferenceLoader::new().with_service_reference("s3", r#"{"Name":"s3"}"#),
+        );
+
+        assert!(loader.load_service_reference("s3").await.unwrap().is_some());
+        assert!(loader
+            .load_service_reference("sqs")
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
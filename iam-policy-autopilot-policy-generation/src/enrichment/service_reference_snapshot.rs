@@ -0,0 +1,339 @@
+//! Service reference snapshots for offline use
+//!
+//! A snapshot contains the service reference mapping index and the service reference JSON of
+//! every (or selected) service, together with a checksum manifest. It is either a directory:
+//!
+//! ```text
+//! snapshot/
+//!   manifest.json        checksums, see ServiceReferenceSnapshotManifest
+//!   mapping.json         mapping index, as served by the service reference endpoint
+//!   services/s3.json     one service reference file per service
+//! ```
+//!
+//! or a single JSON bundle file (any path ending in `.json`) with the same content under the
+//! `Manifest`, `Mapping` and `Services` keys. Checksums are verified when the snapshot is
+//! opened and whenever a service is read from it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use aws_lc_rs::digest::{Context, SHA256};
+use serde::{Deserialize, Serialize};
+
+use crate::api::model::ServiceReferenceSnapshotManifest;
+use crate::enrichment::service_reference_loader::is_valid_service_file_name;
+use crate::errors::{ExtractorError, Result};
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const MAPPING_FILE_NAME: &str = "mapping.json";
+const SERVICES_DIR_NAME: &str = "services";
+
+/// Content of a single-file snapshot bundle
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SnapshotBundle {
+    manifest: ServiceReferenceSnapshotManifest,
+    mapping: String,
+    services: BTreeMap<String, String>,
+}
+
+#[derive(Debug)]
+enum SnapshotContent {
+    Directory(PathBuf),
+    Bundle(HashMap<String, String>),
+}
+
+/// An opened, checksum-verified service reference snapshot
+#[derive(Debug)]
+pub(crate) struct ServiceReferenceSnapshot {
+    path: PathBuf,
+    manifest: ServiceReferenceSnapshotManifest,
+    mapping: String,
+    content: SnapshotContent,
+}
+
+impl ServiceReferenceSnapshot {
+    /// Open the snapshot directory or bundle file at `path` and verify its manifest
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let (manifest, mapping, content) = if path.is_dir() {
+            let manifest = read_to_string(&path.join(MANIFEST_FILE_NAME))?;
+            let manifest: ServiceReferenceSnapshotManifest = serde_json::from_str(&manifest)?;
+            let mapping = read_to_string(&path.join(MAPPING_FILE_NAME))?;
+            (
+                manifest,
+                mapping,
+                SnapshotContent::Directory(path.join(SERVICES_DIR_NAME)),
+            )
+        } else {
+            let bundle: SnapshotBundle = serde_json::from_str(&read_to_string(path)?)?;
+            (
+                bundle.manifest,
+                bundle.mapping,
+                SnapshotContent::Bundle(bundle.services.into_iter().collect()),
+            )
+        };
+
+        let snapshot = Self {
+            path: path.to_path_buf(),
+            manifest,
+            mapping,
+            content,
+        };
+        snapshot.verify(
+            MAPPING_FILE_NAME,
+            &snapshot.mapping,
+            &snapshot.manifest.mapping_hash,
+        )?;
+        if data_hash(&snapshot.manifest.services) != snapshot.manifest.data_hash {
+            return Err(snapshot.checksum_error(MANIFEST_FILE_NAME));
+        }
+        snapshot
+            .manifest
+            .services
+            .keys()
+            .try_for_each(|name| check_service_name(name))?;
+        Ok(snapshot)
+    }
+
+    /// Path of the snapshot directory or bundle file
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The mapping index of the snapshot, as served by the service reference endpoint
+    pub(crate) fn mapping(&self) -> &str {
+        &self.mapping
+    }
+
+    /// Service reference JSON of `service_name`, or `None` if the snapshot does not include it
+    pub(crate) async fn read_service(&self, service_name: &str) -> Result<Option<String>> {
+        let Some(expected) = self.manifest.services.get(service_name) else {
+            return Ok(None);
+        };
+        let content = match &self.content {
+            SnapshotContent::Directory(services_dir) => {
+                let path = services_dir.join(format!("{}.json", service_name));
+                tokio::fs::read_to_string(&path)
+                    .await
+                    .map_err(|e| ExtractorError::file_system("read", path, e))?
+            }
+            SnapshotContent::Bundle(services) => services
+                .get(service_name)
+                .cloned()
+                .ok_or_else(|| self.checksum_error(service_name))?,
+        };
+        self.verify(service_name, &content, expected)?;
+        Ok(Some(content))
+    }
+
+    fn verify(&self, name: &str, content: &str, expected: &str) -> Result<()> {
+        if sha256(content.as_bytes()) == expected {
+            Ok(())
+        } else {
+            Err(self.checksum_error(name))
+        }
+    }
+
+    fn checksum_error(&self, name: &str) -> ExtractorError {
+        ExtractorError::validation(format!(
+            "Service reference snapshot {} is corrupt: checksum mismatch for '{}'",
+            self.path.display(),
+            name
+        ))
+    }
+}
+
+/// Write a snapshot to `path`, as a bundle file if it ends in `.json` and a directory otherwise
+pub(crate) fn write_snapshot(
+    path: &Path,
+    source: &str,
+    mapping: String,
+    services: BTreeMap<String, String>,
+) -> Result<ServiceReferenceSnapshotManifest> {
+    services
+        .keys()
+        .try_for_each(|name| check_service_name(name))?;
+    let service_hashes: BTreeMap<String, String> = services
+        .iter()
+        .map(|(name, content)| (name.clone(), sha256(content.as_bytes())))
+        .collect();
+    let manifest = ServiceReferenceSnapshotManifest {
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        source: source.to_string(),
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        mapping_hash: sha256(mapping.as_bytes()),
+        data_hash: data_hash(&service_hashes),
+        services: service_hashes,
+    };
+
+    if is_bundle_path(path) {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            create_dir_all(parent)?;
+        }
+        let bundle = SnapshotBundle {
+            manifest: manifest.clone(),
+            mapping,
+            services,
+        };
+        write(path, serde_json::to_string(&bundle)?)?;
+    } else {
+        let services_dir = path.join(SERVICES_DIR_NAME);
+        create_dir_all(&services_dir)?;
+        for (name, content) in &services {
+            write(&services_dir.join(format!("{}.json", name)), content)?;
+        }
+        write(&path.join(MAPPING_FILE_NAME), mapping)?;
+        write(
+            &path.join(MANIFEST_FILE_NAME),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+    }
+    Ok(manifest)
+}
+
+/// Reject service names that cannot name a file of the snapshot's `services` directory
+///
+/// The names come from the mapping index of the endpoint or from the manifest of the snapshot,
+/// and must not make the snapshot read or write files outside of it.
+pub(crate) fn check_service_name(name: &str) -> Result<()> {
+    if is_valid_service_file_name(name) {
+        Ok(())
+    } else {
+        Err(ExtractorError::validation(format!(
+            "Invalid service name '{}' in service reference snapshot",
+            name
+        )))
+    }
+}
+
+fn is_bundle_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// Hex-encoded SHA-256 digest
+pub(crate) fn sha256(data: &[u8]) -> String {
+    let mut context = Context::new(&SHA256);
+    context.update(data);
+    context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Combined hash of all service checksums, in service name order
+fn data_hash(services: &BTreeMap<String, String>) -> String {
+    let mut combined = Vec::new();
+    for (name, hash) in services {
+        combined.extend_from_slice(name.as_bytes());
+        combined.extend_from_slice(hash.as_bytes());
+    }
+    sha256(&combined)
+}
+
+fn read_to_string(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| ExtractorError::file_system("read", path, e))
+}
+
+fn write(path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    std::fs::write(path, content).map_err(|e| ExtractorError::file_system("write", path, e))
+}
+
+fn create_dir_all(path: &Path) -> Result<()> {
+    std::fs::create_dir_all(path)
+        .map_err(|e| ExtractorError::file_system("create directory", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn services() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("s3".to_string(), r#"{"Name":"s3"}"#.to_string()),
+            ("sqs".to_string(), r#"{"Name":"sqs"}"#.to_string()),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_directory_snapshot_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("snapshot");
+        let manifest =
+            write_snapshot(&path, "https://example.com", "[]".to_string(), services()).unwrap();
+        assert_eq!(manifest.services.len(), 2);
+
+        let snapshot = ServiceReferenceSnapshot::open(&path).unwrap();
+        assert_eq!(snapshot.mapping(), "[]");
+        assert_eq!(
+            snapshot.read_service("s3").await.unwrap().as_deref(),
+            Some(r#"{"Name":"s3"}"#)
+        );
+        assert_eq!(snapshot.read_service("ec2").await.unwrap(), None);
+
+        // Tampering with a service file is detected when the service is read
+        std::fs::write(path.join("services/sqs.json"), r#"{"Name":"tampered"}"#).unwrap();
+        let error = snapshot.read_service("sqs").await.unwrap_err();
+        assert!(error.to_string().contains("checksum mismatch for 'sqs'"));
+    }
+
+    #[tokio::test]
+    async fn test_bundle_snapshot_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("snapshot.json");
+        write_snapshot(&path, "https://example.com", "[]".to_string(), services()).unwrap();
+        assert!(path.is_file());
+
+        let snapshot = ServiceReferenceSnapshot::open(&path).unwrap();
+        assert_eq!(
+            snapshot.read_service("sqs").await.unwrap().as_deref(),
+            Some(r#"{"Name":"sqs"}"#)
+        );
+    }
+
+    #[test]
+    fn test_open_rejects_tampered_manifest() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("snapshot");
+        write_snapshot(&path, "https://example.com", "[]".to_string(), services()).unwrap();
+        std::fs::write(path.join(MAPPING_FILE_NAME), "[{}]").unwrap();
+
+        let error = ServiceReferenceSnapshot::open(&path).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("checksum mismatch for 'mapping.json'"));
+    }
+
+    #[test]
+    fn test_service_names_cannot_escape_the_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("snapshot");
+        let mut escaping = services();
+        escaping.insert("../../.bashrc".to_string(), "{}".to_string());
+
+        let error =
+            write_snapshot(&path, "https://example.com", "[]".to_string(), escaping).unwrap_err();
+        assert!(error.to_string().contains("'../../.bashrc'"));
+        assert!(!path.exists());
+        assert!(!dir.path().join(".bashrc").exists());
+
+        // A crafted manifest with a consistent data hash is rejected when opened
+        write_snapshot(&path, "https://example.com", "[]".to_string(), services()).unwrap();
+        let manifest_path = path.join(MANIFEST_FILE_NAME);
+        let mut manifest: ServiceReferenceSnapshotManifest =
+            serde_json::from_str(&std::fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        manifest
+            .services
+            .insert("../mapping".to_string(), sha256(b"[]"));
+        manifest.data_hash = data_hash(&manifest.services);
+        std::fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
+
+        let error = ServiceReferenceSnapshot::open(&path).unwrap_err();
+        assert!(error.to_string().contains("'../mapping'"));
+    }
+}
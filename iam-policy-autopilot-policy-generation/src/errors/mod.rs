@@ -198,6 +198,10 @@ pub enum ExtractorError {
         /// Formatted list of invalid services with suggestions
         suggestions: String,
     },
+
+    /// The analysis was cancelled by the caller
+    #[error("Analysis cancelled")]
+    Cancelled,
 }
 
 impl ExtractorError {
@@ -221,6 +225,17 @@ impl ExtractorError {
         }
     }
 
+    /// Create a configuration error with source
+    pub(crate) fn configuration_with_source(
+        message: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self::Configuration {
+            message: message.into(),
+            source: Some(Box::new(source)),
+        }
+    }
+
     /// Create a validation error
     pub(crate) fn validation(message: impl Into<String>) -> Self {
         Self::Validation {
@@ -0,0 +1,164 @@
+//! Cache of parsed source files, reused across extractions
+//!
+//! Parsing is the most expensive step of an extraction. When the same source files are
+//! extracted repeatedly, e.g. after small edits in an editing session, files whose content did
+//! not change are not parsed again. Analyses spanning several files, such as disambiguation and
+//! reachability from entry points, still run on every extraction.
+//!
+//! A cache lives as long as the session using it, e.g. an MCP server, so it is bounded: the
+//! least recently used files are dropped once it holds [`ExtractionCache::DEFAULT_MAX_FILES`]
+//! files, which covers files that were deleted or renamed since they were parsed.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::enrichment::service_reference_snapshot::sha256;
+use crate::extraction::extractor::ExtractorResult;
+use crate::{Language, SourceFile};
+
+/// A parsed source file, with the checksum of the content it was parsed from
+struct CachedParse {
+    language: Language,
+    sha256: String,
+    result: ExtractorResult,
+    /// Value of [`CachedFiles::uses`] when the parse was last inserted or reused
+    last_used: u64,
+}
+
+/// Cached parses, with a counter ordering their uses
+#[derive(Default)]
+struct CachedFiles {
+    files: HashMap<PathBuf, CachedParse>,
+    uses: u64,
+}
+
+/// Number of source files parsed and reused by the extractions using a cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExtractionCacheStatistics {
+    /// Source files that were parsed, because they were not cached or changed since
+    pub parsed_files: usize,
+    /// Source files whose cached parse was reused
+    pub reused_files: usize,
+}
+
+/// Parsed source files, keyed by path and content checksum
+///
+/// Only the parse of the latest content of a path is kept. Cloning a cache shares its entries,
+/// so that an extraction engine can be created per extraction while reusing the files parsed by
+/// previous ones.
+#[derive(Clone)]
+pub struct ExtractionCache {
+    entries: Arc<Mutex<CachedFiles>>,
+    statistics: Arc<Mutex<ExtractionCacheStatistics>>,
+    max_files: usize,
+}
+
+impl Default for ExtractionCache {
+    fn default() -> Self {
+        Self::with_max_files(Self::DEFAULT_MAX_FILES)
+    }
+}
+
+impl fmt::Debug for ExtractionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractionCache")
+            .field("files", &self.len())
+            .field("statistics", &self.statistics())
+            .finish()
+    }
+}
+
+impl ExtractionCache {
+    /// Number of source files a cache created with [`ExtractionCache::new`] holds
+    pub const DEFAULT_MAX_FILES: usize = 10_000;
+
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty cache holding at most `max_files` source files
+    pub fn with_max_files(max_files: usize) -> Self {
+        Self {
+            entries: Arc::default(),
+            statistics: Arc::default(),
+            max_files,
+        }
+    }
+
+    // A panic while the lock is held cannot leave the cache inconsistent: every update of the
+    // cached files or the statistics is a single insertion, removal or increment
+    fn lock_entries(&self) -> MutexGuard<'_, CachedFiles> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_statistics(&self) -> MutexGuard<'_, ExtractionCacheStatistics> {
+        self.statistics
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Number of cached source files
+    pub fn len(&self) -> usize {
+        self.lock_entries().files.len()
+    }
+
+    /// Returns true if no source file is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of source files parsed and reused since the cache was created
+    pub fn statistics(&self) -> ExtractionCacheStatistics {
+        *self.lock_statistics()
+    }
+
+    /// Parse of a source file, if it is cached for the current content of the file
+    pub(crate) fn get(&self, source_file: &SourceFile) -> Option<ExtractorResult> {
+        let checksum = sha256(source_file.content.as_bytes());
+        let result = {
+            let mut entries = self.lock_entries();
+            let uses = entries.uses + 1;
+            let cached = entries.files.get_mut(&source_file.path).filter(|cached| {
+                cached.language == source_file.language && cached.sha256 == checksum
+            })?;
+            cached.last_used = uses;
+            let result = cached.result.clone();
+            entries.uses = uses;
+            result
+        };
+        self.lock_statistics().reused_files += 1;
+        Some(result)
+    }
+
+    /// Cache the parse of a source file, replacing the parse of a previous content
+    ///
+    /// Drops the least recently used file if the cache is full.
+    pub(crate) fn insert(&self, source_file: &SourceFile, result: &ExtractorResult) {
+        let mut entries = self.lock_entries();
+        entries.uses += 1;
+        let cached = CachedParse {
+            language: source_file.language,
+            sha256: sha256(source_file.content.as_bytes()),
+            result: result.clone(),
+            last_used: entries.uses,
+        };
+        if !entries.files.contains_key(&source_file.path) && entries.files.len() >= self.max_files {
+            let least_recently_used = entries
+                .files
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(path, _)| path.clone());
+            if let Some(path) = least_recently_used {
+                entries.files.remove(&path);
+            }
+        }
+        if self.max_files > 0 {
+            entries.files.insert(source_file.path.clone(), cached);
+        }
+        drop(entries);
+        self.lock_statistics().parsed_files += 1;
+    }
+}
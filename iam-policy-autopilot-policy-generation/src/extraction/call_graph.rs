@@ -0,0 +1,1111 @@
+//! Call graph of the functions and methods defined in a set of source files
+//!
+//! Definitions are found per language: Python `def`, Go functions and methods, and JavaScript
+//! and TypeScript function declarations, class methods, and functions assigned to variables or
+//! CommonJS exports. Each file also has a module scope holding its top-level code, which runs
+//! when the file is loaded, and which calls the module scopes of the files it imports (see
+//! [`crate::workload`] for how imports are resolved).
+//!
+//! Call edges are resolved by name, since the analyzed code is not type checked:
+//! - `f()` calls the functions named `f` of the calling file, or else of the files it imports,
+//!   or else of any file.
+//! - `self.f()`, `this.f()` and calls on a Go method's receiver call the methods named `f` of
+//!   the caller's type, or of any type if it has none (e.g. inherited methods).
+//! - `m.f()` calls the functions named `f` of the files or packages named `m`, or otherwise
+//!   the methods named `f` of any type.
+//! - Functions passed by name as call arguments, like callbacks and handlers, are called too.
+//!
+//! Ambiguous calls are linked to all candidates, so reachability over-approximates.
+//!
+//! Entry points are detected as follows:
+//! - Main: Go `main` functions of `main` packages, and the module scope of Python and
+//!   JavaScript files run as scripts (`if __name__ == "__main__":`, `require.main === module`).
+//! - Lambda handlers: top-level functions taking `(event, context)`, Python functions named
+//!   `lambda_handler`, exported JavaScript and TypeScript functions named `handler`, and
+//!   functions passed to Go's `lambda.Start`.
+//! - HTTP routes: functions registered for a route path, like `@app.get("/items")`,
+//!   `router.post("/items", create)` or `http.HandleFunc("/items", list)`. Inline handlers make
+//!   the function registering them the entry point.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use ast_grep_core::tree_sitter::StrDoc;
+use ast_grep_core::Node;
+use ast_grep_language::{Go, JavaScript, LanguageExt, Python, TypeScript};
+use regex::Regex;
+
+use crate::workload::{absolute, imported_files};
+use crate::{Language, Location, SourceFile};
+
+/// Name of the pseudo-function holding the top-level code of a file
+pub(crate) const MODULE_SCOPE: &str = "<module>";
+
+/// Node kinds of anonymous functions
+const FUNCTION_LITERALS: [&str; 5] = [
+    "lambda",
+    "func_literal",
+    "arrow_function",
+    "function_expression",
+    "function",
+];
+
+/// Lowercase names of the methods registering HTTP route handlers
+const ROUTE_METHODS: [&str; 15] = [
+    "route",
+    "add_url_rule",
+    "api_route",
+    "websocket",
+    "handle",
+    "handlefunc",
+    "get",
+    "post",
+    "put",
+    "patch",
+    "delete",
+    "head",
+    "options",
+    "all",
+    "any",
+];
+
+/// A function or method defined in the analyzed source files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FunctionDefinition {
+    /// Function or method name, or [`MODULE_SCOPE`] for the top-level code of a file
+    pub(crate) name: String,
+    /// Class or type declaring the method, `None` for functions
+    pub(crate) receiver: Option<String>,
+    /// Parameter names by position, without `self`, `cls` or Go receivers
+    ///
+    /// Parameters without a simple name, like destructuring patterns, are empty strings.
+    pub(crate) parameters: Vec<String>,
+    /// Whether the function is part of the public interface of its module
+    pub(crate) exported: bool,
+    /// Location of the whole definition
+    pub(crate) location: Location,
+}
+
+/// Why a function is an entry point of the application
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EntryPointKind {
+    /// `main` function or script
+    Main,
+    /// AWS Lambda function handler
+    LambdaHandler,
+    /// HTTP route handler
+    HttpRoute,
+}
+
+impl fmt::Display for EntryPointKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Main => write!(f, "main function"),
+            Self::LambdaHandler => write!(f, "Lambda handler"),
+            Self::HttpRoute => write!(f, "HTTP route handler"),
+        }
+    }
+}
+
+/// Call graph over the functions of a set of source files
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CallGraph {
+    functions: Vec<FunctionDefinition>,
+    callees: Vec<BTreeSet<usize>>,
+    /// Module scope of the file defining each function
+    modules: Vec<usize>,
+    /// Module scopes of the files imported by each module scope
+    imports: BTreeMap<usize, BTreeSet<usize>>,
+    entry_points: BTreeMap<usize, EntryPointKind>,
+}
+
+/// A call, or a reference to a function, found in the body of a function
+#[derive(Clone)]
+struct CallReference {
+    caller: usize,
+    qualifier: Option<String>,
+    name: String,
+}
+
+impl CallReference {
+    fn offset(self, offset: usize) -> Self {
+        Self {
+            caller: self.caller + offset,
+            ..self
+        }
+    }
+}
+
+/// Definitions and calls of one source file
+#[derive(Default)]
+struct FileScan {
+    functions: Vec<FunctionDefinition>,
+    /// Names the function's own receiver is referred to by, e.g. `self`
+    self_names: Vec<Vec<String>>,
+    calls: Vec<CallReference>,
+    /// Functions passed to a Lambda runtime or registered for a route
+    registrations: Vec<(CallReference, EntryPointKind)>,
+    entry_points: Vec<(usize, EntryPointKind)>,
+}
+
+impl CallGraph {
+    /// Build the call graph of the given source files
+    pub(crate) fn build(source_files: &[SourceFile]) -> Self {
+        let mut graph = Self::default();
+        let mut self_names = Vec::new();
+        let mut calls = Vec::new();
+        let mut registrations = Vec::new();
+        let mut file_modules = Vec::with_capacity(source_files.len());
+        for source_file in source_files {
+            let scan = match source_file.language {
+                Language::Python => scan_file(Python, source_file),
+                Language::Go => scan_file(Go, source_file),
+                Language::JavaScript => scan_file(JavaScript, source_file),
+                Language::TypeScript => scan_file(TypeScript, source_file),
+            };
+            let offset = graph.functions.len();
+            file_modules.push(offset);
+            graph.modules.resize(offset + scan.functions.len(), offset);
+            graph.functions.extend(scan.functions);
+            self_names.extend(scan.self_names);
+            calls.extend(scan.calls.into_iter().map(|call| call.offset(offset)));
+            registrations.extend(
+                scan.registrations
+                    .into_iter()
+                    .map(|(call, kind)| (call.offset(offset), kind)),
+            );
+            graph.entry_points.extend(
+                scan.entry_points
+                    .into_iter()
+                    .map(|(id, kind)| (id + offset, kind)),
+            );
+        }
+        graph.callees = vec![BTreeSet::new(); graph.functions.len()];
+
+        let paths: Vec<PathBuf> = source_files.iter().map(|f| absolute(&f.path)).collect();
+        let index: BTreeMap<&Path, usize> = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| (path.as_path(), i))
+            .collect();
+        let common = common_ancestor(&paths);
+        for ((source_file, path), &module) in source_files.iter().zip(&paths).zip(&file_modules) {
+            // Python imports may name packages above the analyzed directories
+            let root = match source_file.language {
+                Language::Python => path.ancestors().last().unwrap_or(&common),
+                _ => &common,
+            };
+            let imported: BTreeSet<usize> = imported_files(
+                source_file.language,
+                path,
+                &source_file.content,
+                root,
+                &index,
+            )
+            .into_iter()
+            .map(|file| file_modules[file])
+            .collect();
+            graph.callees[module].extend(imported.iter().copied());
+            graph.imports.insert(module, imported);
+        }
+
+        for call in &calls {
+            let callees = graph.resolve(call, &self_names[call.caller]);
+            graph.callees[call.caller].extend(callees);
+        }
+        for (call, kind) in &registrations {
+            for id in graph.resolve(call, &self_names[call.caller]) {
+                graph.entry_points.entry(id).or_insert(*kind);
+            }
+        }
+        graph
+    }
+
+    /// All functions of the graph, indexed by their id
+    pub(crate) fn functions(&self) -> &[FunctionDefinition] {
+        &self.functions
+    }
+
+    /// Detected entry points, by function id
+    pub(crate) fn entry_points(&self) -> &BTreeMap<usize, EntryPointKind> {
+        &self.entry_points
+    }
+
+    /// The module scope of the file defining a function
+    pub(crate) fn module_scope(&self, id: usize) -> usize {
+        self.modules[id]
+    }
+
+    /// Functions of `file` named `function` or `Type.method`, or its module scope for `None`
+    pub(crate) fn find(&self, file: &Path, function: Option<&str>) -> Vec<usize> {
+        let file = absolute(file);
+        self.functions
+            .iter()
+            .enumerate()
+            .filter(|(id, candidate)| {
+                let matches = match function {
+                    None => self.modules[*id] == *id,
+                    Some(function) => match function.split_once('.') {
+                        Some((receiver, name)) => {
+                            candidate.receiver.as_deref() == Some(receiver)
+                                && candidate.name == name
+                        }
+                        None => candidate.name == function,
+                    },
+                };
+                matches && absolute(&candidate.location.file_path) == file
+            })
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// The innermost function whose definition contains the location
+    ///
+    /// Locations outside of any function are in the module scope of their file.
+    pub(crate) fn enclosing_function(&self, location: &Location) -> Option<usize> {
+        self.functions
+            .iter()
+            .enumerate()
+            .filter(|(_, function)| {
+                function.location.file_path == location.file_path
+                    && function.location.start_position <= location.start_position
+                    && location.end_position <= function.location.end_position
+            })
+            .max_by_key(|(_, function)| function.location.start_position)
+            .map(|(id, _)| id)
+    }
+
+    /// Ids of the functions reachable from the given functions, including themselves
+    pub(crate) fn reachable_from(&self, roots: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut reachable = BTreeSet::new();
+        let mut queue: VecDeque<usize> = roots.into_iter().collect();
+        while let Some(id) = queue.pop_front() {
+            if reachable.insert(id) {
+                queue.extend(self.callees[id].iter().copied());
+            }
+        }
+        reachable
+    }
+
+    /// Candidate callees of a call
+    fn resolve(&self, call: &CallReference, self_names: &[String]) -> Vec<usize> {
+        let caller = &self.functions[call.caller];
+        let named = |receiver: &dyn Fn(Option<&String>) -> bool| -> Vec<usize> {
+            self.functions
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.name == call.name && receiver(f.receiver.as_ref()))
+                .map(|(id, _)| id)
+                .collect()
+        };
+
+        match &call.qualifier {
+            None => {
+                let candidates = named(&|receiver| receiver.is_none());
+                let module = self.modules[call.caller];
+                let same_file: Vec<usize> = candidates
+                    .iter()
+                    .copied()
+                    .filter(|id| self.modules[*id] == module)
+                    .collect();
+                if !same_file.is_empty() {
+                    return same_file;
+                }
+                let imported: Vec<usize> = candidates
+                    .iter()
+                    .copied()
+                    .filter(|id| {
+                        self.imports
+                            .get(&module)
+                            .is_some_and(|imports| imports.contains(&self.modules[*id]))
+                    })
+                    .collect();
+                if imported.is_empty() {
+                    candidates
+                } else {
+                    imported
+                }
+            }
+            Some(qualifier) if self_names.contains(qualifier) => {
+                let own = named(&|receiver| receiver == caller.receiver.as_ref());
+                if own.is_empty() {
+                    named(&|receiver| receiver.is_some())
+                } else {
+                    own
+                }
+            }
+            Some(qualifier) => {
+                let module = qualifier.rsplit('.').next().unwrap_or(qualifier);
+                let module_functions: Vec<usize> = named(&|receiver| receiver.is_none())
+                    .into_iter()
+                    .filter(|id| module_name_of(&self.functions[*id].location.file_path) == module)
+                    .collect();
+                if module_functions.is_empty() {
+                    named(&|receiver| receiver.is_some())
+                } else {
+                    module_functions
+                }
+            }
+        }
+    }
+}
+
+/// Deepest directory containing all of the given files
+fn common_ancestor(paths: &[PathBuf]) -> PathBuf {
+    let mut dirs = paths.iter().filter_map(|path| path.parent());
+    let Some(first) = dirs.next() else {
+        return PathBuf::new();
+    };
+    dirs.fold(first.to_path_buf(), |common, dir| {
+        common
+            .ancestors()
+            .find(|ancestor| dir.starts_with(ancestor))
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    })
+}
+
+/// Name a file's functions are called through from other modules: the file stem, or the
+/// directory for Go packages and Python or JavaScript package entry points
+fn module_name_of(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let is_go = path.extension().is_some_and(|e| e == "go");
+    if is_go || stem == "__init__" || stem == "index" {
+        path.parent()
+            .and_then(Path::file_name)
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or(stem)
+    } else {
+        stem
+    }
+}
+
+/// Find the definitions, calls and entry points of a source file
+fn scan_file<L: LanguageExt>(language: L, source_file: &SourceFile) -> FileScan {
+    let ast = language.ast_grep(&source_file.content);
+    let root = ast.root();
+    let exports = ExportedNames::new(source_file.language, &source_file.content);
+
+    // The module scope is the first function of the file
+    let mut scan = FileScan::default();
+    scan.functions.push(FunctionDefinition {
+        name: MODULE_SCOPE.to_string(),
+        receiver: None,
+        parameters: Vec::new(),
+        exported: false,
+        location: Location::new(source_file.path.clone(), (1, 1), (usize::MAX, usize::MAX)),
+    });
+    scan.self_names.push(Vec::new());
+    if runs_as_script(source_file.language, &source_file.content) {
+        scan.entry_points.push((0, EntryPointKind::Main));
+    }
+
+    let mut definitions: HashMap<std::ops::Range<usize>, usize> = HashMap::new();
+    for node in root.dfs() {
+        if let Some((function, self_names)) =
+            definition(source_file.language, &node, &source_file.path, &exports)
+        {
+            let id = scan.functions.len();
+            if let Some(kind) =
+                defined_entry_point(source_file.language, &node, &function, source_file)
+            {
+                scan.entry_points.push((id, kind));
+            }
+            definitions.insert(node.range(), id);
+            scan.functions.push(function);
+            scan.self_names.push(self_names);
+        }
+    }
+    for node in root.dfs() {
+        let Some((qualifier, name)) = call(source_file.language, &node) else {
+            continue;
+        };
+        let caller = node
+            .ancestors()
+            .find_map(|ancestor| definitions.get(&ancestor.range()).copied())
+            .unwrap_or(0);
+        let registration = registration(source_file.language, qualifier.as_deref(), &name, &node);
+        scan.calls.push(CallReference {
+            caller,
+            qualifier,
+            name,
+        });
+        for argument in arguments(&node) {
+            if FUNCTION_LITERALS.contains(&&*argument.kind()) {
+                if let Some(kind) = registration {
+                    scan.entry_points.push((caller, kind));
+                }
+                continue;
+            }
+            let Some((qualifier, name)) = callee(source_file.language, &argument) else {
+                continue;
+            };
+            let reference = CallReference {
+                caller,
+                qualifier,
+                name,
+            };
+            if let Some(kind) = registration {
+                scan.registrations.push((reference.clone(), kind));
+            }
+            scan.calls.push(reference);
+        }
+    }
+    scan
+}
+
+/// The function defined by a node, and the names of its own receiver
+fn definition<L: LanguageExt>(
+    language: Language,
+    node: &Node<StrDoc<L>>,
+    path: &Path,
+    exports: &ExportedNames,
+) -> Option<(FunctionDefinition, Vec<String>)> {
+    let text_of = |field: &str| node.field(field).map(|n| n.text().to_string());
+    let (name, receiver, parameters, self_names, exported) = match (language, &*node.kind()) {
+        (Language::Python, "function_definition") => {
+            let name = text_of("name")?;
+            let receiver = python_class(node);
+            let mut parameters = parameter_names(language, node.field("parameters"));
+            if receiver.is_some() && !parameters.is_empty() {
+                parameters.remove(0);
+            }
+            let exported =
+                !name.starts_with('_') && receiver.as_ref().is_none_or(|r| !r.starts_with('_'));
+            let self_names = vec!["self".to_string(), "cls".to_string()];
+            (name, receiver, parameters, self_names, exported)
+        }
+        (Language::Go, "function_declaration" | "method_declaration") => {
+            let name = text_of("name")?;
+            let receiver_declaration = node
+                .field("receiver")
+                .and_then(|r| r.children().find(|c| c.kind() == "parameter_declaration"));
+            let receiver = receiver_declaration
+                .as_ref()
+                .and_then(|r| r.field("type"))
+                .map(|t| go_type_name(&t.text()));
+            let self_names = receiver_declaration
+                .as_ref()
+                .and_then(|r| r.field("name"))
+                .map(|n| vec![n.text().to_string()])
+                .unwrap_or_default();
+            let parameters = parameter_names(language, node.field("parameters"));
+            let exported = name.starts_with(|c: char| c.is_ascii_uppercase())
+                && receiver
+                    .as_ref()
+                    .is_none_or(|r| r.starts_with(|c: char| c.is_ascii_uppercase()));
+            (name, receiver, parameters, self_names, exported)
+        }
+        (
+            Language::JavaScript | Language::TypeScript,
+            "function_declaration" | "generator_function_declaration",
+        ) => {
+            let name = text_of("name")?;
+            let parameters = parameter_names(language, node.field("parameters"));
+            let exported = is_export_statement(node.parent()) || exports.contains(&name);
+            (name, None, parameters, Vec::new(), exported)
+        }
+        (Language::JavaScript | Language::TypeScript, "variable_declarator") => {
+            let value = node.field("value")?;
+            if !FUNCTION_LITERALS.contains(&&*value.kind()) {
+                return None;
+            }
+            let name = text_of("name")?;
+            let parameters = function_parameters(language, &value);
+            let exported = is_export_statement(node.parent().and_then(|p| p.parent()))
+                || exports.contains(&name);
+            (name, None, parameters, Vec::new(), exported)
+        }
+        // `exports.f = function () {}` and `module.exports.f = () => {}`
+        (Language::JavaScript | Language::TypeScript, "assignment_expression") => {
+            let target = node.field("left")?;
+            let value = node.field("right")?;
+            if target.kind() != "member_expression" || !FUNCTION_LITERALS.contains(&&*value.kind())
+            {
+                return None;
+            }
+            let object = target.field("object")?.text().to_string();
+            if object != "exports" && object != "module.exports" {
+                return None;
+            }
+            let name = target.field("property")?.text().to_string();
+            let parameters = function_parameters(language, &value);
+            (name, None, parameters, Vec::new(), true)
+        }
+        (Language::JavaScript | Language::TypeScript, "method_definition") => {
+            let name = text_of("name")?;
+            let class = node
+                .parent()
+                .filter(|p| p.kind() == "class_body")
+                .and_then(|body| body.parent());
+            let receiver = class
+                .as_ref()
+                .and_then(|c| c.field("name"))
+                .map(|n| n.text().to_string());
+            let class_exported = class.as_ref().is_some_and(|c| {
+                is_export_statement(c.parent())
+                    || receiver.as_ref().is_some_and(|r| exports.contains(r))
+            });
+            let exported = class_exported
+                && name != "constructor"
+                && !name.starts_with('_')
+                && !name.starts_with('#');
+            let parameters = parameter_names(language, node.field("parameters"));
+            (
+                name,
+                receiver,
+                parameters,
+                vec!["this".to_string()],
+                exported,
+            )
+        }
+        _ => return None,
+    };
+
+    Some((
+        FunctionDefinition {
+            name,
+            receiver,
+            parameters,
+            exported,
+            location: Location::from_node(path.to_path_buf(), node),
+        },
+        self_names,
+    ))
+}
+
+/// The kind of entry point a function is by its own definition
+fn defined_entry_point<L: LanguageExt>(
+    language: Language,
+    node: &Node<StrDoc<L>>,
+    function: &FunctionDefinition,
+    source_file: &SourceFile,
+) -> Option<EntryPointKind> {
+    if function.receiver.is_some() {
+        return None;
+    }
+    let takes_event_and_context = function.parameters.len() >= 2
+        && function.parameters[0] == "event"
+        && function.parameters[1] == "context";
+    match language {
+        Language::Python => {
+            let routed = node
+                .parent()
+                .filter(|p| p.kind() == "decorated_definition")
+                .is_some_and(|decorated| {
+                    decorated
+                        .children()
+                        .filter(|c| c.kind() == "decorator")
+                        .filter_map(|decorator| decorator.children().find(|c| c.kind() == "call"))
+                        .any(|decorator| {
+                            call(language, &decorator).is_some_and(|(qualifier, name)| {
+                                registration(language, qualifier.as_deref(), &name, &decorator)
+                                    .is_some()
+                            })
+                        })
+                });
+            if routed {
+                Some(EntryPointKind::HttpRoute)
+            } else if function.name == "lambda_handler" || takes_event_and_context {
+                Some(EntryPointKind::LambdaHandler)
+            } else {
+                None
+            }
+        }
+        Language::Go => (function.name == "main" && is_go_main_package(&source_file.content))
+            .then_some(EntryPointKind::Main),
+        Language::JavaScript | Language::TypeScript => {
+            ((function.exported && function.name == "handler") || takes_event_and_context)
+                .then_some(EntryPointKind::LambdaHandler)
+        }
+    }
+}
+
+/// Whether a call passes function arguments to a Lambda runtime or registers them for a route
+fn registration<L: LanguageExt>(
+    language: Language,
+    qualifier: Option<&str>,
+    name: &str,
+    node: &Node<StrDoc<L>>,
+) -> Option<EntryPointKind> {
+    qualifier?;
+    if language == Language::Go && qualifier == Some("lambda") && name.starts_with("Start") {
+        return Some(EntryPointKind::LambdaHandler);
+    }
+    let routed = ROUTE_METHODS.contains(&&*name.to_lowercase())
+        && arguments(node).first().is_some_and(is_route_path);
+    routed.then_some(EntryPointKind::HttpRoute)
+}
+
+/// Whether a node is a string literal holding a route path, optionally after an HTTP method
+fn is_route_path<L: LanguageExt>(node: &Node<StrDoc<L>>) -> bool {
+    if !matches!(
+        &*node.kind(),
+        "string" | "interpreted_string_literal" | "raw_string_literal" | "template_string"
+    ) {
+        return false;
+    }
+    let text = node.text();
+    let value = text
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .trim_matches(|c| matches!(c, '"' | '\'' | '`'));
+    value
+        .split_whitespace()
+        .last()
+        .is_some_and(|path| path.starts_with('/'))
+}
+
+/// Whether a Python or JavaScript file runs its top-level code as a script
+fn runs_as_script(language: Language, content: &str) -> bool {
+    static PYTHON_MAIN: OnceLock<Regex> = OnceLock::new();
+    static JAVASCRIPT_MAIN: OnceLock<Regex> = OnceLock::new();
+    match language {
+        Language::Python => PYTHON_MAIN
+            .get_or_init(|| {
+                Regex::new(r#"(?m)^if\s+__name__\s*==\s*['"]__main__['"]\s*:"#)
+                    .expect("Invalid Python main regex")
+            })
+            .is_match(content),
+        Language::JavaScript | Language::TypeScript => JAVASCRIPT_MAIN
+            .get_or_init(|| {
+                Regex::new(r"\brequire\.main\s*===?\s*module\b")
+                    .expect("Invalid JavaScript main regex")
+            })
+            .is_match(content),
+        Language::Go => false,
+    }
+}
+
+fn is_go_main_package(content: &str) -> bool {
+    static PACKAGE_MAIN: OnceLock<Regex> = OnceLock::new();
+    PACKAGE_MAIN
+        .get_or_init(|| Regex::new(r"(?m)^package\s+main\b").expect("Invalid Go package regex"))
+        .is_match(content)
+}
+
+/// The callee of a call node, as qualifier and name
+fn call<L: LanguageExt>(
+    language: Language,
+    node: &Node<StrDoc<L>>,
+) -> Option<(Option<String>, String)> {
+    let call_kind = match language {
+        Language::Python => "call",
+        Language::Go | Language::JavaScript | Language::TypeScript => "call_expression",
+    };
+    if node.kind() != call_kind {
+        return None;
+    }
+    callee(language, &node.field("function")?)
+}
+
+/// The function an identifier or member expression refers to, as qualifier and name
+fn callee<L: LanguageExt>(
+    language: Language,
+    node: &Node<StrDoc<L>>,
+) -> Option<(Option<String>, String)> {
+    let (member_kinds, object_field, property_field) = match language {
+        Language::Python => (["attribute", ""], "object", "attribute"),
+        Language::Go => (["selector_expression", ""], "operand", "field"),
+        Language::JavaScript | Language::TypeScript => (
+            ["member_expression", "optional_chain"],
+            "object",
+            "property",
+        ),
+    };
+    if node.kind() == "identifier" {
+        return Some((None, node.text().to_string()));
+    }
+    if member_kinds.contains(&&*node.kind()) {
+        let object = node.field(object_field)?;
+        let property = node.field(property_field)?;
+        return Some((Some(object.text().to_string()), property.text().to_string()));
+    }
+    None
+}
+
+/// Argument expressions of a call node, with keyword arguments replaced by their values
+fn arguments<'r, L: LanguageExt>(node: &Node<'r, StrDoc<L>>) -> Vec<Node<'r, StrDoc<L>>> {
+    let Some(arguments) = node.field("arguments") else {
+        return Vec::new();
+    };
+    arguments
+        .children()
+        .filter(|c| c.is_named() && c.kind() != "comment")
+        .filter_map(|argument| {
+            if argument.kind() == "keyword_argument" {
+                argument.field("value")
+            } else {
+                Some(argument)
+            }
+        })
+        .collect()
+}
+
+/// Parameter names of an anonymous function node
+fn function_parameters<L: LanguageExt>(language: Language, node: &Node<StrDoc<L>>) -> Vec<String> {
+    match node.field("parameter") {
+        Some(parameter) => vec![parameter.text().to_string()],
+        None => parameter_names(language, node.field("parameters")),
+    }
+}
+
+/// Names of the parameters of a parameter list node
+fn parameter_names<L: LanguageExt>(
+    language: Language,
+    parameters: Option<Node<StrDoc<L>>>,
+) -> Vec<String> {
+    let Some(parameters) = parameters else {
+        return Vec::new();
+    };
+    let mut names = Vec::new();
+    for parameter in parameters.children().filter(|c| c.is_named()) {
+        let kind = parameter.kind();
+        if kind == "comment" {
+            continue;
+        }
+        if language == Language::Go {
+            // `a, b string` declares several parameters
+            if kind == "parameter_declaration" || kind == "variadic_parameter_declaration" {
+                let declared: Vec<String> = parameter
+                    .field_children("name")
+                    .map(|n| n.text().to_string())
+                    .collect();
+                if declared.is_empty() {
+                    names.push(String::new());
+                }
+                names.extend(declared);
+            }
+            continue;
+        }
+        let name = match &*kind {
+            "identifier" => Some(parameter.clone()),
+            // Python
+            "typed_parameter" => parameter.children().find(|c| c.kind() == "identifier"),
+            "default_parameter" | "typed_default_parameter" => parameter.field("name"),
+            // JavaScript and TypeScript
+            "assignment_pattern" => parameter.field("left"),
+            "required_parameter" | "optional_parameter" => parameter.field("pattern"),
+            _ => None,
+        };
+        names.push(
+            name.filter(|n| n.kind() == "identifier")
+                .map(|n| n.text().to_string())
+                .unwrap_or_default(),
+        );
+    }
+    names
+}
+
+/// Name of the class whose body directly contains a Python function
+fn python_class<L: LanguageExt>(node: &Node<StrDoc<L>>) -> Option<String> {
+    let mut parent = node.parent()?;
+    if parent.kind() == "decorated_definition" {
+        parent = parent.parent()?;
+    }
+    if parent.kind() != "block" {
+        return None;
+    }
+    let class = parent.parent().filter(|p| p.kind() == "class_definition")?;
+    class.field("name").map(|n| n.text().to_string())
+}
+
+/// Type name of a Go method receiver, without pointer and type parameters
+fn go_type_name(receiver_type: &str) -> String {
+    let name = receiver_type.trim().trim_start_matches('*');
+    name.split('[').next().unwrap_or(name).trim().to_string()
+}
+
+fn is_export_statement<L: LanguageExt>(node: Option<Node<StrDoc<L>>>) -> bool {
+    node.is_some_and(|n| n.kind() == "export_statement")
+}
+
+/// Names exported by CommonJS assignments or export lists of a JavaScript or TypeScript file
+struct ExportedNames(BTreeSet<String>);
+
+impl ExportedNames {
+    fn new(language: Language, source: &str) -> Self {
+        if !matches!(language, Language::JavaScript | Language::TypeScript) {
+            return Self(BTreeSet::new());
+        }
+        static EXPORTS: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+        let exports = EXPORTS.get_or_init(|| {
+            Regex::new(
+                r"(?:module\.)?exports\.(\w+)\s*=|module\.exports\s*=\s*(\w+)|(?:module\.exports\s*=|export)\s*\{([^}]*)\}",
+            )
+            .expect("exports regex should compile")
+        });
+        let mut names = BTreeSet::new();
+        for captures in exports.captures_iter(source) {
+            if let Some(name) = captures.get(1).or(captures.get(2)) {
+                names.insert(name.as_str().to_string());
+            }
+            if let Some(list) = captures.get(3) {
+                for item in list.as_str().split(',') {
+                    // `a`, `a: b` (CommonJS) and `a as b` (ES modules) export the local `a` or `b`
+                    let local = match item.split_once(':') {
+                        Some((_, local)) => local,
+                        None => item.split(" as ").next().unwrap_or(item),
+                    };
+                    let local = local.trim();
+                    if !local.is_empty() {
+                        names.insert(local.to_string());
+                    }
+                }
+            }
+        }
+        Self(names)
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.0.contains(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn graph(files: &[(&str, &str, Language)]) -> CallGraph {
+        let source_files: Vec<SourceFile> = files
+            .iter()
+            .map(|(path, content, language)| {
+                SourceFile::with_language(PathBuf::from(path), content.to_string(), *language)
+            })
+            .collect();
+        CallGraph::build(&source_files)
+    }
+
+    fn id(graph: &CallGraph, name: &str) -> usize {
+        graph
+            .functions()
+            .iter()
+            .position(|f| f.name == name)
+            .unwrap_or_else(|| panic!("function {name} not found"))
+    }
+
+    fn reachable_names(graph: &CallGraph, root: &str) -> Vec<String> {
+        graph
+            .reachable_from([id(graph, root)])
+            .into_iter()
+            .map(|id| graph.functions()[id].name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_python_call_graph() {
+        let graph = graph(&[
+            (
+                "app/handler.py",
+                r#"
+from app import storage
+
+def handler(event, context):
+    return process(event["records"])
+
+def process(records):
+    store = storage.Store()
+    for record in records:
+        store.save(record)
+
+def unused():
+    pass
+"#,
+                Language::Python,
+            ),
+            (
+                "app/storage.py",
+                r#"
+class Store:
+    def save(self, record, *, table=None):
+        self._put(record)
+
+    def _put(self, record):
+        table.put_item(Item=record)
+"#,
+                Language::Python,
+            ),
+        ]);
+
+        assert_eq!(
+            reachable_names(&graph, "handler"),
+            vec!["handler", "process", "save", "_put"]
+        );
+        let save = &graph.functions()[id(&graph, "save")];
+        assert_eq!(save.receiver.as_deref(), Some("Store"));
+        assert_eq!(save.parameters, vec!["record", "", "table"]);
+        assert!(save.exported);
+        assert!(!graph.functions()[id(&graph, "_put")].exported);
+
+        let location = Location::new(PathBuf::from("app/storage.py"), (7, 9), (7, 36));
+        assert_eq!(
+            graph.enclosing_function(&location),
+            Some(id(&graph, "_put"))
+        );
+    }
+
+    #[test]
+    fn test_go_and_javascript_call_graph() {
+        let graph = graph(&[(
+            "storage/client.go",
+            r#"
+package storage
+
+func (c *Client) PutBlob(ctx context.Context, bucket, key string) error {
+	return c.put(ctx, bucket, key)
+}
+
+func (c *Client) put(ctx context.Context, bucket, key string) error {
+	return nil
+}
+"#,
+            Language::Go,
+        )]);
+        assert_eq!(reachable_names(&graph, "PutBlob"), vec!["PutBlob", "put"]);
+        let put_blob = &graph.functions()[id(&graph, "PutBlob")];
+        assert_eq!(put_blob.receiver.as_deref(), Some("Client"));
+        assert_eq!(put_blob.parameters, vec!["ctx", "bucket", "key"]);
+        assert!(put_blob.exported);
+        assert!(!graph.functions()[id(&graph, "put")].exported);
+
+        let graph = graph_js();
+        assert_eq!(
+            reachable_names(&graph, "putBlob"),
+            vec!["upload", "putBlob"]
+        );
+        assert!(graph.functions()[id(&graph, "putBlob")].exported);
+        assert!(!graph.functions()[id(&graph, "upload")].exported);
+        assert!(graph.functions()[id(&graph, "remove")].exported);
+    }
+
+    fn entry_points(graph: &CallGraph) -> Vec<(String, EntryPointKind)> {
+        graph
+            .entry_points()
+            .iter()
+            .map(|(&id, &kind)| (graph.functions()[id].name.clone(), kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_entry_points_and_imports() {
+        let python = graph(&[
+            (
+                "api/routes.py",
+                r#"
+from api.service import process
+
+@app.get("/items")
+def list_items():
+    return process()
+
+def lambda_handler(event, context):
+    pass
+
+if __name__ == "__main__":
+    app.run()
+"#,
+                Language::Python,
+            ),
+            (
+                "api/service.py",
+                "def process():\n    pass\n",
+                Language::Python,
+            ),
+            (
+                "jobs/service.py",
+                "def process():\n    pass\n",
+                Language::Python,
+            ),
+        ]);
+        assert_eq!(
+            entry_points(&python),
+            vec![
+                (MODULE_SCOPE.to_string(), EntryPointKind::Main),
+                ("list_items".to_string(), EntryPointKind::HttpRoute),
+                ("lambda_handler".to_string(), EntryPointKind::LambdaHandler),
+            ]
+        );
+        // The imported `process` is preferred over the one of another file
+        let list_items = id(&python, "list_items");
+        let callees: Vec<&Path> = python
+            .reachable_from([list_items])
+            .into_iter()
+            .filter(|&id| id != list_items)
+            .map(|id| python.functions()[id].location.file_path.as_path())
+            .collect();
+        assert_eq!(callees, vec![Path::new("api/service.py")]);
+
+        let go = graph(&[(
+            "cmd/main.go",
+            r#"
+package main
+
+func main() {
+	lambda.Start(handle)
+}
+
+func handle(ctx context.Context) error {
+	return nil
+}
+
+func unused() {}
+"#,
+            Language::Go,
+        )]);
+        assert_eq!(
+            entry_points(&go),
+            vec![
+                ("main".to_string(), EntryPointKind::Main),
+                ("handle".to_string(), EntryPointKind::LambdaHandler),
+            ]
+        );
+        assert_eq!(reachable_names(&go, "main"), vec!["main", "handle"]);
+
+        let js = graph(&[(
+            "src/app.js",
+            r#"
+exports.handler = async (event) => load(event.id);
+
+function load(id) {}
+
+router.post("/items", create);
+app.get("/health", (req, res) => res.send("ok"));
+
+function create(req, res) {}
+"#,
+            Language::JavaScript,
+        )]);
+        assert_eq!(
+            entry_points(&js),
+            vec![
+                (MODULE_SCOPE.to_string(), EntryPointKind::HttpRoute),
+                ("handler".to_string(), EntryPointKind::LambdaHandler),
+                ("create".to_string(), EntryPointKind::HttpRoute),
+            ]
+        );
+        assert_eq!(reachable_names(&js, "handler"), vec!["handler", "load"]);
+    }
+
+    fn graph_js() -> CallGraph {
+        graph(&[(
+            "src/index.js",
+            r#"
+const upload = async (bucket, key) => client.send(bucket, key);
+
+export function putBlob({ bucket }, key) {
+  return upload(bucket, key);
+}
+
+function remove(bucket) {}
+
+module.exports = { remove };
+"#,
+            Language::JavaScript,
+        )])
+    }
+}
@@ -0,0 +1,296 @@
+//! Diagnostics of the extraction: code the analysis skipped, excluded or could not resolve
+//!
+//! Static analysis silently over- or under-approximates when code hides its SDK calls. Every
+//! such case is reported as a [`Diagnostic`] with a stable [`DiagnosticCode`], a severity, the
+//! location it applies to and, where one exists, a suggested fix.
+
+use std::fmt;
+
+use ast_grep_core::tree_sitter::LanguageExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::extraction::{AstWithSourceFile, SdkMethodCall};
+use crate::Location;
+
+/// Kind of an extraction diagnostic
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema,
+)]
+pub enum DiagnosticCode {
+    /// A source file could not be parsed completely
+    ParseError,
+    /// An SDK call matches operations of several services
+    AmbiguousService,
+    /// The parameters of an SDK call are passed with dictionary unpacking
+    UnvalidatedParameters,
+    /// An SDK method is invoked through a name computed at runtime
+    DynamicMethodName,
+    /// A package of the AWS SDK is imported for a service the SDK models do not know
+    UnknownImport,
+    /// A test file, or a file that stubs the SDK, was excluded
+    TestFileExcluded,
+    /// An SDK call was excluded because no entry point reaches it
+    UnreachableCall,
+    /// Entry points were to be detected, but none was found
+    NoEntryPointDetected,
+}
+
+impl DiagnosticCode {
+    /// Severity of diagnostics of this kind
+    pub fn severity(self) -> DiagnosticSeverity {
+        match self {
+            Self::ParseError
+            | Self::AmbiguousService
+            | Self::DynamicMethodName
+            | Self::UnknownImport
+            | Self::NoEntryPointDetected => DiagnosticSeverity::Warning,
+            Self::UnvalidatedParameters | Self::TestFileExcluded | Self::UnreachableCall => {
+                DiagnosticSeverity::Info
+            }
+        }
+    }
+
+    /// One-line description of diagnostics of this kind
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::ParseError => "Source file contains syntax errors, SDK calls may be missed",
+            Self::AmbiguousService => "SDK call matches operations of several services",
+            Self::UnvalidatedParameters => {
+                "SDK call parameters passed with dictionary unpacking are not validated"
+            }
+            Self::DynamicMethodName => "SDK method invoked through a name computed at runtime",
+            Self::UnknownImport => "AWS SDK package of an unknown service",
+            Self::TestFileExcluded => "Test file excluded from the extraction",
+            Self::UnreachableCall => "SDK call not reachable from any entry point",
+            Self::NoEntryPointDetected => "No entry point detected",
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Severity of an extraction diagnostic
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema,
+)]
+pub enum DiagnosticSeverity {
+    /// Informational, e.g. code excluded on purpose
+    Info,
+    /// The generated policies may miss or over-grant permissions
+    Warning,
+    /// The extraction result is unusable
+    Error,
+}
+
+impl fmt::Display for DiagnosticSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A finding of the extraction about the analyzed code
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct Diagnostic {
+    /// Kind of the diagnostic
+    pub code: DiagnosticCode,
+    /// Severity of the diagnostic
+    pub severity: DiagnosticSeverity,
+    /// Location the diagnostic applies to, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    /// Description of the finding
+    pub message: String,
+    /// How to address the finding, e.g. with a service hint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_fix: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic with the default severity of its code
+    pub(crate) fn new(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity: code.severity(),
+            location: None,
+            message: message.into(),
+            suggested_fix: None,
+        }
+    }
+
+    /// Set the location the diagnostic applies to
+    pub(crate) fn with_location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Set how to address the finding
+    pub(crate) fn with_suggested_fix(mut self, suggested_fix: impl Into<String>) -> Self {
+        self.suggested_fix = Some(suggested_fix.into());
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: ", self.severity, self.code)?;
+        if let Some(location) = &self.location {
+            write!(f, "{}: ", location.to_gnu_format())?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(suggested_fix) = &self.suggested_fix {
+            write!(f, " ({suggested_fix})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Report the first syntax error of a parsed file
+pub(crate) fn parse_error<T: LanguageExt>(ast: &AstWithSourceFile<T>) -> Option<Diagnostic> {
+    let root = ast.ast.root();
+    let node = root
+        .dfs()
+        .find(|node| node.is_error() || node.is_missing())?;
+    let location = Location::from_node(ast.source_file.path.clone(), &node);
+    Some(
+        Diagnostic::new(
+            DiagnosticCode::ParseError,
+            format!(
+                "Syntax error in {}, SDK calls in the unparsed code are missed",
+                ast.source_file.path.display()
+            ),
+        )
+        .with_location(location)
+        .with_suggested_fix(format!(
+            "Fix the syntax error, or check that the file is written in {}",
+            ast.source_file.language
+        )),
+    )
+}
+
+/// Report the SDK calls whose service or parameters could not be determined
+pub(crate) fn method_call_diagnostics(method_calls: &[SdkMethodCall]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for call in method_calls {
+        let Some(metadata) = &call.metadata else {
+            continue;
+        };
+        if call.possible_services.len() > 1 {
+            diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticCode::AmbiguousService,
+                    format!(
+                        "'{}' matches operations of {} services: {}; permissions of all of them are granted",
+                        call.name,
+                        call.possible_services.len(),
+                        call.possible_services.join(", ")
+                    ),
+                )
+                .with_location(metadata.location.clone())
+                .with_suggested_fix(format!(
+                    "Add a service hint for the service the client is created for, one of: {}",
+                    call.possible_services.join(", ")
+                )),
+            );
+        }
+        if metadata.has_dictionary_unpacking() {
+            diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticCode::UnvalidatedParameters,
+                    format!(
+                        "Parameters of '{}' are passed with dictionary unpacking, so they are not validated and its resources cannot be resolved",
+                        call.name
+                    ),
+                )
+                .with_location(metadata.location.clone())
+                .with_suggested_fix("Pass the parameters as keyword arguments"),
+            );
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use ast_grep_language::Python;
+
+    use super::*;
+    use crate::extraction::{Parameter, ParameterValue, SdkMethodCallMetadata, SourceFile};
+    use crate::Language;
+
+    #[test]
+    fn test_parse_error() {
+        let parsed = |content: &str| {
+            let source_file = SourceFile::with_language(
+                PathBuf::from("app.py"),
+                content.to_string(),
+                Language::Python,
+            );
+            parse_error(&AstWithSourceFile::new(
+                Python.ast_grep(content),
+                source_file,
+            ))
+        };
+
+        assert_eq!(parsed("import boto3\nboto3.client('s3')\n"), None);
+        let diagnostic = parsed("import boto3\ndef broken(:\n    pass\n").unwrap();
+        assert_eq!(diagnostic.code, DiagnosticCode::ParseError);
+        assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostic.location.unwrap().start_position.0, 2);
+    }
+
+    #[test]
+    fn test_method_call_diagnostics() {
+        let call = |services: &[&str], parameters: Vec<Parameter>| SdkMethodCall {
+            name: "get_object".to_string(),
+            possible_services: services.iter().map(|s| s.to_string()).collect(),
+            metadata: Some(SdkMethodCallMetadata {
+                parameters,
+                return_type: None,
+                expr: "client.get_object(**kwargs)".to_string(),
+                location: Location::new(PathBuf::from("app.py"), (3, 1), (3, 28)),
+                receiver: Some("client".to_string()),
+            }),
+        };
+        let splat = Parameter::DictionarySplat {
+            expression: "kwargs".to_string(),
+            position: 0,
+        };
+        let keyword = Parameter::Keyword {
+            name: "Bucket".to_string(),
+            value: ParameterValue::Resolved("bucket".to_string()),
+            position: 0,
+            type_annotation: None,
+        };
+
+        let diagnostics = method_call_diagnostics(&[
+            call(&["s3"], vec![keyword]),
+            call(&["s3", "s3control"], vec![splat]),
+        ]);
+        let codes: Vec<_> = diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                DiagnosticCode::AmbiguousService,
+                DiagnosticCode::UnvalidatedParameters
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "warning[AmbiguousService]: app.py:3.1-3.28: 'get_object' matches operations of 2 \
+services: s3, s3control; permissions of all of them are granted (Add a service hint for the \
+service the client is created for, one of: s3, s3control)"
+        );
+    }
+}
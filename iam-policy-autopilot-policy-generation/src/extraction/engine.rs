@@ -9,27 +9,89 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 
 use crate::errors::{ExtractorError, Result};
+use crate::extraction::cache::ExtractionCache;
+use crate::extraction::diagnostics::method_call_diagnostics;
+use crate::extraction::entry_points::EntryPoints;
 use crate::extraction::extractor::Extractor;
 use crate::extraction::sdk_model::ServiceDiscovery;
-use crate::extraction::{self, ExtractedMethods, ExtractionMetadata, SourceFile};
-use crate::Language;
+use crate::extraction::sdk_wrappers::SdkWrappers;
+use crate::extraction::test_code::test_code_reason;
+use crate::extraction::{
+    self, Diagnostic, DiagnosticCode, ExtractedMethods, ExtractionMetadata, SourceFile,
+};
+use crate::progress::{self, Progress, ProgressReporter};
+use crate::{Language, Location};
 
 /// Core business logic for extracting method definitions and SDK method calls from source code.
+#[derive(Debug, Clone, Default)]
 #[non_exhaustive]
-pub struct Engine;
-
-impl Default for Engine {
-    fn default() -> Self {
-        Self::new()
-    }
+pub struct Engine {
+    /// User-supplied wrappers of SDK calls
+    sdk_wrappers: SdkWrappers,
+    /// Entry points SDK calls must be reachable from
+    entry_points: EntryPoints,
+    /// Extract SDK calls of test files and files that stub the SDK
+    include_tests: bool,
+    /// Parsed source files reused across extractions
+    cache: Option<ExtractionCache>,
+    /// Receives a report for each parsed source file
+    progress: Option<Arc<dyn ProgressReporter>>,
+    /// Aborts the pending parses when cancelled
+    cancellation: Option<CancellationToken>,
 }
 
 impl Engine {
     /// Create a new SDK method extractor with the specified providers.
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Also extract calls of the wrappers declared in the given SDK wrapper mapping files
+    ///
+    /// # Errors
+    /// Returns an error if a mapping file cannot be read or is invalid
+    pub fn with_sdk_wrappers(mut self, paths: &[PathBuf]) -> Result<Self> {
+        self.sdk_wrappers = SdkWrappers::load(paths)?;
+        Ok(self)
+    }
+
+    /// Only extract SDK calls reachable from the given entry points
+    pub fn with_entry_points(mut self, entry_points: EntryPoints) -> Self {
+        self.entry_points = entry_points;
+        self
+    }
+
+    /// Also extract SDK calls of test files and of files that stub the SDK
+    ///
+    /// By default, such files are excluded and reported in the extraction diagnostics.
+    pub fn with_include_tests(mut self, include_tests: bool) -> Self {
+        self.include_tests = include_tests;
+        self
+    }
+
+    /// Reuse the parses of unchanged source files cached by previous extractions
+    ///
+    /// Files parsed by this engine are added to the cache.
+    pub fn with_cache(mut self, cache: ExtractionCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Report each parsed source file to `progress`
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Abort the extraction when `cancellation` is cancelled
+    ///
+    /// The pending parses are aborted and the extraction fails.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
     }
 
     /// Extract SDK method calls from loaded source files with validation against AWS SDK service definitions.
@@ -85,23 +147,87 @@ impl Engine {
             _ => return Err(ExtractorError::unsupported_language_override(language)),
         };
 
+        // Test code and stubbed SDK usage never run in production
+        let mut diagnostics = Vec::new();
+        let source_files = if self.include_tests {
+            source_files
+        } else {
+            source_files
+                .into_iter()
+                .filter(|source_file| {
+                    let reason = test_code_reason(source_file);
+                    if let Some(reason) = &reason {
+                        diagnostics.push(
+                            Diagnostic::new(
+                                DiagnosticCode::TestFileExcluded,
+                                format!(
+                                    "Excluded test file {}: {}",
+                                    source_file.path.display(),
+                                    reason
+                                ),
+                            )
+                            .with_location(Location::new(source_file.path.clone(), (1, 1), (1, 1)))
+                            .with_suggested_fix(
+                                "Include test files if this code runs in production",
+                            ),
+                        );
+                    }
+                    reason.is_none()
+                })
+                .collect()
+        };
+
         // Initialize metadata with loaded files
-        let mut metadata = ExtractionMetadata::new(source_files.clone(), Vec::new());
+        let mut metadata = ExtractionMetadata::new(source_files.clone(), diagnostics);
 
         // Extract SDK method calls from all source files concurrently
         let mut all_extraction_results = Vec::new();
         let mut join_set = JoinSet::new();
+        let total = source_files.len();
+        let report_parsed = |parsed: usize| {
+            if let Some(progress) = &self.progress {
+                progress.report(Progress::FileParsed { parsed, total });
+            }
+        };
 
         for source_file in source_files {
+            if let Some(cached) = self
+                .cache
+                .as_ref()
+                .and_then(|cache| cache.get(&source_file))
+            {
+                all_extraction_results.push(cached);
+                report_parsed(all_extraction_results.len());
+                continue;
+            }
             let extractor = extractor.clone();
-            join_set.spawn(async move { extractor.parse(&source_file).await });
+            let cache = self.cache.clone();
+            join_set.spawn(async move {
+                let result = extractor.parse(&source_file).await;
+                if let Some(cache) = cache {
+                    cache.insert(&source_file, &result);
+                }
+                result
+            });
         }
 
         // Collect results from concurrent tasks
-        while let Some(result) = join_set.join_next().await {
+        loop {
+            let result = tokio::select! {
+                biased;
+                () = progress::cancelled(self.cancellation.as_ref()) => {
+                    join_set.abort_all();
+                    return Err(ExtractorError::Cancelled);
+                }
+                result = join_set.join_next() => result,
+            };
+            let Some(result) = result else {
+                break;
+            };
             match result {
                 Ok(extraction_result) => {
                     all_extraction_results.push(extraction_result);
+                    report_parsed(all_extraction_results.len());
                 }
                 Err(e) => {
                     // Task join error - this is more serious
@@ -114,16 +240,54 @@ impl Engine {
             }
         }
 
+        // Results arrive in completion order; report diagnostics in file order
+        let mut file_diagnostics: Vec<Diagnostic> = all_extraction_results
+            .iter()
+            .flat_map(|result| {
+                result
+                    .parse_error()
+                    .into_iter()
+                    .chain(extractor.diagnose(result, &service_index))
+            })
+            .collect();
+        file_diagnostics.sort_by_key(|diagnostic| {
+            diagnostic.location.as_ref().map(|location| {
+                (
+                    location.file_path.clone(),
+                    location.start_position,
+                    location.end_position,
+                )
+            })
+        });
+        metadata.diagnostics.append(&mut file_diagnostics);
+
         extractor.filter_map(&mut all_extraction_results, &service_index);
 
         // Disambiguate and validate method calls against SDK definitions
         extractor.disambiguate(&mut all_extraction_results, &service_index);
 
-        let method_calls = all_extraction_results
+        // Wrapper calls name their services explicitly and need no disambiguation
+        if !self.sdk_wrappers.is_empty() {
+            self.sdk_wrappers
+                .extract(language, &mut all_extraction_results, &service_index);
+        }
+
+        let mut method_calls = all_extraction_results
             .into_iter()
             .flat_map(|r| r.method_calls())
             .collect::<Vec<_>>();
 
+        if !self.entry_points.is_empty() {
+            self.entry_points.retain_reachable(
+                &metadata.source_files,
+                &mut method_calls,
+                &mut metadata.diagnostics,
+            )?;
+        }
+        metadata
+            .diagnostics
+            .extend(method_call_diagnostics(&method_calls));
+
         // Update metadata with final method count
         metadata.update_method_count(method_calls.len());
 
@@ -187,7 +351,10 @@ impl Engine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::extraction::ExtractionCacheStatistics;
+    use crate::progress::tests::RecordingReporter;
     use std::path::PathBuf;
+    use std::sync::PoisonError;
 
     #[test]
     fn test_language_detection() {
@@ -261,6 +428,249 @@ def helper_function():
         assert_eq!(results.metadata.source_files[0].language, Language::Python);
     }
 
+    /// Test that test files and files stubbing the SDK are excluded unless included explicitly.
+    #[tokio::test]
+    async fn test_exclude_test_files() {
+        let source_files = vec![
+            SourceFile::with_language(
+                PathBuf::from("app/handler.py"),
+                "import boto3\nboto3.client('s3').list_buckets()\n".to_string(),
+                Language::Python,
+            ),
+            SourceFile::with_language(
+                PathBuf::from("tests/test_handler.py"),
+                "import boto3\nboto3.client('s3').create_bucket(Bucket='b')\n".to_string(),
+                Language::Python,
+            ),
+            SourceFile::with_language(
+                PathBuf::from("app/fixtures.py"),
+                "import boto3\nfrom botocore.stub import Stubber\nboto3.client('s3').delete_bucket(Bucket='b')\n"
+                    .to_string(),
+                Language::Python,
+            ),
+        ];
+
+        let results = Engine::new()
+            .extract_sdk_method_calls(Language::Python, source_files.clone())
+            .await
+            .unwrap();
+        let names: Vec<_> = results.methods.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["list_buckets"]);
+        assert_eq!(results.metadata.source_files.len(), 1);
+        let messages: Vec<_> = results
+            .metadata
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Excluded test file tests/test_handler.py: named like a test module",
+                "Excluded test file app/fixtures.py: stubs the AWS SDK with botocore Stubber",
+            ]
+        );
+
+        let results = Engine::new()
+            .with_include_tests(true)
+            .extract_sdk_method_calls(Language::Python, source_files)
+            .await
+            .unwrap();
+        assert_eq!(results.methods.len(), 3);
+        assert!(!results
+            .metadata
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.code == DiagnosticCode::TestFileExcluded));
+    }
+
+    /// Test that only source files whose content changed are parsed again with a cache.
+    #[tokio::test]
+    async fn test_extraction_cache() {
+        let source_file = |name: &str, content: &str| {
+            SourceFile::with_language(PathBuf::from(name), content.to_string(), Language::Python)
+        };
+        let app = "import boto3\ns3 = boto3.client('s3')\ns3.get_object(Bucket='b', Key='k')\n";
+        let cache = ExtractionCache::new();
+        let engine = Engine::new().with_cache(cache.clone());
+
+        let results = engine
+            .extract_sdk_method_calls(
+                Language::Python,
+                vec![
+                    source_file("app.py", app),
+                    source_file("util.py", "x = 1\n"),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.methods.len(), 1);
+        assert_eq!(cache.len(), 2);
+
+        let edited = format!("{app}s3.put_object(Bucket='b', Key='k', Body=b'')\n");
+        let results = Engine::new()
+            .with_cache(cache.clone())
+            .extract_sdk_method_calls(
+                Language::Python,
+                vec![
+                    source_file("app.py", &edited),
+                    source_file("util.py", "x = 1\n"),
+                ],
+            )
+            .await
+            .unwrap();
+        let mut names: Vec<_> = results.methods.iter().map(|m| m.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["get_object", "put_object"]);
+        assert_eq!(
+            cache.statistics(),
+            ExtractionCacheStatistics {
+                parsed_files: 3,
+                reused_files: 1,
+            }
+        );
+    }
+
+    /// Test that a full cache drops the least recently used source files.
+    #[tokio::test]
+    async fn test_extraction_cache_eviction() {
+        let extract = |cache: &ExtractionCache, names: &[&str]| {
+            let source_files = names
+                .iter()
+                .map(|name| {
+                    SourceFile::with_language(
+                        PathBuf::from(name),
+                        "x = 1\n".into(),
+                        Language::Python,
+                    )
+                })
+                .collect();
+            let engine = Engine::new().with_cache(cache.clone());
+            async move {
+                engine
+                    .extract_sdk_method_calls(Language::Python, source_files)
+                    .await
+                    .unwrap();
+            }
+        };
+        let cache = ExtractionCache::with_max_files(2);
+
+        extract(&cache, &["a.py", "b.py"]).await;
+        extract(&cache, &["a.py"]).await;
+        // b.py is dropped for c.py, while the more recently used a.py is kept
+        extract(&cache, &["c.py"]).await;
+        assert_eq!(cache.len(), 2);
+        extract(&cache, &["a.py"]).await;
+        extract(&cache, &["b.py"]).await;
+        assert_eq!(
+            cache.statistics(),
+            ExtractionCacheStatistics {
+                parsed_files: 4,
+                reused_files: 2,
+            }
+        );
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_extraction_progress_and_cancellation() {
+        let source_files = || {
+            ["app.py", "util.py", "main.py"]
+                .into_iter()
+                .map(|name| {
+                    SourceFile::with_language(
+                        PathBuf::from(name),
+                        "x = 1\n".into(),
+                        Language::Python,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let reporter = Arc::new(RecordingReporter::default());
+        Engine::new()
+            .with_progress(reporter.clone())
+            .extract_sdk_method_calls(Language::Python, source_files())
+            .await
+            .unwrap();
+        assert_eq!(
+            *reporter.0.lock().unwrap_or_else(PoisonError::into_inner),
+            (1..=3)
+                .map(|parsed| Progress::FileParsed { parsed, total: 3 })
+                .collect::<Vec<_>>()
+        );
+
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let error = Engine::new()
+            .with_cancellation(cancellation)
+            .extract_sdk_method_calls(Language::Python, source_files())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ExtractorError::Cancelled));
+    }
+
+    /// Test that code hiding SDK calls from the extraction is reported in file order.
+    #[tokio::test]
+    async fn test_extraction_diagnostics() {
+        let codes = |results: &ExtractedMethods| {
+            results
+                .metadata
+                .diagnostics
+                .iter()
+                .map(|diagnostic| {
+                    let line = diagnostic.location.as_ref().map(Location::start_line);
+                    (diagnostic.code, line)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let results = Engine::new()
+            .extract_sdk_method_calls(
+                Language::Python,
+                vec![
+                    SourceFile::with_language(
+                        PathBuf::from("b.py"),
+                        "import boto3\ndef broken(:\n    pass\n".to_string(),
+                        Language::Python,
+                    ),
+                    SourceFile::with_language(
+                        PathBuf::from("a.py"),
+                        "import boto3\nclient = boto3.client('s3')\ngetattr(client, operation)(**kwargs)\nclient.put_object(**kwargs)\n"
+                            .to_string(),
+                        Language::Python,
+                    ),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            codes(&results),
+            vec![
+                (DiagnosticCode::DynamicMethodName, Some(3)),
+                (DiagnosticCode::ParseError, Some(2)),
+                (DiagnosticCode::AmbiguousService, Some(4)),
+                (DiagnosticCode::UnvalidatedParameters, Some(4)),
+            ]
+        );
+
+        let results = Engine::new()
+            .extract_sdk_method_calls(
+                Language::Go,
+                vec![SourceFile::with_language(
+                    PathBuf::from("main.go"),
+                    "package main\n\nimport (\n\t\"github.com/aws/aws-sdk-go-v2/service/cloudwatchlogs\"\n\t\"github.com/aws/aws-sdk-go-v2/service/notaservice\"\n)\n"
+                        .to_string(),
+                    Language::Go,
+                )],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            codes(&results),
+            vec![(DiagnosticCode::UnknownImport, Some(5))]
+        );
+    }
+
     /// Test that the extractor handles empty source files list appropriately.
     #[tokio::test]
     async fn test_empty_source_files_handling() {
@@ -0,0 +1,289 @@
+//! Reachability of SDK calls from the entry points of an application
+//!
+//! SDK calls in code that never runs, like dead code, tests and one-off scripts, do not need
+//! permissions. When entry points are given, the [`CallGraph`] of the analyzed files is built
+//! and only the SDK calls of functions reachable from an entry point are kept. The module scope
+//! of an entry point's file, and of every file it imports, runs too. Entry points are declared
+//! explicitly, or detected: `main` functions, Lambda handlers and HTTP route handlers.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{ExtractorError, Result};
+use crate::extraction::call_graph::CallGraph;
+use crate::extraction::{Diagnostic, DiagnosticCode, SdkMethodCall, SourceFile};
+
+/// A declared entry point: a function, or the top-level code of a file
+///
+/// Written as `FILE:FUNCTION`, `FILE:TYPE.METHOD` or `FILE`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct EntryPoint {
+    /// Source file defining the entry point
+    pub file: PathBuf,
+    /// Function or `Type.method` name, or `None` for the top-level code of the file
+    pub function: Option<String>,
+}
+
+impl FromStr for EntryPoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        // The function name never contains path separators, unlike a Windows drive prefix
+        let (file, function) = match s.rsplit_once(':') {
+            Some((file, function)) if !file.is_empty() && !function.contains(['/', '\\']) => {
+                (file, Some(function))
+            }
+            _ => (s, None),
+        };
+        if file.is_empty() || function.is_some_and(str::is_empty) {
+            return Err(format!(
+                "Invalid entry point '{s}', expected FILE, FILE:FUNCTION or FILE:TYPE.METHOD"
+            ));
+        }
+        Ok(Self {
+            file: PathBuf::from(file),
+            function: function.map(str::to_string),
+        })
+    }
+}
+
+impl TryFrom<String> for EntryPoint {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<EntryPoint> for String {
+    fn from(value: EntryPoint) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for EntryPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.function {
+            Some(function) => write!(f, "{}:{}", self.file.display(), function),
+            None => write!(f, "{}", self.file.display()),
+        }
+    }
+}
+
+/// Entry points SDK calls must be reachable from to be extracted
+///
+/// Without any entry point, all SDK calls are extracted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntryPoints {
+    /// Detect `main` functions, Lambda handlers and HTTP route handlers
+    pub detect: bool,
+    /// Explicitly declared entry points
+    pub declared: Vec<EntryPoint>,
+}
+
+impl EntryPoints {
+    /// Whether SDK calls are extracted regardless of reachability
+    pub fn is_empty(&self) -> bool {
+        !self.detect && self.declared.is_empty()
+    }
+
+    /// Keep only the method calls reachable from the entry points
+    ///
+    /// Every excluded call is reported in `diagnostics`. If entry points are only detected and
+    /// none is found, no call is excluded.
+    ///
+    /// # Errors
+    /// Returns an error if a declared entry point is not defined in the source files
+    pub(crate) fn retain_reachable(
+        &self,
+        source_files: &[SourceFile],
+        method_calls: &mut Vec<SdkMethodCall>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<()> {
+        let graph = CallGraph::build(source_files);
+
+        let mut roots = Vec::new();
+        if self.detect {
+            for (&id, kind) in graph.entry_points() {
+                let function = &graph.functions()[id];
+                log::debug!(
+                    "Detected {} '{}' at {}",
+                    kind,
+                    function.name,
+                    function.location.to_gnu_format()
+                );
+                roots.push(id);
+            }
+        }
+        for entry_point in &self.declared {
+            let found = graph.find(&entry_point.file, entry_point.function.as_deref());
+            if found.is_empty() {
+                return Err(ExtractorError::validation(format!(
+                    "Entry point '{entry_point}' is not defined in the analyzed source files"
+                )));
+            }
+            roots.extend(found);
+        }
+        if roots.is_empty() {
+            diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticCode::NoEntryPointDetected,
+                    "No entry points detected, SDK calls are not filtered by reachability",
+                )
+                .with_suggested_fix("Declare the entry points of the application"),
+            );
+            return Ok(());
+        }
+
+        let modules: Vec<usize> = roots.iter().map(|&id| graph.module_scope(id)).collect();
+        let reachable = graph.reachable_from(roots.into_iter().chain(modules));
+        method_calls.retain(|call| {
+            let Some(metadata) = &call.metadata else {
+                return true;
+            };
+            let is_reachable = graph
+                .enclosing_function(&metadata.location)
+                .is_none_or(|id| reachable.contains(&id));
+            if !is_reachable {
+                diagnostics.push(
+                    Diagnostic::new(
+                        DiagnosticCode::UnreachableCall,
+                        format!(
+                            "Excluded SDK call '{}': not reachable from any entry point",
+                            metadata.expr
+                        ),
+                    )
+                    .with_location(metadata.location.clone())
+                    .with_suggested_fix(
+                        "Declare the function running this call as an entry point if it runs in production",
+                    ),
+                );
+            }
+            is_reachable
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extraction::SdkMethodCallMetadata;
+    use crate::{Language, Location};
+
+    fn source(path: &str, content: &str) -> SourceFile {
+        SourceFile::with_language(PathBuf::from(path), content.to_string(), Language::Python)
+    }
+
+    fn sdk_call(expr: &str, path: &str, line: usize) -> SdkMethodCall {
+        SdkMethodCall {
+            name: expr.to_string(),
+            possible_services: vec!["s3".to_string()],
+            metadata: Some(SdkMethodCallMetadata {
+                parameters: Vec::new(),
+                return_type: None,
+                expr: expr.to_string(),
+                location: Location::new(PathBuf::from(path), (line, 5), (line, 5 + expr.len())),
+                receiver: Some("s3".to_string()),
+            }),
+        }
+    }
+
+    fn retained(entry_points: &EntryPoints, source_files: &[SourceFile]) -> Vec<String> {
+        let mut calls = vec![
+            sdk_call("s3.get_object()", "app/handler.py", 5),
+            sdk_call("s3.put_object()", "app/storage.py", 6),
+            sdk_call("s3.delete_bucket()", "app/storage.py", 9),
+            sdk_call("s3.create_bucket()", "scripts/setup.py", 5),
+        ];
+        let mut diagnostics = Vec::new();
+        entry_points
+            .retain_reachable(source_files, &mut calls, &mut diagnostics)
+            .unwrap();
+        calls.into_iter().map(|call| call.name).collect()
+    }
+
+    #[test]
+    fn test_retain_reachable_from_detected_and_declared_entry_points() {
+        let source_files = [
+            source(
+                "app/handler.py",
+                r#"
+from app.storage import save
+
+def handler(event, context):
+    s3.get_object()
+    save(event)
+"#,
+            ),
+            source(
+                "app/storage.py",
+                r#"
+import boto3
+s3 = boto3.client("s3")
+
+def save(event):
+    s3.put_object()
+
+def purge():
+    s3.delete_bucket()
+"#,
+            ),
+            source(
+                "scripts/setup.py",
+                r#"
+import boto3
+
+def setup():
+    s3.create_bucket()
+"#,
+            ),
+        ];
+
+        let detected = EntryPoints {
+            detect: true,
+            declared: Vec::new(),
+        };
+        assert_eq!(
+            retained(&detected, &source_files),
+            vec!["s3.get_object()", "s3.put_object()"]
+        );
+
+        let declared = EntryPoints {
+            detect: true,
+            declared: vec!["scripts/setup.py:setup".parse().unwrap()],
+        };
+        assert_eq!(
+            retained(&declared, &source_files),
+            vec!["s3.get_object()", "s3.put_object()", "s3.create_bucket()"]
+        );
+
+        let missing = EntryPoints {
+            detect: false,
+            declared: vec!["app/handler.py:main".parse().unwrap()],
+        };
+        let mut calls = Vec::new();
+        assert!(missing
+            .retain_reachable(&source_files, &mut calls, &mut Vec::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_entry_point() {
+        let entry_point: EntryPoint = "src/app.py:Handler.handle".parse().unwrap();
+        assert_eq!(entry_point.file, PathBuf::from("src/app.py"));
+        assert_eq!(entry_point.function.as_deref(), Some("Handler.handle"));
+        assert_eq!(entry_point.to_string(), "src/app.py:Handler.handle");
+
+        let entry_point: EntryPoint = "scripts/job.py".parse().unwrap();
+        assert_eq!(entry_point.function, None);
+        let entry_point: EntryPoint = r"C:\app\main.go".parse().unwrap();
+        assert_eq!(entry_point.file, PathBuf::from(r"C:\app\main.go"));
+
+        assert!("app.py:".parse::<EntryPoint>().is_err());
+    }
+}
@@ -2,6 +2,7 @@
 use ast_grep_language::{Go, JavaScript, Python, TypeScript};
 use async_trait::async_trait;
 
+use crate::extraction::diagnostics::{parse_error, Diagnostic};
 use crate::extraction::go::types::GoImportInfo;
 use crate::extraction::AstWithSourceFile;
 use crate::{SdkMethodCall, ServiceModelIndex, SourceFile};
@@ -37,6 +38,26 @@ impl ExtractorResult {
         }
     }
 
+    /// Get a mutable reference to the method calls
+    pub(crate) fn method_calls_mut(&mut self) -> &mut Vec<SdkMethodCall> {
+        match self {
+            ExtractorResult::Python(_, calls) => calls,
+            ExtractorResult::Go(_, calls, _) => calls,
+            ExtractorResult::JavaScript(_, calls) => calls,
+            ExtractorResult::TypeScript(_, calls) => calls,
+        }
+    }
+
+    /// Report the first syntax error of the parsed file
+    pub(crate) fn parse_error(&self) -> Option<Diagnostic> {
+        match self {
+            ExtractorResult::Python(ast, _) => parse_error(ast),
+            ExtractorResult::Go(ast, _, _) => parse_error(ast),
+            ExtractorResult::JavaScript(ast, _) => parse_error(ast),
+            ExtractorResult::TypeScript(ast, _) => parse_error(ast),
+        }
+    }
+
     /// Get a reference to the import information for Go results
     #[allow(dead_code)]
     pub(crate) fn go_import_info(&self) -> Option<&GoImportInfo> {
@@ -65,4 +86,13 @@ pub(crate) trait Extractor: Send + Sync {
         extraction_results: &mut [ExtractorResult],
         service_index: &ServiceModelIndex,
     );
+
+    /// Report language constructs of a parsed file that hide SDK calls from the extraction
+    fn diagnose(
+        &self,
+        _extraction_result: &ExtractorResult,
+        _service_index: &ServiceModelIndex,
+    ) -> Vec<Diagnostic> {
+        Vec::new()
+    }
 }
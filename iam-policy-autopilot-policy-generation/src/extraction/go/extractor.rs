@@ -1,5 +1,6 @@
 //! SDK method extraction for Go using ast-grep
 
+use crate::extraction::diagnostics::{Diagnostic, DiagnosticCode};
 use crate::extraction::extractor::{Extractor, ExtractorResult};
 use crate::extraction::go::disambiguation::GoMethodDisambiguator;
 use crate::extraction::go::features_extractor::GoFeaturesExtractor;
@@ -524,6 +525,43 @@ rule:
         _service_index: &ServiceModelIndex,
     ) {
     }
+
+    fn diagnose(
+        &self,
+        extraction_result: &ExtractorResult,
+        service_index: &ServiceModelIndex,
+    ) -> Vec<Diagnostic> {
+        let ExtractorResult::Go(ast, _, import_info) = extraction_result else {
+            return Vec::new();
+        };
+        import_info
+            .imports
+            .iter()
+            .filter_map(|import| {
+                let service_name = import.service_name.as_ref()?;
+                if service_index.has_sdk_package(service_name) {
+                    return None;
+                }
+                Some(
+                    Diagnostic::new(
+                        DiagnosticCode::UnknownImport,
+                        format!(
+                            "Import of '{}' refers to service '{}', which the SDK models do not know; its calls are not extracted",
+                            import.original_name, service_name
+                        ),
+                    )
+                    .with_location(Location::new(
+                        ast.source_file.path.clone(),
+                        (import.line, 1),
+                        (import.line, 1),
+                    ))
+                    .with_suggested_fix(
+                        "Update the SDK models, or add the permissions of this service manually",
+                    ),
+                )
+            })
+            .collect()
+    }
 }
 
 // Helper methods for creating Go-specific parameters
@@ -911,10 +911,12 @@ func main() {
 
         // Should have the input variable parameter (client is skipped in creation_arguments)
         assert_eq!(metadata.parameters.len(), 1);
-        if let Parameter::Positional { value, .. } = &metadata.parameters[0] {
-            if let crate::extraction::ParameterValue::Unresolved(var_name) = value {
-                assert_eq!(var_name, "input");
-            }
+        if let Parameter::Positional {
+            value: crate::extraction::ParameterValue::Unresolved(var_name),
+            ..
+        } = &metadata.parameters[0]
+        {
+            assert_eq!(var_name, "input");
         }
     }
 
@@ -975,10 +977,12 @@ func main() {
         // Should have the input variable parameter
         let metadata = paginator_calls[0].metadata.as_ref().unwrap();
         assert_eq!(metadata.parameters.len(), 1);
-        if let Parameter::Positional { value, .. } = &metadata.parameters[0] {
-            if let crate::extraction::ParameterValue::Unresolved(var_name) = value {
-                assert_eq!(var_name, "input");
-            }
+        if let Parameter::Positional {
+            value: crate::extraction::ParameterValue::Unresolved(var_name),
+            ..
+        } = &metadata.parameters[0]
+        {
+            assert_eq!(var_name, "input");
         }
     }
 }
@@ -7,6 +7,7 @@ use ast_grep_language::JavaScript;
 use async_trait::async_trait;
 use std::collections::HashSet;
 
+use crate::extraction::diagnostics::Diagnostic;
 use crate::extraction::extractor::{Extractor, ExtractorResult};
 use crate::extraction::javascript::scanner::ASTScanner;
 use crate::extraction::javascript::shared::ExtractionUtils;
@@ -154,6 +155,18 @@ impl Extractor for JavaScriptExtractor {
         // @aws-sdk/client-{service} imports clearly indicate the target service
         // No disambiguation needed - pass through all operations
     }
+
+    fn diagnose(
+        &self,
+        extraction_result: &ExtractorResult,
+        service_index: &ServiceModelIndex,
+    ) -> Vec<Diagnostic> {
+        let ExtractorResult::JavaScript(ast, _) = extraction_result else {
+            return Vec::new();
+        };
+        let mut scanner = ASTScanner::new(ast.clone(), JavaScript.into());
+        ExtractionUtils::unknown_import_diagnostics(&mut scanner, service_index)
+    }
 }
 
 #[cfg(test)]
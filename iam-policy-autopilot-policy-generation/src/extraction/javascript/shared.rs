@@ -3,9 +3,10 @@
 //! This module contains common functionality shared between JavaScript and TypeScript
 //! extractors.
 
+use crate::extraction::diagnostics::{Diagnostic, DiagnosticCode};
 use crate::extraction::javascript::types::{ImportInfo, JavaScriptScanResults};
 use crate::extraction::{Parameter, ParameterValue, SdkMethodCall, SdkMethodCallMetadata};
-use crate::Location;
+use crate::{Location, ServiceModelIndex};
 use rust_embed::RustEmbed;
 use serde::Deserialize;
 use std::borrow::Cow;
@@ -93,6 +94,43 @@ impl From<&ImportInfo> for CommandUsage<'_> {
 pub(crate) struct ExtractionUtils;
 
 impl ExtractionUtils {
+    /// Report imports of `@aws-sdk/client-*` packages of services the SDK models do not know
+    pub(crate) fn unknown_import_diagnostics<T>(
+        scanner: &mut crate::extraction::javascript::scanner::ASTScanner<T>,
+        service_index: &ServiceModelIndex,
+    ) -> Vec<Diagnostic>
+    where
+        T: ast_grep_language::LanguageExt,
+    {
+        let Ok((imports, requires)) = scanner.scan_all_aws_imports() else {
+            return Vec::new();
+        };
+        imports
+            .iter()
+            .chain(&requires)
+            .filter_map(|sublibrary_info| {
+                let service = sublibrary_info.sublibrary.strip_prefix("client-")?;
+                if service_index.has_sdk_package(service) {
+                    return None;
+                }
+                let mut diagnostic = Diagnostic::new(
+                    DiagnosticCode::UnknownImport,
+                    format!(
+                        "Import of '@aws-sdk/{}' refers to service '{}', which the SDK models do not know; its calls are not extracted",
+                        sublibrary_info.sublibrary, service
+                    ),
+                )
+                .with_suggested_fix(
+                    "Update the SDK models, or add the permissions of this service manually",
+                );
+                if let Some(import_info) = sublibrary_info.imports.first() {
+                    diagnostic = diagnostic.with_location(import_info.location.clone());
+                }
+                Some(diagnostic)
+            })
+            .collect()
+    }
+
     /// Extract operations from imported types and their usage patterns
     pub(crate) fn extract_operations_from_imports<T>(
         scan_results: &JavaScriptScanResults,
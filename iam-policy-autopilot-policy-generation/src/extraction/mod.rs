@@ -7,20 +7,31 @@
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+pub(crate) mod cache;
+pub(crate) mod call_graph;
+pub(crate) mod diagnostics;
 pub(crate) mod engine;
+pub(crate) mod entry_points;
 pub(crate) mod extractor;
 pub(crate) mod go;
 pub(crate) mod javascript;
 pub(crate) mod python;
 pub(crate) mod sdk_model;
+pub(crate) mod sdk_wrappers;
 pub(crate) mod service_hints;
 pub(crate) mod shared;
+pub(crate) mod test_code;
 pub(crate) mod typescript;
 pub(crate) mod waiter_model;
+pub(crate) mod wrapper_derivation;
 
 // Re-export main types for convenience
+pub use cache::{ExtractionCache, ExtractionCacheStatistics};
+pub use diagnostics::{Diagnostic, DiagnosticCode, DiagnosticSeverity};
 pub use engine::Engine;
+pub use entry_points::{EntryPoint, EntryPoints};
 pub(crate) use sdk_model::ServiceModelIndex;
+pub use sdk_wrappers::{SdkWrapper, SdkWrapperMapping, SdkWrapperParameter};
 pub(crate) use service_hints::ServiceHintsProcessor;
 
 // Re-export all core and output types for convenience
@@ -125,6 +136,11 @@ pub mod core {
     }
 
     impl SdkMethodCallMetadata {
+        /// Location of the method call in the source code
+        pub fn location(&self) -> &Location {
+            &self.location
+        }
+
         /// Returns whether this method call uses dictionary unpacking
         /// If true, parameter validation should be skipped
         pub(crate) fn has_dictionary_unpacking(&self) -> bool {
@@ -138,7 +154,7 @@ pub mod core {
     ///
     /// Contains the essential method identification information with optional
     /// detailed metadata. This is the core output of the parsing process.
-    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
     #[serde(rename_all = "PascalCase")]
     #[non_exhaustive]
     pub struct SdkMethodCall {
@@ -252,7 +268,7 @@ pub mod core {
 
 /// Output data structures for extraction results and metadata
 pub mod output {
-    use super::{Deserialize, SdkMethodCall, Serialize, SourceFile};
+    use super::{Deserialize, Diagnostic, SdkMethodCall, Serialize, SourceFile};
 
     /// Complete extraction results
     ///
@@ -273,7 +289,7 @@ pub mod output {
     /// Metadata about the extraction process
     ///
     /// Provides information about when the extraction was performed,
-    /// what files were processed, and any warnings or issues encountered.
+    /// what files were processed, and any diagnostics about the analyzed code.
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
     #[serde(rename_all = "PascalCase")]
     #[non_exhaustive]
@@ -284,14 +300,14 @@ pub mod output {
         pub source_files: Vec<SourceFile>,
         /// Total number of methods extracted
         pub total_methods: usize,
-        /// List of warnings or non-fatal issues encountered
-        pub warnings: Vec<String>,
+        /// Code the extraction skipped, excluded or could not resolve
+        pub diagnostics: Vec<Diagnostic>,
     }
 
     impl ExtractionMetadata {
         /// Create new extraction metadata with current timestamp
         #[must_use]
-        pub(crate) fn new(source_files: Vec<SourceFile>, warnings: Vec<String>) -> Self {
+        pub(crate) fn new(source_files: Vec<SourceFile>, diagnostics: Vec<Diagnostic>) -> Self {
             let extraction_time = Self::current_timestamp();
             let total_methods = 0; // Will be updated when methods are added
 
@@ -299,7 +315,7 @@ pub mod output {
                 extraction_time,
                 source_files,
                 total_methods,
-                warnings,
+                diagnostics,
             }
         }
 
@@ -356,7 +372,7 @@ mod tests {
         let metadata = ExtractionMetadata::new(vec![], vec![]);
         assert!(!metadata.extraction_time.is_empty());
         assert_eq!(metadata.total_methods, 0);
-        assert_eq!(metadata.warnings.len(), 0);
+        assert_eq!(metadata.diagnostics.len(), 0);
     }
 
     #[test]
@@ -426,7 +442,13 @@ fn test_extracted_methods_serialization() {
 
 #[test]
 fn test_extraction_metadata_serialization() {
-    let metadata = ExtractionMetadata::new(vec![], vec!["warning1".to_string()]);
+    let metadata = ExtractionMetadata::new(
+        vec![],
+        vec![Diagnostic::new(
+            DiagnosticCode::NoEntryPointDetected,
+            "No entry point detected",
+        )],
+    );
 
     let json = serde_json::to_string(&metadata).unwrap();
 
@@ -434,5 +456,7 @@ fn test_extraction_metadata_serialization() {
     assert!(json.contains("\"ExtractionTime\""));
     assert!(json.contains("\"SourceFiles\""));
     assert!(json.contains("\"TotalMethods\""));
-    assert!(json.contains("\"Warnings\""));
+    assert!(json.contains("\"Diagnostics\""));
+    assert!(json.contains("\"Code\":\"NoEntryPointDetected\""));
+    assert!(json.contains("\"Severity\":\"Warning\""));
 }
@@ -724,8 +724,7 @@ mod tests {
         let result = Boto3ResourcesModel::load_from_embedded("dynamodb");
 
         // This test will only pass if embedded data is available
-        if result.is_ok() {
-            let model = result.unwrap();
+        if let Ok(model) = result {
             assert_eq!(model.service_name, "dynamodb");
 
             // Check that Table constructor exists
@@ -748,8 +747,7 @@ mod tests {
         let result = Boto3ResourcesModel::load_from_embedded("s3");
 
         // This test will only pass if embedded data is available
-        if result.is_ok() {
-            let model = result.unwrap();
+        if let Ok(model) = result {
             assert_eq!(model.service_name, "s3");
 
             // Check that Bucket constructor exists
@@ -772,8 +770,7 @@ mod tests {
         // Test that we can access the embedded utilities mapping
         let result = extract_services_from_embedded_utilities_mapping();
 
-        if result.is_ok() {
-            let services = result.unwrap();
+        if let Ok(services) = result {
             assert!(
                 !services.is_empty(),
                 "Should extract at least one service from utilities mapping"
@@ -1,5 +1,6 @@
 //! SDK method extraction for Python using ast-grep
 
+use crate::extraction::diagnostics::{Diagnostic, DiagnosticCode};
 use crate::extraction::extractor::{Extractor, ExtractorResult};
 use crate::extraction::python::common::ArgumentExtractor;
 use crate::extraction::python::disambiguation::MethodDisambiguator;
@@ -147,6 +148,48 @@ impl Extractor for PythonExtractor {
         _service_index: &ServiceModelIndex,
     ) {
     }
+
+    fn diagnose(
+        &self,
+        extraction_result: &ExtractorResult,
+        _service_index: &ServiceModelIndex,
+    ) -> Vec<Diagnostic> {
+        let ExtractorResult::Python(ast, _) = extraction_result else {
+            return Vec::new();
+        };
+        if !ast.source_file.content.contains("boto3") {
+            return Vec::new();
+        }
+
+        // getattr(client, operation) hides the operation name from the extraction
+        let root = ast.ast.root();
+        ["getattr($OBJ, $NAME)", "getattr($OBJ, $NAME, $DEFAULT)"]
+            .into_iter()
+            .flat_map(|pattern| root.find_all(pattern).collect::<Vec<_>>())
+            .filter(|node_match| {
+                node_match
+                    .get_env()
+                    .get_match("NAME")
+                    .is_some_and(|name| name.kind() != "string")
+            })
+            .map(|node_match| {
+                Diagnostic::new(
+                    DiagnosticCode::DynamicMethodName,
+                    format!(
+                        "'{}' looks up a method by a name computed at runtime; SDK operations called through it are missed",
+                        node_match.text()
+                    ),
+                )
+                .with_location(Location::from_node(
+                    ast.source_file.path.clone(),
+                    node_match.get_node(),
+                ))
+                .with_suggested_fix(
+                    "Call the SDK operations directly, or declare them in an SDK wrapper mapping",
+                )
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
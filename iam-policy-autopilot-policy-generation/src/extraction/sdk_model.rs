@@ -132,6 +132,26 @@ pub(crate) struct ServiceModelIndex {
     pub(crate) waiter_lookup: HashMap<String, Vec<ServiceMethodRef>>,
 }
 
+impl ServiceModelIndex {
+    /// Whether an SDK package name refers to a known service
+    ///
+    /// Package names of the Go and JavaScript SDKs follow the service ID rather than the
+    /// botocore name (e.g. `cloudwatchlogs` and `cloudwatch-logs` for `logs`), so both are
+    /// compared with case, dashes and spaces ignored.
+    pub(crate) fn has_sdk_package(&self, package: &str) -> bool {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(|c| *c != '-' && *c != ' ')
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let package = normalize(package);
+        self.services.iter().any(|(name, definition)| {
+            normalize(name) == package || normalize(&definition.metadata.service_id) == package
+        })
+    }
+}
+
 /// Reference to a service method for lookup purposes
 ///
 /// Links a language-specific method name to its corresponding AWS service
@@ -165,7 +185,7 @@ impl ServiceDiscovery {
     /// Gets all available AWS services from the embedded service definitions.
     /// Each service includes its name and API version. Since build.rs only processes
     /// the latest version for each service, there's exactly one version per service.
-    fn discover_services() -> Result<Vec<SdkModel>> {
+    pub(crate) fn discover_services() -> Result<Vec<SdkModel>> {
         let start_time = std::time::Instant::now();
         log::debug!("Starting optimized service discovery...");
 
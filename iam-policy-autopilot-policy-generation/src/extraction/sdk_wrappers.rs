@@ -0,0 +1,728 @@
+//! Custom SDK wrapper mappings
+//!
+//! Applications often call AWS through in-house helpers, e.g. `storage.put_blob(bucket, key)`
+//! wrapping `s3.put_object`, which the SDK extractors cannot see through. A wrapper mapping file
+//! declares such helpers and the operations they invoke:
+//!
+//! ```json
+//! {
+//!   "Wrappers": [
+//!     {
+//!       "Language": "python",
+//!       "Module": "mycorp.storage",
+//!       "Name": "put_blob",
+//!       "Operations": ["s3:PutObject"],
+//!       "Parameters": [
+//!         { "Name": "bucket", "Member": "Bucket" },
+//!         { "Name": "key", "Member": "Key" }
+//!       ]
+//!     },
+//!     {
+//!       "Module": "github.com/mycorp/storage",
+//!       "Name": "Delete",
+//!       "Receiver": "Client",
+//!       "Operations": ["s3:DeleteObject"],
+//!       "Parameters": [{ "Position": 1, "Member": "Bucket" }]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! A wrapper without a `Receiver` is a function of `Module`, and matches calls through the
+//! names under which the calling file imports it. A wrapper with a `Receiver` is a method, and
+//! matches calls on variables that are declared, annotated or constructed with the receiver
+//! type; such clients are usually passed around, so the calling file need not import the module.
+//! Wrappers without a `Language` apply to every language.
+//!
+//! Each matching call produces one [`SdkMethodCall`] per operation, whose parameters are the
+//! mapped arguments renamed to the operation's input members. A parameter is matched by its
+//! keyword `Name`, or by its `Position`, which defaults to the index in the `Parameters` list.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use ast_grep_core::tree_sitter::StrDoc;
+use ast_grep_core::Node;
+use ast_grep_language::{Go, LanguageExt, Python};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::enrichment::operation_fas_map::{is_valid_operation_name, is_valid_service_name};
+use crate::errors::{ExtractorError, Result};
+use crate::extraction::extractor::ExtractorResult;
+use crate::extraction::go::types::GoImportInfo;
+use crate::extraction::javascript::argument_extractor::ArgumentExtractor as JsArgumentExtractor;
+use crate::extraction::python::common::ArgumentExtractor as PythonArgumentExtractor;
+use crate::extraction::{
+    go, AstWithSourceFile, Parameter, ParameterValue, SdkMethodCall, SdkMethodCallMetadata,
+};
+use crate::{Language, Location, ServiceModelIndex};
+
+/// Contents of an SDK wrapper mapping file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", deny_unknown_fields)]
+pub struct SdkWrapperMapping {
+    /// Declared wrappers
+    pub wrappers: Vec<SdkWrapper>,
+}
+
+/// A wrapper function or method and the AWS operations it invokes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", deny_unknown_fields)]
+pub struct SdkWrapper {
+    /// Language of the wrapper, or `None` for all languages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    /// Module, package or import path defining the wrapper
+    pub module: String,
+    /// Function or method name
+    pub name: String,
+    /// Type declaring the method, or `None` for a function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receiver: Option<String>,
+    /// Invoked operations, as `service:Operation`
+    pub operations: Vec<String>,
+    /// Mapping of wrapper parameters to operation input members
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<SdkWrapperParameter>,
+}
+
+/// Mapping of a wrapper parameter to an operation input member
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", deny_unknown_fields)]
+pub struct SdkWrapperParameter {
+    /// Keyword name of the parameter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Position of the parameter, defaults to its index in the parameter list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+    /// Input member of the operations, e.g. `Bucket`
+    pub member: String,
+}
+
+/// Wrapper mappings loaded from user-supplied files
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SdkWrappers {
+    wrappers: Vec<SdkWrapper>,
+}
+
+/// Names under which a file can call the functions of a module
+#[derive(Debug, Default)]
+struct ModuleBindings {
+    /// Local names of the module itself, used as call qualifiers
+    qualifiers: HashSet<String>,
+    /// Local names of functions imported from the module, keyed by function name
+    names: HashMap<String, String>,
+}
+
+/// A call expression found in a source file
+struct CallSite {
+    /// Callee expression before the last `.`, if any
+    qualifier: Option<String>,
+    /// Called function or method name
+    name: String,
+    parameters: Vec<Parameter>,
+    expr: String,
+    location: Location,
+}
+
+impl SdkWrappers {
+    /// Load and validate wrapper mapping files
+    ///
+    /// # Errors
+    /// Returns `ExtractorError` if a file cannot be read, is not valid JSON, does not match the
+    /// schema, or contains an invalid operation or a duplicate wrapper
+    pub(crate) fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut wrappers: Vec<SdkWrapper> = Vec::new();
+        let mut seen = HashSet::new();
+        for path in paths {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| ExtractorError::file_system("read", path, e))?;
+            let invalid = |message: String| {
+                ExtractorError::validation(format!(
+                    "Invalid SDK wrapper mapping '{}': {}",
+                    path.display(),
+                    message
+                ))
+            };
+
+            let file: SdkWrapperMapping = serde_json::from_str(&content).map_err(|e| {
+                ExtractorError::configuration_with_source(
+                    format!("Invalid SDK wrapper mapping '{}'", path.display()),
+                    e,
+                )
+            })?;
+            for wrapper in file.wrappers {
+                if wrapper.module.is_empty() || wrapper.name.is_empty() {
+                    return Err(invalid("wrappers need a Module and a Name".to_string()));
+                }
+                let qualified_name = match &wrapper.receiver {
+                    Some(receiver) => format!("{}.{}.{}", wrapper.module, receiver, wrapper.name),
+                    None => format!("{}.{}", wrapper.module, wrapper.name),
+                };
+                if wrapper.operations.is_empty() {
+                    return Err(invalid(format!(
+                        "wrapper '{qualified_name}' has no operations"
+                    )));
+                }
+                for operation in &wrapper.operations {
+                    let valid = operation.split_once(':').is_some_and(|(service, name)| {
+                        is_valid_service_name(service) && is_valid_operation_name(name)
+                    });
+                    if !valid {
+                        return Err(invalid(format!(
+                            "invalid operation '{operation}' of wrapper '{qualified_name}'"
+                        )));
+                    }
+                }
+                if let Some(parameter) = wrapper.parameters.iter().find(|p| p.member.is_empty()) {
+                    return Err(invalid(format!(
+                        "parameter {:?} of wrapper '{qualified_name}' has no Member",
+                        parameter.name
+                    )));
+                }
+                if !seen.insert((wrapper.language, qualified_name.clone())) {
+                    return Err(invalid(format!("duplicate wrapper '{qualified_name}'")));
+                }
+                wrappers.push(wrapper);
+            }
+            log::debug!("Loaded SDK wrapper mapping from {}", path.display());
+        }
+        Ok(Self { wrappers })
+    }
+
+    /// Whether no wrappers are declared
+    pub(crate) fn is_empty(&self) -> bool {
+        self.wrappers.is_empty()
+    }
+
+    /// Add the SDK method calls of wrapper calls to the extraction results
+    ///
+    /// Operations that the SDK of the language does not provide are skipped with a warning.
+    pub(crate) fn extract(
+        &self,
+        language: Language,
+        extraction_results: &mut [ExtractorResult],
+        service_index: &ServiceModelIndex,
+    ) {
+        let wrappers: Vec<&SdkWrapper> = self
+            .wrappers
+            .iter()
+            .filter(|w| w.language.is_none_or(|l| l == language))
+            .collect();
+        if wrappers.is_empty() {
+            return;
+        }
+
+        // Language-specific method names by (service, operation)
+        let mut method_names: HashMap<(&str, &str), &str> = HashMap::new();
+        for (method_name, refs) in &service_index.method_lookup {
+            for method_ref in refs {
+                let key = (
+                    method_ref.service_name.as_str(),
+                    method_ref.operation_name.as_str(),
+                );
+                let entry = method_names.entry(key).or_insert(method_name);
+                if method_name.as_str() < *entry {
+                    *entry = method_name;
+                }
+            }
+        }
+
+        for result in extraction_results.iter_mut() {
+            let (call_sites, source, bindings) = match result {
+                ExtractorResult::Python(ast, _) => (
+                    find_call_sites(ast, python_arguments),
+                    &ast.source_file.content,
+                    wrappers
+                        .iter()
+                        .map(|w| python_bindings(&ast.source_file.content, &w.module))
+                        .collect::<Vec<_>>(),
+                ),
+                ExtractorResult::Go(ast, _, import_info) => (
+                    find_call_sites(ast, go_arguments),
+                    &ast.source_file.content,
+                    wrappers
+                        .iter()
+                        .map(|w| go_bindings(import_info, &w.module))
+                        .collect(),
+                ),
+                ExtractorResult::JavaScript(ast, _) => (
+                    find_call_sites(ast, javascript_arguments),
+                    &ast.source_file.content,
+                    wrappers
+                        .iter()
+                        .map(|w| javascript_bindings(&ast.source_file.content, &w.module))
+                        .collect(),
+                ),
+                ExtractorResult::TypeScript(ast, _) => (
+                    find_call_sites(ast, javascript_arguments),
+                    &ast.source_file.content,
+                    wrappers
+                        .iter()
+                        .map(|w| javascript_bindings(&ast.source_file.content, &w.module))
+                        .collect(),
+                ),
+            };
+
+            let mut wrapper_calls = Vec::new();
+            for call_site in &call_sites {
+                for (wrapper, bindings) in wrappers.iter().zip(&bindings) {
+                    if is_wrapper_call(wrapper, bindings, call_site, source) {
+                        wrapper_calls.extend(wrapper_method_calls(
+                            wrapper,
+                            call_site,
+                            &method_names,
+                        ));
+                    }
+                }
+            }
+            result.method_calls_mut().extend(wrapper_calls);
+        }
+    }
+}
+
+/// Find all call expressions of a source file
+fn find_call_sites<T: LanguageExt>(
+    ast: &AstWithSourceFile<T>,
+    extract_arguments: fn(&[Node<StrDoc<T>>]) -> Vec<Parameter>,
+) -> Vec<CallSite> {
+    let root = ast.ast.root();
+    let mut call_sites = Vec::new();
+    for node_match in root.find_all("$CALLEE($$$ARGS)") {
+        let env = node_match.get_env();
+        let Some(callee) = env.get_match("CALLEE") else {
+            continue;
+        };
+        let callee: String = callee.text().split_whitespace().collect();
+        let (qualifier, name) = match callee.rsplit_once('.') {
+            Some((qualifier, name)) => (Some(qualifier.to_string()), name.to_string()),
+            None => (None, callee),
+        };
+        call_sites.push(CallSite {
+            qualifier,
+            name,
+            parameters: extract_arguments(&env.get_multiple_matches("ARGS")),
+            expr: node_match.text().to_string(),
+            location: Location::from_node(ast.source_file.path.clone(), node_match.get_node()),
+        });
+    }
+    call_sites
+}
+
+fn python_arguments(nodes: &[Node<StrDoc<Python>>]) -> Vec<Parameter> {
+    PythonArgumentExtractor::extract_arguments(nodes)
+}
+
+fn go_arguments(nodes: &[Node<StrDoc<Go>>]) -> Vec<Parameter> {
+    go::utils::extract_arguments(nodes)
+}
+
+/// Positional arguments, with the properties of object literal arguments as keyword arguments
+fn javascript_arguments<T: LanguageExt>(nodes: &[Node<StrDoc<T>>]) -> Vec<Parameter> {
+    let mut parameters = Vec::new();
+    let mut position = 0;
+    for node in nodes {
+        let text = node.text();
+        let text = text.trim();
+        if text.is_empty() || text == "," || node.kind() == "comment" {
+            continue;
+        }
+        if node.kind() == "object" {
+            parameters.extend(JsArgumentExtractor::extract_object_parameters(Some(node)));
+        } else {
+            let unquoted = ['"', '\'', '`'].iter().find_map(|quote| {
+                text.strip_prefix(*quote)
+                    .and_then(|rest| rest.strip_suffix(*quote))
+            });
+            let value = match unquoted {
+                Some(literal) if !literal.contains("${") => {
+                    ParameterValue::Resolved(literal.to_string())
+                }
+                _ => ParameterValue::Unresolved(text.to_string()),
+            };
+            parameters.push(Parameter::Positional {
+                value,
+                position,
+                type_annotation: None,
+                struct_fields: None,
+            });
+        }
+        position += 1;
+    }
+    parameters
+}
+
+/// Bindings of a Python module from `import` and `from ... import` statements
+fn python_bindings(source: &str, module: &str) -> ModuleBindings {
+    static IMPORT: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    static FROM_IMPORT: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let import = IMPORT.get_or_init(|| {
+        Regex::new(r"(?m)^[ \t]*import[ \t]+([^\n#]+)").expect("import regex should compile")
+    });
+    let from_import = FROM_IMPORT.get_or_init(|| {
+        Regex::new(r"(?m)^[ \t]*from[ \t]+([\w.]+)[ \t]+import[ \t]+(\([^)]*\)|[^\n#]+)")
+            .expect("from import regex should compile")
+    });
+
+    let mut bindings = ModuleBindings::default();
+    for captures in import.captures_iter(source) {
+        for (name, alias) in import_names(&captures[1], " as ") {
+            if name == module {
+                bindings.qualifiers.insert(alias.to_string());
+            }
+        }
+    }
+    for captures in from_import.captures_iter(source) {
+        let package = &captures[1];
+        for (name, alias) in import_names(&captures[2], " as ") {
+            if package == module {
+                bindings.names.insert(name.to_string(), alias.to_string());
+            } else if format!("{package}.{name}") == module {
+                bindings.qualifiers.insert(alias.to_string());
+            }
+        }
+    }
+    bindings
+}
+
+/// Bindings of a Go package from the file's imports
+fn go_bindings(import_info: &GoImportInfo, module: &str) -> ModuleBindings {
+    let mut bindings = ModuleBindings::default();
+    for import in &import_info.imports {
+        if import.original_name == module {
+            bindings.qualifiers.insert(import.local_name.clone());
+        }
+    }
+    bindings
+}
+
+/// Bindings of a JavaScript module from `import` statements and `require` calls
+fn javascript_bindings(source: &str, module: &str) -> ModuleBindings {
+    let quoted = regex::escape(module);
+    let import = Regex::new(&format!(
+        r#"import\s+([^;]+?)\s+from\s+['"]{quoted}['"]|(?:const|let|var)\s+([^=;]+?)\s*=\s*require\(\s*['"]{quoted}['"]\s*\)"#
+    ))
+    .expect("escaped module regex should compile");
+
+    let mut bindings = ModuleBindings::default();
+    for captures in import.captures_iter(source) {
+        let (clause, alias_separator) = match (captures.get(1), captures.get(2)) {
+            (Some(clause), _) => (clause.as_str(), " as "),
+            (None, Some(clause)) => (clause.as_str(), ":"),
+            (None, None) => continue,
+        };
+        let (named, rest) = match (clause.find('{'), clause.rfind('}')) {
+            (Some(start), Some(end)) if start < end => (
+                &clause[start + 1..end],
+                format!("{}{}", &clause[..start], &clause[end + 1..]),
+            ),
+            _ => ("", clause.to_string()),
+        };
+        for (name, alias) in import_names(named, alias_separator) {
+            bindings.names.insert(name.to_string(), alias.to_string());
+        }
+        for binding in rest.split(',').map(str::trim).filter(|b| !b.is_empty()) {
+            let local = binding.strip_prefix("* as ").unwrap_or(binding).trim();
+            bindings.qualifiers.insert(local.to_string());
+        }
+    }
+    bindings
+}
+
+/// Split an import list like `a, b as c` into (name, local name) pairs
+fn import_names<'a>(list: &'a str, alias_separator: &str) -> Vec<(&'a str, &'a str)> {
+    list.trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace())
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| match item.split_once(alias_separator) {
+            Some((name, alias)) => (name.trim(), alias.trim()),
+            None => (item, item),
+        })
+        .collect()
+}
+
+/// Whether a call site calls the wrapper
+fn is_wrapper_call(
+    wrapper: &SdkWrapper,
+    bindings: &ModuleBindings,
+    call_site: &CallSite,
+    source: &str,
+) -> bool {
+    match (&wrapper.receiver, &call_site.qualifier) {
+        (Some(receiver), Some(qualifier)) => {
+            call_site.name == wrapper.name && receiver_has_type(source, qualifier, receiver)
+        }
+        (Some(_), None) => false,
+        (None, Some(qualifier)) => {
+            call_site.name == wrapper.name && bindings.qualifiers.contains(qualifier)
+        }
+        (None, None) => bindings.names.get(&wrapper.name) == Some(&call_site.name),
+    }
+}
+
+/// Whether the receiver expression is the receiver type or a variable of that type
+///
+/// Matches assignments of constructor calls (`c = Client()`, `c := &pkg.Client{}`,
+/// `c = new Client()`), type annotations (`c: Client`), Go declarations (`c *pkg.Client`) and
+/// calls on the type itself.
+fn receiver_has_type(source: &str, receiver: &str, type_name: &str) -> bool {
+    if receiver == type_name || receiver.ends_with(&format!(".{type_name}")) {
+        return true;
+    }
+    let receiver = regex::escape(receiver);
+    let type_name = regex::escape(type_name);
+    let declaration = Regex::new(&format!(
+        r"(?:^|[^\w.]){receiver}(?:\s*(?::=|=|:)\s*(?:new\s+|&)?|[ \t]+\*?)(?:[A-Za-z_]\w*\.)*{type_name}\b"
+    ))
+    .expect("escaped receiver regex should compile");
+    declaration.is_match(source)
+}
+
+/// Build the SDK method calls of a wrapper call
+fn wrapper_method_calls(
+    wrapper: &SdkWrapper,
+    call_site: &CallSite,
+    method_names: &HashMap<(&str, &str), &str>,
+) -> Vec<SdkMethodCall> {
+    let parameters: Vec<Parameter> = wrapper
+        .parameters
+        .iter()
+        .enumerate()
+        .filter_map(|(index, mapping)| {
+            let position = mapping.position.unwrap_or(index);
+            let value = call_site
+                .parameters
+                .iter()
+                .find_map(|parameter| match parameter {
+                    Parameter::Keyword { name, value, .. }
+                        if Some(name) == mapping.name.as_ref() =>
+                    {
+                        Some(value)
+                    }
+                    _ => None,
+                })
+                .or_else(|| {
+                    call_site
+                        .parameters
+                        .iter()
+                        .find_map(|parameter| match parameter {
+                            Parameter::Positional {
+                                value, position: p, ..
+                            } if *p == position => Some(value),
+                            _ => None,
+                        })
+                })?;
+            Some((mapping, value.clone()))
+        })
+        .enumerate()
+        .map(|(position, (mapping, value))| Parameter::Keyword {
+            name: mapping.member.clone(),
+            value,
+            position,
+            type_annotation: None,
+        })
+        .collect();
+
+    let mut calls = Vec::new();
+    for operation in &wrapper.operations {
+        let Some((service, operation_name)) = operation.split_once(':') else {
+            continue;
+        };
+        let Some(method_name) = method_names.get(&(service, operation_name)) else {
+            log::warn!(
+                "Skipping operation '{}' of SDK wrapper '{}': not found in the SDK",
+                operation,
+                wrapper.name
+            );
+            continue;
+        };
+        log::debug!(
+            "Found call of SDK wrapper '{}' invoking {}",
+            wrapper.name,
+            operation
+        );
+        calls.push(SdkMethodCall {
+            name: (*method_name).to_string(),
+            possible_services: vec![service.to_string()],
+            metadata: Some(SdkMethodCallMetadata {
+                parameters: parameters.clone(),
+                return_type: None,
+                expr: call_site.expr.clone(),
+                location: call_site.location.clone(),
+                receiver: call_site.qualifier.clone(),
+            }),
+        });
+    }
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extraction::Engine;
+    use crate::SourceFile;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn mapping_file(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    const WRAPPERS: &str = r#"{
+        "Wrappers": [
+            {
+                "Language": "python",
+                "Module": "mycorp.storage",
+                "Name": "put_blob",
+                "Operations": ["s3:PutObject"],
+                "Parameters": [
+                    { "Name": "bucket", "Member": "Bucket" },
+                    { "Name": "key", "Member": "Key" }
+                ]
+            },
+            {
+                "Module": "mycorp.queue",
+                "Name": "publish",
+                "Receiver": "Publisher",
+                "Operations": ["sqs:SendMessage"],
+                "Parameters": [{ "Position": 0, "Member": "QueueUrl" }]
+            },
+            {
+                "Language": "go",
+                "Module": "github.com/mycorp/storage",
+                "Name": "PutBlob",
+                "Operations": ["s3:PutObject"],
+                "Parameters": [{ "Position": 1, "Member": "Bucket" }]
+            },
+            {
+                "Language": "javascript",
+                "Module": "@mycorp/storage",
+                "Name": "putBlob",
+                "Operations": ["s3:PutObject"],
+                "Parameters": [{ "Name": "bucket", "Member": "Bucket" }]
+            }
+        ]
+    }"#;
+
+    async fn extract(language: Language, path: &str, source: &str) -> Vec<SdkMethodCall> {
+        let file = mapping_file(WRAPPERS);
+        let engine = Engine::new()
+            .with_sdk_wrappers(&[file.path().to_path_buf()])
+            .unwrap();
+        let source_file =
+            SourceFile::with_language(PathBuf::from(path), source.to_string(), language);
+        engine
+            .extract_sdk_method_calls(language, vec![source_file])
+            .await
+            .unwrap()
+            .methods
+    }
+
+    fn parameter_values(call: &SdkMethodCall) -> Vec<(String, String)> {
+        call.metadata
+            .as_ref()
+            .unwrap()
+            .parameters
+            .iter()
+            .filter_map(|p| match p {
+                Parameter::Keyword { name, value, .. } => {
+                    Some((name.clone(), value.as_string().to_string()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_mappings() {
+        let file = mapping_file(WRAPPERS);
+        let wrappers = SdkWrappers::load(&[file.path().to_path_buf()]).unwrap();
+        assert_eq!(wrappers.wrappers.len(), 4);
+
+        let invalid_operation = mapping_file(
+            r#"{"Wrappers": [{"Module": "m", "Name": "f", "Operations": ["PutObject"]}]}"#,
+        );
+        let error = SdkWrappers::load(&[invalid_operation.path().to_path_buf()]).unwrap_err();
+        assert!(error.to_string().contains("invalid operation 'PutObject'"));
+
+        let unknown_field = mapping_file(
+            r#"{"Wrappers": [{"Module": "m", "Name": "f", "Operations": [], "Type": "x"}]}"#,
+        );
+        assert!(SdkWrappers::load(&[unknown_field.path().to_path_buf()]).is_err());
+
+        let error =
+            SdkWrappers::load(&[file.path().to_path_buf(), file.path().to_path_buf()]).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("duplicate wrapper 'mycorp.storage.put_blob'"));
+    }
+
+    #[tokio::test]
+    async fn test_python_wrapper_calls() {
+        let source = r#"
+from mycorp import storage
+from mycorp.queue import Publisher
+
+def handler(event, publisher: Publisher):
+    storage.put_blob("my-bucket", key=event["key"])
+    publisher.publish("https://queue", "body")
+    other.put_blob("not-a-wrapper", "x")
+"#;
+        let calls = extract(Language::Python, "handler.py", source).await;
+
+        let put_object = calls.iter().find(|c| c.name == "put_object").unwrap();
+        assert_eq!(put_object.possible_services, vec!["s3".to_string()]);
+        assert_eq!(
+            parameter_values(put_object),
+            vec![
+                ("Bucket".to_string(), "my-bucket".to_string()),
+                ("Key".to_string(), "event[\"key\"]".to_string()),
+            ]
+        );
+
+        let send_message = calls.iter().find(|c| c.name == "send_message").unwrap();
+        assert_eq!(
+            parameter_values(send_message),
+            vec![("QueueUrl".to_string(), "https://queue".to_string())]
+        );
+        assert_eq!(calls.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_go_and_javascript_wrapper_calls() {
+        let go_source = r#"
+package main
+
+import blobs "github.com/mycorp/storage"
+
+func main() {
+	blobs.PutBlob(ctx, "my-bucket", "key")
+}
+"#;
+        let calls = extract(Language::Go, "main.go", go_source).await;
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "PutObject");
+        assert_eq!(
+            parameter_values(&calls[0]),
+            vec![("Bucket".to_string(), "\"my-bucket\"".to_string())]
+        );
+
+        let js_source = r#"
+const { putBlob: store } = require('@mycorp/storage');
+
+store({ bucket: 'my-bucket', key: 'key' });
+"#;
+        let calls = extract(Language::JavaScript, "index.js", js_source).await;
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "PutObject");
+        assert_eq!(
+            parameter_values(&calls[0]),
+            vec![("Bucket".to_string(), "my-bucket".to_string())]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Detection of test code, whose SDK calls never run in production
+//!
+//! Test files are recognized by the conventions of each language's test runners:
+//! - Python: `test_*.py`, `*_test.py` and `conftest.py`
+//! - Go: `*_test.go`
+//! - JavaScript and TypeScript: `*.test.*` and `*.spec.*`, and files below `__tests__` or
+//!   `__mocks__` directories
+//!
+//! Files that stub the SDK are test code too, whatever their name: Python files using `moto`
+//! or `botocore.stub.Stubber`, JavaScript and TypeScript files importing `aws-sdk-client-mock`
+//! or `aws-sdk-mock`, and Go files importing a `mocks` package or a mocking library.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::extraction::SourceFile;
+use crate::Language;
+
+/// Directories of JavaScript and TypeScript test runners
+const TEST_DIRECTORIES: &[&str] = &["__tests__", "__mocks__"];
+
+/// Why a source file is test code, or `None` if it is not
+pub(crate) fn test_code_reason(source_file: &SourceFile) -> Option<String> {
+    test_file_convention(&source_file.path, source_file.language)
+        .map(str::to_string)
+        .or_else(|| {
+            mocking_library(&source_file.content, source_file.language)
+                .map(|library| format!("stubs the AWS SDK with {library}"))
+        })
+}
+
+/// The naming convention that makes `path` a test file, if any
+fn test_file_convention(path: &Path, language: Language) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    let stem = name.split('.').next().unwrap_or(name);
+    match language {
+        Language::Python => {
+            if name == "conftest.py" {
+                Some("pytest configuration")
+            } else if stem.starts_with("test_") || stem.ends_with("_test") {
+                Some("named like a test module")
+            } else {
+                None
+            }
+        }
+        Language::Go => name
+            .ends_with("_test.go")
+            .then_some("named like a Go test file"),
+        Language::JavaScript | Language::TypeScript => {
+            let mut extensions = name.split('.').skip(1);
+            if extensions.any(|extension| extension == "test" || extension == "spec") {
+                Some("named like a test file")
+            } else if path.components().any(|component| {
+                TEST_DIRECTORIES
+                    .iter()
+                    .any(|directory| component.as_os_str() == *directory)
+            }) {
+                Some("in a test directory")
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// The mocking library of the AWS SDK that `content` uses, if any
+fn mocking_library(content: &str, language: Language) -> Option<&'static str> {
+    static PATTERNS: OnceLock<Vec<(Language, &'static str, Regex)>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            (Language::Python, "moto", r"(?m)^\s*(from\s+moto\b|import\s+moto\b)"),
+            (
+                Language::Python,
+                "botocore Stubber",
+                r"(?m)^\s*(from\s+botocore\.stub\s+import|import\s+botocore\.stub\b)|botocore\.stub\.Stubber",
+            ),
+            (
+                Language::JavaScript,
+                "aws-sdk-client-mock",
+                r#"['"]aws-sdk-client-mock(-jest)?['"]"#,
+            ),
+            (Language::JavaScript, "aws-sdk-mock", r#"['"]aws-sdk-mock['"]"#),
+            (
+                Language::Go,
+                "a mocking library",
+                r"^(github\.com/golang/mock/gomock|go\.uber\.org/mock/gomock|github\.com/stretchr/testify/mock)$",
+            ),
+            (Language::Go, "a mocks package", r"/mocks?$"),
+        ]
+        .into_iter()
+        .map(|(language, library, pattern)| {
+            (
+                language,
+                library,
+                Regex::new(pattern).expect("mocking library pattern is valid"),
+            )
+        })
+        .collect()
+    });
+
+    // TypeScript imports the same mocking libraries as JavaScript
+    let language = match language {
+        Language::TypeScript => Language::JavaScript,
+        language => language,
+    };
+    // Go patterns match import paths, not string literals of the code
+    let subjects = match language {
+        Language::Go => go_import_paths(content),
+        _ => vec![content],
+    };
+    patterns
+        .iter()
+        .find(|(pattern_language, _, pattern)| {
+            *pattern_language == language && subjects.iter().any(|s| pattern.is_match(s))
+        })
+        .map(|(_, library, _)| *library)
+}
+
+/// Paths imported by Go source code, by single imports and import blocks
+fn go_import_paths(content: &str) -> Vec<&str> {
+    static IMPORT: OnceLock<Regex> = OnceLock::new();
+    static BLOCK_IMPORT: OnceLock<Regex> = OnceLock::new();
+    let import = IMPORT.get_or_init(|| {
+        Regex::new(r#"(?m)^\s*import\s*(?:\(([^)]*)\)|(?:[\w.]+\s+)?"([^"]*)")"#)
+            .expect("Go import pattern is valid")
+    });
+    let block_import = BLOCK_IMPORT.get_or_init(|| {
+        Regex::new(r#"(?m)^\s*(?:[\w.]+\s+)?"([^"]*)""#).expect("Go import pattern is valid")
+    });
+
+    let mut paths = Vec::new();
+    for captures in import.captures_iter(content) {
+        if let Some(block) = captures.get(1) {
+            paths.extend(
+                block_import
+                    .captures_iter(block.as_str())
+                    .filter_map(|captures| captures.get(1))
+                    .map(|path| path.as_str()),
+            );
+        } else if let Some(path) = captures.get(2) {
+            paths.push(path.as_str());
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn reason(path: &str, language: Language, content: &str) -> Option<String> {
+        test_code_reason(&SourceFile::with_language(
+            PathBuf::from(path),
+            content.to_string(),
+            language,
+        ))
+    }
+
+    #[test]
+    fn test_test_file_conventions() {
+        assert!(reason("tests/test_handler.py", Language::Python, "").is_some());
+        assert!(reason("app/handler_test.py", Language::Python, "").is_some());
+        assert!(reason("tests/conftest.py", Language::Python, "").is_some());
+        assert!(reason("app/testing.py", Language::Python, "").is_none());
+        assert!(reason("pkg/store_test.go", Language::Go, "").is_some());
+        assert!(reason("pkg/store.go", Language::Go, "").is_none());
+        assert!(reason("src/store.spec.ts", Language::TypeScript, "").is_some());
+        assert!(reason("src/store.test.mjs", Language::JavaScript, "").is_some());
+        assert!(reason("src/__tests__/store.ts", Language::TypeScript, "").is_some());
+        assert!(reason("src/latest.ts", Language::TypeScript, "").is_none());
+    }
+
+    #[test]
+    fn test_mocking_libraries() {
+        assert_eq!(
+            reason(
+                "app/fixtures.py",
+                Language::Python,
+                "from moto import mock_aws\n"
+            )
+            .as_deref(),
+            Some("stubs the AWS SDK with moto")
+        );
+        assert!(reason(
+            "app/fixtures.py",
+            Language::Python,
+            "from botocore.stub import Stubber\n"
+        )
+        .is_some());
+        assert!(reason(
+            "src/setup.ts",
+            Language::TypeScript,
+            "import { mockClient } from 'aws-sdk-client-mock';\n"
+        )
+        .is_some());
+        assert!(reason(
+            "pkg/fake.go",
+            Language::Go,
+            "import (\n\t\"example.com/app/internal/mocks\"\n)\n"
+        )
+        .is_some());
+        assert!(reason(
+            "pkg/fake.go",
+            Language::Go,
+            "import gomock \"go.uber.org/mock/gomock\"\n"
+        )
+        .is_some());
+        assert!(reason(
+            "pkg/store.go",
+            Language::Go,
+            "import (\n\t\"log\"\n)\n\nfunc f() {\n\tlog.Print(\"s3://bucket/mock\", \"/api/mocks\")\n}\n"
+        )
+        .is_none());
+        assert!(reason(
+            "app/motor.py",
+            Language::Python,
+            "import motor\nimport boto3\n"
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,467 @@
+//! Derivation of SDK wrapper mappings from library source code
+//!
+//! Platform teams publish libraries that wrap AWS SDK calls. Deriving a mapping from the
+//! library's source records, for each exported function and method, the operations it calls
+//! directly or through other functions of the library, so that applications using the library
+//! get the right permissions from the mapping alone.
+//!
+//! Parameters of a wrapper are mapped to input members when the wrapper passes them unchanged
+//! to an SDK call in its own body, e.g. `def put_blob(bucket, key)` calling
+//! `s3.put_object(Bucket=bucket, Key=key)`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::enrichment::operation_fas_map::is_valid_operation_name;
+use crate::errors::Result;
+use crate::extraction::call_graph::{CallGraph, FunctionDefinition};
+use crate::extraction::go::utils::parse_struct_fields;
+use crate::extraction::sdk_model::ServiceDiscovery;
+use crate::extraction::sdk_wrappers::{SdkWrapper, SdkWrapperMapping, SdkWrapperParameter};
+use crate::extraction::{Engine, Parameter, ParameterValue, SdkMethodCall};
+use crate::{Language, ServiceModelIndex, SourceFile};
+
+/// Derive the wrapper mapping of the exported functions of a library
+///
+/// `source_files` are the files of the library below `library_root`, which is used to name the
+/// modules of files that are not part of a Python package, Go module or npm package.
+pub(crate) async fn derive_sdk_wrappers(
+    engine: &Engine,
+    language: Language,
+    library_root: &Path,
+    source_files: Vec<SourceFile>,
+) -> Result<SdkWrapperMapping> {
+    let graph = CallGraph::build(&source_files);
+    let extracted = engine
+        .extract_sdk_method_calls(language, source_files)
+        .await?;
+    let service_index = ServiceDiscovery::load_service_index(language).await?;
+
+    let mut direct_operations: HashMap<usize, BTreeSet<String>> = HashMap::new();
+    let mut direct_parameters: HashMap<usize, BTreeSet<SdkWrapperParameter>> = HashMap::new();
+    for call in &extracted.methods {
+        let Some(metadata) = &call.metadata else {
+            continue;
+        };
+        let Some(function) = graph.enclosing_function(&metadata.location) else {
+            continue;
+        };
+        direct_operations
+            .entry(function)
+            .or_default()
+            .extend(operations(call, &service_index));
+        direct_parameters
+            .entry(function)
+            .or_default()
+            .extend(parameter_mappings(call, &graph.functions()[function]));
+    }
+
+    let mut wrappers: BTreeMap<(String, Option<String>, String), SdkWrapper> = BTreeMap::new();
+    for (id, function) in graph.functions().iter().enumerate() {
+        if !function.exported {
+            continue;
+        }
+        let operations: BTreeSet<String> = graph
+            .reachable_from([id])
+            .into_iter()
+            .filter_map(|reachable| direct_operations.get(&reachable))
+            .flatten()
+            .cloned()
+            .collect();
+        if operations.is_empty() {
+            continue;
+        }
+
+        let module = module_name(language, library_root, &function.location.file_path);
+        log::debug!(
+            "Derived wrapper {}.{} invoking {:?}",
+            module,
+            function.name,
+            operations
+        );
+        let wrapper = wrappers
+            .entry((
+                module.clone(),
+                function.receiver.clone(),
+                function.name.clone(),
+            ))
+            .or_insert_with(|| SdkWrapper {
+                language: Some(language),
+                module,
+                name: function.name.clone(),
+                receiver: function.receiver.clone(),
+                operations: Vec::new(),
+                parameters: Vec::new(),
+            });
+        let merged: BTreeSet<String> = wrapper.operations.drain(..).chain(operations).collect();
+        wrapper.operations = merged.into_iter().collect();
+        let parameters: BTreeSet<SdkWrapperParameter> = wrapper
+            .parameters
+            .drain(..)
+            .chain(direct_parameters.get(&id).into_iter().flatten().cloned())
+            .collect();
+        wrapper.parameters = parameters.into_iter().collect();
+    }
+
+    Ok(SdkWrapperMapping {
+        wrappers: wrappers.into_values().collect(),
+    })
+}
+
+/// Operations of an SDK method call, as `service:Operation`
+fn operations(call: &SdkMethodCall, service_index: &ServiceModelIndex) -> Vec<String> {
+    let known: Vec<String> = service_index
+        .method_lookup
+        .get(&call.name)
+        .into_iter()
+        .flatten()
+        .filter(|method| call.possible_services.contains(&method.service_name))
+        .map(|method| format!("{}:{}", method.service_name, method.operation_name))
+        .collect();
+    if !known.is_empty() || !is_valid_operation_name(&call.name) {
+        return known;
+    }
+    // Go and JavaScript calls are named after the operation
+    call.possible_services
+        .iter()
+        .map(|service| format!("{}:{}", service, call.name))
+        .collect()
+}
+
+/// Mappings of the function's parameters that the call passes unchanged as input members
+fn parameter_mappings(
+    call: &SdkMethodCall,
+    function: &FunctionDefinition,
+) -> Vec<SdkWrapperParameter> {
+    let Some(metadata) = &call.metadata else {
+        return Vec::new();
+    };
+    let mut members = Vec::new();
+    for parameter in &metadata.parameters {
+        match parameter {
+            Parameter::Keyword {
+                name,
+                value: ParameterValue::Unresolved(value),
+                ..
+            } => members.push((name.clone(), value.clone())),
+            // Go input structs, e.g. `&s3.PutObjectInput{ Bucket: bucket }`
+            Parameter::Positional {
+                value: ParameterValue::Unresolved(value),
+                struct_fields: Some(_),
+                ..
+            } => {
+                if let (Some(start), Some(end)) = (value.find('{'), value.rfind('}')) {
+                    if start < end {
+                        members.extend(
+                            parse_struct_fields(&value[start + 1..end])
+                                .into_iter()
+                                .map(|field| (field.name, field.value)),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    members
+        .into_iter()
+        .filter_map(|(member, value)| {
+            let value = unwrap_value(&value);
+            let position = function
+                .parameters
+                .iter()
+                .position(|parameter| !parameter.is_empty() && parameter == value)?;
+            Some(SdkWrapperParameter {
+                name: Some(value.to_string()),
+                position: Some(position),
+                member,
+            })
+        })
+        .collect()
+}
+
+/// The variable of a value, without Go pointer helpers like `aws.String(bucket)` or `&bucket`
+fn unwrap_value(value: &str) -> &str {
+    let value = value.trim();
+    if let Some(inner) = value
+        .strip_prefix("aws.")
+        .and_then(|rest| rest.split_once('('))
+        .and_then(|(_, rest)| rest.strip_suffix(')'))
+    {
+        return inner.trim();
+    }
+    value.trim_start_matches(['&', '*'])
+}
+
+/// Module under which the functions of a file are imported
+fn module_name(language: Language, library_root: &Path, path: &Path) -> String {
+    match language {
+        Language::Python => python_module(library_root, path),
+        Language::Go => go_package(library_root, path),
+        Language::JavaScript | Language::TypeScript => javascript_module(library_root, path),
+    }
+}
+
+/// Dotted module path of a Python file
+///
+/// Files in packages are named from the outermost package containing them, other files
+/// relative to the library root.
+fn python_module(library_root: &Path, path: &Path) -> String {
+    let mut components = Vec::new();
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if stem != "__init__" {
+        components.push(stem);
+    }
+    let is_package = |dir: &Path| dir.join("__init__.py").is_file();
+    let in_package = path.parent().is_some_and(is_package);
+    let mut directory = path.parent();
+    while let Some(dir) = directory {
+        let named = if in_package {
+            is_package(dir)
+        } else {
+            dir.starts_with(library_root) && dir != library_root
+        };
+        if !named {
+            break;
+        }
+        if let Some(name) = dir.file_name() {
+            components.push(name.to_string_lossy().to_string());
+        }
+        directory = dir.parent();
+    }
+    components.reverse();
+    components.join(".")
+}
+
+/// Import path of a Go package, from the `go.mod` of its module
+fn go_package(library_root: &Path, path: &Path) -> String {
+    let package_dir = path.parent().unwrap_or(library_root);
+    for dir in package_dir.ancestors() {
+        let Ok(go_mod) = std::fs::read_to_string(dir.join("go.mod")) else {
+            continue;
+        };
+        let Some(module) = go_mod
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("module "))
+        else {
+            continue;
+        };
+        let module = module.trim().trim_matches('"');
+        let relative = relative_slash_path(dir, package_dir);
+        return if relative.is_empty() {
+            module.to_string()
+        } else {
+            format!("{module}/{relative}")
+        };
+    }
+    relative_slash_path(library_root.parent().unwrap_or(library_root), package_dir)
+}
+
+/// Package specifier of a JavaScript or TypeScript file, from its closest `package.json`
+///
+/// The package entry point (`main`, or an `index` file at the package root, in `src` or in
+/// `lib`) is imported as the package itself, other files by their path within the package.
+fn javascript_module(library_root: &Path, path: &Path) -> String {
+    let without_extension = path.with_extension("");
+    for dir in path.parent().into_iter().flat_map(Path::ancestors) {
+        let Ok(content) = std::fs::read_to_string(dir.join("package.json")) else {
+            continue;
+        };
+        let Some(package) = serde_json::from_str::<serde_json::Value>(&content)
+            .ok()
+            .and_then(|json| {
+                json.get("name")
+                    .and_then(|n| n.as_str())
+                    .map(str::to_string)
+            })
+        else {
+            continue;
+        };
+        let relative = relative_slash_path(dir, &without_extension);
+        let main = serde_json::from_str::<serde_json::Value>(&content)
+            .ok()
+            .and_then(|json| {
+                json.get("main")
+                    .and_then(|m| m.as_str())
+                    .map(str::to_string)
+            })
+            .map(|main| {
+                relative_slash_path(
+                    Path::new(""),
+                    &PathBuf::from(main.trim_start_matches("./")).with_extension(""),
+                )
+            });
+        let is_entry_point = ["index", "src/index", "lib/index"].contains(&relative.as_str())
+            || main.as_deref() == Some(relative.as_str());
+        return if is_entry_point {
+            package
+        } else {
+            format!("{package}/{relative}")
+        };
+    }
+    relative_slash_path(library_root, &without_extension)
+}
+
+/// `path` relative to `base`, with `/` separators
+fn relative_slash_path(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = dir.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    async fn derive(root: &Path, language: Language, files: &[PathBuf]) -> SdkWrapperMapping {
+        let source_files = files
+            .iter()
+            .map(|path| {
+                SourceFile::with_language(
+                    path.clone(),
+                    std::fs::read_to_string(path).unwrap(),
+                    language,
+                )
+            })
+            .collect();
+        derive_sdk_wrappers(&Engine::new(), language, root, source_files)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_derive_python_wrappers() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("lib");
+        write(&root, "mycorp/__init__.py", "");
+        let storage = write(
+            &root,
+            "mycorp/storage.py",
+            r#"
+import boto3
+
+s3 = boto3.client("s3")
+
+def put_blob(bucket, key, body):
+    _check(key)
+    s3.put_object(Bucket=bucket, Key=key, Body=body)
+
+def copy_blob(bucket, source, target):
+    put_blob(bucket, target, get_blob(bucket, source))
+
+def get_blob(bucket, key):
+    return s3.get_object(Bucket=bucket, Key=key)["Body"].read()
+
+def _check(key):
+    pass
+
+class Store:
+    def delete(self, bucket, key):
+        s3.delete_object(Bucket=bucket, Key=key)
+"#,
+        );
+
+        let mapping = derive(&root, Language::Python, &[storage]).await;
+        let names: Vec<(&str, Option<&str>, &str)> = mapping
+            .wrappers
+            .iter()
+            .map(|w| (w.module.as_str(), w.receiver.as_deref(), w.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("mycorp.storage", None, "copy_blob"),
+                ("mycorp.storage", None, "get_blob"),
+                ("mycorp.storage", None, "put_blob"),
+                ("mycorp.storage", Some("Store"), "delete"),
+            ]
+        );
+
+        let copy_blob = &mapping.wrappers[0];
+        assert_eq!(
+            copy_blob.operations,
+            vec!["s3:GetObject".to_string(), "s3:PutObject".to_string()]
+        );
+        assert!(copy_blob.parameters.is_empty());
+
+        let put_blob = &mapping.wrappers[2];
+        assert_eq!(put_blob.language, Some(Language::Python));
+        assert_eq!(put_blob.operations, vec!["s3:PutObject".to_string()]);
+        assert_eq!(
+            put_blob.parameters,
+            vec![
+                SdkWrapperParameter {
+                    name: Some("body".to_string()),
+                    position: Some(2),
+                    member: "Body".to_string(),
+                },
+                SdkWrapperParameter {
+                    name: Some("bucket".to_string()),
+                    position: Some(0),
+                    member: "Bucket".to_string(),
+                },
+                SdkWrapperParameter {
+                    name: Some("key".to_string()),
+                    position: Some(1),
+                    member: "Key".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_names() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+
+        write(
+            root,
+            "go.mod",
+            "module github.com/mycorp/storage\n\ngo 1.22\n",
+        );
+        let client = write(root, "blob/client.go", "package blob\n");
+        assert_eq!(
+            module_name(Language::Go, root, &client),
+            "github.com/mycorp/storage/blob"
+        );
+
+        write(
+            root,
+            "js/package.json",
+            r#"{"name": "@mycorp/storage", "main": "./dist/main.js"}"#,
+        );
+        let index = write(root, "js/src/index.ts", "");
+        let helpers = write(root, "js/src/helpers.ts", "");
+        let main = write(root, "js/dist/main.js", "");
+        assert_eq!(
+            module_name(Language::TypeScript, root, &index),
+            "@mycorp/storage"
+        );
+        assert_eq!(
+            module_name(Language::TypeScript, root, &helpers),
+            "@mycorp/storage/src/helpers"
+        );
+        assert_eq!(
+            module_name(Language::JavaScript, root, &main),
+            "@mycorp/storage"
+        );
+
+        let script = write(root, "scripts/tool.py", "");
+        assert_eq!(module_name(Language::Python, root, &script), "scripts.tool");
+    }
+}
@@ -0,0 +1,547 @@
+//! Lambda functions declared in infrastructure as code
+//!
+//! Functions and their handlers are read from:
+//! - AWS SAM and CloudFormation templates (`template.yaml`, `template.yml`, `template.json`, or
+//!   any YAML or JSON file given explicitly): `AWS::Serverless::Function` resources, with the
+//!   SAM `Globals`, and `AWS::Lambda::Function` resources whose `Code` is a local path.
+//! - Serverless Framework configurations (`serverless.yml`, `serverless.yaml`): the `functions`
+//!   section, with the `provider` runtime.
+//! - AWS CDK code in TypeScript, JavaScript and Python: `lambda.Function` constructs with a
+//!   `handler` string and code from `Code.fromAsset` (`Code.from_asset` in Python).
+//!
+//! A handler string is resolved to an [`EntryPoint`] in the function's code directory.
+//! `module.function` names a function of `module.py`, `module.js`, `module.mjs`, `module.cjs`,
+//! `module.ts` or `module.mts`, where `module` is a path, or a dotted module path for Python.
+//! Go functions (`go1.x` and `provided` runtimes, or handlers without a `.`) enter through the
+//! `main` function of the `main` package in their code directory.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Serialize;
+use serde_yaml::Value;
+use walkdir::WalkDir;
+
+use crate::errors::{ExtractorError, Result};
+use crate::extraction::EntryPoint;
+use crate::workload::normalize;
+use crate::{Language, SourceFile};
+
+/// Directories that never contain infrastructure definitions of the project
+const SKIPPED_DIRECTORIES: &[&str] = &["node_modules", "vendor", "__pycache__", "cdk.out"];
+
+/// Extensions of handler modules, for runtimes other than Go
+const HANDLER_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("python", &["py"]),
+    ("nodejs", &["js", "mjs", "cjs", "ts", "mts"]),
+    ("", &["py", "js", "mjs", "cjs", "ts", "mts"]),
+];
+
+/// A Lambda function declared in infrastructure as code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LambdaFunction {
+    /// Logical ID, function name or construct ID
+    pub name: String,
+    /// Handler string, e.g. `app.lambda_handler`
+    pub handler: String,
+    /// Runtime, e.g. `python3.12`, if declared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    /// Directory of the function's code
+    pub code_directory: PathBuf,
+    /// Template, configuration or CDK source file declaring the function
+    pub declared_in: PathBuf,
+}
+
+impl LambdaFunction {
+    /// The entry point the handler refers to, if its source file exists
+    pub fn entry_point(&self) -> Option<EntryPoint> {
+        let runtime = self.runtime.as_deref().unwrap_or_default().to_lowercase();
+        if runtime.starts_with("go")
+            || runtime.starts_with("provided")
+            || !self.handler.contains('.')
+        {
+            let binary = Path::new(&self.handler).file_name().unwrap_or_default();
+            return go_main(&self.code_directory)
+                .or_else(|| go_main(&self.code_directory.join(binary)));
+        }
+
+        let (module, function) = self.handler.rsplit_once('.')?;
+        let extensions = HANDLER_EXTENSIONS
+            .iter()
+            .find(|(prefix, _)| runtime.starts_with(prefix))
+            .map_or(&[][..], |(_, extensions)| *extensions);
+        let mut modules = vec![module.to_string()];
+        if !runtime.starts_with("nodejs") {
+            modules.push(module.replace('.', "/"));
+        }
+        modules
+            .iter()
+            .flat_map(|module| {
+                extensions
+                    .iter()
+                    .map(move |extension| format!("{module}.{extension}"))
+            })
+            .map(|file| self.code_directory.join(file))
+            .find(|file| file.is_file())
+            .map(|file| EntryPoint {
+                file,
+                function: Some(function.to_string()),
+            })
+    }
+}
+
+/// Discover the Lambda functions declared in a file, or in the files below a directory
+///
+/// Directories are searched for SAM and CloudFormation templates, Serverless Framework
+/// configurations and CDK source files, skipping hidden directories, dependencies and CDK
+/// output. Functions are returned in the order of their declaring files' paths.
+///
+/// # Errors
+/// Returns an error if a file cannot be read, or a template or configuration is invalid
+pub fn discover_lambda_functions(path: &Path) -> Result<Vec<LambdaFunction>> {
+    if path.is_file() {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ExtractorError::file_system("read", path, e))?;
+        return match infrastructure_kind(path, true) {
+            Some(Infrastructure::Serverless) => serverless_functions(path, &content),
+            Some(Infrastructure::Cdk) => Ok(cdk_functions(path, &content)),
+            _ => cloudformation_functions(path, &content),
+        };
+    }
+
+    let mut functions = Vec::new();
+    let walker = WalkDir::new(path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || !(entry.file_type().is_dir()
+                    && (name.starts_with('.') || SKIPPED_DIRECTORIES.contains(&name.as_ref())))
+        });
+    for entry in walker {
+        let entry = entry.map_err(|e| {
+            let path = e.path().unwrap_or(path).to_path_buf();
+            ExtractorError::file_system(
+                "walk infrastructure directory",
+                path,
+                e.into_io_error()
+                    .unwrap_or_else(|| std::io::Error::other("file system loop")),
+            )
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Some(kind) = infrastructure_kind(entry.path(), false) else {
+            continue;
+        };
+        let content = std::fs::read_to_string(entry.path())
+            .map_err(|e| ExtractorError::file_system("read", entry.path(), e))?;
+        match kind {
+            Infrastructure::CloudFormation => {
+                functions.extend(cloudformation_functions(entry.path(), &content)?);
+            }
+            Infrastructure::Serverless => {
+                functions.extend(serverless_functions(entry.path(), &content)?);
+            }
+            Infrastructure::Cdk => functions.extend(cdk_functions(entry.path(), &content)),
+        }
+    }
+    Ok(functions)
+}
+
+/// Kinds of files declaring Lambda functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Infrastructure {
+    CloudFormation,
+    Serverless,
+    Cdk,
+}
+
+/// What kind of infrastructure definition a file may be
+///
+/// While searching a directory, only templates with the conventional SAM file names are
+/// considered; a file given explicitly may be any YAML or JSON template.
+fn infrastructure_kind(path: &Path, explicit: bool) -> Option<Infrastructure> {
+    let name = path.file_name()?.to_string_lossy();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+    if name.starts_with("serverless.") && matches!(extension.as_deref(), Some("yml" | "yaml")) {
+        return Some(Infrastructure::Serverless);
+    }
+    if matches!(extension.as_deref(), Some("yml" | "yaml" | "json")) {
+        let conventional = matches!(&*name, "template.yaml" | "template.yml" | "template.json");
+        return (explicit || conventional).then_some(Infrastructure::CloudFormation);
+    }
+    match SourceFile::detect_language(path)? {
+        Language::Python | Language::JavaScript | Language::TypeScript
+            if !name.ends_with(".d.ts") =>
+        {
+            Some(Infrastructure::Cdk)
+        }
+        _ => None,
+    }
+}
+
+/// Functions of a SAM or CloudFormation template
+fn cloudformation_functions(path: &Path, content: &str) -> Result<Vec<LambdaFunction>> {
+    let template: Value = serde_yaml::from_str(content).map_err(|e| {
+        ExtractorError::configuration_with_source(format!("Invalid template {}", path.display()), e)
+    })?;
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let globals = template.get("Globals").and_then(|g| g.get("Function"));
+    let Some(resources) = template.get("Resources").and_then(Value::as_mapping) else {
+        return Ok(Vec::new());
+    };
+
+    let mut functions = Vec::new();
+    for (id, resource) in resources {
+        let (Some(id), Some(kind)) = (id.as_str(), resource.get("Type").and_then(Value::as_str))
+        else {
+            continue;
+        };
+        let (globals, code) = match kind {
+            "AWS::Serverless::Function" => (globals, "CodeUri"),
+            "AWS::Lambda::Function" => (None, "Code"),
+            _ => continue,
+        };
+        let property = |key: &str| resource_property(resource, globals, key);
+        if property("PackageType") == Some("Image") {
+            continue;
+        }
+        let Some(handler) = property("Handler") else {
+            continue;
+        };
+        functions.push(LambdaFunction {
+            name: id.to_string(),
+            handler: handler.to_string(),
+            runtime: property("Runtime").map(str::to_string),
+            code_directory: directory.join(property(code).unwrap_or(".")),
+            declared_in: path.to_path_buf(),
+        });
+    }
+    Ok(functions)
+}
+
+/// A string property of a template resource, or of the SAM `Globals` if given
+fn resource_property<'a>(
+    resource: &'a Value,
+    globals: Option<&'a Value>,
+    key: &str,
+) -> Option<&'a str> {
+    resource
+        .get("Properties")
+        .and_then(|p| p.get(key))
+        .or_else(|| globals.and_then(|g| g.get(key)))
+        .and_then(Value::as_str)
+}
+
+/// Functions of a Serverless Framework configuration
+fn serverless_functions(path: &Path, content: &str) -> Result<Vec<LambdaFunction>> {
+    let configuration: Value = serde_yaml::from_str(content).map_err(|e| {
+        ExtractorError::configuration_with_source(
+            format!("Invalid Serverless configuration {}", path.display()),
+            e,
+        )
+    })?;
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let provider_runtime = configuration
+        .get("provider")
+        .and_then(|p| p.get("runtime"))
+        .and_then(Value::as_str);
+    let Some(declared) = configuration.get("functions").and_then(Value::as_mapping) else {
+        return Ok(Vec::new());
+    };
+
+    let mut functions = Vec::new();
+    for (name, function) in declared {
+        let (Some(name), Some(handler)) = (
+            name.as_str(),
+            function.get("handler").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        let runtime = function
+            .get("runtime")
+            .and_then(Value::as_str)
+            .or(provider_runtime);
+        functions.push(LambdaFunction {
+            name: name.to_string(),
+            handler: handler.to_string(),
+            runtime: runtime.map(str::to_string),
+            code_directory: directory.to_path_buf(),
+            declared_in: path.to_path_buf(),
+        });
+    }
+    Ok(functions)
+}
+
+/// Functions of the `lambda.Function` constructs of a CDK source file
+fn cdk_functions(path: &Path, content: &str) -> Vec<LambdaFunction> {
+    static CONSTRUCT: OnceLock<Regex> = OnceLock::new();
+    static HANDLER: OnceLock<Regex> = OnceLock::new();
+    static RUNTIME: OnceLock<Regex> = OnceLock::new();
+    static ASSET: OnceLock<Regex> = OnceLock::new();
+    static STRING: OnceLock<Regex> = OnceLock::new();
+    let construct = CONSTRUCT.get_or_init(|| {
+        Regex::new(
+            r#"(?:\bnew\s+(?:\w+\.)?Function|\b(?:_?lambda_?|aws_lambda)\.Function)\s*\(\s*(?:this|self)\s*,\s*['"]([^'"]+)['"]"#,
+        )
+        .expect("Invalid CDK construct regex")
+    });
+    let handler = HANDLER.get_or_init(|| {
+        Regex::new(r#"\bhandler\s*[:=]\s*['"]([^'"]+)['"]"#).expect("Invalid CDK handler regex")
+    });
+    let runtime =
+        RUNTIME.get_or_init(|| Regex::new(r"\bRuntime\.(\w+)").expect("Invalid CDK runtime regex"));
+    let asset = ASSET.get_or_init(|| {
+        Regex::new(r"\bCode\.(?:fromAsset|from_asset)\s*\(").expect("Invalid CDK asset regex")
+    });
+    let string =
+        STRING.get_or_init(|| Regex::new(r#"['"]([^'"]*)['"]"#).expect("Invalid string regex"));
+
+    let directory = path.parent().unwrap_or(Path::new("."));
+    // `cdk` runs from the directory of `cdk.json`, against which asset paths are resolved
+    let app_root = directory
+        .ancestors()
+        .find(|dir| dir.join("cdk.json").is_file())
+        .unwrap_or(directory);
+
+    let mut functions = Vec::new();
+    for captures in construct.captures_iter(content) {
+        let (Some(whole), Some(name)) = (captures.get(0), captures.get(1)) else {
+            continue;
+        };
+        let Some(open) = content[..whole.end()].rfind('(') else {
+            continue;
+        };
+        let properties = parenthesized(&content[open..]);
+        let Some(handler) = handler.captures(properties).map(|c| c[1].to_string()) else {
+            continue;
+        };
+        let code_directory = asset.find(properties).map(|m| {
+            let arguments = parenthesized(&properties[m.end() - 1..]);
+            let base = if arguments.contains("__dirname") || arguments.contains("__file__") {
+                directory
+            } else {
+                app_root
+            };
+            normalize(
+                &string
+                    .captures_iter(arguments)
+                    .fold(base.to_path_buf(), |dir, segment| dir.join(&segment[1])),
+            )
+        });
+        functions.push(LambdaFunction {
+            name: name.as_str().to_string(),
+            handler,
+            runtime: runtime.captures(properties).map(|c| runtime_name(&c[1])),
+            code_directory: code_directory.unwrap_or_else(|| directory.to_path_buf()),
+            declared_in: path.to_path_buf(),
+        });
+    }
+    functions
+}
+
+/// Runtime identifier of a CDK `Runtime` constant, e.g. `python3.12` for `PYTHON_3_12`
+fn runtime_name(constant: &str) -> String {
+    let constant = constant.to_lowercase();
+    match constant.split_once('_') {
+        Some((family, version)) if family != "provided" => {
+            format!("{}{}", family, version.replace('_', "."))
+        }
+        _ => constant.replace('_', "."),
+    }
+}
+
+/// The text of the parenthesized expression `text` starts with, including the parentheses
+fn parenthesized(text: &str) -> &str {
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return &text[..=i];
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+/// The `main` function of the Go `main` package in a directory
+fn go_main(directory: &Path) -> Option<EntryPoint> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(directory)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|e| e == "go")
+                && !path.to_string_lossy().ends_with("_test.go")
+        })
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .find(|file| {
+            std::fs::read_to_string(file).is_ok_and(|content| {
+                content.contains("package main") && content.contains("func main(")
+            })
+        })
+        .map(|file| EntryPoint {
+            file,
+            function: Some("main".to_string()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = dir.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_discover_sam_and_serverless_functions() {
+        let dir = TempDir::new().unwrap();
+        let template = write(
+            dir.path(),
+            "template.yaml",
+            r#"
+Transform: AWS::Serverless-2016-10-31
+Globals:
+  Function:
+    Runtime: python3.12
+Resources:
+  IngestFunction:
+    Type: AWS::Serverless::Function
+    Properties:
+      CodeUri: functions/ingest/
+      Handler: app.lambda_handler
+      Environment:
+        Variables:
+          TABLE: !Ref Table
+  ReportFunction:
+    Type: AWS::Serverless::Function
+    Properties:
+      CodeUri: functions/report
+      Handler: bootstrap
+      Runtime: provided.al2023
+  Table:
+    Type: AWS::Serverless::SimpleTable
+"#,
+        );
+        let ingest = write(
+            dir.path(),
+            "functions/ingest/app.py",
+            "def lambda_handler(event, context): pass\n",
+        );
+        let report = write(
+            dir.path(),
+            "functions/report/main.go",
+            "package main\n\nfunc main() {\n\tlambda.Start(handle)\n}\n",
+        );
+        let serverless = write(
+            dir.path(),
+            "api/serverless.yml",
+            "service: api\nprovider:\n  runtime: nodejs20.x\nfunctions:\n  list:\n    handler: src/items.list\n",
+        );
+        let items = write(
+            dir.path(),
+            "api/src/items.js",
+            "exports.list = async () => {};\n",
+        );
+
+        let functions = discover_lambda_functions(dir.path()).unwrap();
+        let names: Vec<(&str, &Path)> = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.declared_in.as_path()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("list", serverless.as_path()),
+                ("IngestFunction", template.as_path()),
+                ("ReportFunction", template.as_path()),
+            ]
+        );
+        assert_eq!(functions[1].runtime.as_deref(), Some("python3.12"));
+
+        let entry_points: Vec<EntryPoint> = functions
+            .iter()
+            .filter_map(LambdaFunction::entry_point)
+            .collect();
+        assert_eq!(
+            entry_points,
+            vec![
+                EntryPoint {
+                    file: items,
+                    function: Some("list".to_string()),
+                },
+                EntryPoint {
+                    file: ingest,
+                    function: Some("lambda_handler".to_string()),
+                },
+                EntryPoint {
+                    file: report,
+                    function: Some("main".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discover_cdk_functions() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "cdk.json", "{}");
+        let stack = write(
+            dir.path(),
+            "lib/stack.ts",
+            r#"
+const fn = new lambda.Function(this, 'Uploader', {
+  runtime: lambda.Runtime.NODEJS_20_X,
+  handler: 'upload.handler',
+  code: lambda.Code.fromAsset(path.join(__dirname, '..', 'lambda')),
+});
+new lambda.Function(this, "Cleaner", {
+  runtime: lambda.Runtime.PYTHON_3_12,
+  code: lambda.Code.fromAsset("jobs"),
+  handler: "cleanup.main",
+});
+"#,
+        );
+        let upload = write(
+            dir.path(),
+            "lambda/upload.ts",
+            "export const handler = async () => {};\n",
+        );
+        let cleanup = write(
+            dir.path(),
+            "jobs/cleanup.py",
+            "def main(event, context): pass\n",
+        );
+
+        let functions = discover_lambda_functions(&stack).unwrap();
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].name, "Uploader");
+        assert_eq!(functions[0].runtime.as_deref(), Some("nodejs20.x"));
+        assert_eq!(functions[0].entry_point().map(|e| e.file), Some(upload));
+        assert_eq!(functions[1].name, "Cleaner");
+        assert_eq!(
+            functions[1].entry_point(),
+            Some(EntryPoint {
+                file: cleanup,
+                function: Some("main".to_string()),
+            })
+        );
+    }
+}
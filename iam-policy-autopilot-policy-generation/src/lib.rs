@@ -20,9 +20,21 @@ pub(crate) mod providers;
 // Service configuration
 pub(crate) mod service_configuration;
 
+// Project configuration file
+pub mod project_config;
+
+// Workloads of a repository
+pub mod workload;
+
+// Lambda functions declared in infrastructure as code
+pub mod lambda_functions;
+
 // Embedded AWS service data
 pub mod embedded_data;
 
+// Progress reporting and cancellation of policy generations
+pub mod progress;
+
 // Re-export the extraction module for public use
 pub mod extraction;
 // Re-export the policy_generation module for public use
@@ -34,20 +46,35 @@ pub mod api;
 use std::fmt::Display;
 use std::path::PathBuf;
 
-pub use enrichment::{Engine as EnrichmentEngine, Explanation};
-pub use extraction::{Engine as ExtractionEngine, ExtractedMethods, SdkMethodCall, SourceFile};
+pub use enrichment::{
+    AccessLevel, DirectoryServiceReferenceLoader, Engine as EnrichmentEngine, Explanation,
+    Explanations, InMemoryServiceReferenceLoader, RemoteServiceReferenceLoader,
+    RemoteServiceReferenceLoaderBuilder, ServiceReferenceLoader,
+};
+pub use extraction::{
+    Diagnostic, DiagnosticCode, DiagnosticSeverity, Engine as ExtractionEngine, ExtractedMethods,
+    ExtractionCache, ExtractionCacheStatistics, SdkMethodCall, SourceFile,
+};
+pub use lambda_functions::{discover_lambda_functions, LambdaFunction};
 pub use policy_generation::{
-    Effect, Engine as PolicyGenerationEngine, IamPolicy, PolicyType, PolicyWithMetadata, Statement,
+    BroadenedResource, Effect, Engine as PolicyGenerationEngine, FindingType, IamPolicy, IssueCode,
+    MissingPermission, NarrowedResource, PolicyComparison, PolicyFindings, PolicyKind, PolicyType,
+    PolicyWithMetadata, Statement, StatementFinding, StatementFindings, UnexplainedPermission,
+    ValidationFinding, WildcardReason,
 };
 
 // Re-export commonly used types for convenience
 pub(crate) use extraction::ServiceModelIndex;
 
+pub use progress::{Progress, ProgressReporter};
+pub use project_config::{ProjectConfig, PROJECT_CONFIG_FILE_NAME};
 pub use providers::FileSystemProvider;
 pub use providers::JsonProvider;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
+pub use tokio_util::sync::CancellationToken;
+pub use workload::{Workload, WorkloadAssignment, WorkloadManifest};
 
 use crate::errors::ExtractorError;
 
@@ -0,0 +1,569 @@
+//! Comparison of an edited policy document with the policies generated for source code
+//!
+//! Assistants and users edit generated policies, e.g. when writing infrastructure as code. The
+//! edited document is compared with the generated policies to find the permissions it lost,
+//! the resources it narrowed, the permissions no SDK call needs, and the resources it widened.
+//! A generated resource is only granted if a single statement covers all of it. Conditions are not
+//! evaluated: a statement with conditions is assumed to apply.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::{ExtractorError, Result};
+use crate::policy_generation::validation::{
+    resource_covers, resources_overlap, string_or_array, wildcard_matches,
+};
+use crate::policy_generation::{Effect, PolicyWithMetadata};
+
+/// Action of the generated policies the edited policy does not grant
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct MissingPermission {
+    /// Action needed by the source code
+    pub action: String,
+    /// Generated resources of the action that are not granted
+    pub resources: Vec<String>,
+    /// Whether a Deny statement of the edited policy denies the action
+    pub denied: bool,
+}
+
+/// Generated resource of an action the edited policy grants only in part
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct NarrowedResource {
+    /// Action needed by the source code
+    pub action: String,
+    /// Generated resource of the action
+    pub resource: String,
+    /// Resources of the edited policy granting the action on part of the generated resource
+    pub granted_resources: Vec<String>,
+    /// Whether a Deny statement of the edited policy denies part of the generated resource
+    pub partially_denied: bool,
+}
+
+/// Action of the edited policy that the source code does not explain
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct UnexplainedPermission {
+    /// Index of the statement within the edited policy
+    pub statement_index: usize,
+    /// Action, action pattern or `NotAction` element of the statement
+    pub action: String,
+    /// Generated actions matched by a pattern granting more actions, empty if the source code
+    /// needs none of the actions
+    pub covered_actions: Vec<String>,
+}
+
+/// Resource of the edited policy broader than the generated resources of its actions
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct BroadenedResource {
+    /// Index of the statement within the edited policy
+    pub statement_index: usize,
+    /// Resource, or `NotResource` element, of the statement
+    pub resource: String,
+    /// Generated actions of the statement whose generated resources do not cover the resource
+    pub actions: Vec<String>,
+    /// Generated resources of these actions
+    pub generated_resources: Vec<String>,
+}
+
+/// Differences between an edited policy document and the policies generated for source code
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct PolicyComparison {
+    /// Generated permissions the edited policy lacks
+    pub missing_permissions: Vec<MissingPermission>,
+    /// Generated resources the edited policy grants only in part
+    pub narrowed_resources: Vec<NarrowedResource>,
+    /// Permissions of the edited policy no SDK call needs
+    pub unexplained_permissions: Vec<UnexplainedPermission>,
+    /// Resources of the edited policy broader than the generated resources
+    pub broadened_resources: Vec<BroadenedResource>,
+}
+
+impl PolicyComparison {
+    /// Whether the edited policy grants exactly what the generated policies grant
+    pub fn is_consistent(&self) -> bool {
+        self.missing_permissions.is_empty()
+            && self.narrowed_resources.is_empty()
+            && self.unexplained_permissions.is_empty()
+            && self.broadened_resources.is_empty()
+    }
+}
+
+/// Action or resource element of a statement
+enum Element {
+    Listed(Vec<String>),
+    Not(Vec<String>),
+}
+
+impl Element {
+    fn parse(statement: &Value, name: &str, index: usize) -> Result<Self> {
+        let parse = |name: &str| {
+            statement.get(name).map(|value| {
+                string_or_array(value).map_err(|()| {
+                    ExtractorError::validation(format!(
+                        "{name} of statement {index} must be a string or an array of strings"
+                    ))
+                })
+            })
+        };
+        match (parse(name), parse(&format!("Not{name}"))) {
+            (Some(listed), None) => Ok(Self::Listed(listed?)),
+            (None, Some(not)) => Ok(Self::Not(not?)),
+            _ => Err(ExtractorError::validation(format!(
+                "Statement {index} must have either {name} or Not{name}"
+            ))),
+        }
+    }
+
+    fn matches_action(&self, action: &str) -> bool {
+        match self {
+            Self::Listed(patterns) => patterns.iter().any(|p| wildcard_matches(p, action)),
+            Self::Not(patterns) => !patterns.iter().any(|p| wildcard_matches(p, action)),
+        }
+    }
+
+    /// Whether the element applies to some of the ARNs of `resource`
+    fn overlaps(&self, resource: &str) -> bool {
+        match self {
+            Self::Listed(patterns) => patterns.iter().any(|p| resources_overlap(p, resource)),
+            Self::Not(patterns) => !patterns.iter().any(|p| resource_covers(p, resource)),
+        }
+    }
+
+    /// Whether the element applies to all the ARNs of `resource`
+    fn covers(&self, resource: &str) -> bool {
+        match self {
+            Self::Listed(patterns) => patterns.iter().any(|p| resource_covers(p, resource)),
+            Self::Not(patterns) => !patterns.iter().any(|p| resources_overlap(p, resource)),
+        }
+    }
+}
+
+/// Statement of the edited policy
+struct EditedStatement {
+    index: usize,
+    allow: bool,
+    action: Element,
+    resource: Element,
+}
+
+fn parse_document(document: &str) -> Result<Vec<EditedStatement>> {
+    let invalid = |message: &str| {
+        ExtractorError::validation(format!(
+            "Invalid policy document: {message}. Use validate_policies to find the problems of the policy"
+        ))
+    };
+    let value: Value =
+        serde_json::from_str(document).map_err(|e| invalid(&format!("not valid JSON, {e}")))?;
+    let statements = match value.get("Statement") {
+        Some(Value::Array(statements)) => statements.clone(),
+        Some(statement @ Value::Object(_)) => vec![statement.clone()],
+        _ => {
+            return Err(invalid(
+                "Statement must be an object or an array of objects",
+            ))
+        }
+    };
+
+    statements
+        .iter()
+        .enumerate()
+        .map(|(index, statement)| {
+            let allow = match statement.get("Effect").and_then(Value::as_str) {
+                Some("Allow") => true,
+                Some("Deny") => false,
+                _ => {
+                    return Err(invalid(&format!(
+                        "Effect of statement {index} must be Allow or Deny"
+                    )))
+                }
+            };
+            Ok(EditedStatement {
+                index,
+                allow,
+                action: Element::parse(statement, "Action", index)?,
+                resource: Element::parse(statement, "Resource", index)?,
+            })
+        })
+        .collect()
+}
+
+fn display(element: &Element, name: &str) -> Vec<String> {
+    match element {
+        Element::Listed(values) => values.clone(),
+        Element::Not(values) => vec![format!("Not{name} {}", values.join(", "))],
+    }
+}
+
+/// Compare the edited policy `document` with the `generated` policies
+///
+/// # Errors
+/// Returns an error if the document is not a policy document
+pub(crate) fn compare_policy(
+    document: &str,
+    generated: &[PolicyWithMetadata],
+) -> Result<PolicyComparison> {
+    let statements = parse_document(document)?;
+
+    // Resources of each generated action
+    let mut generated_actions: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for statement in generated
+        .iter()
+        .flat_map(|policy| &policy.policy.statements)
+        .filter(|statement| statement.effect == Effect::Allow)
+    {
+        for action in &statement.action {
+            generated_actions
+                .entry(action)
+                .or_default()
+                .extend(statement.resource.iter().map(String::as_str));
+        }
+    }
+
+    let mut comparison = PolicyComparison::default();
+    for (action, resources) in &generated_actions {
+        let applying: Vec<&EditedStatement> = statements
+            .iter()
+            .filter(|statement| statement.action.matches_action(action))
+            .collect();
+        let mut denied = false;
+        let mut missing = Vec::new();
+        for resource in resources {
+            let allowing: Vec<&EditedStatement> = applying
+                .iter()
+                .copied()
+                .filter(|s| s.allow && s.resource.overlaps(resource))
+                .collect();
+            let is_denied = applying
+                .iter()
+                .any(|s| !s.allow && s.resource.covers(resource));
+            denied |= is_denied;
+            if is_denied || allowing.is_empty() {
+                missing.push(resource.to_string());
+                continue;
+            }
+
+            // Only part of the resource is granted, e.g. some objects of a bucket
+            let partially_denied = applying
+                .iter()
+                .any(|s| !s.allow && s.resource.overlaps(resource));
+            if partially_denied || !allowing.iter().any(|s| s.resource.covers(resource)) {
+                let granted_resources: BTreeSet<String> = allowing
+                    .iter()
+                    .flat_map(|s| match &s.resource {
+                        Element::Listed(patterns) => patterns
+                            .iter()
+                            .filter(|p| resources_overlap(p, resource))
+                            .cloned()
+                            .collect(),
+                        Element::Not(_) => display(&s.resource, "Resource"),
+                    })
+                    .collect();
+                comparison.narrowed_resources.push(NarrowedResource {
+                    action: action.to_string(),
+                    resource: resource.to_string(),
+                    granted_resources: granted_resources.into_iter().collect(),
+                    partially_denied,
+                });
+            }
+        }
+        if !missing.is_empty() {
+            comparison.missing_permissions.push(MissingPermission {
+                action: action.to_string(),
+                resources: missing,
+                denied,
+            });
+        }
+    }
+
+    for statement in statements.iter().filter(|statement| statement.allow) {
+        let matched: Vec<&str> = generated_actions
+            .keys()
+            .copied()
+            .filter(|action| statement.action.matches_action(action))
+            .collect();
+
+        match &statement.action {
+            Element::Listed(patterns) => {
+                for pattern in patterns {
+                    let covered: Vec<String> = matched
+                        .iter()
+                        .filter(|action| wildcard_matches(pattern, action))
+                        .map(|action| action.to_string())
+                        .collect();
+                    if covered.is_empty() || pattern.contains(['*', '?']) {
+                        comparison
+                            .unexplained_permissions
+                            .push(UnexplainedPermission {
+                                statement_index: statement.index,
+                                action: pattern.clone(),
+                                covered_actions: covered,
+                            });
+                    }
+                }
+            }
+            Element::Not(_) => comparison
+                .unexplained_permissions
+                .push(UnexplainedPermission {
+                    statement_index: statement.index,
+                    action: display(&statement.action, "Action").remove(0),
+                    covered_actions: matched.iter().map(|a| a.to_string()).collect(),
+                }),
+        }
+
+        let resources = match &statement.resource {
+            Element::Listed(resources) => resources.iter().map(String::as_str).collect(),
+            // A NotResource element is broader than any generated resource
+            Element::Not(_) => vec![""],
+        };
+        let displayed = display(&statement.resource, "Resource");
+        for (position, resource) in resources.into_iter().enumerate() {
+            let actions: Vec<&str> = matched
+                .iter()
+                .copied()
+                .filter(|action| {
+                    matches!(statement.resource, Element::Not(_))
+                        || !generated_actions[action]
+                            .iter()
+                            .any(|generated| resource_covers(generated, resource))
+                })
+                .collect();
+            if actions.is_empty() {
+                continue;
+            }
+            let generated_resources: BTreeSet<&str> = actions
+                .iter()
+                .flat_map(|action| generated_actions[action].iter().copied())
+                .collect();
+            comparison.broadened_resources.push(BroadenedResource {
+                statement_index: statement.index,
+                resource: displayed[position].clone(),
+                actions: actions.iter().map(|a| a.to_string()).collect(),
+                generated_resources: generated_resources.iter().map(|r| r.to_string()).collect(),
+            });
+        }
+    }
+
+    Ok(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy_generation::{IamPolicy, PolicyType, Statement};
+    use serde_json::json;
+
+    fn generated() -> Vec<PolicyWithMetadata> {
+        let mut policy = IamPolicy::new();
+        policy.add_statement(Statement::allow(
+            vec!["s3:GetObject".to_string(), "s3:PutObject".to_string()],
+            vec!["arn:aws:s3:::*/*".to_string()],
+        ));
+        policy.add_statement(Statement::allow(
+            vec!["dynamodb:GetItem".to_string()],
+            vec!["arn:aws:dynamodb:*:*:table/*".to_string()],
+        ));
+        vec![PolicyWithMetadata {
+            policy,
+            policy_type: PolicyType::Identity,
+            findings: None,
+        }]
+    }
+
+    fn compare(document: serde_json::Value) -> PolicyComparison {
+        compare_policy(&document.to_string(), &generated()).unwrap()
+    }
+
+    #[test]
+    fn test_equivalent_policy_is_consistent() {
+        let comparison = compare(json!({
+            "Version": "2012-10-17",
+            "Statement": [
+                {
+                    "Effect": "Allow",
+                    "Action": ["s3:GetObject", "s3:putobject"],
+                    "Resource": "arn:aws:s3:::*/*"
+                },
+                {
+                    "Effect": "Allow",
+                    "Action": "dynamodb:GetItem",
+                    "Resource": "arn:aws:dynamodb:*:*:table/*"
+                }
+            ]
+        }));
+        assert_eq!(comparison, PolicyComparison::default());
+        assert!(comparison.is_consistent());
+    }
+
+    #[test]
+    fn test_narrowed_resources() {
+        let comparison = compare(json!({
+            "Version": "2012-10-17",
+            "Statement": [
+                {
+                    "Effect": "Allow",
+                    "Action": ["s3:GetObject", "s3:PutObject"],
+                    "Resource": "arn:aws:s3:::my-bucket/logs/*"
+                },
+                {
+                    "Effect": "Allow",
+                    "Action": "dynamodb:GetItem",
+                    "Resource": "arn:aws:dynamodb:*:*:table/*"
+                },
+                {
+                    "Effect": "Deny",
+                    "Action": "dynamodb:GetItem",
+                    "Resource": "arn:aws:dynamodb:*:*:table/Secrets"
+                }
+            ]
+        }));
+        assert_eq!(
+            comparison.narrowed_resources,
+            vec![
+                NarrowedResource {
+                    action: "dynamodb:GetItem".to_string(),
+                    resource: "arn:aws:dynamodb:*:*:table/*".to_string(),
+                    granted_resources: vec!["arn:aws:dynamodb:*:*:table/*".to_string()],
+                    partially_denied: true,
+                },
+                NarrowedResource {
+                    action: "s3:GetObject".to_string(),
+                    resource: "arn:aws:s3:::*/*".to_string(),
+                    granted_resources: vec!["arn:aws:s3:::my-bucket/logs/*".to_string()],
+                    partially_denied: false,
+                },
+                NarrowedResource {
+                    action: "s3:PutObject".to_string(),
+                    resource: "arn:aws:s3:::*/*".to_string(),
+                    granted_resources: vec!["arn:aws:s3:::my-bucket/logs/*".to_string()],
+                    partially_denied: false,
+                },
+            ]
+        );
+        assert!(comparison.missing_permissions.is_empty());
+        assert!(comparison.broadened_resources.is_empty());
+        assert!(!comparison.is_consistent());
+    }
+
+    #[test]
+    fn test_missing_permissions() {
+        let comparison = compare(json!({
+            "Version": "2012-10-17",
+            "Statement": [
+                {"Effect": "Allow", "Action": "s3:GetObject", "Resource": "arn:aws:s3:::my-bucket/*"},
+                {"Effect": "Allow", "Action": "dynamodb:GetItem", "Resource": "arn:aws:s3:::my-bucket/*"},
+                {"Effect": "Deny", "Action": "s3:*", "Resource": "*"}
+            ]
+        }));
+        assert_eq!(
+            comparison.missing_permissions,
+            vec![
+                MissingPermission {
+                    action: "dynamodb:GetItem".to_string(),
+                    resources: vec!["arn:aws:dynamodb:*:*:table/*".to_string()],
+                    denied: false,
+                },
+                MissingPermission {
+                    action: "s3:GetObject".to_string(),
+                    resources: vec!["arn:aws:s3:::*/*".to_string()],
+                    denied: true,
+                },
+                MissingPermission {
+                    action: "s3:PutObject".to_string(),
+                    resources: vec!["arn:aws:s3:::*/*".to_string()],
+                    denied: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unexplained_permissions() {
+        let comparison = compare(json!({
+            "Version": "2012-10-17",
+            "Statement": [
+                {"Effect": "Allow", "Action": ["s3:Get*", "s3:PutObject", "s3:DeleteObject"], "Resource": "arn:aws:s3:::*/*"},
+                {"Effect": "Allow", "Action": "dynamodb:GetItem", "Resource": "arn:aws:dynamodb:*:*:table/*"},
+                {"Effect": "Allow", "NotAction": "iam:*", "Resource": "arn:aws:dynamodb:*:*:table/*"}
+            ]
+        }));
+        assert_eq!(
+            comparison.unexplained_permissions,
+            vec![
+                UnexplainedPermission {
+                    statement_index: 0,
+                    action: "s3:Get*".to_string(),
+                    covered_actions: vec!["s3:GetObject".to_string()],
+                },
+                UnexplainedPermission {
+                    statement_index: 0,
+                    action: "s3:DeleteObject".to_string(),
+                    covered_actions: vec![],
+                },
+                UnexplainedPermission {
+                    statement_index: 2,
+                    action: "NotAction iam:*".to_string(),
+                    covered_actions: vec![
+                        "dynamodb:GetItem".to_string(),
+                        "s3:GetObject".to_string(),
+                        "s3:PutObject".to_string()
+                    ],
+                },
+            ]
+        );
+        assert!(comparison.missing_permissions.is_empty());
+    }
+
+    #[test]
+    fn test_broadened_resources() {
+        let comparison = compare(json!({
+            "Version": "2012-10-17",
+            "Statement": [
+                {"Effect": "Allow", "Action": ["s3:GetObject", "s3:PutObject"], "Resource": ["arn:aws:s3:::my-bucket/*", "*"]},
+                {"Effect": "Allow", "Action": "dynamodb:GetItem", "NotResource": "arn:aws:dynamodb:*:*:table/Secrets"}
+            ]
+        }));
+        assert_eq!(
+            comparison.broadened_resources,
+            vec![
+                BroadenedResource {
+                    statement_index: 0,
+                    resource: "*".to_string(),
+                    actions: vec!["s3:GetObject".to_string(), "s3:PutObject".to_string()],
+                    generated_resources: vec!["arn:aws:s3:::*/*".to_string()],
+                },
+                BroadenedResource {
+                    statement_index: 1,
+                    resource: "NotResource arn:aws:dynamodb:*:*:table/Secrets".to_string(),
+                    actions: vec!["dynamodb:GetItem".to_string()],
+                    generated_resources: vec!["arn:aws:dynamodb:*:*:table/*".to_string()],
+                },
+            ]
+        );
+        assert!(comparison.missing_permissions.is_empty());
+        assert!(comparison.unexplained_permissions.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_documents() {
+        for document in [
+            "not json".to_string(),
+            json!({"Version": "2012-10-17"}).to_string(),
+            json!({"Statement": {"Effect": "Maybe", "Action": "s3:GetObject", "Resource": "*"}})
+                .to_string(),
+            json!({"Statement": {"Effect": "Allow", "Resource": "*"}}).to_string(),
+            json!({"Statement": {"Effect": "Allow", "Action": 1, "Resource": "*"}}).to_string(),
+        ] {
+            assert!(
+                compare_policy(&document, &generated()).is_err(),
+                "{document}"
+            );
+        }
+    }
+}
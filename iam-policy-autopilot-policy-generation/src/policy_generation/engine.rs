@@ -6,6 +6,7 @@
 
 use std::collections::BTreeMap;
 
+use super::findings::{FindingFacts, WildcardReason};
 use super::merge::{PolicyMerger, PolicyMergerConfig};
 use super::utils::{ArnParser, ConditionValueProcessor};
 use super::{IamPolicy, Statement};
@@ -33,6 +34,14 @@ impl<'a> Engine<'a> {
         Self::with_merger_config(partition, region, account, PolicyMergerConfig::default())
     }
 
+    /// Use the given values for resource placeholders (e.g. `BucketName`) instead of wildcards
+    #[must_use]
+    pub fn with_resource_bindings(mut self, bindings: BTreeMap<String, String>) -> Self {
+        self.arn_parser = self.arn_parser.with_bindings(bindings.clone());
+        self.condition_processor = self.condition_processor.with_bindings(bindings);
+        self
+    }
+
     /// Create a new policy generation engine with AWS context and merger configuration
     pub fn with_merger_config(
         partition: &'a str,
@@ -89,6 +98,7 @@ impl<'a> Engine<'a> {
         let policy_with_metadata = PolicyWithMetadata {
             policy,
             policy_type: PolicyType::Identity,
+            findings: None,
         };
 
         Ok(policy_with_metadata)
@@ -245,12 +255,74 @@ impl<'a> Engine<'a> {
                     .map(|policy| PolicyWithMetadata {
                         policy: policy.clone(),
                         policy_type: first.policy_type,
+                        findings: None,
                     })
                     .collect::<Vec<_>>())
             }
         }
     }
 
+    /// Attach least-privilege findings to policies generated from enriched method calls
+    ///
+    /// Findings are computed on the final statements, so this also works for merged policies:
+    /// the wildcard reasons and access levels of every action are collected from the enriched
+    /// calls and looked up for each (action, resource) pair of a statement.
+    ///
+    /// # Errors
+    /// Returns an error if an ARN pattern contains invalid placeholders
+    pub(crate) fn annotate_findings(
+        &self,
+        policies: &mut [PolicyWithMetadata],
+        enriched_calls: &[EnrichedSdkMethodCall],
+    ) -> Result<()> {
+        let mut facts = FindingFacts::default();
+
+        for action in enriched_calls.iter().flat_map(|call| call.actions.iter()) {
+            if let Some(access_level) = action.access_level {
+                facts.set_access_level(&action.name, access_level);
+            }
+
+            for resource in &action.resources {
+                match &resource.arn_patterns {
+                    Some(arn_patterns) => {
+                        for arn_pattern in arn_patterns {
+                            let (processed, mut reasons) = self
+                                .arn_parser
+                                .process_arn_pattern_with_reasons(arn_pattern)?;
+                            if action.resource_cutoff {
+                                reasons.insert(WildcardReason::ResourceCutoff);
+                            }
+                            if processed == "*" && reasons.is_empty() {
+                                reasons.insert(WildcardReason::NotResourceScoped);
+                            }
+                            facts.add_wildcard_reasons(&action.name, &processed, reasons);
+                        }
+                    }
+                    None if action.resource_cutoff => {
+                        facts.add_wildcard_reasons(
+                            &action.name,
+                            "*",
+                            [WildcardReason::ResourceCutoff],
+                        );
+                    }
+                    None => {
+                        facts.add_wildcard_reasons(
+                            &action.name,
+                            "*",
+                            [WildcardReason::NotResourceScoped],
+                        );
+                    }
+                }
+            }
+        }
+
+        for policy in policies {
+            policy.findings = Some(facts.policy_findings(&policy.policy));
+        }
+
+        Ok(())
+    }
+
     /// Generate IAM policies with explanations from enriched method calls
     ///
     /// This method generates policies and collects explanations for why each action
@@ -268,7 +340,8 @@ impl<'a> Engine<'a> {
         &self,
         enriched_calls: &[EnrichedSdkMethodCall],
     ) -> Result<GeneratePoliciesResult> {
-        let policies = self.generate_individual_policies(enriched_calls)?;
+        let mut policies = self.generate_individual_policies(enriched_calls)?;
+        self.annotate_findings(&mut policies, enriched_calls)?;
 
         // Collect explanations
         let explanations = extract_explanations(enriched_calls);
@@ -276,6 +349,7 @@ impl<'a> Engine<'a> {
         Ok(GeneratePoliciesResult {
             policies,
             explanations: Some(explanations),
+            diagnostics: vec![],
         })
     }
 }
@@ -544,6 +618,7 @@ mod tests {
         let policy1 = PolicyWithMetadata {
             policy: policy1,
             policy_type: PolicyType::Identity,
+            findings: None,
         };
 
         let mut policy2 = IamPolicy::new();
@@ -554,6 +629,7 @@ mod tests {
         let policy2 = PolicyWithMetadata {
             policy: policy2,
             policy_type: PolicyType::Identity,
+            findings: None,
         };
 
         let merged = engine.merge_policies(&[policy1, policy2]).unwrap();
@@ -569,6 +645,75 @@ mod tests {
         assert_eq!(statement.resource, vec!["arn:aws:s3:::bucket/*"]);
     }
 
+    #[test]
+    fn test_annotate_findings_after_merge() {
+        use crate::enrichment::AccessLevel;
+        use crate::policy_generation::{StatementFinding, WildcardReason};
+
+        let engine = Engine::new("aws", "*", "*");
+        let sdk_call = create_test_sdk_call();
+
+        let enriched_call = EnrichedSdkMethodCall {
+            method_name: "send_message".to_string(),
+            service: "sqs".to_string(),
+            actions: vec![
+                Action::new(
+                    "sqs:SendMessage".to_string(),
+                    vec![Resource::new(
+                        "queue".to_string(),
+                        Some(vec![
+                            "arn:${Partition}:sqs:${Region}:${Account}:${QueueName}".to_string(),
+                        ]),
+                    )],
+                    vec![],
+                    Explanation::default(),
+                )
+                .with_access_level(Some(AccessLevel::Write)),
+                Action::new(
+                    "ec2:DescribeInstances".to_string(),
+                    vec![Resource::new("*".to_string(), None)],
+                    vec![],
+                    Explanation::default(),
+                )
+                .with_access_level(Some(AccessLevel::List))
+                .with_resource_cutoff(true),
+            ],
+            sdk_method_call: &sdk_call,
+        };
+
+        let enriched_calls = [enriched_call];
+        let result = engine.generate_policies(&enriched_calls).unwrap();
+        assert!(result.policies[0].findings.is_some());
+
+        let mut merged = engine.merge_policies(&result.policies).unwrap();
+        assert!(merged[0].findings.is_none());
+        engine
+            .annotate_findings(&mut merged, &enriched_calls)
+            .unwrap();
+
+        let findings = merged[0].findings.as_ref().unwrap();
+        let findings = findings.findings().collect::<Vec<_>>();
+        assert!(findings.contains(&&StatementFinding::WildcardResource {
+            resource: "arn:aws:sqs:*:*:*".to_string(),
+            actions: vec!["sqs:SendMessage".to_string()],
+            reasons: vec![
+                WildcardReason::UnresolvedParameter,
+                WildcardReason::UnknownAccount,
+                WildcardReason::UnknownRegion,
+            ],
+        }));
+        assert!(findings.contains(&&StatementFinding::WildcardResource {
+            resource: "*".to_string(),
+            actions: vec!["ec2:DescribeInstances".to_string()],
+            reasons: vec![WildcardReason::ResourceCutoff],
+        }));
+        assert!(findings.contains(&&StatementFinding::SensitiveAccessLevel {
+            action: "sqs:SendMessage".to_string(),
+            access_level: AccessLevel::Write,
+        }));
+        assert_eq!(findings.len(), 3);
+    }
+
     #[test]
     fn test_merge_policies_empty() {
         let engine = create_test_engine();
@@ -1121,6 +1266,18 @@ mod tests {
             }
             _ => panic!("Expected FAS operation source"),
         }
+
+        // A single action is explained with the documentation of the concepts it involves
+        let explanations = result.explanations.as_ref().unwrap();
+        let kms = explanations.for_action("KMS:decrypt").unwrap();
+        assert_eq!(
+            kms.explanation_for_action.keys().collect::<Vec<_>>(),
+            vec!["kms:Decrypt"]
+        );
+        assert_eq!(kms.documentation.len(), 1);
+        let s3 = explanations.for_action("s3:GetObject").unwrap();
+        assert!(s3.documentation.is_empty());
+        assert!(explanations.for_action("s3:PutObject").is_none());
     }
 
     #[test]
@@ -0,0 +1,407 @@
+//! Least-privilege findings for generated policies
+//!
+//! This module records where the policy engine had to fall back to broader permissions than
+//! the code strictly needs, so that reviewers know which statements to tighten. Findings are
+//! attached per statement to [`PolicyWithMetadata`](super::PolicyWithMetadata), together with
+//! an overall breadth score for the policy.
+
+use std::collections::{BTreeSet, HashMap};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::IamPolicy;
+use crate::enrichment::AccessLevel;
+
+/// Reason why a resource in a generated statement contains a wildcard
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema,
+)]
+pub enum WildcardReason {
+    /// The action accepts too many resource types, so its resources were collapsed to `*`
+    ResourceCutoff,
+    /// A resource name placeholder (e.g. `${BucketName}`) could not be resolved from the code
+    UnresolvedParameter,
+    /// No AWS account was provided
+    UnknownAccount,
+    /// No AWS region was provided
+    UnknownRegion,
+    /// No AWS partition was provided
+    UnknownPartition,
+    /// The action does not support resource-level permissions
+    NotResourceScoped,
+}
+
+/// A single least-privilege finding for a policy statement
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(tag = "Type")]
+pub enum StatementFinding {
+    /// A resource of the statement contains a wildcard
+    #[serde(rename_all = "PascalCase")]
+    WildcardResource {
+        /// The resource containing the wildcard
+        resource: String,
+        /// Actions of the statement for which the wildcard was introduced
+        actions: Vec<String>,
+        /// Why the wildcard was introduced
+        reasons: Vec<WildcardReason>,
+    },
+    /// An action of the statement can modify resources or permissions
+    #[serde(rename_all = "PascalCase")]
+    SensitiveAccessLevel {
+        /// The action
+        action: String,
+        /// Access level of the action from the service reference
+        access_level: AccessLevel,
+    },
+}
+
+/// Findings for a single statement of a policy
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct StatementFindings {
+    /// Zero-based index of the statement in the policy
+    pub statement_index: usize,
+    /// Statement identifier, if the statement has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Findings for the statement
+    pub findings: Vec<StatementFinding>,
+}
+
+/// Least-privilege findings for a generated policy
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct PolicyFindings {
+    /// How broad the policy is, from 0 (every action is scoped to specific resources)
+    /// to 100 (every action applies to all resources). Sensitive actions weigh more.
+    pub breadth_score: u8,
+    /// Statements with at least one finding
+    pub statements: Vec<StatementFindings>,
+}
+
+impl PolicyFindings {
+    /// Returns true if no statement has a finding
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// Iterate over all findings of the policy
+    pub fn findings(&self) -> impl Iterator<Item = &StatementFinding> {
+        self.statements
+            .iter()
+            .flat_map(|statement| statement.findings.iter())
+    }
+}
+
+/// Facts about actions and resources collected while generating statements,
+/// used to attach findings to the final (possibly merged) policies
+#[derive(Debug, Default)]
+pub(crate) struct FindingFacts {
+    /// Wildcard reasons keyed by (action, processed resource)
+    wildcard_reasons: HashMap<(String, String), BTreeSet<WildcardReason>>,
+    /// Access levels keyed by action
+    access_levels: HashMap<String, AccessLevel>,
+}
+
+impl FindingFacts {
+    /// Record why a processed resource of an action contains wildcards
+    pub(crate) fn add_wildcard_reasons(
+        &mut self,
+        action: &str,
+        resource: &str,
+        reasons: impl IntoIterator<Item = WildcardReason>,
+    ) {
+        let mut reasons = reasons.into_iter().peekable();
+        if reasons.peek().is_none() {
+            return;
+        }
+        self.wildcard_reasons
+            .entry((action.to_string(), resource.to_string()))
+            .or_default()
+            .extend(reasons);
+    }
+
+    /// Record the access level of an action
+    pub(crate) fn set_access_level(&mut self, action: &str, access_level: AccessLevel) {
+        self.access_levels.insert(action.to_string(), access_level);
+    }
+
+    /// Compute the findings for a policy
+    pub(crate) fn policy_findings(&self, policy: &IamPolicy) -> PolicyFindings {
+        let mut statements = Vec::new();
+        let mut weighted_breadth = 0.0;
+        let mut total_weight = 0.0;
+
+        for (statement_index, statement) in policy.statements.iter().enumerate() {
+            let mut findings = Vec::new();
+
+            for resource in &statement.resource {
+                let mut actions = Vec::new();
+                let mut reasons = BTreeSet::new();
+                for action in &statement.action {
+                    if let Some(action_reasons) = self
+                        .wildcard_reasons
+                        .get(&(action.clone(), resource.clone()))
+                    {
+                        actions.push(action.clone());
+                        reasons.extend(action_reasons.iter().copied());
+                    }
+                }
+                if reasons.is_empty() && resource == "*" {
+                    // A bare wildcard without a recorded cause comes from an action
+                    // without resource types or a resource override
+                    actions = statement.action.clone();
+                    reasons.insert(WildcardReason::NotResourceScoped);
+                }
+                if !reasons.is_empty() {
+                    findings.push(StatementFinding::WildcardResource {
+                        resource: resource.clone(),
+                        actions,
+                        reasons: reasons.into_iter().collect(),
+                    });
+                }
+            }
+
+            let breadth = statement
+                .resource
+                .iter()
+                .map(|resource| resource_breadth(resource))
+                .fold(0.0, f64::max);
+
+            for action in &statement.action {
+                let access_level = self.access_levels.get(action).copied();
+                if let Some(
+                    access_level @ (AccessLevel::Write | AccessLevel::PermissionsManagement),
+                ) = access_level
+                {
+                    findings.push(StatementFinding::SensitiveAccessLevel {
+                        action: action.clone(),
+                        access_level,
+                    });
+                }
+                let weight = access_level_weight(access_level);
+                weighted_breadth += weight * breadth;
+                total_weight += weight;
+            }
+
+            if !findings.is_empty() {
+                statements.push(StatementFindings {
+                    statement_index,
+                    sid: statement.sid.clone(),
+                    findings,
+                });
+            }
+        }
+
+        let breadth_score = if total_weight > 0.0 {
+            (100.0 * weighted_breadth / total_weight).round() as u8
+        } else {
+            0
+        };
+
+        PolicyFindings {
+            breadth_score,
+            statements,
+        }
+    }
+}
+
+/// How much of the resource space a resource covers, from 0.0 (a specific resource)
+/// to 1.0 (all resources)
+fn resource_breadth(resource: &str) -> f64 {
+    if resource == "*" {
+        return 1.0;
+    }
+    if !resource.contains('*') {
+        return 0.0;
+    }
+    // arn:partition:service:region:account:resource
+    match resource.splitn(6, ':').nth(5) {
+        Some(resource_part) if resource_part.contains('*') => 0.5,
+        _ => 0.25,
+    }
+}
+
+/// Weight of an action in the breadth score, so that broad sensitive actions count more
+fn access_level_weight(access_level: Option<AccessLevel>) -> f64 {
+    match access_level {
+        Some(AccessLevel::PermissionsManagement) => 4.0,
+        Some(AccessLevel::Write) => 2.0,
+        Some(AccessLevel::List | AccessLevel::Read | AccessLevel::Tagging) | None => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy_generation::Statement;
+
+    fn policy(statements: Vec<Statement>) -> IamPolicy {
+        let mut policy = IamPolicy::new();
+        for statement in statements {
+            policy.add_statement(statement);
+        }
+        policy
+    }
+
+    #[test]
+    fn test_specific_resources_have_no_findings() {
+        let facts = FindingFacts::default();
+        let findings = facts.policy_findings(&policy(vec![Statement::allow(
+            vec!["s3:GetObject".to_string()],
+            vec!["arn:aws:s3:::my-bucket/key".to_string()],
+        )]));
+
+        assert!(findings.is_empty());
+        assert_eq!(findings.breadth_score, 0);
+    }
+
+    #[test]
+    fn test_wildcard_reasons_are_attached_to_statements() {
+        let mut facts = FindingFacts::default();
+        facts.add_wildcard_reasons(
+            "s3:GetObject",
+            "arn:aws:s3:::*/*",
+            [WildcardReason::UnresolvedParameter],
+        );
+        facts.add_wildcard_reasons(
+            "ec2:DescribeInstances",
+            "*",
+            [WildcardReason::ResourceCutoff],
+        );
+
+        let findings = facts.policy_findings(&policy(vec![
+            Statement::allow(
+                vec!["s3:GetObject".to_string()],
+                vec!["arn:aws:s3:::*/*".to_string()],
+            )
+            .with_sid("AllowS3GetObject".to_string()),
+            Statement::allow(
+                vec!["ec2:DescribeInstances".to_string()],
+                vec!["*".to_string()],
+            ),
+        ]));
+
+        assert_eq!(findings.statements.len(), 2);
+        assert_eq!(
+            findings.statements[0].sid.as_deref(),
+            Some("AllowS3GetObject")
+        );
+        assert_eq!(
+            findings.statements[0].findings,
+            vec![StatementFinding::WildcardResource {
+                resource: "arn:aws:s3:::*/*".to_string(),
+                actions: vec!["s3:GetObject".to_string()],
+                reasons: vec![WildcardReason::UnresolvedParameter],
+            }]
+        );
+        assert_eq!(findings.statements[1].statement_index, 1);
+        assert_eq!(
+            findings.statements[1].findings,
+            vec![StatementFinding::WildcardResource {
+                resource: "*".to_string(),
+                actions: vec!["ec2:DescribeInstances".to_string()],
+                reasons: vec![WildcardReason::ResourceCutoff],
+            }]
+        );
+        // (0.5 + 1.0) / 2
+        assert_eq!(findings.breadth_score, 75);
+    }
+
+    #[test]
+    fn test_bare_wildcard_without_facts_is_not_resource_scoped() {
+        let facts = FindingFacts::default();
+        let findings = facts.policy_findings(&policy(vec![Statement::allow(
+            vec!["sts:GetCallerIdentity".to_string()],
+            vec!["*".to_string()],
+        )]));
+
+        assert_eq!(
+            findings.findings().collect::<Vec<_>>(),
+            vec![&StatementFinding::WildcardResource {
+                resource: "*".to_string(),
+                actions: vec!["sts:GetCallerIdentity".to_string()],
+                reasons: vec![WildcardReason::NotResourceScoped],
+            }]
+        );
+        assert_eq!(findings.breadth_score, 100);
+    }
+
+    #[test]
+    fn test_sensitive_access_levels_are_reported_and_weighted() {
+        let mut facts = FindingFacts::default();
+        facts.set_access_level("iam:PutRolePolicy", AccessLevel::PermissionsManagement);
+        facts.set_access_level("s3:GetObject", AccessLevel::Read);
+
+        let findings = facts.policy_findings(&policy(vec![
+            Statement::allow(
+                vec!["iam:PutRolePolicy".to_string()],
+                vec!["arn:aws:iam::*:role/*".to_string()],
+            ),
+            Statement::allow(
+                vec!["s3:GetObject".to_string()],
+                vec!["arn:aws:s3:::bucket/key".to_string()],
+            ),
+        ]));
+
+        assert_eq!(
+            findings.findings().collect::<Vec<_>>(),
+            vec![&StatementFinding::SensitiveAccessLevel {
+                action: "iam:PutRolePolicy".to_string(),
+                access_level: AccessLevel::PermissionsManagement,
+            }]
+        );
+        // (4 * 0.5 + 1 * 0.0) / 5
+        assert_eq!(findings.breadth_score, 40);
+    }
+
+    #[test]
+    fn test_findings_serialization() {
+        let findings = PolicyFindings {
+            breadth_score: 50,
+            statements: vec![StatementFindings {
+                statement_index: 0,
+                sid: None,
+                findings: vec![
+                    StatementFinding::WildcardResource {
+                        resource: "arn:aws:sqs:*:*:queue".to_string(),
+                        actions: vec!["sqs:SendMessage".to_string()],
+                        reasons: vec![
+                            WildcardReason::UnknownAccount,
+                            WildcardReason::UnknownRegion,
+                        ],
+                    },
+                    StatementFinding::SensitiveAccessLevel {
+                        action: "sqs:SendMessage".to_string(),
+                        access_level: AccessLevel::Write,
+                    },
+                ],
+            }],
+        };
+
+        let json = serde_json::to_value(&findings).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "BreadthScore": 50,
+                "Statements": [{
+                    "StatementIndex": 0,
+                    "Findings": [
+                        {
+                            "Type": "WildcardResource",
+                            "Resource": "arn:aws:sqs:*:*:queue",
+                            "Actions": ["sqs:SendMessage"],
+                            "Reasons": ["UnknownAccount", "UnknownRegion"]
+                        },
+                        {
+                            "Type": "SensitiveAccessLevel",
+                            "Action": "sqs:SendMessage",
+                            "AccessLevel": "Write"
+                        }
+                    ]
+                }]
+            })
+        );
+    }
+}
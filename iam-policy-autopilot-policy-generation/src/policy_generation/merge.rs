@@ -18,7 +18,7 @@ use std::collections::HashSet;
 
 /// IAM managed policy size limit in characters (excluding whitespace)
 /// https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_iam-quotas.html
-const IAM_MANAGED_POLICY_SIZE_LIMIT: usize = 6144;
+pub(crate) const IAM_MANAGED_POLICY_SIZE_LIMIT: usize = 6144;
 
 /// Represents the relationship between two resources
 #[derive(Debug, Clone, PartialEq)]
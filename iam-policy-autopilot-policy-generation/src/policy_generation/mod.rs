@@ -4,17 +4,26 @@
 //! Each EnrichedSdkMethodCall produces one IAM policy, with each Action becoming a separate statement.
 //! ARN patterns are processed to replace placeholder variables with actual values or wildcards.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize, Serializer};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+pub(crate) mod comparison;
 pub(crate) mod engine;
+pub(crate) mod findings;
 pub(crate) mod merge;
 pub(crate) mod utils;
+pub(crate) mod validation;
 
 #[cfg(test)]
 mod integration_tests;
 
+pub use comparison::{
+    BroadenedResource, MissingPermission, NarrowedResource, PolicyComparison, UnexplainedPermission,
+};
 pub use engine::Engine;
+pub use findings::{PolicyFindings, StatementFinding, StatementFindings, WildcardReason};
+pub use validation::{FindingType, IssueCode, PolicyKind, ValidationFinding};
 
 use crate::enrichment::Condition;
 
@@ -43,7 +52,7 @@ where
 }
 
 /// Represents a complete IAM policy document
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, JsonSchema)]
 #[non_exhaustive]
 pub struct IamPolicy {
     /// Policy ID
@@ -58,7 +67,7 @@ pub struct IamPolicy {
 }
 
 /// Represents an individual IAM policy statement
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, JsonSchema)]
 pub struct Statement {
     /// Optional statement identifier
     #[serde(rename = "Sid", skip_serializing_if = "Option::is_none")]
@@ -79,11 +88,12 @@ pub struct Statement {
         skip_serializing_if = "Vec::is_empty",
         serialize_with = "serialize_conditions"
     )]
+    #[schemars(with = "BTreeMap<String, BTreeMap<String, Vec<String>>>")]
     pub(crate) condition: Vec<Condition>,
 }
 
 /// Effect of an IAM policy statement
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub enum Effect {
     /// Allow access
     Allow,
@@ -92,7 +102,7 @@ pub enum Effect {
 }
 
 /// Policy type enumeration
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
 pub enum PolicyType {
     /// Identity-based policy (attached to users, groups, or roles)
     #[default]
@@ -107,6 +117,9 @@ pub struct PolicyWithMetadata {
     pub policy: IamPolicy,
     /// Type of the policy
     pub policy_type: PolicyType,
+    /// Least-privilege findings for the policy, if they were computed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub findings: Option<PolicyFindings>,
 }
 
 impl IamPolicy {
@@ -123,6 +136,21 @@ impl IamPolicy {
     pub fn add_statement(&mut self, statement: Statement) {
         self.statements.push(statement);
     }
+
+    /// Size of the policy in characters, excluding whitespace, as counted against the IAM
+    /// policy size quotas
+    pub fn size(&self) -> usize {
+        serde_json::to_string(self)
+            .map(|json| json.chars().filter(|c| !c.is_whitespace()).count())
+            .unwrap_or_default()
+    }
+
+    /// Actions of all statements of the policy
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.statements
+            .iter()
+            .flat_map(|statement| statement.action.iter().map(String::as_str))
+    }
 }
 
 impl Default for IamPolicy {
@@ -173,6 +201,23 @@ mod tests {
         assert_eq!(policy.statements.len(), 0);
     }
 
+    #[test]
+    fn test_iam_policy_size() {
+        let mut policy = IamPolicy::new();
+        let empty_size = policy.size();
+        assert_eq!(
+            empty_size,
+            r#"{"Id":"IamPolicyAutopilot","Version":"2012-10-17","Statement":[]}"#.len()
+        );
+
+        policy.add_statement(Statement::allow(
+            vec!["s3:GetObject".to_string()],
+            vec!["arn:aws:s3:::my bucket/*".to_string()],
+        ));
+        let json = serde_json::to_string(&policy).unwrap();
+        assert_eq!(policy.size(), json.len() - 1);
+    }
+
     #[test]
     fn test_statement_creation() {
         let statement = Statement::allow(
@@ -271,6 +316,7 @@ mod tests {
         let policy_with_metadata = PolicyWithMetadata {
             policy,
             policy_type: PolicyType::Identity,
+            findings: None,
         };
 
         let json = serde_json::to_string(&policy_with_metadata).unwrap();
@@ -3,8 +3,10 @@
 //! This module provides functionality to parse ARN patterns and replace placeholder variables
 //! with actual values or wildcards. Placeholder variables are in the format ${VariableName}.
 
+use super::findings::WildcardReason;
 use crate::errors::{ExtractorError, Result};
 use regex::{Captures, Regex};
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::OnceLock;
 
 /// Regex pattern to match ARN placeholder variables in the format ${VariableName}
@@ -22,6 +24,7 @@ fn get_placeholder_regex() -> &'static Regex {
 /// - ${partition} or ${Partition} -> provided partition value
 /// - ${region} or ${Region} -> provided region value
 /// - ${account} or ${Account} -> provided account value
+/// - ${...} with a resource binding -> the bound value
 /// - All other ${...} -> "*" (wildcard)
 ///
 /// # Arguments
@@ -29,10 +32,11 @@ fn get_placeholder_regex() -> &'static Regex {
 /// * `partition` - The partition value to substitute
 /// * `region` - The region value to substitute
 /// * `account` - The account value to substitute
+/// * `bindings` - Values for other placeholders, keyed by placeholder name
 ///
 /// # Returns
-/// A tuple containing the processed value and a boolean indicating
-/// whether wildcards were introduced (true if any unknown placeholders were replaced with "*")
+/// A tuple containing the processed value and the reasons for every wildcard introduced
+/// (empty if no placeholder was replaced with "*")
 ///
 /// # Errors
 /// Returns an error if the value contains invalid placeholders (e.g., empty placeholders like ${})
@@ -41,7 +45,8 @@ fn process_placeholder_value(
     partition: &str,
     region: &str,
     account: &str,
-) -> Result<(String, bool)> {
+    bindings: &BTreeMap<String, String>,
+) -> Result<(String, BTreeSet<WildcardReason>)> {
     // Check for empty placeholders like ${}
     if value.contains("${}") {
         return Err(ExtractorError::policy_generation(format!(
@@ -51,7 +56,7 @@ fn process_placeholder_value(
     }
 
     let regex = get_placeholder_regex();
-    let mut wildcards_introduced = false;
+    let mut wildcard_reasons = BTreeSet::new();
 
     let result = regex
         .replace_all(value, |caps: &Captures| {
@@ -60,37 +65,53 @@ fn process_placeholder_value(
                     match placeholder.to_lowercase().as_str() {
                         "partition" => {
                             if partition == "*" {
-                                wildcards_introduced = true;
+                                wildcard_reasons.insert(WildcardReason::UnknownPartition);
                             }
                             partition
                         }
                         "region" => {
                             if region == "*" {
-                                wildcards_introduced = true;
+                                wildcard_reasons.insert(WildcardReason::UnknownRegion);
                             }
                             region
                         }
                         "account" => {
                             if account == "*" {
-                                wildcards_introduced = true;
+                                wildcard_reasons.insert(WildcardReason::UnknownAccount);
                             }
                             account
                         }
-                        _ => {
-                            wildcards_introduced = true;
-                            "*" // All other variables become wildcards
-                        }
+                        _ => match find_binding(bindings, placeholder) {
+                            Some(value) => value,
+                            None => {
+                                wildcard_reasons.insert(WildcardReason::UnresolvedParameter);
+                                "*" // All other variables become wildcards
+                            }
+                        },
                     }
                 }
                 None => {
-                    wildcards_introduced = true;
+                    wildcard_reasons.insert(WildcardReason::UnresolvedParameter);
                     "*" // Fallback (should not happen due to validation)
                 }
             }
         })
         .to_string();
 
-    Ok((result, wildcards_introduced))
+    Ok((result, wildcard_reasons))
+}
+
+/// Find the bound value of a placeholder, preferring an exact match of the name
+fn find_binding<'b>(bindings: &'b BTreeMap<String, String>, placeholder: &str) -> Option<&'b str> {
+    bindings
+        .get(placeholder)
+        .or_else(|| {
+            bindings
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(placeholder))
+                .map(|(_, value)| value)
+        })
+        .map(String::as_str)
 }
 
 /// ARN pattern processor for replacing placeholder variables
@@ -102,6 +123,8 @@ pub(crate) struct ArnParser<'a> {
     region: &'a str,
     /// AWS account number (e.g., "123456789012")
     account: &'a str,
+    /// Values for resource placeholders (e.g., "BucketName" -> "my-bucket")
+    bindings: BTreeMap<String, String>,
 }
 
 impl<'a> ArnParser<'a> {
@@ -111,9 +134,17 @@ impl<'a> ArnParser<'a> {
             partition,
             region,
             account,
+            bindings: BTreeMap::new(),
         }
     }
 
+    /// Use the given values for resource placeholders instead of wildcards
+    #[must_use]
+    pub(crate) fn with_bindings(mut self, bindings: BTreeMap<String, String>) -> Self {
+        self.bindings = bindings;
+        self
+    }
+
     /// Process an ARN pattern by replacing placeholder variables
     ///
     /// Replaces the following placeholders (case-insensitive):
@@ -131,11 +162,31 @@ impl<'a> ArnParser<'a> {
     /// # Errors
     /// Returns an error if the pattern contains invalid placeholders (e.g., empty placeholders like ${})
     pub(crate) fn process_arn_pattern(&self, pattern: &str) -> Result<String> {
-        let (result, _wildcards_introduced) =
-            process_placeholder_value(pattern, self.partition, self.region, self.account)?;
+        let (result, _wildcard_reasons) = self.process_arn_pattern_with_reasons(pattern)?;
         Ok(result)
     }
 
+    /// Process an ARN pattern and report why wildcards were introduced
+    ///
+    /// # Returns
+    /// A tuple containing the processed ARN pattern and the reasons for every wildcard
+    /// introduced while replacing placeholders
+    ///
+    /// # Errors
+    /// Returns an error if the pattern contains invalid placeholders (e.g., empty placeholders like ${})
+    pub(crate) fn process_arn_pattern_with_reasons(
+        &self,
+        pattern: &str,
+    ) -> Result<(String, BTreeSet<WildcardReason>)> {
+        process_placeholder_value(
+            pattern,
+            self.partition,
+            self.region,
+            self.account,
+            &self.bindings,
+        )
+    }
+
     /// Process multiple ARN patterns
     ///
     /// # Arguments
@@ -163,6 +214,8 @@ pub(crate) struct ConditionValueProcessor<'a> {
     region: &'a str,
     /// AWS account number (e.g., "123456789012")
     account: &'a str,
+    /// Values for resource placeholders (e.g., "BucketName" -> "my-bucket")
+    bindings: BTreeMap<String, String>,
 }
 
 impl<'a> ConditionValueProcessor<'a> {
@@ -172,9 +225,17 @@ impl<'a> ConditionValueProcessor<'a> {
             partition,
             region,
             account,
+            bindings: BTreeMap::new(),
         }
     }
 
+    /// Use the given values for resource placeholders instead of wildcards
+    #[must_use]
+    pub(crate) fn with_bindings(mut self, bindings: BTreeMap<String, String>) -> Self {
+        self.bindings = bindings;
+        self
+    }
+
     /// Process a condition value by replacing placeholder variables
     ///
     /// Replaces the following placeholders (case-insensitive):
@@ -193,7 +254,14 @@ impl<'a> ConditionValueProcessor<'a> {
     /// # Errors
     /// Returns an error if the value contains invalid placeholders (e.g., empty placeholders like ${})
     pub(crate) fn process_condition_value(&self, value: &str) -> Result<(String, bool)> {
-        process_placeholder_value(value, self.partition, self.region, self.account)
+        let (result, wildcard_reasons) = process_placeholder_value(
+            value,
+            self.partition,
+            self.region,
+            self.account,
+            &self.bindings,
+        )?;
+        Ok((result, !wildcard_reasons.is_empty()))
     }
 
     /// Process multiple condition values
@@ -642,4 +710,61 @@ mod tests {
             .unwrap();
         assert_eq!(result, "arn:aws:s3:us-east-1:*:bucket/*");
     }
+
+    #[test]
+    fn test_process_arn_pattern_with_reasons() {
+        let pattern = "arn:${Partition}:s3:${Region}:${Account}:bucket/${BucketName}";
+
+        let (result, reasons) = create_test_parser()
+            .process_arn_pattern_with_reasons(pattern)
+            .unwrap();
+        assert_eq!(result, "arn:aws:s3:us-east-1:123456789012:bucket/*");
+        assert_eq!(
+            reasons.into_iter().collect::<Vec<_>>(),
+            vec![WildcardReason::UnresolvedParameter]
+        );
+
+        let (result, reasons) = ArnParser::new("aws", "*", "*")
+            .process_arn_pattern_with_reasons("arn:${Partition}:sqs:${Region}:${Account}:queue")
+            .unwrap();
+        assert_eq!(result, "arn:aws:sqs:*:*:queue");
+        assert_eq!(
+            reasons.into_iter().collect::<Vec<_>>(),
+            vec![
+                WildcardReason::UnknownAccount,
+                WildcardReason::UnknownRegion
+            ]
+        );
+
+        let (_, reasons) = create_test_parser()
+            .process_arn_pattern_with_reasons("arn:aws:s3:::my-bucket")
+            .unwrap();
+        assert!(reasons.is_empty());
+    }
+
+    #[test]
+    fn test_process_arn_pattern_with_bindings() {
+        let parser = create_test_parser().with_bindings(BTreeMap::from([
+            ("BucketName".to_string(), "my-bucket".to_string()),
+            ("queuename".to_string(), "orders".to_string()),
+        ]));
+
+        let (result, reasons) = parser
+            .process_arn_pattern_with_reasons("arn:${Partition}:s3:::${BucketName}/${ObjectName}")
+            .unwrap();
+        assert_eq!(result, "arn:aws:s3:::my-bucket/*");
+        assert_eq!(
+            reasons.into_iter().collect::<Vec<_>>(),
+            vec![WildcardReason::UnresolvedParameter]
+        );
+
+        // Bindings match placeholder names case-insensitively
+        let (result, reasons) = parser
+            .process_arn_pattern_with_reasons(
+                "arn:${Partition}:sqs:${Region}:${Account}:${QueueName}",
+            )
+            .unwrap();
+        assert_eq!(result, "arn:aws:sqs:us-east-1:123456789012:orders");
+        assert!(reasons.is_empty());
+    }
 }
@@ -0,0 +1,1436 @@
+//! Offline validation of IAM policy documents
+//!
+//! This module checks identity-based policy documents for problems that IAM would
+//! reject or that usually indicate a mistake, similar in spirit to IAM Access Analyzer's
+//! policy checks. Validation is purely local: action names, resource types and condition
+//! keys are checked against service reference data obtained through the
+//! [`ServiceReferenceCache`], which serves cached data whenever it is available.
+//!
+//! Checks are split into two phases: [`collect_service_prefixes`] and
+//! [`load_service_references`] gather the service reference data required by the
+//! documents, then [`validate_documents`] runs all checks synchronously.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use log::debug;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::enrichment::service_reference::ServiceReference;
+use crate::enrichment::ServiceReferenceCache;
+use crate::policy_generation::merge::IAM_MANAGED_POLICY_SIZE_LIMIT;
+
+/// Maximum aggregate size of all inline policies attached to a single IAM role
+/// https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_iam-quotas.html
+pub(crate) const IAM_ROLE_INLINE_POLICY_SIZE_LIMIT: usize = 10240;
+
+/// Default number of managed policies that can be attached to a single IAM role
+pub(crate) const IAM_ROLE_MANAGED_POLICY_ATTACHMENT_LIMIT: usize = 10;
+
+const SUPPORTED_POLICY_VERSION: &str = "2012-10-17";
+const LEGACY_POLICY_VERSION: &str = "2008-10-17";
+
+const POLICY_ELEMENTS: &[&str] = &["Version", "Id", "Statement"];
+const STATEMENT_ELEMENTS: &[&str] = &[
+    "Sid",
+    "Effect",
+    "Action",
+    "NotAction",
+    "Resource",
+    "NotResource",
+    "Condition",
+    "Principal",
+    "NotPrincipal",
+];
+
+const CONDITION_OPERATORS: &[&str] = &[
+    "StringEquals",
+    "StringNotEquals",
+    "StringEqualsIgnoreCase",
+    "StringNotEqualsIgnoreCase",
+    "StringLike",
+    "StringNotLike",
+    "NumericEquals",
+    "NumericNotEquals",
+    "NumericLessThan",
+    "NumericLessThanEquals",
+    "NumericGreaterThan",
+    "NumericGreaterThanEquals",
+    "DateEquals",
+    "DateNotEquals",
+    "DateLessThan",
+    "DateLessThanEquals",
+    "DateGreaterThan",
+    "DateGreaterThanEquals",
+    "Bool",
+    "BinaryEquals",
+    "IpAddress",
+    "NotIpAddress",
+    "ArnEquals",
+    "ArnLike",
+    "ArnNotEquals",
+    "ArnNotLike",
+    "Null",
+];
+
+/// Kind of policy being validated, which determines the applicable size quotas
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyKind {
+    /// Customer managed policies, each attached separately to the role
+    #[default]
+    Managed,
+    /// Inline policies embedded in a single role
+    Inline,
+}
+
+/// Severity of a validation finding, following IAM Access Analyzer's finding types
+#[derive(
+    Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FindingType {
+    /// The policy would be rejected by IAM or does not work as written
+    Error,
+    /// The policy grants access that is broader than it likely should be
+    SecurityWarning,
+    /// The policy does not conform to best practices or contains ineffective parts
+    Warning,
+    /// The policy could be improved without changing its effect
+    Suggestion,
+}
+
+/// Identifies the check that produced a validation finding
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum IssueCode {
+    /// The document is not valid JSON
+    InvalidJson,
+    /// An element has the wrong JSON type
+    MalformedElement,
+    /// An element is not supported in identity-based policies
+    UnsupportedElement,
+    /// The `Version` element is missing
+    MissingVersion,
+    /// The `Version` element has an unknown value
+    InvalidVersion,
+    /// The `Version` element uses the legacy 2008-10-17 policy language
+    LegacyVersion,
+    /// The `Statement` element is missing or empty
+    MissingStatement,
+    /// The `Effect` element is missing or not `Allow`/`Deny`
+    InvalidEffect,
+    /// Neither `Action` nor `NotAction` is present
+    MissingAction,
+    /// Neither `Resource` nor `NotResource` is present
+    MissingResource,
+    /// Mutually exclusive elements such as `Action` and `NotAction` are both present
+    ConflictingElements,
+    /// The `Sid` element contains characters other than ASCII letters and digits
+    InvalidSid,
+    /// The same `Sid` is used by more than one statement in a policy
+    DuplicateSid,
+    /// An action is not in the `service:action` format
+    InvalidActionFormat,
+    /// The service prefix of an action is not known
+    UnknownService,
+    /// The action does not exist in the service reference
+    InvalidAction,
+    /// Service reference data could not be loaded, so service checks were skipped
+    ServiceReferenceUnavailable,
+    /// A resource is neither `*` nor an ARN
+    InvalidArn,
+    /// An action does not apply to any resource in the statement
+    ResourceArnMismatch,
+    /// A condition operator is not recognized
+    InvalidConditionOperator,
+    /// A condition key is not supported by any action in the statement
+    UnsupportedConditionKey,
+    /// A managed policy exceeds the managed policy size quota
+    PolicySizeQuotaExceeded,
+    /// The inline policies exceed the aggregate inline policy size quota of a role
+    InlinePolicyQuotaExceeded,
+    /// More managed policies than can be attached to a role by default
+    ManagedPolicyAttachmentQuotaExceeded,
+    /// `Allow` is combined with `NotAction`
+    AllowWithNotAction,
+    /// `Allow` is combined with `NotResource`
+    AllowWithNotResource,
+    /// `Allow` grants every action of every service
+    FullWildcardAction,
+    /// `Allow` grants every action of a service
+    ServiceWildcardAction,
+}
+
+impl IssueCode {
+    /// The finding type reported for this issue
+    pub fn finding_type(self) -> FindingType {
+        match self {
+            Self::InvalidJson
+            | Self::MalformedElement
+            | Self::UnsupportedElement
+            | Self::InvalidVersion
+            | Self::MissingStatement
+            | Self::InvalidEffect
+            | Self::MissingAction
+            | Self::MissingResource
+            | Self::ConflictingElements
+            | Self::InvalidSid
+            | Self::DuplicateSid
+            | Self::InvalidActionFormat
+            | Self::UnknownService
+            | Self::InvalidAction
+            | Self::InvalidArn
+            | Self::InvalidConditionOperator
+            | Self::PolicySizeQuotaExceeded
+            | Self::InlinePolicyQuotaExceeded => FindingType::Error,
+            Self::AllowWithNotAction | Self::AllowWithNotResource | Self::FullWildcardAction => {
+                FindingType::SecurityWarning
+            }
+            Self::MissingVersion
+            | Self::LegacyVersion
+            | Self::ResourceArnMismatch
+            | Self::UnsupportedConditionKey
+            | Self::ManagedPolicyAttachmentQuotaExceeded
+            | Self::ServiceReferenceUnavailable
+            | Self::ServiceWildcardAction => FindingType::Warning,
+        }
+    }
+}
+
+/// A single problem found while validating policy documents
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ValidationFinding {
+    /// Severity of the finding
+    pub finding_type: FindingType,
+    /// Check that produced the finding
+    pub issue_code: IssueCode,
+    /// Human readable description of the problem
+    pub message: String,
+    /// Index of the policy the finding applies to, absent for findings about the whole set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_index: Option<usize>,
+    /// Index of the statement within the policy the finding applies to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statement_index: Option<usize>,
+    /// Sid of the statement the finding applies to, if it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+}
+
+/// Outcome of looking up a service prefix in the service reference
+#[derive(Debug, Clone)]
+pub(crate) enum ServiceLookup {
+    Found(Box<ServiceReference>),
+    Unknown,
+    Unavailable(String),
+}
+
+/// Collect the service prefixes of all actions referenced in the given documents
+pub(crate) fn collect_service_prefixes(documents: &[&str]) -> BTreeSet<String> {
+    let mut prefixes = BTreeSet::new();
+    for document in documents {
+        let Ok(Value::Object(policy)) = serde_json::from_str::<Value>(document) else {
+            continue;
+        };
+        let statements = match policy.get("Statement") {
+            Some(Value::Array(statements)) => statements.iter().collect::<Vec<_>>(),
+            Some(statement) => vec![statement],
+            None => continue,
+        };
+        for statement in statements.into_iter().filter_map(Value::as_object) {
+            for element in ["Action", "NotAction"] {
+                let Some(Ok(actions)) = statement.get(element).map(string_or_array) else {
+                    continue;
+                };
+                for action in actions {
+                    if let Some((prefix, _)) = action.split_once(':') {
+                        if is_valid_service_prefix(prefix) {
+                            prefixes.insert(prefix.to_lowercase());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    prefixes
+}
+
+/// Load the service reference for every prefix, recording failures instead of aborting
+pub(crate) async fn load_service_references(
+    loader: &ServiceReferenceCache,
+    prefixes: BTreeSet<String>,
+) -> HashMap<String, ServiceLookup> {
+    let mut services = HashMap::new();
+    for prefix in prefixes {
+        let lookup = match loader.load(&prefix).await {
+            Ok(Some(service_reference)) => ServiceLookup::Found(Box::new(service_reference)),
+            Ok(None) => ServiceLookup::Unknown,
+            Err(e) => {
+                debug!("Failed to load service reference for {}: {}", prefix, e);
+                ServiceLookup::Unavailable(e.to_string())
+            }
+        };
+        services.insert(prefix, lookup);
+    }
+    services
+}
+
+/// Validate policy documents against the IAM policy grammar, quotas and service reference data
+pub(crate) fn validate_documents(
+    documents: &[&str],
+    kind: PolicyKind,
+    services: &HashMap<String, ServiceLookup>,
+) -> Vec<ValidationFinding> {
+    let mut findings = Vec::new();
+    let mut total_size = 0;
+    let mut reported_services = HashSet::new();
+
+    for (policy_index, document) in documents.iter().enumerate() {
+        let mut validator = DocumentValidator {
+            policy_index,
+            services,
+            reported_services: &mut reported_services,
+            findings: &mut findings,
+        };
+        if let Some(size) = validator.validate(document) {
+            if kind == PolicyKind::Managed && size > IAM_MANAGED_POLICY_SIZE_LIMIT {
+                validator.report(
+                    IssueCode::PolicySizeQuotaExceeded,
+                    format!(
+                        "Policy is {} characters long, which exceeds the managed policy quota of {} characters",
+                        size, IAM_MANAGED_POLICY_SIZE_LIMIT
+                    ),
+                    None,
+                );
+            }
+            total_size += size;
+        }
+    }
+
+    match kind {
+        PolicyKind::Inline if total_size > IAM_ROLE_INLINE_POLICY_SIZE_LIMIT => {
+            findings.push(set_finding(
+                IssueCode::InlinePolicyQuotaExceeded,
+                format!(
+                    "Inline policies total {} characters, which exceeds the role quota of {} characters",
+                    total_size, IAM_ROLE_INLINE_POLICY_SIZE_LIMIT
+                ),
+            ));
+        }
+        PolicyKind::Managed if documents.len() > IAM_ROLE_MANAGED_POLICY_ATTACHMENT_LIMIT => {
+            findings.push(set_finding(
+                IssueCode::ManagedPolicyAttachmentQuotaExceeded,
+                format!(
+                    "{} managed policies exceed the default quota of {} policies attached to a role",
+                    documents.len(),
+                    IAM_ROLE_MANAGED_POLICY_ATTACHMENT_LIMIT
+                ),
+            ));
+        }
+        _ => {}
+    }
+
+    findings
+}
+
+fn set_finding(issue_code: IssueCode, message: String) -> ValidationFinding {
+    ValidationFinding {
+        finding_type: issue_code.finding_type(),
+        issue_code,
+        message,
+        policy_index: None,
+        statement_index: None,
+        sid: None,
+    }
+}
+
+/// Location of the statement currently being validated
+#[derive(Clone, Copy)]
+struct StatementRef<'s> {
+    index: usize,
+    sid: Option<&'s str>,
+}
+
+struct DocumentValidator<'v> {
+    policy_index: usize,
+    services: &'v HashMap<String, ServiceLookup>,
+    reported_services: &'v mut HashSet<String>,
+    findings: &'v mut Vec<ValidationFinding>,
+}
+
+impl<'v> DocumentValidator<'v> {
+    fn report(&mut self, issue_code: IssueCode, message: String, statement: Option<StatementRef>) {
+        self.findings.push(ValidationFinding {
+            finding_type: issue_code.finding_type(),
+            issue_code,
+            message,
+            policy_index: Some(self.policy_index),
+            statement_index: statement.map(|s| s.index),
+            sid: statement.and_then(|s| s.sid).map(str::to_string),
+        });
+    }
+
+    /// Validate a single document, returning its size if it could be parsed
+    fn validate(&mut self, document: &str) -> Option<usize> {
+        let value = match serde_json::from_str::<Value>(document) {
+            Ok(value) => value,
+            Err(e) => {
+                self.report(
+                    IssueCode::InvalidJson,
+                    format!("Policy is not valid JSON: {}", e),
+                    None,
+                );
+                return None;
+            }
+        };
+        // Same measure as the policy merger: non-whitespace characters of the serialized JSON
+        let size = value
+            .to_string()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .count();
+
+        let Value::Object(policy) = value else {
+            self.report(
+                IssueCode::MalformedElement,
+                "Policy document must be a JSON object".to_string(),
+                None,
+            );
+            return Some(size);
+        };
+
+        for key in policy.keys() {
+            if !POLICY_ELEMENTS.contains(&key.as_str()) {
+                self.report(
+                    IssueCode::UnsupportedElement,
+                    format!("Unsupported policy element '{}'", key),
+                    None,
+                );
+            }
+        }
+
+        self.validate_version(policy.get("Version"));
+
+        let statements = match policy.get("Statement") {
+            Some(Value::Array(statements)) if !statements.is_empty() => {
+                statements.iter().collect::<Vec<_>>()
+            }
+            Some(Value::Object(_)) => vec![&policy["Statement"]],
+            Some(Value::Array(_)) | None => {
+                self.report(
+                    IssueCode::MissingStatement,
+                    "Policy must contain at least one statement".to_string(),
+                    None,
+                );
+                return Some(size);
+            }
+            Some(_) => {
+                self.report(
+                    IssueCode::MalformedElement,
+                    "Statement must be an object or an array of objects".to_string(),
+                    None,
+                );
+                return Some(size);
+            }
+        };
+
+        let mut seen_sids = HashSet::new();
+        for (index, statement) in statements.into_iter().enumerate() {
+            let Value::Object(statement) = statement else {
+                self.report(
+                    IssueCode::MalformedElement,
+                    "Statement must be a JSON object".to_string(),
+                    Some(StatementRef { index, sid: None }),
+                );
+                continue;
+            };
+            self.validate_statement(index, statement, &mut seen_sids);
+        }
+
+        Some(size)
+    }
+
+    fn validate_version(&mut self, version: Option<&Value>) {
+        match version {
+            None => self.report(
+                IssueCode::MissingVersion,
+                format!(
+                    "Policy has no Version element; IAM defaults to {}, which does not support policy variables",
+                    LEGACY_POLICY_VERSION
+                ),
+                None,
+            ),
+            Some(Value::String(v)) if v == SUPPORTED_POLICY_VERSION => {}
+            Some(Value::String(v)) if v == LEGACY_POLICY_VERSION => self.report(
+                IssueCode::LegacyVersion,
+                format!(
+                    "Policy uses the legacy version {}; use {} instead",
+                    LEGACY_POLICY_VERSION, SUPPORTED_POLICY_VERSION
+                ),
+                None,
+            ),
+            Some(other) => self.report(
+                IssueCode::InvalidVersion,
+                format!(
+                    "Invalid policy version {}; expected \"{}\"",
+                    other, SUPPORTED_POLICY_VERSION
+                ),
+                None,
+            ),
+        }
+    }
+
+    fn validate_statement(
+        &mut self,
+        index: usize,
+        statement: &Map<String, Value>,
+        seen_sids: &mut HashSet<String>,
+    ) {
+        let sid = statement.get("Sid").and_then(Value::as_str);
+        let at = Some(StatementRef { index, sid });
+
+        for key in statement.keys() {
+            if !STATEMENT_ELEMENTS.contains(&key.as_str()) {
+                self.report(
+                    IssueCode::UnsupportedElement,
+                    format!("Unsupported statement element '{}'", key),
+                    at,
+                );
+            } else if key == "Principal" || key == "NotPrincipal" {
+                self.report(
+                    IssueCode::UnsupportedElement,
+                    format!("{} is not supported in identity-based policies", key),
+                    at,
+                );
+            }
+        }
+
+        match statement.get("Sid") {
+            None => {}
+            Some(Value::String(sid)) => {
+                if !sid.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    self.report(
+                        IssueCode::InvalidSid,
+                        format!("Sid '{}' may only contain ASCII letters and digits", sid),
+                        at,
+                    );
+                }
+                if !seen_sids.insert(sid.clone()) {
+                    self.report(
+                        IssueCode::DuplicateSid,
+                        format!("Sid '{}' is used by more than one statement", sid),
+                        at,
+                    );
+                }
+            }
+            Some(_) => self.report(
+                IssueCode::MalformedElement,
+                "Sid must be a string".to_string(),
+                at,
+            ),
+        }
+
+        let allow = match statement.get("Effect").and_then(Value::as_str) {
+            Some("Allow") => true,
+            Some("Deny") => false,
+            _ => {
+                self.report(
+                    IssueCode::InvalidEffect,
+                    "Effect must be either \"Allow\" or \"Deny\"".to_string(),
+                    at,
+                );
+                false
+            }
+        };
+
+        let actions = self.exclusive_pair(statement, "Action", "NotAction", at);
+        let resources = self.exclusive_pair(statement, "Resource", "NotResource", at);
+
+        let Some((action_element, actions)) = actions else {
+            if !statement.contains_key("Action") && !statement.contains_key("NotAction") {
+                self.report(
+                    IssueCode::MissingAction,
+                    "Statement must contain Action or NotAction".to_string(),
+                    at,
+                );
+            }
+            return;
+        };
+        if resources.is_none()
+            && !statement.contains_key("Resource")
+            && !statement.contains_key("NotResource")
+        {
+            self.report(
+                IssueCode::MissingResource,
+                "Statement must contain Resource or NotResource".to_string(),
+                at,
+            );
+        }
+
+        if allow && action_element == "NotAction" {
+            self.report(
+                IssueCode::AllowWithNotAction,
+                "Allow with NotAction grants every action that is not listed, including actions added to AWS in the future".to_string(),
+                at,
+            );
+        }
+        if allow && matches!(resources, Some(("NotResource", _))) {
+            self.report(
+                IssueCode::AllowWithNotResource,
+                "Allow with NotResource grants access to every resource that is not listed"
+                    .to_string(),
+                at,
+            );
+        }
+
+        let resolved = self.validate_actions(&actions, allow && action_element == "Action", at);
+
+        if let Some((resource_element, resources)) = &resources {
+            let valid_resources = self.validate_resources(resources, at);
+            if action_element == "Action" && *resource_element == "Resource" {
+                self.check_resource_types(&resolved, &valid_resources, at);
+            }
+        }
+
+        match statement.get("Condition") {
+            None => {}
+            Some(Value::Object(condition)) => {
+                let resolved = if action_element == "Action" {
+                    Some(resolved.as_slice())
+                } else {
+                    None
+                };
+                self.validate_condition(condition, resolved, at);
+            }
+            Some(_) => self.report(
+                IssueCode::MalformedElement,
+                "Condition must be a JSON object".to_string(),
+                at,
+            ),
+        }
+    }
+
+    /// Read one of two mutually exclusive elements, reporting conflicts and malformed values
+    fn exclusive_pair(
+        &mut self,
+        statement: &Map<String, Value>,
+        element: &'static str,
+        not_element: &'static str,
+        at: Option<StatementRef>,
+    ) -> Option<(&'static str, Vec<String>)> {
+        let present = match (statement.get(element), statement.get(not_element)) {
+            (Some(_), Some(_)) => {
+                self.report(
+                    IssueCode::ConflictingElements,
+                    format!(
+                        "Statement cannot contain both {} and {}",
+                        element, not_element
+                    ),
+                    at,
+                );
+                return None;
+            }
+            (Some(value), None) => (element, value),
+            (None, Some(value)) => (not_element, value),
+            (None, None) => return None,
+        };
+
+        match string_or_array(present.1) {
+            Ok(values) if !values.is_empty() => Some((present.0, values)),
+            Ok(_) => None,
+            Err(()) => {
+                self.report(
+                    IssueCode::MalformedElement,
+                    format!("{} must be a string or an array of strings", present.0),
+                    at,
+                );
+                None
+            }
+        }
+    }
+
+    /// Validate action names and return the service reference actions each one resolves to
+    fn validate_actions(
+        &mut self,
+        actions: &[String],
+        check_wildcards: bool,
+        at: Option<StatementRef>,
+    ) -> Vec<ResolvedAction<'v>> {
+        let services = self.services;
+        let mut resolved = Vec::new();
+        for action in actions {
+            // Actions that cannot be resolved still take part, so that resource and
+            // condition key checks are skipped rather than reporting false positives
+            resolved.push(ResolvedAction {
+                action: action.clone(),
+                service_reference: None,
+                actions: Vec::new(),
+                is_pattern: true,
+            });
+            if action == "*" {
+                if check_wildcards {
+                    self.report(
+                        IssueCode::FullWildcardAction,
+                        "Action \"*\" grants every action of every AWS service".to_string(),
+                        at,
+                    );
+                }
+                continue;
+            }
+            let Some((prefix, name)) = action.split_once(':').filter(|(prefix, name)| {
+                is_valid_service_prefix(prefix) && is_valid_action_name(name)
+            }) else {
+                self.report(
+                    IssueCode::InvalidActionFormat,
+                    format!("Action '{}' is not in the format service:action", action),
+                    at,
+                );
+                continue;
+            };
+            if check_wildcards && name == "*" {
+                self.report(
+                    IssueCode::ServiceWildcardAction,
+                    format!(
+                        "Action '{}' grants every action of the {} service",
+                        action, prefix
+                    ),
+                    at,
+                );
+            }
+
+            let prefix = prefix.to_lowercase();
+            match services.get(&prefix) {
+                Some(ServiceLookup::Found(service_reference)) => {
+                    let matched = service_reference
+                        .actions
+                        .values()
+                        .filter(|a| wildcard_matches(name, &a.name))
+                        .collect::<Vec<_>>();
+                    if matched.is_empty() {
+                        self.report(
+                            IssueCode::InvalidAction,
+                            format!(
+                                "Action '{}' does not match any action of the {} service",
+                                action, prefix
+                            ),
+                            at,
+                        );
+                    }
+                    if let Some(last) = resolved.last_mut() {
+                        last.service_reference = Some(service_reference);
+                        last.actions = matched;
+                        last.is_pattern = name.contains(['*', '?']);
+                    }
+                }
+                Some(ServiceLookup::Unknown) => self.report(
+                    IssueCode::UnknownService,
+                    format!(
+                        "Service prefix '{}' in action '{}' is not known",
+                        prefix, action
+                    ),
+                    at,
+                ),
+                // Only report an unavailable service once across all policies
+                Some(ServiceLookup::Unavailable(reason))
+                    if self.reported_services.insert(prefix.clone()) =>
+                {
+                    self.report(
+                        IssueCode::ServiceReferenceUnavailable,
+                        format!(
+                            "Service reference for '{}' could not be loaded, skipping action, resource and condition key checks for it: {}",
+                            prefix, reason
+                        ),
+                        at,
+                    );
+                }
+                Some(ServiceLookup::Unavailable(_)) | None => {}
+            }
+        }
+        resolved
+    }
+
+    /// Validate resource syntax and return the resources usable for resource type matching
+    fn validate_resources<'r>(
+        &mut self,
+        resources: &'r [String],
+        at: Option<StatementRef>,
+    ) -> Vec<&'r str> {
+        let mut valid = Vec::new();
+        for resource in resources {
+            if resource == "*" || is_arn(resource) {
+                valid.push(resource.as_str());
+            } else {
+                self.report(
+                    IssueCode::InvalidArn,
+                    format!("Resource '{}' must be \"*\" or an ARN", resource),
+                    at,
+                );
+            }
+        }
+        valid
+    }
+
+    /// Report actions that cannot apply to any resource in the statement
+    fn check_resource_types(
+        &mut self,
+        resolved: &[ResolvedAction],
+        resources: &[&str],
+        at: Option<StatementRef>,
+    ) {
+        if resources.is_empty() || resources.contains(&"*") {
+            return;
+        }
+        for resolved_action in resolved {
+            // Wildcard actions only need one of the matched actions to apply
+            let applies = resolved_action.actions.is_empty()
+                || resolved_action.actions.iter().any(|action| {
+                    resources.iter().any(|resource| {
+                        action.resources.iter().any(|resource_type| {
+                            resolved_action
+                                .service_reference
+                                .and_then(|service_reference| {
+                                    service_reference.resources.get(resource_type)
+                                })
+                                .into_iter()
+                                .flatten()
+                                .any(|arn_format| arn_patterns_overlap(resource, arn_format))
+                        })
+                    })
+                });
+            if !applies {
+                let message = if !resolved_action.is_pattern
+                    && resolved_action
+                        .actions
+                        .iter()
+                        .all(|a| a.resources.is_empty())
+                {
+                    format!(
+                        "Action '{}' does not support resource-level permissions and requires Resource \"*\"",
+                        resolved_action.action
+                    )
+                } else {
+                    format!(
+                        "Action '{}' does not apply to any resource in the statement",
+                        resolved_action.action
+                    )
+                };
+                self.report(IssueCode::ResourceArnMismatch, message, at);
+            }
+        }
+    }
+
+    fn validate_condition(
+        &mut self,
+        condition: &Map<String, Value>,
+        resolved: Option<&[ResolvedAction]>,
+        at: Option<StatementRef>,
+    ) {
+        for (operator, keys) in condition {
+            if !is_valid_condition_operator(operator) {
+                self.report(
+                    IssueCode::InvalidConditionOperator,
+                    format!("Unknown condition operator '{}'", operator),
+                    at,
+                );
+            }
+            let Value::Object(keys) = keys else {
+                self.report(
+                    IssueCode::MalformedElement,
+                    format!(
+                        "Condition operator '{}' must map to a JSON object",
+                        operator
+                    ),
+                    at,
+                );
+                continue;
+            };
+            for (key, values) in keys {
+                if !is_valid_condition_value(values) {
+                    self.report(
+                        IssueCode::MalformedElement,
+                        format!(
+                            "Values of condition key '{}' must be scalars or an array of scalars",
+                            key
+                        ),
+                        at,
+                    );
+                }
+                if let Some(resolved) = resolved {
+                    self.check_condition_key(key, resolved, at);
+                }
+            }
+        }
+    }
+
+    fn check_condition_key(
+        &mut self,
+        key: &str,
+        resolved: &[ResolvedAction],
+        at: Option<StatementRef>,
+    ) {
+        let Some((prefix, _)) = key.split_once(':') else {
+            self.report(
+                IssueCode::UnsupportedConditionKey,
+                format!("Condition key '{}' is not in the format service:key", key),
+                at,
+            );
+            return;
+        };
+        // Global condition keys apply to every action
+        if prefix.eq_ignore_ascii_case("aws") {
+            return;
+        }
+        // Only judge keys when every action in the statement could be resolved
+        if resolved.is_empty() || resolved.iter().any(|r| r.actions.is_empty()) {
+            return;
+        }
+        let supported = resolved.iter().any(|r| {
+            r.actions.iter().any(|action| {
+                action
+                    .condition_keys
+                    .iter()
+                    .chain(
+                        action
+                            .resources
+                            .iter()
+                            .filter_map(|resource| {
+                                r.service_reference?.resource_condition_keys.get(resource)
+                            })
+                            .flatten(),
+                    )
+                    .any(|supported_key| condition_key_matches(supported_key, key))
+            })
+        });
+        if !supported {
+            self.report(
+                IssueCode::UnsupportedConditionKey,
+                format!(
+                    "Condition key '{}' is not supported by any action in the statement",
+                    key
+                ),
+                at,
+            );
+        }
+    }
+}
+
+/// An action from the policy together with the service reference actions it matches
+struct ResolvedAction<'s> {
+    action: String,
+    service_reference: Option<&'s ServiceReference>,
+    actions: Vec<&'s crate::enrichment::service_reference::Action>,
+    is_pattern: bool,
+}
+
+pub(crate) fn string_or_array(value: &Value) -> Result<Vec<String>, ()> {
+    match value {
+        Value::String(s) => Ok(vec![s.clone()]),
+        Value::Array(values) => values
+            .iter()
+            .map(|v| v.as_str().map(str::to_string).ok_or(()))
+            .collect(),
+        _ => Err(()),
+    }
+}
+
+fn is_valid_service_prefix(prefix: &str) -> bool {
+    !prefix.is_empty()
+        && prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn is_valid_action_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '*' || c == '?' || c == '-' || c == '_')
+}
+
+fn is_arn(resource: &str) -> bool {
+    let parts = resource.splitn(6, ':').collect::<Vec<_>>();
+    parts.len() == 6 && parts[0] == "arn" && !parts[2].is_empty() && !parts[5].is_empty()
+}
+
+fn is_valid_condition_operator(operator: &str) -> bool {
+    let operator = operator
+        .strip_prefix("ForAllValues:")
+        .or_else(|| operator.strip_prefix("ForAnyValue:"))
+        .unwrap_or(operator);
+    let (operator, if_exists) = match operator.strip_suffix("IfExists") {
+        Some(operator) => (operator, true),
+        None => (operator, false),
+    };
+    CONDITION_OPERATORS.contains(&operator) && !(if_exists && operator == "Null")
+}
+
+fn is_valid_condition_value(value: &Value) -> bool {
+    match value {
+        Value::String(_) | Value::Bool(_) | Value::Number(_) => true,
+        Value::Array(values) => values
+            .iter()
+            .all(|v| matches!(v, Value::String(_) | Value::Bool(_) | Value::Number(_))),
+        _ => false,
+    }
+}
+
+/// Match a condition key against a supported key, where the supported key may end
+/// in a template such as `s3:RequestObjectTag/${TagKey}`
+fn condition_key_matches(supported: &str, key: &str) -> bool {
+    match supported.split_once('/') {
+        Some((supported_base, _)) => key
+            .split_once('/')
+            .is_some_and(|(key_base, _)| key_base.eq_ignore_ascii_case(supported_base)),
+        None => supported.eq_ignore_ascii_case(key),
+    }
+}
+
+/// Case-insensitive match of an action name against a pattern using `*` and `?` wildcards
+pub(crate) fn wildcard_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern
+        .chars()
+        .map(|c| match c {
+            '*' => Token::Any,
+            '?' => Token::One,
+            c => Token::Char(c.to_ascii_lowercase()),
+        })
+        .collect::<Vec<_>>();
+    let name = name
+        .chars()
+        .map(|c| Token::Char(c.to_ascii_lowercase()))
+        .collect::<Vec<_>>();
+    patterns_overlap(&pattern, &name)
+}
+
+/// Whether a policy resource and an ARN format from the service reference can match a
+/// common ARN. The partition, service, region and account segments are compared
+/// separately, so wildcards cannot stretch across them. Wildcards and policy variables
+/// in the resource, as well as placeholders in the ARN format, are treated as matching
+/// any sequence of characters.
+fn arn_patterns_overlap(resource: &str, arn_format: &str) -> bool {
+    let resource_segments = resource.splitn(6, ':').collect::<Vec<_>>();
+    let format_segments = arn_format.splitn(6, ':').collect::<Vec<_>>();
+    resource_segments.len() == format_segments.len()
+        && resource_segments
+            .iter()
+            .zip(&format_segments)
+            .all(|(resource, arn_format)| {
+                patterns_overlap(
+                    &tokenize_arn(resource, true),
+                    &tokenize_arn(arn_format, false),
+                )
+            })
+}
+
+/// Whether the resource `pattern` matches every ARN the policy resource `resource` matches
+///
+/// Wildcards and policy variables of `resource` are taken literally, so they are only covered
+/// by wildcards of `pattern`.
+pub(crate) fn resource_covers(pattern: &str, resource: &str) -> bool {
+    let resource = resource.chars().map(Token::Char).collect::<Vec<_>>();
+    patterns_overlap(&tokenize_arn(pattern, true), &resource)
+}
+
+/// Whether two policy resources can match a common ARN
+pub(crate) fn resources_overlap(a: &str, b: &str) -> bool {
+    patterns_overlap(&tokenize_arn(a, true), &tokenize_arn(b, true))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Char(char),
+    One,
+    Any,
+}
+
+fn tokenize_arn(pattern: &str, wildcards: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' if chars.peek() == Some(&'{') => {
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                }
+                tokens.push(Token::Any);
+            }
+            '*' if wildcards => tokens.push(Token::Any),
+            '?' if wildcards => tokens.push(Token::One),
+            c => tokens.push(Token::Char(c)),
+        }
+    }
+    tokens
+}
+
+/// Whether two wildcard patterns can match a common string
+fn patterns_overlap(a: &[Token], b: &[Token]) -> bool {
+    let mut memo = HashMap::new();
+    overlap_from(a, b, 0, 0, &mut memo)
+}
+
+fn overlap_from(
+    a: &[Token],
+    b: &[Token],
+    i: usize,
+    j: usize,
+    memo: &mut HashMap<(usize, usize), bool>,
+) -> bool {
+    if let Some(&result) = memo.get(&(i, j)) {
+        return result;
+    }
+    let result = match (a.get(i), b.get(j)) {
+        (None, None) => true,
+        (Some(Token::Any), _) => {
+            overlap_from(a, b, i + 1, j, memo)
+                || (j < b.len() && overlap_from(a, b, i, j + 1, memo))
+        }
+        (_, Some(Token::Any)) => {
+            overlap_from(a, b, i, j + 1, memo)
+                || (i < a.len() && overlap_from(a, b, i + 1, j, memo))
+        }
+        (Some(x), Some(y)) => {
+            let compatible = match (x, y) {
+                (Token::Char(x), Token::Char(y)) => x == y,
+                _ => true,
+            };
+            compatible && overlap_from(a, b, i + 1, j + 1, memo)
+        }
+        _ => false,
+    };
+    memo.insert((i, j), result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enrichment::mock_remote_service_reference;
+
+    fn s3_services() -> HashMap<String, ServiceLookup> {
+        let service_reference: ServiceReference = serde_json::from_value(serde_json::json!({
+            "Name": "s3",
+            "Actions": [
+                {
+                    "Name": "GetObject",
+                    "ActionConditionKeys": ["s3:ExistingObjectTag/${TagKey}"],
+                    "Resources": [{"Name": "object"}]
+                },
+                {
+                    "Name": "ListBucket",
+                    "ActionConditionKeys": ["s3:prefix"],
+                    "Resources": [{"Name": "bucket"}]
+                },
+                {
+                    "Name": "ListAllMyBuckets",
+                    "ActionConditionKeys": []
+                }
+            ],
+            "Resources": [
+                {
+                    "Name": "bucket",
+                    "ARNFormats": ["arn:${Partition}:s3:::${BucketName}"],
+                    "ConditionKeys": ["aws:ResourceTag/${TagKey}"]
+                },
+                {
+                    "Name": "object",
+                    "ARNFormats": ["arn:${Partition}:s3:::${BucketName}/${ObjectName}"],
+                    "ConditionKeys": ["s3:ResourceAccount"]
+                }
+            ]
+        }))
+        .unwrap();
+        HashMap::from([
+            (
+                "s3".to_string(),
+                ServiceLookup::Found(Box::new(service_reference)),
+            ),
+            ("foo".to_string(), ServiceLookup::Unknown),
+            (
+                "ec2".to_string(),
+                ServiceLookup::Unavailable("offline".to_string()),
+            ),
+        ])
+    }
+
+    fn validate(document: serde_json::Value) -> Vec<ValidationFinding> {
+        let document = document.to_string();
+        validate_documents(&[document.as_str()], PolicyKind::Managed, &s3_services())
+    }
+
+    fn issue_codes(findings: &[ValidationFinding]) -> Vec<IssueCode> {
+        findings.iter().map(|f| f.issue_code).collect()
+    }
+
+    #[test]
+    fn test_valid_policy_has_no_findings() {
+        let findings = validate(serde_json::json!({
+            "Version": "2012-10-17",
+            "Statement": [{
+                "Sid": "ReadObjects",
+                "Effect": "Allow",
+                "Action": ["s3:GetObject", "s3:getobject"],
+                "Resource": "arn:aws:s3:::my-bucket/*",
+                "Condition": {"StringEquals": {"s3:ExistingObjectTag/team": "a", "aws:PrincipalAccount": "123456789012"}}
+            }]
+        }));
+        assert!(findings.is_empty(), "{:?}", findings);
+    }
+
+    #[test]
+    fn test_grammar_errors() {
+        let findings = validate(serde_json::json!({
+            "Version": "2012-10-18",
+            "Extra": true,
+            "Statement": {
+                "Sid": "Not valid!",
+                "Effect": "Permit",
+                "Action": "s3:GetObject",
+                "NotAction": "s3:ListBucket",
+                "Principal": "*"
+            }
+        }));
+        let codes = issue_codes(&findings);
+        assert!(codes.contains(&IssueCode::InvalidVersion));
+        assert!(codes.contains(&IssueCode::UnsupportedElement));
+        assert!(codes.contains(&IssueCode::InvalidSid));
+        assert!(codes.contains(&IssueCode::InvalidEffect));
+        assert!(codes.contains(&IssueCode::ConflictingElements));
+        assert!(!codes.contains(&IssueCode::MissingAction));
+        assert!(findings
+            .iter()
+            .filter(|f| f.statement_index.is_some())
+            .all(|f| f.sid.as_deref() == Some("Not valid!")));
+    }
+
+    #[test]
+    fn test_missing_version_and_statement() {
+        let codes = issue_codes(&validate(serde_json::json!({"Statement": []})));
+        assert_eq!(
+            codes,
+            vec![IssueCode::MissingVersion, IssueCode::MissingStatement]
+        );
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let findings = validate_documents(&["{not json"], PolicyKind::Managed, &s3_services());
+        assert_eq!(issue_codes(&findings), vec![IssueCode::InvalidJson]);
+        assert_eq!(findings[0].finding_type, FindingType::Error);
+        assert_eq!(findings[0].policy_index, Some(0));
+    }
+
+    #[test]
+    fn test_duplicate_sids() {
+        let statement = serde_json::json!({
+            "Sid": "Same",
+            "Effect": "Allow",
+            "Action": "s3:GetObject",
+            "Resource": "*"
+        });
+        let findings = validate(serde_json::json!({
+            "Version": "2012-10-17",
+            "Statement": [statement.clone(), statement]
+        }));
+        assert_eq!(issue_codes(&findings), vec![IssueCode::DuplicateSid]);
+        assert_eq!(findings[0].statement_index, Some(1));
+    }
+
+    #[test]
+    fn test_invalid_actions_and_services() {
+        let findings = validate(serde_json::json!({
+            "Version": "2012-10-17",
+            "Statement": [{
+                "Effect": "Allow",
+                "Action": ["s3:GetObjekt", "s3:Get*", "foo:Bar", "ec2:RunInstances", "ec2:StopInstances", "GetObject"],
+                "Resource": "*"
+            }]
+        }));
+        assert_eq!(
+            issue_codes(&findings),
+            vec![
+                IssueCode::InvalidAction,
+                IssueCode::UnknownService,
+                IssueCode::ServiceReferenceUnavailable,
+                IssueCode::InvalidActionFormat,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resource_arn_mismatch() {
+        let findings = validate(serde_json::json!({
+            "Version": "2012-10-17",
+            "Statement": [{
+                "Effect": "Allow",
+                "Action": ["s3:GetObject", "s3:ListBucket", "s3:ListAllMyBuckets"],
+                "Resource": ["arn:aws:s3:::my-bucket", "not-an-arn"]
+            }]
+        }));
+        assert_eq!(
+            issue_codes(&findings),
+            vec![
+                IssueCode::InvalidArn,
+                IssueCode::ResourceArnMismatch,
+                IssueCode::ResourceArnMismatch,
+            ]
+        );
+        assert!(findings[1].message.contains("s3:GetObject"));
+        assert!(findings[2].message.contains("requires Resource"));
+    }
+
+    #[test]
+    fn test_resource_wildcards_match_arn_formats() {
+        assert!(arn_patterns_overlap(
+            "arn:aws:s3:::*",
+            "arn:${Partition}:s3:::${BucketName}/${ObjectName}"
+        ));
+        assert!(arn_patterns_overlap(
+            "arn:aws:s3:::${aws:username}-bucket/*",
+            "arn:${Partition}:s3:::${BucketName}/${ObjectName}"
+        ));
+        assert!(!arn_patterns_overlap(
+            "arn:aws:s3:::bucket",
+            "arn:${Partition}:s3:::${BucketName}/${ObjectName}"
+        ));
+        assert!(!arn_patterns_overlap(
+            "arn:aws:dynamodb:*:*:table/*",
+            "arn:${Partition}:s3:::${BucketName}"
+        ));
+    }
+
+    #[test]
+    fn test_condition_checks() {
+        let findings = validate(serde_json::json!({
+            "Version": "2012-10-17",
+            "Statement": [{
+                "Effect": "Allow",
+                "Action": "s3:GetObject",
+                "Resource": "*",
+                "Condition": {
+                    "StringEqualz": {"s3:ResourceAccount": "123456789012"},
+                    "ForAnyValue:StringLikeIfExists": {"s3:prefix": "home/", "aws:ResourceTag/team": ["a"]},
+                    "Bool": {"aws:SecureTransport": {"nested": true}}
+                }
+            }]
+        }));
+        // Condition operators are visited in map order, so only compare the set of findings
+        let codes = issue_codes(&findings);
+        assert_eq!(codes.len(), 3, "{:?}", findings);
+        assert!(codes.contains(&IssueCode::InvalidConditionOperator));
+        assert!(codes.contains(&IssueCode::MalformedElement));
+        let unsupported = findings
+            .iter()
+            .find(|f| f.issue_code == IssueCode::UnsupportedConditionKey)
+            .unwrap();
+        assert!(unsupported.message.contains("s3:prefix"));
+    }
+
+    #[test]
+    fn test_wildcard_and_not_action_misuse() {
+        let findings = validate(serde_json::json!({
+            "Version": "2012-10-17",
+            "Statement": [
+                {"Effect": "Allow", "Action": ["*", "s3:*"], "Resource": "*"},
+                {"Effect": "Allow", "NotAction": "s3:GetObject", "NotResource": "arn:aws:s3:::secret/*"},
+                {"Effect": "Deny", "Action": "*", "NotResource": "arn:aws:s3:::secret/*"}
+            ]
+        }));
+        assert_eq!(
+            issue_codes(&findings),
+            vec![
+                IssueCode::FullWildcardAction,
+                IssueCode::ServiceWildcardAction,
+                IssueCode::AllowWithNotAction,
+                IssueCode::AllowWithNotResource,
+            ]
+        );
+        assert_eq!(findings[0].finding_type, FindingType::SecurityWarning);
+        assert_eq!(findings[1].finding_type, FindingType::Warning);
+    }
+
+    #[test]
+    fn test_managed_policy_quotas() {
+        let actions = (0..400)
+            .map(|i| format!("s3:GetObject{}", i))
+            .collect::<Vec<_>>();
+        let large = serde_json::json!({
+            "Version": "2012-10-17",
+            "Statement": [{"Effect": "Allow", "NotAction": actions, "Resource": "*"}]
+        })
+        .to_string();
+        let small = serde_json::json!({
+            "Version": "2012-10-17",
+            "Statement": [{"Effect": "Deny", "Action": "s3:GetObject", "Resource": "*"}]
+        })
+        .to_string();
+
+        let mut documents = vec![large.as_str()];
+        documents.extend(std::iter::repeat_n(small.as_str(), 10));
+        let findings = validate_documents(&documents, PolicyKind::Managed, &s3_services());
+        let codes = issue_codes(&findings);
+        assert!(codes.contains(&IssueCode::PolicySizeQuotaExceeded));
+        let attachment = findings
+            .iter()
+            .find(|f| f.issue_code == IssueCode::ManagedPolicyAttachmentQuotaExceeded)
+            .unwrap();
+        assert_eq!(attachment.policy_index, None);
+    }
+
+    #[test]
+    fn test_inline_policy_quota() {
+        let actions = (0..300)
+            .map(|i| format!("s3:GetObject{}", i))
+            .collect::<Vec<_>>();
+        let document = serde_json::json!({
+            "Version": "2012-10-17",
+            "Statement": [{"Effect": "Deny", "Action": actions, "Resource": "*"}]
+        })
+        .to_string();
+
+        let findings = validate_documents(
+            &[document.as_str(), document.as_str()],
+            PolicyKind::Inline,
+            &HashMap::new(),
+        );
+        assert_eq!(
+            issue_codes(&findings),
+            vec![IssueCode::InlinePolicyQuotaExceeded]
+        );
+    }
+
+    #[test]
+    fn test_collect_service_prefixes() {
+        let document = serde_json::json!({
+            "Statement": [
+                {"Action": ["s3:GetObject", "S3:PutObject", "*"]},
+                {"NotAction": "dynamodb:*"},
+                {"Action": "bad prefix:Get"}
+            ]
+        })
+        .to_string();
+        let prefixes = collect_service_prefixes(&[document.as_str(), "not json"]);
+        assert_eq!(
+            prefixes.into_iter().collect::<Vec<_>>(),
+            vec!["dynamodb".to_string(), "s3".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_service_references() {
+        let (_mock_server, loader) =
+            mock_remote_service_reference::setup_mock_server_with_loader().await;
+        let services = load_service_references(
+            &loader,
+            BTreeSet::from(["s3".to_string(), "unknown".to_string()]),
+        )
+        .await;
+        assert!(matches!(services["s3"], ServiceLookup::Found(_)));
+        assert!(matches!(services["unknown"], ServiceLookup::Unknown));
+    }
+}
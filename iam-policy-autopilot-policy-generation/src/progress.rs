@@ -0,0 +1,115 @@
+//! Progress of long analyses
+//!
+//! Parsing and enriching a large repository takes a while. Callers observe the stages of a
+//! policy generation with a [`ProgressReporter`], and cancel it with a [`CancellationToken`]:
+//! pending parses are aborted and outstanding service reference fetches are dropped.
+
+use std::fmt;
+
+use tokio_util::sync::CancellationToken;
+
+/// Step of a policy generation
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Progress {
+    /// A source file was parsed, or taken from the extraction cache
+    FileParsed {
+        /// Number of source files parsed so far
+        parsed: usize,
+        /// Number of source files to parse
+        total: usize,
+    },
+    /// The service reference of a service was loaded
+    ServiceLoaded {
+        /// Service prefix, e.g. `s3`
+        service: String,
+        /// Number of service references loaded so far
+        loaded: usize,
+        /// Number of service references to load
+        total: usize,
+    },
+    /// The policies were generated and merged
+    PoliciesMerged {
+        /// Number of policies
+        policies: usize,
+    },
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileParsed { parsed, total } => {
+                write!(f, "Parsed {parsed} of {total} source files")
+            }
+            Self::ServiceLoaded {
+                service,
+                loaded,
+                total,
+            } => write!(
+                f,
+                "Loaded the service reference of {service} ({loaded} of {total})"
+            ),
+            Self::PoliciesMerged { policies } => write!(f, "Merged into {policies} policies"),
+        }
+    }
+}
+
+/// Receives the progress of policy generations
+///
+/// Reports are sent from the tasks doing the work and must not block.
+pub trait ProgressReporter: fmt::Debug + Send + Sync {
+    /// Report a completed step
+    fn report(&self, progress: Progress);
+}
+
+/// Complete when `cancellation` is cancelled, or never without a token
+pub(crate) async fn cancelled(cancellation: Option<&CancellationToken>) {
+    match cancellation {
+        Some(cancellation) => cancellation.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::{Mutex, PoisonError};
+
+    /// Reporter recording the reported progress
+    #[derive(Debug, Default)]
+    pub(crate) struct RecordingReporter(pub(crate) Mutex<Vec<Progress>>);
+
+    impl ProgressReporter for RecordingReporter {
+        fn report(&self, progress: Progress) {
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(progress);
+        }
+    }
+
+    #[test]
+    fn test_progress_display() {
+        assert_eq!(
+            Progress::FileParsed {
+                parsed: 2,
+                total: 5
+            }
+            .to_string(),
+            "Parsed 2 of 5 source files"
+        );
+        assert_eq!(
+            Progress::ServiceLoaded {
+                service: "s3".to_string(),
+                loaded: 1,
+                total: 3
+            }
+            .to_string(),
+            "Loaded the service reference of s3 (1 of 3)"
+        );
+        assert_eq!(
+            Progress::PoliciesMerged { policies: 2 }.to_string(),
+            "Merged into 2 policies"
+        );
+    }
+}
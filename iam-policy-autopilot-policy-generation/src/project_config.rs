@@ -0,0 +1,672 @@
+//! Project configuration file (`.iam-autopilot.toml`)
+//!
+//! A project configuration declares the settings that would otherwise be repeated on every
+//! CLI invocation: source roots and globs, language override, service hints, AWS context,
+//! resource cutoffs and bindings, and output options. The file is discovered by walking up
+//! from a starting directory, so the CLI (from the working directory) and the MCP server
+//! (from the project root of the analyzed files) produce the same policies.
+//!
+//! ```toml
+//! [sources]
+//! roots = ["src"]
+//! include = ["**/*.py"]
+//! exclude = ["**/tests/**"]
+//! language = "python"
+//! service-hints = ["s3", "dynamodb"]
+//! sdk-wrappers = ["iam/wrappers.json"]
+//! detect-entry-points = true
+//! entry-points = ["src/jobs/nightly.py:run"]
+//! include-tests = false
+//!
+//! [aws]
+//! region = "us-east-1"
+//! account = "123456789012"
+//!
+//! [generation]
+//! minimize-policy-size = false
+//! disable-cache = false
+//! explain = false
+//! offline = false
+//! service-reference = "vendor/service-reference"
+//! service-reference-endpoint = "https://service-reference.example.internal/"
+//! ca-certificate = "certs/internal-ca.pem"
+//! proxy = "http://proxy.example.internal:3128"
+//! fas-maps = ["iam/fas/lambda.json"]
+//! lambda-functions = "template.yaml"
+//!
+//! [resources]
+//! cutoff = 5
+//! wildcard-mode = "wildcard"
+//! bindings = { BucketName = "my-app-bucket" }
+//!
+//! [resources.services.ec2]
+//! wildcard-mode = "service-wildcard"
+//!
+//! [resources.actions."iam:PassRole"]
+//! wildcard-mode = "never"
+//!
+//! [output]
+//! pretty = true
+//!
+//! [workloads.ingest]
+//! paths = ["functions/ingest/**"]
+//!
+//! [workloads.report]
+//! paths = ["functions/report/**"]
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use glob::Pattern;
+use serde::Deserialize;
+use walkdir::WalkDir;
+
+use crate::api::model::{
+    PolicyGenerationOptions, ResourceCutoffOverride, ServiceReferenceSource, WildcardMode,
+};
+use crate::errors::{ExtractorError, Result};
+use crate::extraction::{EntryPoint, EntryPoints};
+use crate::workload::{Workload, WorkloadManifest};
+use crate::{Language, SourceFile};
+
+/// File name of the project configuration
+pub const PROJECT_CONFIG_FILE_NAME: &str = ".iam-autopilot.toml";
+
+/// Project configuration loaded from `.iam-autopilot.toml`
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Source files to analyze
+    #[serde(default)]
+    pub sources: SourcesConfig,
+    /// AWS context used for ARNs
+    #[serde(default)]
+    pub aws: AwsConfig,
+    /// Policy generation settings
+    #[serde(default)]
+    pub generation: GenerationConfig,
+    /// Resource cutoffs and bindings
+    #[serde(default)]
+    pub resources: ResourcesConfig,
+    /// Output settings
+    #[serde(default)]
+    pub output: OutputConfig,
+    /// Workloads of the project, keyed by name
+    #[serde(default)]
+    pub workloads: BTreeMap<String, WorkloadConfig>,
+    /// Directory containing the configuration file, against which relative paths are resolved
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+/// `[sources]` table of the project configuration
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SourcesConfig {
+    /// Directories to search for source files, relative to the project root
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+    /// Globs, relative to the project root, that source files must match
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs, relative to the project root, of source files to skip
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Override programming language detection
+    pub language: Option<String>,
+    /// AWS services the application uses
+    pub service_hints: Option<Vec<String>>,
+    /// SDK wrapper mapping files, relative to the project root
+    #[serde(default)]
+    pub sdk_wrappers: Vec<PathBuf>,
+    /// Only extract SDK calls reachable from detected `main` functions, Lambda handlers and
+    /// HTTP route handlers
+    #[serde(default)]
+    pub detect_entry_points: bool,
+    /// Entry points SDK calls must be reachable from, with files relative to the project root
+    #[serde(default)]
+    pub entry_points: Vec<EntryPoint>,
+    /// Extract SDK calls of test files and files that stub the SDK
+    #[serde(default)]
+    pub include_tests: bool,
+}
+
+/// `[aws]` table of the project configuration
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AwsConfig {
+    /// AWS region
+    pub region: Option<String>,
+    /// AWS account ID
+    pub account: Option<String>,
+}
+
+/// `[generation]` table of the project configuration
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct GenerationConfig {
+    /// Allow merging actions of different services into one statement
+    #[serde(default)]
+    pub minimize_policy_size: bool,
+    /// Disable file system caching for service references
+    #[serde(default)]
+    pub disable_cache: bool,
+    /// Generate explanations for why actions were added
+    #[serde(default)]
+    pub explain: bool,
+    /// Never fetch service references from the network
+    #[serde(default)]
+    pub offline: bool,
+    /// Service reference snapshot directory or bundle file, relative to the project root
+    pub service_reference: Option<PathBuf>,
+    /// Mapping index URL of an internal mirror of the service reference endpoint
+    pub service_reference_endpoint: Option<String>,
+    /// PEM file with additional CA certificates to trust, relative to the project root
+    pub ca_certificate: Option<PathBuf>,
+    /// Proxy for service reference requests
+    pub proxy: Option<String>,
+    /// Additional operation FAS map files, relative to the project root
+    #[serde(default)]
+    pub fas_maps: Vec<PathBuf>,
+    /// Template, configuration, CDK source file or directory declaring Lambda functions to
+    /// generate a policy for each, relative to the project root
+    pub lambda_functions: Option<PathBuf>,
+}
+
+/// `[resources]` table of the project configuration
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ResourcesConfig {
+    /// Number of resource types from which the resources of an action are collapsed
+    pub cutoff: Option<usize>,
+    /// How resources are collapsed once the cutoff is reached
+    pub wildcard_mode: Option<WildcardMode>,
+    /// Overrides keyed by service prefix
+    #[serde(default)]
+    pub services: BTreeMap<String, CutoffOverrideConfig>,
+    /// Overrides keyed by action
+    #[serde(default)]
+    pub actions: BTreeMap<String, CutoffOverrideConfig>,
+    /// Values for resource placeholders of ARNs, e.g. `BucketName = "my-bucket"`
+    #[serde(default)]
+    pub bindings: BTreeMap<String, String>,
+}
+
+/// Cutoff override for a service or an action in the project configuration
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CutoffOverrideConfig {
+    /// Number of resource types from which resources are collapsed
+    pub cutoff: Option<usize>,
+    /// How resources are collapsed once the cutoff is reached
+    pub wildcard_mode: Option<WildcardMode>,
+}
+
+/// `[output]` table of the project configuration
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct OutputConfig {
+    /// Format JSON output with indentation
+    #[serde(default)]
+    pub pretty: bool,
+}
+
+/// `[workloads.<name>]` table of the project configuration
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WorkloadConfig {
+    /// Globs, relative to the project root, of the workload's entry points
+    pub paths: Vec<String>,
+}
+
+impl From<&CutoffOverrideConfig> for ResourceCutoffOverride {
+    fn from(config: &CutoffOverrideConfig) -> Self {
+        Self {
+            cutoff: config.cutoff,
+            wildcard_mode: config.wildcard_mode,
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// Find the project configuration in `start` or its closest ancestor containing one
+    ///
+    /// Returns None if no configuration file exists.
+    ///
+    /// # Errors
+    /// Returns an error if a configuration file was found but cannot be read or parsed
+    pub fn discover(start: &Path) -> Result<Option<Self>> {
+        for directory in start.ancestors() {
+            let path = directory.join(PROJECT_CONFIG_FILE_NAME);
+            if path.is_file() {
+                log::debug!("Using project configuration {}", path.display());
+                return Self::load(&path).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Load the project configuration from a file
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a valid project configuration
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ExtractorError::file_system("read", path, e))?;
+        let mut config: Self = toml::from_str(&content).map_err(|e| {
+            ExtractorError::configuration_with_source(
+                format!("Invalid project configuration '{}'", path.display()),
+                e,
+            )
+        })?;
+        config.root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(config)
+    }
+
+    /// Policy generation options declared in the `[resources]` table
+    pub fn policy_options(&self) -> PolicyGenerationOptions {
+        let defaults = PolicyGenerationOptions::default();
+        PolicyGenerationOptions {
+            resource_cutoff: self.resources.cutoff.unwrap_or(defaults.resource_cutoff),
+            wildcard_mode: self
+                .resources
+                .wildcard_mode
+                .unwrap_or(defaults.wildcard_mode),
+            service_overrides: convert_overrides(&self.resources.services),
+            action_overrides: convert_overrides(&self.resources.actions),
+            resource_bindings: self.resources.bindings.clone(),
+        }
+    }
+
+    /// Service reference source declared in the `[generation]` table
+    pub fn service_reference_source(&self) -> ServiceReferenceSource {
+        ServiceReferenceSource {
+            snapshot: self
+                .generation
+                .service_reference
+                .as_ref()
+                .map(|path| self.root.join(path)),
+            offline: self.generation.offline,
+            endpoint: self.generation.service_reference_endpoint.clone(),
+            ca_certificate: self
+                .generation
+                .ca_certificate
+                .as_ref()
+                .map(|path| self.root.join(path)),
+            proxy: self.generation.proxy.clone(),
+        }
+    }
+
+    /// SDK wrapper mapping files declared in the `[sources]` table
+    pub fn sdk_wrappers(&self) -> Vec<PathBuf> {
+        self.sources
+            .sdk_wrappers
+            .iter()
+            .map(|path| self.root.join(path))
+            .collect()
+    }
+
+    /// Entry points declared in the `[sources]` table
+    pub fn entry_points(&self) -> EntryPoints {
+        EntryPoints {
+            detect: self.sources.detect_entry_points,
+            declared: self
+                .sources
+                .entry_points
+                .iter()
+                .map(|entry_point| EntryPoint {
+                    file: self.root.join(&entry_point.file),
+                    function: entry_point.function.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Operation FAS map files declared in the `[generation]` table
+    pub fn fas_map_extensions(&self) -> Vec<PathBuf> {
+        self.generation
+            .fas_maps
+            .iter()
+            .map(|path| self.root.join(path))
+            .collect()
+    }
+
+    /// Lambda functions path declared in the `[generation]` table
+    pub fn lambda_functions(&self) -> Option<PathBuf> {
+        self.generation
+            .lambda_functions
+            .as_ref()
+            .map(|path| self.root.join(path))
+    }
+
+    /// Workloads declared in the `[workloads]` table, or `None` if there are none
+    pub fn workload_manifest(&self) -> Option<WorkloadManifest> {
+        if self.workloads.is_empty() {
+            return None;
+        }
+        let workloads = self
+            .workloads
+            .iter()
+            .map(|(name, config)| Workload {
+                name: name.clone(),
+                paths: config.paths.clone(),
+            })
+            .collect();
+        Some(WorkloadManifest::new(self.root.clone(), workloads))
+    }
+
+    /// Source files under the source roots that match the include and exclude globs
+    ///
+    /// Without include globs, all files of the configured language are selected, or all files
+    /// of a supported language if no language is configured. Hidden directories are skipped.
+    /// The result is sorted.
+    ///
+    /// # Errors
+    /// Returns an error if a glob or the language override is invalid, or a source root
+    /// cannot be read
+    pub fn source_files(&self) -> Result<Vec<PathBuf>> {
+        let include = compile_globs(&self.sources.include)?;
+        let exclude = compile_globs(&self.sources.exclude)?;
+        let language = self
+            .sources
+            .language
+            .as_deref()
+            .map(Language::try_from_str)
+            .transpose()?;
+
+        let roots = if self.sources.roots.is_empty() {
+            vec![self.root.clone()]
+        } else {
+            self.sources
+                .roots
+                .iter()
+                .map(|root| self.root.join(root))
+                .collect()
+        };
+
+        let mut files = Vec::new();
+        for root in roots {
+            let walker = WalkDir::new(&root).into_iter().filter_entry(|entry| {
+                entry.depth() == 0
+                    || !(entry.file_type().is_dir()
+                        && entry.file_name().to_string_lossy().starts_with('.'))
+            });
+            for entry in walker {
+                let entry = entry.map_err(|e| {
+                    let path = e.path().unwrap_or(&root).to_path_buf();
+                    ExtractorError::file_system(
+                        "walk source root",
+                        path,
+                        e.into_io_error()
+                            .unwrap_or_else(|| std::io::Error::other("file system loop")),
+                    )
+                })?;
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+
+                let path = entry.path();
+                let relative = path.strip_prefix(&self.root).unwrap_or(path);
+                let selected = if include.is_empty() {
+                    match (SourceFile::detect_language(path), language) {
+                        (Some(detected), Some(language)) => detected == language,
+                        (detected, None) => detected.is_some(),
+                        (None, Some(_)) => false,
+                    }
+                } else {
+                    include.iter().any(|glob| glob.matches_path(relative))
+                };
+                if selected && !exclude.iter().any(|glob| glob.matches_path(relative)) {
+                    files.push(path.to_path_buf());
+                }
+            }
+        }
+
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+}
+
+fn convert_overrides(
+    overrides: &BTreeMap<String, CutoffOverrideConfig>,
+) -> BTreeMap<String, ResourceCutoffOverride> {
+    overrides
+        .iter()
+        .map(|(key, config)| (key.clone(), config.into()))
+        .collect()
+}
+
+fn compile_globs(globs: &[String]) -> Result<Vec<Pattern>> {
+    globs
+        .iter()
+        .map(|glob| {
+            Pattern::new(glob).map_err(|e| {
+                ExtractorError::configuration_with_source(
+                    format!("Invalid glob '{}' in project configuration", glob),
+                    e,
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, relative: &str, content: &str) {
+        let path = dir.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_load_full_config() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            PROJECT_CONFIG_FILE_NAME,
+            r#"
+[sources]
+roots = ["src"]
+include = ["**/*.py"]
+language = "python"
+service-hints = ["s3"]
+sdk-wrappers = ["iam/wrappers.json"]
+entry-points = ["jobs/nightly.py:run"]
+
+[aws]
+region = "eu-west-1"
+account = "123456789012"
+
+[generation]
+explain = true
+offline = true
+service-reference = "vendor/snapshot.json"
+service-reference-endpoint = "https://mirror.example.com/"
+ca-certificate = "certs/ca.pem"
+fas-maps = ["iam/fas/lambda.json"]
+lambda-functions = "template.yaml"
+
+[resources]
+cutoff = 8
+bindings = { BucketName = "my-bucket" }
+
+[resources.services.ec2]
+wildcard-mode = "service-wildcard"
+
+[resources.actions."iam:PassRole"]
+cutoff = 1
+wildcard-mode = "never"
+
+[output]
+pretty = true
+"#,
+        );
+
+        let config = ProjectConfig::discover(&dir.path().join("src/nested"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.root, dir.path());
+        assert_eq!(config.sources.language.as_deref(), Some("python"));
+        assert_eq!(config.sources.service_hints, Some(vec!["s3".to_string()]));
+        assert_eq!(
+            config.sdk_wrappers(),
+            vec![dir.path().join("iam/wrappers.json")]
+        );
+        assert_eq!(
+            config.entry_points(),
+            EntryPoints {
+                detect: false,
+                declared: vec![EntryPoint {
+                    file: dir.path().join("jobs/nightly.py"),
+                    function: Some("run".to_string()),
+                }],
+            }
+        );
+        assert_eq!(config.aws.region.as_deref(), Some("eu-west-1"));
+        assert!(config.generation.explain);
+        assert!(!config.generation.minimize_policy_size);
+        assert_eq!(
+            config.service_reference_source(),
+            ServiceReferenceSource {
+                snapshot: Some(dir.path().join("vendor/snapshot.json")),
+                offline: true,
+                endpoint: Some("https://mirror.example.com/".to_string()),
+                ca_certificate: Some(dir.path().join("certs/ca.pem")),
+                proxy: None,
+            }
+        );
+        assert_eq!(
+            config.fas_map_extensions(),
+            vec![dir.path().join("iam/fas/lambda.json")]
+        );
+        assert_eq!(
+            config.lambda_functions(),
+            Some(dir.path().join("template.yaml"))
+        );
+        assert!(config.output.pretty);
+
+        let options = config.policy_options();
+        assert_eq!(options.resource_cutoff, 8);
+        assert_eq!(options.wildcard_mode, WildcardMode::Wildcard);
+        assert_eq!(
+            options.resource_cutoff_for("ec2:RunInstances"),
+            (8, WildcardMode::ServiceWildcard)
+        );
+        assert_eq!(
+            options.resource_cutoff_for("iam:PassRole"),
+            (1, WildcardMode::Never)
+        );
+        assert_eq!(
+            options
+                .resource_bindings
+                .get("BucketName")
+                .map(String::as_str),
+            Some("my-bucket")
+        );
+    }
+
+    #[test]
+    fn test_workload_manifest() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            PROJECT_CONFIG_FILE_NAME,
+            r#"
+[workloads.ingest]
+paths = ["functions/ingest/**"]
+
+[workloads.report]
+paths = ["functions/report/**", "jobs/report.py"]
+"#,
+        );
+
+        let config = ProjectConfig::discover(dir.path()).unwrap().unwrap();
+        let manifest = config.workload_manifest().unwrap();
+        assert_eq!(manifest.root, dir.path());
+        assert_eq!(
+            manifest.workloads,
+            vec![
+                Workload {
+                    name: "ingest".to_string(),
+                    paths: vec!["functions/ingest/**".to_string()],
+                },
+                Workload {
+                    name: "report".to_string(),
+                    paths: vec![
+                        "functions/report/**".to_string(),
+                        "jobs/report.py".to_string()
+                    ],
+                },
+            ]
+        );
+        assert_eq!(ProjectConfig::default().workload_manifest(), None);
+    }
+
+    #[test]
+    fn test_discover_without_config() {
+        let dir = TempDir::new().unwrap();
+        // Ancestors of a temporary directory are not expected to contain a configuration
+        assert_eq!(ProjectConfig::discover(dir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            PROJECT_CONFIG_FILE_NAME,
+            "[aws]\nregoin = \"us-east-1\"\n",
+        );
+
+        let error = ProjectConfig::discover(dir.path()).unwrap_err();
+        assert!(error.to_string().contains("Invalid project configuration"));
+    }
+
+    #[test]
+    fn test_source_files_with_globs() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            PROJECT_CONFIG_FILE_NAME,
+            "[sources]\nroots = [\"app\"]\ninclude = [\"app/**/*.py\"]\nexclude = [\"**/test_*.py\"]\n",
+        );
+        write(dir.path(), "app/main.py", "");
+        write(dir.path(), "app/handlers/orders.py", "");
+        write(dir.path(), "app/handlers/test_orders.py", "");
+        write(dir.path(), "app/.venv/lib/boto3.py", "");
+        write(dir.path(), "app/README.md", "");
+        write(dir.path(), "scripts/deploy.py", "");
+
+        let config = ProjectConfig::discover(dir.path()).unwrap().unwrap();
+        assert_eq!(
+            config.source_files().unwrap(),
+            vec![
+                dir.path().join("app/handlers/orders.py"),
+                dir.path().join("app/main.py"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_source_files_by_language() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            PROJECT_CONFIG_FILE_NAME,
+            "[sources]\nlanguage = \"go\"\n",
+        );
+        write(dir.path(), "main.go", "");
+        write(dir.path(), "tools/gen.py", "");
+
+        let config = ProjectConfig::discover(dir.path()).unwrap().unwrap();
+        assert_eq!(
+            config.source_files().unwrap(),
+            vec![dir.path().join("main.go")]
+        );
+    }
+}
@@ -0,0 +1,518 @@
+//! Workloads: named deployables of a repository that each run as their own role
+//!
+//! A [`WorkloadManifest`] maps path globs to workload names. Source files matching a workload's
+//! globs belong to it, and so does every other source file they import, directly or
+//! transitively. Shared library code imported by several workloads is therefore attributed
+//! to each of them, so that each role gets the permissions of all code it runs.
+//!
+//! Imports are resolved against the analyzed source files only:
+//! - Python: `import a.b` and `from a.b import c` resolve to `a/b.py`, `a/b/__init__.py` or
+//!   `a/b/c.py` below the importing file's directory or any of its parents up to the root.
+//!   Relative imports (`from .a import b`) resolve against the importing file's package.
+//! - JavaScript and TypeScript: relative specifiers (`./a`, `../a/b`) in `import`, `export`
+//!   and `require` resolve to the file with or without a source extension, or to its `index`.
+//! - Go: an import path resolves to the package directory whose path, relative to the root,
+//!   is a suffix of the import path. Files in the same directory form one package.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+use glob::Pattern;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{ExtractorError, Result};
+use crate::{Language, SourceFile};
+
+/// A named workload and the path globs of its entry points
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Workload {
+    /// Name of the workload, typically the role it runs as
+    pub name: String,
+    /// Globs, relative to the manifest root, of the source files that belong to the workload
+    pub paths: Vec<String>,
+}
+
+/// Workloads of a repository
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkloadManifest {
+    /// Directory against which the workload globs are matched
+    pub root: PathBuf,
+    /// The workloads, in declaration order
+    pub workloads: Vec<Workload>,
+}
+
+/// Source files attributed to each workload
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkloadAssignment {
+    /// Source files of each workload, keyed by workload name
+    pub workloads: BTreeMap<String, Vec<PathBuf>>,
+    /// Source files that neither match a workload nor are imported by one
+    pub unassigned_files: Vec<PathBuf>,
+}
+
+impl WorkloadManifest {
+    /// Create a manifest whose globs are matched relative to `root`
+    pub fn new(root: impl Into<PathBuf>, workloads: Vec<Workload>) -> Self {
+        Self {
+            root: root.into(),
+            workloads,
+        }
+    }
+
+    /// Attribute source files to workloads
+    ///
+    /// A file belongs to every workload whose globs it matches, and to every workload that
+    /// imports it. The file lists keep the order of `source_files`.
+    ///
+    /// # Errors
+    /// Returns an error if a workload has no globs, a glob is invalid, two workloads have the
+    /// same name, or a source file cannot be read
+    pub fn assign(&self, source_files: &[PathBuf]) -> Result<WorkloadAssignment> {
+        let root = absolute(&self.root);
+        let files: Vec<PathBuf> = source_files.iter().map(|f| absolute(f)).collect();
+        let index: BTreeMap<&Path, usize> = files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| (file.as_path(), i))
+            .collect();
+
+        let mut imports = Vec::with_capacity(files.len());
+        for file in &files {
+            imports.push(resolve_imports(file, &root, &index)?);
+        }
+
+        let mut assignment = WorkloadAssignment::default();
+        let mut assigned = vec![false; files.len()];
+        for workload in &self.workloads {
+            if workload.paths.is_empty() {
+                return Err(ExtractorError::validation(format!(
+                    "Workload '{}' has no paths",
+                    workload.name
+                )));
+            }
+            let globs = workload
+                .paths
+                .iter()
+                .map(|glob| {
+                    Pattern::new(glob).map_err(|e| {
+                        ExtractorError::configuration_with_source(
+                            format!("Invalid glob '{}' for workload '{}'", glob, workload.name),
+                            e,
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            // Entry points matched by the globs, then everything they import
+            let mut reached = vec![false; files.len()];
+            let mut queue: VecDeque<usize> = files
+                .iter()
+                .enumerate()
+                .filter(|(_, file)| {
+                    let relative = file.strip_prefix(&root).unwrap_or(file);
+                    globs.iter().any(|glob| glob.matches_path(relative))
+                })
+                .map(|(i, _)| i)
+                .collect();
+            for &i in &queue {
+                reached[i] = true;
+            }
+            while let Some(i) = queue.pop_front() {
+                for &imported in &imports[i] {
+                    if !reached[imported] {
+                        reached[imported] = true;
+                        queue.push_back(imported);
+                    }
+                }
+            }
+
+            let workload_files = source_files
+                .iter()
+                .zip(&reached)
+                .filter(|(_, reached)| **reached)
+                .map(|(file, _)| file.clone())
+                .collect();
+            if assignment
+                .workloads
+                .insert(workload.name.clone(), workload_files)
+                .is_some()
+            {
+                return Err(ExtractorError::validation(format!(
+                    "Workload '{}' is declared more than once",
+                    workload.name
+                )));
+            }
+            for (assigned, reached) in assigned.iter_mut().zip(reached) {
+                *assigned |= reached;
+            }
+        }
+
+        assignment.unassigned_files = source_files
+            .iter()
+            .zip(assigned)
+            .filter(|(_, assigned)| !assigned)
+            .map(|(file, _)| file.clone())
+            .collect();
+        Ok(assignment)
+    }
+}
+
+/// Indices of the source files imported by `file`
+fn resolve_imports(
+    file: &Path,
+    root: &Path,
+    index: &BTreeMap<&Path, usize>,
+) -> Result<BTreeSet<usize>> {
+    let Some(language) = SourceFile::detect_language(file) else {
+        return Ok(BTreeSet::new());
+    };
+    let content = std::fs::read_to_string(file)
+        .map_err(|e| ExtractorError::file_system("read", file.to_path_buf(), e))?;
+    Ok(imported_files(language, file, &content, root, index))
+}
+
+/// Indices of the source files imported by `file`, whose source is `content`
+///
+/// `file`, `root` and the keys of `index` must be absolute and normalized.
+pub(crate) fn imported_files(
+    language: Language,
+    file: &Path,
+    content: &str,
+    root: &Path,
+    index: &BTreeMap<&Path, usize>,
+) -> BTreeSet<usize> {
+    let dir = file.parent().unwrap_or(root);
+    let lookup = |candidate: PathBuf| index.get(normalize(&candidate).as_path()).copied();
+
+    let mut imported = BTreeSet::new();
+    match language {
+        Language::Python => {
+            for (level, module) in python_imports(content) {
+                let parts: Vec<&str> = module.split('.').filter(|p| !p.is_empty()).collect();
+                let bases: Vec<PathBuf> = if level > 0 {
+                    dir.ancestors()
+                        .nth(level - 1)
+                        .map(Path::to_path_buf)
+                        .into_iter()
+                        .collect()
+                } else {
+                    dir.ancestors()
+                        .take_while(|base| base.starts_with(root))
+                        .map(Path::to_path_buf)
+                        .collect()
+                };
+                for base in bases {
+                    let module_path = parts.iter().fold(base, |path, part| path.join(part));
+                    let candidates = [
+                        module_path.with_extension("py"),
+                        module_path.join("__init__.py"),
+                    ];
+                    imported.extend(candidates.into_iter().filter_map(lookup));
+                }
+            }
+        }
+        Language::JavaScript | Language::TypeScript => {
+            for specifier in javascript_imports(content) {
+                let target = dir.join(&specifier);
+                let stem = target.with_extension("");
+                let mut candidates = vec![target.clone()];
+                for extension in ["ts", "tsx", "js", "jsx", "mjs", "cjs"] {
+                    candidates.push(PathBuf::from(format!("{}.{}", target.display(), extension)));
+                    candidates.push(stem.with_extension(extension));
+                    candidates.push(target.join("index").with_extension(extension));
+                }
+                imported.extend(candidates.into_iter().filter_map(lookup));
+            }
+        }
+        Language::Go => {
+            let packages = go_imports(content);
+            for (candidate, &i) in index {
+                let Some(candidate_dir) = candidate.parent() else {
+                    continue;
+                };
+                if SourceFile::detect_language(candidate) != Some(Language::Go) {
+                    continue;
+                }
+                if candidate_dir == dir {
+                    imported.insert(i);
+                    continue;
+                }
+                let Ok(relative) = candidate_dir.strip_prefix(root) else {
+                    continue;
+                };
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                if relative.is_empty() {
+                    continue;
+                }
+                let suffix = format!("/{}", relative);
+                if packages
+                    .iter()
+                    .any(|package| *package == relative || package.ends_with(&suffix))
+                {
+                    imported.insert(i);
+                }
+            }
+        }
+    }
+    if let Some(own) = index.get(file) {
+        imported.remove(own);
+    }
+    imported
+}
+
+/// Imported Python modules, as the number of leading dots and the dotted module path
+///
+/// For `from a import b`, both `a` and `a.b` are returned since `b` may be a submodule.
+fn python_imports(content: &str) -> Vec<(usize, String)> {
+    static FROM_IMPORT: OnceLock<Regex> = OnceLock::new();
+    static IMPORT: OnceLock<Regex> = OnceLock::new();
+    let from_import = FROM_IMPORT.get_or_init(|| {
+        Regex::new(r"(?m)^\s*from\s+(\.*)([\w.]*)\s+import\s+\(?([^)\n]*)")
+            .expect("Invalid Python from-import regex")
+    });
+    let import = IMPORT.get_or_init(|| {
+        Regex::new(r"(?m)^\s*import\s+([\w., ]+)").expect("Invalid Python import regex")
+    });
+
+    let mut modules = Vec::new();
+    for captures in from_import.captures_iter(content) {
+        let level = captures[1].len();
+        let module = captures[2].to_string();
+        for name in import_names(&captures[3]) {
+            let submodule = if module.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", module, name)
+            };
+            modules.push((level, submodule));
+        }
+        if !module.is_empty() {
+            modules.push((level, module));
+        }
+    }
+    for captures in import.captures_iter(content) {
+        modules.extend(import_names(&captures[1]).map(|name| (0, name.to_string())));
+    }
+    modules
+}
+
+/// Names of an import list, without `as` aliases
+fn import_names(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .filter_map(|name| name.split_whitespace().next())
+        .filter(|name| *name != "*")
+}
+
+/// Relative module specifiers imported by a JavaScript or TypeScript file
+fn javascript_imports(content: &str) -> Vec<String> {
+    static SPECIFIER: OnceLock<Regex> = OnceLock::new();
+    let specifier = SPECIFIER.get_or_init(|| {
+        Regex::new(r#"(?:\bfrom\s*|\bimport\s*\(?\s*|\brequire\s*\(\s*)['"](\.{1,2}/[^'"]*)['"]"#)
+            .expect("Invalid JavaScript import regex")
+    });
+    specifier
+        .captures_iter(content)
+        .map(|captures| captures[1].to_string())
+        .collect()
+}
+
+/// Package paths imported by a Go file
+fn go_imports(content: &str) -> Vec<String> {
+    static IMPORT_BLOCK: OnceLock<Regex> = OnceLock::new();
+    static IMPORT_LINE: OnceLock<Regex> = OnceLock::new();
+    static IMPORT_PATH: OnceLock<Regex> = OnceLock::new();
+    let import_block = IMPORT_BLOCK.get_or_init(|| {
+        Regex::new(r"(?s)\bimport\s*\((.*?)\)").expect("Invalid Go import block regex")
+    });
+    let import_line = IMPORT_LINE.get_or_init(|| {
+        Regex::new(r#"(?m)^\s*import\s+(?:[\w.]+\s+)?"([^"]+)""#).expect("Invalid Go import regex")
+    });
+    let import_path =
+        IMPORT_PATH.get_or_init(|| Regex::new(r#""([^"]+)""#).expect("Invalid Go path regex"));
+
+    let mut packages: Vec<String> = import_block
+        .captures_iter(content)
+        .flat_map(|block| {
+            import_path
+                .captures_iter(block.get(1).map_or("", |m| m.as_str()))
+                .map(|captures| captures[1].to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    packages.extend(
+        import_line
+            .captures_iter(content)
+            .map(|captures| captures[1].to_string()),
+    );
+    packages
+}
+
+/// Absolute, lexically normalized form of `path`
+pub(crate) fn absolute(path: &Path) -> PathBuf {
+    normalize(&std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()))
+}
+
+/// Remove `.` components and resolve `..` components without touching the file system
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = dir.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn workload(name: &str, paths: &[&str]) -> Workload {
+        Workload {
+            name: name.to_string(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_assign_python_shared_library() {
+        let dir = TempDir::new().unwrap();
+        let ingest = write(
+            dir.path(),
+            "functions/ingest/handler.py",
+            "from lib.storage import put_object\nimport boto3\n",
+        );
+        let report = write(
+            dir.path(),
+            "functions/report/handler.py",
+            "from lib import storage, queue as q\n",
+        );
+        let storage = write(dir.path(), "lib/storage.py", "from .util import retry\n");
+        let util = write(dir.path(), "lib/util.py", "");
+        let queue = write(dir.path(), "lib/queue.py", "");
+        let unused = write(dir.path(), "scripts/migrate.py", "");
+        let files = vec![
+            ingest.clone(),
+            report.clone(),
+            storage.clone(),
+            util.clone(),
+            queue.clone(),
+            unused.clone(),
+        ];
+
+        let manifest = WorkloadManifest::new(
+            dir.path(),
+            vec![
+                workload("ingest", &["functions/ingest/**"]),
+                workload("report", &["functions/report/**"]),
+            ],
+        );
+        let assignment = manifest.assign(&files).unwrap();
+
+        assert_eq!(
+            assignment.workloads["ingest"],
+            vec![ingest, storage.clone(), util.clone()]
+        );
+        assert_eq!(
+            assignment.workloads["report"],
+            vec![report, storage, util, queue]
+        );
+        assert_eq!(assignment.unassigned_files, vec![unused]);
+    }
+
+    #[test]
+    fn test_assign_typescript_relative_imports() {
+        let dir = TempDir::new().unwrap();
+        let handler = write(
+            dir.path(),
+            "functions/ingest/index.ts",
+            "import { put } from '../../shared/s3';\nconst db = require('../../shared/db');\n",
+        );
+        let s3 = write(dir.path(), "shared/s3.ts", "export * from './client.js';\n");
+        let client = write(dir.path(), "shared/client.ts", "");
+        let db = write(dir.path(), "shared/db/index.ts", "");
+        let other = write(dir.path(), "shared/other.ts", "");
+        let files = vec![
+            handler.clone(),
+            s3.clone(),
+            client.clone(),
+            db.clone(),
+            other.clone(),
+        ];
+
+        let manifest = WorkloadManifest::new(
+            dir.path(),
+            vec![workload("ingest", &["functions/ingest/*"])],
+        );
+        let assignment = manifest.assign(&files).unwrap();
+
+        assert_eq!(
+            assignment.workloads["ingest"],
+            vec![handler, s3, client, db]
+        );
+        assert_eq!(assignment.unassigned_files, vec![other]);
+    }
+
+    #[test]
+    fn test_assign_go_packages() {
+        let dir = TempDir::new().unwrap();
+        let main = write(
+            dir.path(),
+            "cmd/worker/main.go",
+            "package main\n\nimport (\n\t\"fmt\"\n\tstore \"example.com/app/internal/store\"\n)\n",
+        );
+        let flags = write(dir.path(), "cmd/worker/flags.go", "package main\n");
+        let store = write(dir.path(), "internal/store/store.go", "package store\n");
+        let api = write(dir.path(), "cmd/api/main.go", "package main\n");
+        let files = vec![main.clone(), flags.clone(), store.clone(), api.clone()];
+
+        let manifest = WorkloadManifest::new(
+            dir.path(),
+            vec![
+                workload("worker", &["cmd/worker/main.go"]),
+                workload("api", &["cmd/api/**"]),
+            ],
+        );
+        let assignment = manifest.assign(&files).unwrap();
+
+        assert_eq!(assignment.workloads["worker"], vec![main, flags, store]);
+        assert_eq!(assignment.workloads["api"], vec![api]);
+        assert!(assignment.unassigned_files.is_empty());
+    }
+
+    #[test]
+    fn test_assign_rejects_invalid_manifest() {
+        let dir = TempDir::new().unwrap();
+        let file = write(dir.path(), "app.py", "");
+
+        let duplicate = WorkloadManifest::new(
+            dir.path(),
+            vec![workload("app", &["*.py"]), workload("app", &["*.py"])],
+        );
+        assert!(duplicate
+            .assign(std::slice::from_ref(&file))
+            .unwrap_err()
+            .to_string()
+            .contains("declared more than once"));
+
+        let no_paths = WorkloadManifest::new(dir.path(), vec![workload("app", &[])]);
+        assert!(no_paths.assign(&[file]).is_err());
+    }
+}
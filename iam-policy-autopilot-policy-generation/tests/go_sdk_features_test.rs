@@ -8,7 +8,8 @@
 //! Based on go-analysis.json which documents operations requiring IAM permissions.
 
 use iam_policy_autopilot_policy_generation::{
-    EnrichmentEngine, ExtractionEngine, Language, PolicyGenerationEngine, SdkType, SourceFile,
+    EnrichmentEngine, ExtractionEngine, Language, PolicyGenerationEngine,
+    RemoteServiceReferenceLoader, SdkType, SourceFile,
 };
 use std::path::PathBuf;
 
@@ -72,7 +73,8 @@ func main() {
         "Should extract Uploader.Upload method call"
     );
 
-    let mut enrichment_engine = EnrichmentEngine::new(false).unwrap();
+    let mut enrichment_engine =
+        EnrichmentEngine::new(RemoteServiceReferenceLoader::new(false).unwrap());
     let enriched = enrichment_engine
         .enrich_methods(&extracted.methods, SdkType::Other)
         .await
@@ -152,7 +154,8 @@ func main() {
         .await
         .expect("Extraction should succeed");
 
-    let mut enrichment_engine = EnrichmentEngine::new(false).unwrap();
+    let mut enrichment_engine =
+        EnrichmentEngine::new(RemoteServiceReferenceLoader::new(false).unwrap());
     let enriched = enrichment_engine
         .enrich_methods(&extracted.methods, SdkType::Other)
         .await
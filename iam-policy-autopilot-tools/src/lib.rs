@@ -4,11 +4,12 @@
 //! It includes policy name generation with automatic numbering and policy listing capabilities.
 
 use aws_config::BehaviorVersion;
+use aws_sdk_iam::operation::attach_role_policy::AttachRolePolicyError;
 use aws_sdk_iam::operation::create_policy::CreatePolicyError;
 use aws_sdk_iam::operation::list_policies::ListPoliciesError;
 use aws_sdk_iam::Client as IamClient;
 use aws_smithy_runtime_api::client::result::SdkError;
-use iam_policy_autopilot_policy_generation::{IamPolicy, PolicyWithMetadata};
+use iam_policy_autopilot_policy_generation::PolicyWithMetadata;
 use regex::Regex;
 use thiserror::Error;
 
@@ -37,6 +38,12 @@ pub enum UploaderError {
     #[error("AWS IAM create policy error: {0}")]
     CreatePolicy(#[from] SdkError<CreatePolicyError, aws_smithy_runtime_api::http::Response>),
 
+    /// AWS IAM attach role policy error
+    #[error("AWS IAM attach role policy error: {0}")]
+    AttachRolePolicy(
+        #[from] SdkError<AttachRolePolicyError, aws_smithy_runtime_api::http::Response>,
+    ),
+
     /// JSON serialization error
     #[error("JSON serialization error: {0}")]
     JsonSerialization(#[from] serde_json::Error),
@@ -234,7 +241,29 @@ impl PolicyUploader {
         policies: &[PolicyWithMetadata],
         custom_name: Option<&str>,
     ) -> UploaderResult<BatchUploadResponse> {
-        if policies.is_empty() {
+        let documents = policies
+            .iter()
+            .map(|policy| serde_json::to_string(&policy.policy))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.upload_policy_documents(&documents, custom_name).await
+    }
+
+    /// Upload multiple IAM policy documents to AWS, e.g. policies edited after generation
+    ///
+    /// # Arguments
+    ///
+    /// * `documents` - JSON policy documents to upload
+    /// * `custom_name` - Optional custom name prefix for all policies
+    ///
+    /// # Returns
+    ///
+    /// A `BatchUploadResponse` containing successful uploads and any failures
+    pub async fn upload_policy_documents(
+        &self,
+        documents: &[String],
+        custom_name: Option<&str>,
+    ) -> UploaderResult<BatchUploadResponse> {
+        if documents.is_empty() {
             return Ok(BatchUploadResponse {
                 successful: Vec::new(),
                 failed: Vec::new(),
@@ -252,10 +281,10 @@ impl PolicyUploader {
         let mut successful = Vec::new();
         let mut failed = Vec::new();
 
-        for (index, policy) in policies.iter().enumerate() {
+        for (index, document) in documents.iter().enumerate() {
             match self
                 .upload_single_policy_with_existing(
-                    &policy.policy,
+                    document,
                     custom_name,
                     &mut existing_policy_names,
                 )
@@ -275,20 +304,64 @@ impl PolicyUploader {
         Ok(BatchUploadResponse { successful, failed })
     }
 
+    /// Names the policies would be uploaded with, without uploading them
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of policies to upload
+    /// * `custom_name` - Optional custom name prefix for all policies
+    pub async fn planned_policy_names(
+        &self,
+        count: usize,
+        custom_name: Option<&str>,
+    ) -> UploaderResult<Vec<String>> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let mut existing_policy_names = self.list_policies().await?;
+
+        let mut names = Vec::with_capacity(count);
+        for _ in 0..count {
+            let name = Self::generate_policy_name(&existing_policy_names, custom_name)?;
+            existing_policy_names.push(name.clone());
+            names.push(name);
+        }
+        Ok(names)
+    }
+
+    /// Attach an uploaded policy to an IAM role
+    ///
+    /// # Arguments
+    ///
+    /// * `role_name` - Name of the role
+    /// * `policy_arn` - ARN of the policy to attach
+    pub async fn attach_role_policy(
+        &self,
+        role_name: &str,
+        policy_arn: &str,
+    ) -> UploaderResult<()> {
+        self.client
+            .attach_role_policy()
+            .role_name(role_name)
+            .policy_arn(policy_arn)
+            .send()
+            .await?;
+
+        log::info!("Attached policy {} to role {}", policy_arn, role_name);
+        Ok(())
+    }
+
     /// Helper method to upload a single policy with a pre-fetched existing policies list
     #[allow(clippy::ptr_arg)]
     async fn upload_single_policy_with_existing(
         &self,
-        policy: &IamPolicy,
+        policy_document: &str,
         custom_name: Option<&str>,
         existing_policies: &mut Vec<String>,
     ) -> UploaderResult<UploadResponse> {
         // Generate a unique policy name
         let policy_name = Self::generate_policy_name(existing_policies, custom_name)?;
 
-        // Serialize the policy to JSON
-        let policy_document = serde_json::to_string(policy)?;
-
         // Upload the policy
         let response = self
             .client